@@ -15,6 +15,7 @@ pub mod generic;
 
 pub mod canary;
 pub mod counters;
+pub mod record;
 
 pub mod zero_copy;
 
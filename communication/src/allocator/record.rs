@@ -0,0 +1,327 @@
+//! Allocators recording a worker's communication, and replaying it deterministically.
+//!
+//! The messages a worker receives from other workers, the order in which it receives them, and
+//! the steps at which their arrival activates its operators vary from run to run. A `Recorder`
+//! wraps an allocator and writes to a log each message its channels deliver, with the channel
+//! and the number of times the channel was pulled before, and the channel events the worker
+//! observes at each step. A `Replayer` reads the log, and presents the same messages and events
+//! at the same pulls and steps, without any other workers. A worker that builds the same
+//! dataflows and is driven by the same logic then repeats its original execution, including
+//! the progress information other workers sent it, which makes its failures reproducible.
+//!
+//! Sources of non-determinism within the worker, like timers and the system clock, are not
+//! recorded. Messages sent by a replaying worker are discarded.
+
+use std::rc::Rc;
+use std::cell::{Cell, RefCell};
+use std::collections::{HashMap, VecDeque};
+use std::fs::File;
+use std::io::{self, BufReader, BufWriter, Read, Write};
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+
+use bytes::arc::Bytes;
+
+use crate::allocator::{Allocate, AllocateBuilder, Event};
+use crate::{Push, Pull, Data, Message};
+
+// Tags of the entries of a log.
+const STEP: u8 = 0;
+const MESSAGE: u8 = 1;
+
+/// Builds a `Recorder` around the allocator of another builder.
+pub struct RecorderBuilder<B> {
+    inner: B,
+    directory: PathBuf,
+}
+
+impl<B> RecorderBuilder<B> {
+    /// Records the allocator built by `inner` to the file `worker-<index>.log` in `directory`.
+    pub fn new<P: AsRef<Path>>(inner: B, directory: P) -> Self {
+        RecorderBuilder { inner, directory: directory.as_ref().to_owned() }
+    }
+}
+
+impl<B: AllocateBuilder> AllocateBuilder for RecorderBuilder<B> {
+    type Allocator = Recorder<B::Allocator>;
+    fn build(self) -> Self::Allocator {
+        let inner = self.inner.build();
+        let path = self.directory.join(format!("worker-{}.log", inner.index()));
+        let file = File::create(&path).unwrap_or_else(|error| panic!("failed to create {:?}: {}", path, error));
+        Recorder::new(inner, file).unwrap_or_else(|error| panic!("failed to write {:?}: {}", path, error))
+    }
+}
+
+/// An allocator recording the messages and events of another allocator to a log.
+pub struct Recorder<A> {
+    inner: A,
+    log: Rc<RefCell<BufWriter<File>>>,
+}
+
+impl<A: Allocate> Recorder<A> {
+    /// Records the communication of `inner` to `file`.
+    pub fn new(inner: A, file: File) -> io::Result<Self> {
+        let mut log = BufWriter::new(file);
+        write_u64(&mut log, inner.index() as u64)?;
+        write_u64(&mut log, inner.peers() as u64)?;
+        Ok(Recorder { inner, log: Rc::new(RefCell::new(log)) })
+    }
+}
+
+impl<A: Allocate> Allocate for Recorder<A> {
+    fn index(&self) -> usize { self.inner.index() }
+    fn peers(&self) -> usize { self.inner.peers() }
+    fn allocate<T: Data>(&mut self, identifier: usize) -> (Vec<Box<dyn Push<Message<T>>>>, Box<dyn Pull<Message<T>>>) {
+        let (pushers, puller) = self.inner.allocate(identifier);
+        let puller = RecordPuller { channel: identifier, pulls: 0, inner: puller, log: self.log.clone() };
+        (pushers, Box::new(puller))
+    }
+    fn events(&self) -> &Rc<RefCell<VecDeque<(usize, Event)>>> {
+        self.inner.events()
+    }
+    fn await_events(&self, duration: Option<Duration>) {
+        self.inner.await_events(duration);
+    }
+    fn receive(&mut self) {
+        self.inner.receive();
+        // The worker acts on the events queued at this moment, from this and earlier steps.
+        let events = self.inner.events().borrow();
+        let mut log = self.log.borrow_mut();
+        let result = (|| {
+            log.write_all(&[STEP])?;
+            write_u64(&mut *log, events.len() as u64)?;
+            for (channel, event) in events.iter() {
+                let (kind, count) = match event {
+                    Event::Pushed(count) => (0, count),
+                    Event::Pulled(count) => (1, count),
+                };
+                write_u64(&mut *log, *channel as u64)?;
+                log.write_all(&[kind])?;
+                write_u64(&mut *log, *count as u64)?;
+            }
+            // Flush each step, so that the log describes a worker that later crashes.
+            log.flush()
+        })();
+        result.expect("failed to write recording");
+    }
+    fn release(&mut self) {
+        self.inner.release();
+    }
+}
+
+/// Records the messages pulled from a channel.
+struct RecordPuller<T> {
+    channel: usize,
+    pulls: u64,
+    inner: Box<dyn Pull<Message<T>>>,
+    log: Rc<RefCell<BufWriter<File>>>,
+}
+
+impl<T: Data> Pull<Message<T>> for RecordPuller<T> {
+    fn pull(&mut self) -> &mut Option<Message<T>> {
+        let (channel, pulls) = (self.channel, self.pulls);
+        self.pulls += 1;
+        let message = self.inner.pull();
+        if let Some(message) = message.as_ref() {
+            let mut bytes = Vec::with_capacity(message.length_in_bytes());
+            message.into_bytes(&mut bytes);
+            let mut log = self.log.borrow_mut();
+            let result = (|| {
+                log.write_all(&[MESSAGE])?;
+                write_u64(&mut *log, channel as u64)?;
+                write_u64(&mut *log, pulls)?;
+                write_u64(&mut *log, bytes.len() as u64)?;
+                log.write_all(&bytes)
+            })();
+            result.expect("failed to write recording");
+        }
+        message
+    }
+}
+
+/// Messages read from a log but not yet pulled, by channel, with the pull that delivers each.
+type Staged = Rc<RefCell<HashMap<usize, VecDeque<(u64, Vec<u8>)>>>>;
+
+/// An allocator replaying the messages and events of a log written by a `Recorder`.
+///
+/// # Examples
+/// ```
+/// use timely_communication::Allocate;
+/// use timely_communication::allocator::record::{RecorderBuilder, Replayer};
+/// use timely_communication::initialize::{Config, initialize_from};
+///
+/// // each worker sends its index to all workers, and adds up what it receives.
+/// fn run<A: Allocate>(allocator: &mut A) -> u64 {
+///     let (mut senders, mut receiver) = allocator.allocate::<u64>(0);
+///     for sender in senders.iter_mut() {
+///         sender.send(timely_communication::Message::from_typed(allocator.index() as u64));
+///         sender.done();
+///     }
+///     let (mut sum, mut received) = (0, 0);
+///     while received < allocator.peers() {
+///         allocator.receive();
+///         while let Some(message) = receiver.recv() {
+///             sum += *message;
+///             received += 1;
+///         }
+///         allocator.release();
+///     }
+///     sum
+/// }
+///
+/// let directory = std::env::temp_dir().join(format!("timely-replay-{}", std::process::id()));
+/// std::fs::create_dir_all(&directory).unwrap();
+///
+/// let (builders, others) = Config::Process(2).try_build().unwrap();
+/// let builders = builders.into_iter().map(|builder| RecorderBuilder::new(builder, &directory)).collect();
+/// let guards = initialize_from(builders, others, |mut allocator| run(&mut allocator)).unwrap();
+/// assert!(guards.join().into_iter().all(|sum| sum.unwrap() == 1));
+///
+/// // the second worker, replayed alone.
+/// let (mut replayer, _exhausted) = Replayer::open(directory.join("worker-1.log")).unwrap();
+/// assert_eq!(replayer.index(), 1);
+/// assert_eq!(run(&mut replayer), 1);
+///
+/// std::fs::remove_dir_all(directory).unwrap();
+/// ```
+pub struct Replayer {
+    index: usize,
+    peers: usize,
+    log: BufReader<File>,
+    staged: Staged,
+    events: Rc<RefCell<VecDeque<(usize, Event)>>>,
+    exhausted: Rc<Cell<bool>>,
+}
+
+impl Replayer {
+    /// Replays the log in `path`, returning the allocator and an indicator that the log is exhausted.
+    pub fn open<P: AsRef<Path>>(path: P) -> io::Result<(Self, Rc<Cell<bool>>)> {
+        let mut log = BufReader::new(File::open(path)?);
+        let index = read_u64(&mut log)? as usize;
+        let peers = read_u64(&mut log)? as usize;
+        let exhausted = Rc::new(Cell::new(false));
+        let mut replayer = Replayer {
+            index,
+            peers,
+            log,
+            staged: Rc::new(RefCell::new(HashMap::new())),
+            events: Rc::new(RefCell::new(VecDeque::new())),
+            exhausted: exhausted.clone(),
+        };
+        // Messages may be pulled before the first step, as dataflows are built.
+        replayer.read_messages()?;
+        Ok((replayer, exhausted))
+    }
+
+    /// Reads the events of the next step, and stages the messages pulled during it.
+    fn read_step(&mut self) -> io::Result<()> {
+        let mut events = self.events.borrow_mut();
+        events.clear();
+        for _ in 0 .. read_u64(&mut self.log)? {
+            let channel = read_u64(&mut self.log)? as usize;
+            let mut kind = [0u8];
+            self.log.read_exact(&mut kind)?;
+            let count = read_u64(&mut self.log)? as usize;
+            events.push_back((channel, if kind[0] == 0 { Event::Pushed(count) } else { Event::Pulled(count) }));
+        }
+        drop(events);
+        self.read_messages()
+    }
+
+    /// Stages messages up to the next step, reading its tag, or to the end of the log.
+    fn read_messages(&mut self) -> io::Result<()> {
+        loop {
+            match read_tag(&mut self.log)? {
+                Some(MESSAGE) => {
+                    let channel = read_u64(&mut self.log)? as usize;
+                    let pulls = read_u64(&mut self.log)?;
+                    let mut bytes = vec![0u8; read_u64(&mut self.log)? as usize];
+                    self.log.read_exact(&mut bytes)?;
+                    self.staged.borrow_mut().entry(channel).or_default().push_back((pulls, bytes));
+                },
+                Some(STEP) => return Ok(()),
+                Some(_) => return Err(io::Error::new(io::ErrorKind::InvalidData, "unknown entry")),
+                None => {
+                    self.exhausted.set(true);
+                    return Ok(());
+                },
+            }
+        }
+    }
+}
+
+impl Allocate for Replayer {
+    fn index(&self) -> usize { self.index }
+    fn peers(&self) -> usize { self.peers }
+    fn allocate<T: Data>(&mut self, identifier: usize) -> (Vec<Box<dyn Push<Message<T>>>>, Box<dyn Pull<Message<T>>>) {
+        let pushers = (0 .. self.peers).map(|_| Box::new(Discard) as Box<dyn Push<Message<T>>>).collect();
+        let puller = ReplayPuller { channel: identifier, pulls: 0, current: None, staged: self.staged.clone() };
+        (pushers, Box::new(puller))
+    }
+    fn events(&self) -> &Rc<RefCell<VecDeque<(usize, Event)>>> {
+        &self.events
+    }
+    fn receive(&mut self) {
+        if !self.exhausted.get() {
+            self.read_step().expect("failed to read recording");
+        }
+    }
+}
+
+/// Discards the messages sent by a replaying worker.
+struct Discard;
+
+impl<T> Push<T> for Discard {
+    fn push(&mut self, element: &mut Option<T>) {
+        *element = None;
+    }
+}
+
+/// Presents recorded messages at the pulls that originally received them.
+struct ReplayPuller<T> {
+    channel: usize,
+    pulls: u64,
+    current: Option<Message<T>>,
+    staged: Staged,
+}
+
+impl<T: Data> Pull<Message<T>> for ReplayPuller<T> {
+    fn pull(&mut self) -> &mut Option<Message<T>> {
+        let pulls = self.pulls;
+        self.pulls += 1;
+        let mut staged = self.staged.borrow_mut();
+        let queue = staged.get_mut(&self.channel);
+        self.current = match queue {
+            Some(queue) if queue.front().map(|(at, _)| *at == pulls).unwrap_or(false) => {
+                let (_, bytes) = queue.pop_front().expect("staged message");
+                // The bytes were written by `Message::into_bytes` for the same type.
+                #[cfg(not(feature = "bincode"))]
+                let message = unsafe { Message::from_bytes(Bytes::from(bytes)) };
+                #[cfg(feature = "bincode")]
+                let message = Message::from_bytes(Bytes::from(bytes));
+                Some(message)
+            },
+            _ => None,
+        };
+        &mut self.current
+    }
+}
+
+fn write_u64<W: Write>(writer: &mut W, value: u64) -> io::Result<()> {
+    writer.write_all(&value.to_le_bytes())
+}
+
+fn read_u64<R: Read>(reader: &mut R) -> io::Result<u64> {
+    let mut bytes = [0u8; 8];
+    reader.read_exact(&mut bytes)?;
+    Ok(u64::from_le_bytes(bytes))
+}
+
+/// Reads the tag of the next entry, or `None` at the end of the log.
+fn read_tag<R: Read>(reader: &mut R) -> io::Result<Option<u8>> {
+    let mut tag = [0u8];
+    match reader.read(&mut tag)? {
+        0 => Ok(None),
+        _ => Ok(Some(tag[0])),
+    }
+}
@@ -26,14 +26,19 @@ pub struct TcpBuilder<A: AllocateBuilder> {
     inner:  A,
     index:  usize,                      // number out of peers
     peers:  usize,                      // number of peer allocators.
+    process: usize,                     // index of this process.
+    offsets: Vec<usize>,                // index of the first worker of each process, and then peers.
+    connections: usize,                 // number of connections to each remote process.
     futures:   Vec<Receiver<MergeQueue>>,  // to receive queues to each network thread.
     promises:   Vec<Sender<MergeQueue>>,    // to send queues from each network thread.
 }
 
 /// Creates a vector of builders, sharing appropriate state.
 ///
-/// `threads` is the number of workers in a single process, `processes` is the
-/// total number of processes.
+/// `process_threads` is the number of workers in each process, of which `allocators`
+/// are those of process `my_process`, and `connections` the number of connections to each
+/// remote process. Workers are numbered consecutively by process. Each channel is sent over
+/// the connection of its identifier modulo `connections`.
 /// The returned tuple contains
 /// ```ignore
 /// (
@@ -42,19 +47,25 @@ pub struct TcpBuilder<A: AllocateBuilder> {
 ///   info to spawn ingress comm thresds,
 /// )
 /// ```
+/// where the comm thread information is ordered by remote process and then by connection.
 pub fn new_vector<A: AllocateBuilder>(
     allocators: Vec<A>,
     my_process: usize,
-    processes: usize)
+    process_threads: &[usize],
+    connections: usize)
 -> (Vec<TcpBuilder<A>>,
     Vec<Vec<Sender<MergeQueue>>>,
     Vec<Vec<Receiver<MergeQueue>>>)
 {
     let threads = allocators.len();
+    let processes = process_threads.len();
+    assert_eq!(process_threads[my_process], threads, "allocators do not match the workers of process {}", my_process);
+
+    let offsets = worker_offsets(process_threads);
 
     // For queues from worker threads to network threads, and vice versa.
-    let (network_promises, worker_futures) = crate::promise_futures(processes-1, threads);
-    let (worker_promises, network_futures) = crate::promise_futures(threads, processes-1);
+    let (network_promises, worker_futures) = crate::promise_futures((processes-1) * connections, threads);
+    let (worker_promises, network_futures) = crate::promise_futures(threads, (processes-1) * connections);
 
     let builders =
     allocators
@@ -65,8 +76,11 @@ pub fn new_vector<A: AllocateBuilder>(
         .map(|(index, ((inner, promises), futures))| {
             TcpBuilder {
                 inner,
-                index: my_process * threads + index,
-                peers: threads * processes,
+                index: offsets[my_process] + index,
+                peers: offsets[processes],
+                process: my_process,
+                offsets: offsets.clone(),
+                connections,
                 promises,
                 futures,
             }})
@@ -75,6 +89,16 @@ pub fn new_vector<A: AllocateBuilder>(
     (builders, network_promises, network_futures)
 }
 
+/// The index of the first worker of each process, followed by the total number of workers.
+pub fn worker_offsets(process_threads: &[usize]) -> Vec<usize> {
+    let mut offsets = Vec::with_capacity(process_threads.len() + 1);
+    offsets.push(0);
+    for threads in process_threads.iter() {
+        offsets.push(offsets[offsets.len() - 1] + threads);
+    }
+    offsets
+}
+
 impl<A: AllocateBuilder> TcpBuilder<A> {
 
     /// Builds a `TcpAllocator`, instantiating `Rc<RefCell<_>>` elements.
@@ -104,6 +128,9 @@ impl<A: AllocateBuilder> TcpBuilder<A> {
             inner: self.inner.build(),
             index: self.index,
             peers: self.peers,
+            process: self.process,
+            offsets: self.offsets,
+            connections: self.connections,
             canaries: Rc::new(RefCell::new(Vec::new())),
             channel_id_bound: None,
             staged: Vec::new(),
@@ -121,6 +148,9 @@ pub struct TcpAllocator<A: Allocate> {
 
     index:      usize,                              // number out of peers
     peers:      usize,                              // number of peer allocators (for typed channel allocation).
+    process:    usize,                              // index of this process.
+    offsets:    Vec<usize>,                         // index of the first worker of each process, and then peers.
+    connections: usize,                             // number of connections to each remote process.
 
     staged:     Vec<Bytes>,                         // staging area for incoming Bytes
     canaries:   Rc<RefCell<Vec<usize>>>,
@@ -128,8 +158,8 @@ pub struct TcpAllocator<A: Allocate> {
     channel_id_bound: Option<usize>,
 
     // sending, receiving, and responding to binary buffers.
-    sends:      Vec<Rc<RefCell<SendEndpoint<MergeQueue>>>>,     // sends[x] -> goes to process x.
-    recvs:      Vec<MergeQueue>,                                // recvs[x] <- from process x.
+    sends:      Vec<Rc<RefCell<SendEndpoint<MergeQueue>>>>,     // sends[x * connections + c] -> goes to process x on connection c.
+    recvs:      Vec<MergeQueue>,                                // recvs[x * connections + c] <- from process x on connection c.
     to_local:   HashMap<usize, Rc<RefCell<VecDeque<Bytes>>>>,   // to worker-local typed pullers.
 }
 
@@ -148,15 +178,20 @@ impl<A: Allocate> Allocate for TcpAllocator<A> {
         let mut pushes = Vec::<Box<dyn Push<Message<T>>>>::new();
 
         // Inner exchange allocations.
-        let inner_peers = self.inner.peers();
         let (mut inner_sends, inner_recv) = self.inner.allocate(identifier);
 
         for target_index in 0 .. self.peers() {
 
-            // TODO: crappy place to hardcode this rule.
-            let mut process_id = target_index / inner_peers;
+            // The process hosting the target worker.
+            // Never returns `Ok`, and so finds the last process whose first worker is at most the
+            // target, skipping processes without workers.
+            let mut process_id = match self.offsets.binary_search_by(|offset| {
+                if *offset <= target_index { ::std::cmp::Ordering::Less } else { ::std::cmp::Ordering::Greater }
+            }) {
+                Ok(index) | Err(index) => index - 1,
+            };
 
-            if process_id == self.index / inner_peers {
+            if process_id == self.process {
                 pushes.push(inner_sends.remove(0));
             }
             else {
@@ -170,8 +205,9 @@ impl<A: Allocate> Allocate for TcpAllocator<A> {
                 };
 
                 // create, box, and stash new process_binary pusher.
-                if process_id > self.index / inner_peers { process_id -= 1; }
-                pushes.push(Box::new(Pusher::new(header, self.sends[process_id].clone())));
+                if process_id > self.process { process_id -= 1; }
+                let connection = identifier % self.connections;
+                pushes.push(Box::new(Pusher::new(header, self.sends[process_id * self.connections + connection].clone())));
             }
         }
 
@@ -227,6 +263,9 @@ impl<A: Allocate> Allocate for TcpAllocator<A> {
                     let mut peel = bytes.extract_to(header.required_bytes());
                     let _ = peel.extract_to(::std::mem::size_of::<MessageHeader>());
 
+                    // Each channel is carried by a single connection, which preserves the
+                    // order in which its messages were sent.
+
                     // Increment message count for channel.
                     // Safe to do this even if the channel has been dropped.
                     events.push_back((header.channel, Event::Pushed(1)));
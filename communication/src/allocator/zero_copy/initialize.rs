@@ -3,9 +3,11 @@
 use std::sync::Arc;
 // use crate::allocator::Process;
 use crate::allocator::process::ProcessBuilder;
-use crate::networking::create_sockets;
-use super::tcp::{send_loop, recv_loop};
-use super::allocator::{TcpBuilder, new_vector};
+use crate::networking::create_connections;
+use std::io::Read;
+use crate::authentication::Key;
+use super::tcp::{send_loop_shared, recv_loop_shared, SendConnection, RecvConnection, Link, BlockingWrites};
+use super::allocator::{TcpBuilder, new_vector, worker_offsets};
 
 /// Join handles for send and receive threads.
 ///
@@ -32,17 +34,112 @@ impl Drop for CommsGuard {
 use crate::logging::{CommunicationSetup, CommunicationEvent};
 use logging_core::Logger;
 
+/// Tuning parameters for the connections between processes.
+///
+/// Start from `NetworkConfig::default()` and adjust parameters with the builder methods, as
+/// further parameters may be added.
+///
+/// # Examples
+/// ```
+/// use timely_communication::NetworkConfig;
+///
+/// let config = NetworkConfig::default().connections(4).send_threads(2);
+/// assert_eq!(config.connections, 4);
+/// assert_eq!(config.send_threads, Some(2));
+/// ```
+#[derive(Clone, Debug, PartialEq, Eq)]
+#[non_exhaustive]
+pub struct NetworkConfig {
+    /// Number of connections to each other process.
+    pub connections: usize,
+    /// Number of threads sending to other processes, each serving a share of the connections,
+    /// or `None` for one thread per connection.
+    pub send_threads: Option<usize>,
+    /// Number of threads receiving from other processes, each serving a share of the connections,
+    /// or `None` for one thread per connection.
+    ///
+    /// Threads serving several connections read from them without blocking, and back off while
+    /// none of them have bytes to read.
+    pub recv_threads: Option<usize>,
+    /// Number of bytes of messages gathered before writing them to a connection, or zero to
+    /// write each message directly.
+    pub write_buffer: usize,
+    /// Whether to disable Nagle's algorithm on the connections, sending small writes immediately.
+    pub nodelay: bool,
+    /// The key with which processes tag and check each message, if any.
+    ///
+    /// Processes with different keys, or without keys, cannot exchange messages: the first
+    /// message that fails its check stops the computation.
+    pub key: Option<Key>,
+}
+
+impl Default for NetworkConfig {
+    fn default() -> Self {
+        NetworkConfig {
+            connections: 1,
+            send_threads: None,
+            recv_threads: None,
+            write_buffer: 1 << 16,
+            nodelay: true,
+            key: None,
+        }
+    }
+}
+
+impl NetworkConfig {
+    /// The numbers of send and receive threads serving `links` connections, each at least one and
+    /// at most `links`.
+    pub(crate) fn threads_for(&self, links: usize) -> (usize, usize) {
+        let bound = |threads: Option<usize>| ::std::cmp::max(1, ::std::cmp::min(threads.unwrap_or(links), links));
+        (bound(self.send_threads), bound(self.recv_threads))
+    }
+    /// Sets the number of connections to each other process.
+    pub fn connections(mut self, connections: usize) -> Self {
+        self.connections = connections;
+        self
+    }
+    /// Sets the number of threads sending to other processes.
+    pub fn send_threads(mut self, threads: usize) -> Self {
+        self.send_threads = Some(threads);
+        self
+    }
+    /// Sets the number of threads receiving from other processes.
+    pub fn recv_threads(mut self, threads: usize) -> Self {
+        self.recv_threads = Some(threads);
+        self
+    }
+    /// Sets the number of bytes of messages gathered before writing them to a connection.
+    pub fn write_buffer(mut self, bytes: usize) -> Self {
+        self.write_buffer = bytes;
+        self
+    }
+    /// Sets whether to disable Nagle's algorithm on the connections.
+    pub fn nodelay(mut self, nodelay: bool) -> Self {
+        self.nodelay = nodelay;
+        self
+    }
+    /// Sets the key with which processes tag and check each message.
+    pub fn key(mut self, key: Key) -> Self {
+        self.key = Some(key);
+        self
+    }
+}
+
 /// Initializes network connections
+///
+/// Each pair of processes is connected by `config.connections` TCP connections, over which
+/// channels are striped by their identifiers. Process `i` hosts `process_threads[i]` workers.
 pub fn initialize_networking(
     addresses: Vec<String>,
     my_index: usize,
-    threads: usize,
+    process_threads: Vec<usize>,
+    config: &NetworkConfig,
     noisy: bool,
     log_sender: Box<dyn Fn(CommunicationSetup)->Option<Logger<CommunicationEvent, CommunicationSetup>>+Send+Sync>)
 -> ::std::io::Result<(Vec<TcpBuilder<ProcessBuilder>>, CommsGuard)>
 {
-    let sockets = create_sockets(addresses, my_index, noisy)?;
-    initialize_networking_from_sockets(sockets, my_index, threads, log_sender)
+    let connections = create_connections(addresses, my_index, config.connections, noisy)?;
+    initialize_networking_from_connections(connections, my_index, process_threads, config, log_sender)
 }
 
 /// Initialize send and recv threads from sockets.
@@ -53,75 +150,160 @@ pub fn initialize_networking(
 /// It is important that the `sockets` argument contain sockets for each remote process, in order, and
 /// with position `my_index` set to `None`.
 pub fn initialize_networking_from_sockets(
-    mut sockets: Vec<Option<std::net::TcpStream>>,
+    sockets: Vec<Option<std::net::TcpStream>>,
     my_index: usize,
     threads: usize,
     log_sender: Box<dyn Fn(CommunicationSetup)->Option<Logger<CommunicationEvent, CommunicationSetup>>+Send+Sync>)
 -> ::std::io::Result<(Vec<TcpBuilder<ProcessBuilder>>, CommsGuard)>
+{
+    let process_threads = vec![threads; sockets.len()];
+    let connections = sockets.into_iter().map(|socket| socket.into_iter().collect()).collect();
+    initialize_networking_from_connections(connections, my_index, process_threads, &NetworkConfig::default(), log_sender)
+}
+
+/// Initialize send and recv threads from several sockets to each process.
+///
+/// As `initialize_networking_from_sockets`, except that the `connections` argument contains the same
+/// number of sockets for each remote process, in order of their connection index, and no sockets at
+/// position `my_index`. Processes must agree on the order of the sockets between them. The number of
+/// connections in `config` is ignored in favor of the number of sockets. Process `i` hosts
+/// `process_threads[i]` workers.
+pub fn initialize_networking_from_connections(
+    mut connections: Vec<Vec<std::net::TcpStream>>,
+    my_index: usize,
+    process_threads: Vec<usize>,
+    config: &NetworkConfig,
+    log_sender: Box<dyn Fn(CommunicationSetup)->Option<Logger<CommunicationEvent, CommunicationSetup>>+Send+Sync>)
+-> ::std::io::Result<(Vec<TcpBuilder<ProcessBuilder>>, CommsGuard)>
 {
     // Sockets are expected to be blocking,
-    for socket in sockets.iter_mut() {
-        if let Some(socket) = socket {
-            socket.set_nonblocking(false).expect("failed to set socket to blocking");
+    for socket in connections.iter_mut().flatten() {
+        socket.set_nonblocking(false).expect("failed to set socket to blocking");
+        socket.set_nodelay(config.nodelay)?;
+    }
+
+    // Receive threads serving several connections require non-blocking reads, which the socket
+    // shares with its writer, whose writes then wait until the socket is ready.
+    let count = connections.iter().map(|streams| streams.len()).sum();
+    let (_, recv_threads) = config.threads_for(count);
+    let nonblocking = recv_threads < count;
+
+    let mut links = Vec::with_capacity(connections.len());
+    for streams in connections {
+        let mut pairs = Vec::with_capacity(streams.len());
+        for stream in streams {
+            stream.set_nonblocking(nonblocking)?;
+            pairs.push((stream.try_clone()?, BlockingWrites(stream)));
         }
+        links.push(pairs);
     }
+    initialize_networking_from_links(links, my_index, process_threads, config, log_sender)
+}
 
+/// Initialize send and recv threads from byte streams to each process.
+///
+/// As `initialize_networking_from_connections`, except that each connection is a pair of a
+/// stream to read from and a stream to write to, which need not be sockets. The send thread of
+/// a connection shuts down writes to its stream once it has written its last message, and the
+/// recv thread of the other end then reads the end of the stream. If `config.recv_threads` is
+/// less than the number of connections, the streams to read from must be non-blocking.
+pub fn initialize_networking_from_links<R, W>(
+    connections: Vec<Vec<(R, W)>>,
+    my_index: usize,
+    process_threads: Vec<usize>,
+    config: &NetworkConfig,
+    log_sender: Box<dyn Fn(CommunicationSetup)->Option<Logger<CommunicationEvent, CommunicationSetup>>+Send+Sync>)
+-> ::std::io::Result<(Vec<TcpBuilder<ProcessBuilder>>, CommsGuard)>
+where
+    R: Read+Send+'static,
+    W: Link+Send+'static,
+{
     let log_sender = Arc::new(log_sender);
-    let processes = sockets.len();
+    let processes = connections.len();
+    if process_threads.len() != processes {
+        return Err(::std::io::Error::new(::std::io::ErrorKind::InvalidInput,
+            format!("expected worker counts for {} processes, found {}", processes, process_threads.len())));
+    }
+    let threads = process_threads[my_index];
+    let worker_offset = worker_offsets(&process_threads)[my_index];
+    let per_process = connections.iter().map(|streams| streams.len()).max().unwrap_or(1).max(1);
+    for (index, streams) in connections.iter().enumerate() {
+        let expected = if index == my_index { 0 } else { per_process };
+        if streams.len() != expected {
+            return Err(::std::io::Error::new(::std::io::ErrorKind::InvalidInput,
+                format!("expected {} connections to process {}, found {}", expected, index, streams.len())));
+        }
+    }
 
     let process_allocators = crate::allocator::process::Process::new_vector(threads);
-    let (builders, promises, futures) = new_vector(process_allocators, my_index, processes);
+    let (builders, promises, futures) = new_vector(process_allocators, my_index, &process_threads, per_process);
 
     let mut promises_iter = promises.into_iter();
     let mut futures_iter = futures.into_iter();
 
-    let mut send_guards = Vec::with_capacity(sockets.len());
-    let mut recv_guards = Vec::with_capacity(sockets.len());
+    // connections served by each send and each receive thread.
+    let links = (processes - 1) * per_process;
+    let (send_threads, recv_threads) = config.threads_for(links);
+    let mut shared: Vec<Vec<_>> = (0 .. send_threads).map(|_| Vec::new()).collect();
+    let mut shared_recv: Vec<Vec<_>> = (0 .. recv_threads).map(|_| Vec::new()).collect();
 
-    // for each process, if a stream exists (i.e. not local) ...
-    for (index, stream) in sockets.into_iter().enumerate().filter_map(|(i, s)| s.map(|s| (i, s))) {
-        let remote_recv = promises_iter.next().unwrap();
+    let mut link = 0;
 
-        {
-            let log_sender = log_sender.clone();
-            let stream = stream.try_clone()?;
-            let join_guard =
-            ::std::thread::Builder::new()
-                .name(format!("timely:send-{}", index))
-                .spawn(move || {
+    // for each process and each connection to it (i.e. not local) ...
+    for (index, streams) in connections.into_iter().enumerate() {
+        for (reader, writer) in streams.into_iter() {
+            let remote_recv = promises_iter.next().unwrap();
+            let remote_send = futures_iter.next().unwrap();
 
+            shared[link % send_threads].push((writer, remote_recv, index));
+            shared_recv[link % recv_threads].push((reader, remote_send, index));
+            link += 1;
+        }
+    }
+
+    let mut recv_guards = Vec::with_capacity(recv_threads);
+    for (thread, connections) in shared_recv.into_iter().enumerate().filter(|(_, connections)| !connections.is_empty()) {
+        let log_sender = log_sender.clone();
+        let key = config.key.clone();
+        let join_guard =
+        ::std::thread::Builder::new()
+            .name(format!("timely:recv-{}", thread))
+            .spawn(move || {
+                let connections = connections.into_iter().map(|(reader, targets, remote)| {
                     let logger = log_sender(CommunicationSetup {
                         process: my_index,
-                        sender: true,
-                        remote: Some(index),
+                        sender: false,
+                        remote: Some(remote),
                     });
+                    RecvConnection { reader, targets, remote, key: key.clone(), logger }
+                }).collect();
+                recv_loop_shared(connections, worker_offset, my_index);
+            })?;
 
-                    send_loop(stream, remote_recv, my_index, index, logger);
-                })?;
-
-            send_guards.push(join_guard);
-        }
-
-        let remote_send = futures_iter.next().unwrap();
+        recv_guards.push(join_guard);
+    }
 
-        {
-            // let remote_sends = remote_sends.clone();
-            let log_sender = log_sender.clone();
-            let stream = stream.try_clone()?;
-            let join_guard =
-            ::std::thread::Builder::new()
-                .name(format!("timely:recv-{}", index))
-                .spawn(move || {
+    let mut send_guards = Vec::with_capacity(send_threads);
+    for (thread, connections) in shared.into_iter().enumerate().filter(|(_, connections)| !connections.is_empty()) {
+        let log_sender = log_sender.clone();
+        let write_buffer = config.write_buffer;
+        let key = config.key.clone();
+        let join_guard =
+        ::std::thread::Builder::new()
+            .name(format!("timely:send-{}", thread))
+            .spawn(move || {
+                let connections = connections.into_iter().map(|(writer, sources, remote)| {
                     let logger = log_sender(CommunicationSetup {
                         process: my_index,
-                        sender: false,
-                        remote: Some(index),
+                        sender: true,
+                        remote: Some(remote),
                     });
-                    recv_loop(stream, remote_send, threads * my_index, my_index, index, logger);
-                })?;
+                    SendConnection { writer, sources, remote, key: key.clone(), logger }
+                }).collect();
+                send_loop_shared(connections, my_index, write_buffer);
+            })?;
 
-            recv_guards.push(join_guard);
-        }
+        send_guards.push(join_guard);
     }
 
     Ok((builders, CommsGuard { send_guards, recv_guards }))
@@ -10,4 +10,7 @@ pub mod tcp;
 pub mod allocator;
 pub mod allocator_process;
 pub mod initialize;
-pub mod push_pull;
\ No newline at end of file
+pub mod simulated;
+pub mod push_pull;
+pub mod reorder;
+pub mod transport;
\ No newline at end of file
@@ -0,0 +1,90 @@
+//! Restores the order of messages received out of order.
+//!
+//! Progress tracking assumes that the messages sent by one worker to another on a channel are
+//! received in the order they were sent. A single TCP connection between each pair of processes
+//! provides this, as do several connections that each carry a fixed subset of the channels, but
+//! user-supplied transports may reorder messages. Each message header carries a sequence number for its
+//! channel, source, and target, and a `ReorderBuffer` holds back messages that arrive ahead of
+//! their predecessors.
+
+use std::collections::{BTreeMap, HashMap};
+
+use bytes::arc::Bytes;
+
+use crate::networking::MessageHeader;
+
+/// Messages held back until their predecessors arrive, by channel and source worker.
+///
+/// # Examples
+/// ```
+/// use timely_communication::networking::MessageHeader;
+/// use timely_communication::allocator::zero_copy::reorder::ReorderBuffer;
+/// use timely_bytes::arc::Bytes;
+///
+/// let header = |seqno| MessageHeader { channel: 0, source: 1, target: 2, length: 1, seqno };
+///
+/// let mut buffer = ReorderBuffer::default();
+/// let mut ready = Vec::new();
+/// buffer.accept(header(1), Bytes::from(vec![1u8]), &mut ready).unwrap();
+/// assert!(ready.is_empty());
+/// assert_eq!(buffer.pending(), 1);
+/// buffer.accept(header(0), Bytes::from(vec![0u8]), &mut ready).unwrap();
+/// let ready = ready.into_iter().map(|(_header, bytes)| bytes[0]).collect::<Vec<_>>();
+/// assert_eq!(ready, vec![0, 1]);
+/// assert_eq!(buffer.pending(), 0);
+///
+/// // a message received twice is an error.
+/// assert!(buffer.accept(header(1), Bytes::from(vec![1u8]), &mut Vec::new()).is_err());
+/// ```
+#[derive(Default)]
+pub struct ReorderBuffer {
+    // the sequence number of the next message to deliver, by channel and source.
+    next: HashMap<(usize, usize), usize>,
+    // messages received ahead of their predecessors, by channel and source.
+    held: HashMap<(usize, usize), BTreeMap<usize, (MessageHeader, Bytes)>>,
+}
+
+impl ReorderBuffer {
+    /// Accepts a received message, appending to `ready` it and any held messages it releases,
+    /// in order.
+    ///
+    /// Messages for dropped channels should not be accepted, as their sequence numbers would
+    /// restart from zero. A message that has already been received, or is already held, is
+    /// discarded and reported as an error.
+    pub fn accept(&mut self, header: MessageHeader, bytes: Bytes, ready: &mut Vec<(MessageHeader, Bytes)>) -> Result<(), String> {
+        let key = (header.channel, header.source);
+        let next = self.next.entry(key).or_insert(0);
+        if header.seqno == *next {
+            ready.push((header, bytes));
+            *next += 1;
+            if let Some(held) = self.held.get_mut(&key) {
+                while let Some(message) = held.remove(next) {
+                    ready.push(message);
+                    *next += 1;
+                }
+                if held.is_empty() {
+                    self.held.remove(&key);
+                }
+            }
+        }
+        else {
+            let held = self.held.entry(key).or_default();
+            if header.seqno < *next || held.contains_key(&header.seqno) {
+                return Err(format!("message {} on channel {} from worker {} received twice", header.seqno, header.channel, header.source));
+            }
+            held.insert(header.seqno, (header, bytes));
+        }
+        Ok(())
+    }
+
+    /// The number of messages held back.
+    pub fn pending(&self) -> usize {
+        self.held.values().map(|held| held.len()).sum()
+    }
+
+    /// Discards the state of a dropped channel.
+    pub fn drop_channel(&mut self, channel: usize) {
+        self.next.retain(|(c, _), _| *c != channel);
+        self.held.retain(|(c, _), _| *c != channel);
+    }
+}
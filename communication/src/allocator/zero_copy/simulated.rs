@@ -0,0 +1,168 @@
+//! Simulated clusters of processes within one process.
+//!
+//! A simulated cluster hosts the workers of several processes in one process, with the
+//! allocators of each simulated process connected by in-memory pipes rather than sockets. The
+//! workers of different simulated processes exchange data exactly as they would over a network,
+//! serializing it and passing it through send and receive threads, so that distributed behavior
+//! can be tested without sockets. The pipes can delay the bytes written to them, to simulate
+//! network latency.
+
+use std::io::{Read, Write};
+use std::time::{Duration, Instant};
+use crossbeam_channel::{Sender, Receiver};
+
+use crate::allocator::process::ProcessBuilder;
+use crate::allocator::zero_copy::allocator::TcpBuilder;
+use crate::allocator::zero_copy::initialize::{initialize_networking_from_links, CommsGuard, NetworkConfig};
+use crate::allocator::zero_copy::tcp::Link;
+
+/// Creates a pipe whose bytes are readable `latency` after they are written.
+///
+/// The reader reads the end of the stream once the writer is shut down or dropped, and all
+/// bytes written before have been read.
+///
+/// # Examples
+/// ```
+/// use std::io::{Read, Write};
+/// use std::time::Duration;
+/// use timely_communication::allocator::zero_copy::simulated::pipe;
+/// use timely_communication::allocator::zero_copy::tcp::Link;
+///
+/// let (mut writer, mut reader) = pipe(Duration::from_millis(1));
+/// writer.write_all(b"hello").unwrap();
+/// writer.shutdown_write().unwrap();
+///
+/// let mut text = String::new();
+/// reader.read_to_string(&mut text).unwrap();
+/// assert_eq!(text, "hello");
+/// ```
+pub fn pipe(latency: Duration) -> (PipeWriter, PipeReader) {
+    let (sender, receiver) = crossbeam_channel::unbounded();
+    let writer = PipeWriter { sender: Some(sender), latency };
+    let reader = PipeReader { receiver, pending: Vec::new(), offset: 0, readable: Instant::now(), nonblocking: false };
+    (writer, reader)
+}
+
+/// The writing end of a `pipe`.
+pub struct PipeWriter {
+    // the bytes of each write, with the instant from which they may be read.
+    sender: Option<Sender<(Instant, Vec<u8>)>>,
+    latency: Duration,
+}
+
+impl Write for PipeWriter {
+    fn write(&mut self, buf: &[u8]) -> ::std::io::Result<usize> {
+        let sender = self.sender.as_ref().ok_or_else(|| ::std::io::Error::new(::std::io::ErrorKind::BrokenPipe, "pipe shut down"))?;
+        if !buf.is_empty() {
+            sender.send((Instant::now() + self.latency, buf.to_vec()))
+                  .map_err(|_| ::std::io::Error::new(::std::io::ErrorKind::BrokenPipe, "pipe reader dropped"))?;
+        }
+        Ok(buf.len())
+    }
+    fn flush(&mut self) -> ::std::io::Result<()> {
+        Ok(())
+    }
+}
+
+impl Link for PipeWriter {
+    fn shutdown_write(&mut self) -> ::std::io::Result<()> {
+        self.sender = None;
+        Ok(())
+    }
+}
+
+/// The reading end of a `pipe`.
+pub struct PipeReader {
+    receiver: Receiver<(Instant, Vec<u8>)>,
+    // bytes received but not yet read, from `offset`, and the instant from which they may be read.
+    pending: Vec<u8>,
+    offset: usize,
+    readable: Instant,
+    nonblocking: bool,
+}
+
+impl PipeReader {
+    /// Moves the reader into or out of non-blocking mode.
+    ///
+    /// In non-blocking mode, reads return an error of kind `WouldBlock` rather than wait for
+    /// bytes to be written, or to become readable.
+    pub fn set_nonblocking(&mut self, nonblocking: bool) {
+        self.nonblocking = nonblocking;
+    }
+}
+
+impl Read for PipeReader {
+    fn read(&mut self, buf: &mut [u8]) -> ::std::io::Result<usize> {
+        let would_block = || ::std::io::Error::new(::std::io::ErrorKind::WouldBlock, "no bytes readable");
+        if self.offset == self.pending.len() {
+            let received = if self.nonblocking {
+                match self.receiver.try_recv() {
+                    Ok(received) => Some(received),
+                    Err(crossbeam_channel::TryRecvError::Empty) => return Err(would_block()),
+                    Err(crossbeam_channel::TryRecvError::Disconnected) => None,
+                }
+            }
+            else {
+                self.receiver.recv().ok()
+            };
+            match received {
+                Some((readable, bytes)) => {
+                    self.pending = bytes;
+                    self.offset = 0;
+                    self.readable = readable;
+                },
+                None => return Ok(0),
+            }
+        }
+        let now = Instant::now();
+        if self.readable > now {
+            if self.nonblocking { return Err(would_block()); }
+            ::std::thread::sleep(self.readable - now);
+        }
+        let length = ::std::cmp::min(buf.len(), self.pending.len() - self.offset);
+        buf[.. length].copy_from_slice(&self.pending[self.offset .. self.offset + length]);
+        self.offset += length;
+        Ok(length)
+    }
+}
+
+/// Initializes the allocators of a simulated cluster, whose process `i` hosts `process_threads[i]` workers.
+///
+/// Each pair of simulated processes is connected by `config.connections` pairs of pipes, which
+/// delay the bytes written to them by `latency`. The allocators are returned in order of their
+/// worker index, with a guard for the send and receive threads of each simulated process.
+pub fn initialize_simulation(process_threads: Vec<usize>, latency: Duration, config: &NetworkConfig)
+-> ::std::io::Result<(Vec<TcpBuilder<ProcessBuilder>>, Vec<CommsGuard>)>
+{
+    let processes = process_threads.len();
+    let connections = config.connections.max(1);
+
+    // the ends of the pipes of each process, to each other process.
+    let mut readers: Vec<Vec<Vec<PipeReader>>> = (0 .. processes).map(|_| (0 .. processes).map(|_| Vec::new()).collect()).collect();
+    let mut writers: Vec<Vec<Vec<PipeWriter>>> = (0 .. processes).map(|_| (0 .. processes).map(|_| Vec::new()).collect()).collect();
+    // receive threads serving several pipes read from them without blocking.
+    let links = processes.saturating_sub(1) * connections;
+    let (_, recv_threads) = config.threads_for(links);
+    let nonblocking = recv_threads < links;
+    for (process, remotes) in writers.iter_mut().enumerate() {
+        for (remote, pipes) in remotes.iter_mut().enumerate().filter(|(remote, _)| *remote != process) {
+            for _ in 0 .. connections {
+                let (writer, mut reader) = pipe(latency);
+                reader.set_nonblocking(nonblocking);
+                pipes.push(writer);
+                readers[remote][process].push(reader);
+            }
+        }
+    }
+
+    let mut builders = Vec::new();
+    let mut guards = Vec::with_capacity(processes);
+    for (process, (readers, writers)) in readers.into_iter().zip(writers).enumerate() {
+        let links = readers.into_iter().zip(writers).map(|(readers, writers)| readers.into_iter().zip(writers).collect()).collect();
+        let (process_builders, guard) = initialize_networking_from_links(links, process, process_threads.clone(), config, Box::new(|_| None))?;
+        builders.extend(process_builders);
+        guards.push(guard);
+    }
+
+    Ok((builders, guards))
+}
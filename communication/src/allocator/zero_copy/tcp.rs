@@ -1,10 +1,13 @@
 //!
 
 use std::io::{Read, Write};
-use std::net::TcpStream;
+use std::net::{Shutdown, TcpStream};
 use crossbeam_channel::{Sender, Receiver};
 
 use crate::networking::MessageHeader;
+use crate::authentication::{Key, TAG_BYTES, MAX_MESSAGE_BYTES};
+
+use bytes::arc::Bytes;
 
 use super::bytes_slab::BytesSlab;
 use super::bytes_exchange::MergeQueue;
@@ -13,50 +16,184 @@ use logging_core::Logger;
 
 use crate::logging::{CommunicationEvent, CommunicationSetup, MessageEvent, StateEvent};
 
-/// Repeatedly reads from a TcpStream and carves out messages.
+/// A byte stream to another process, written by the send loops.
+///
+/// Once a send loop has written its last message, it shuts down writes to the stream, which
+/// the receive loop at the other end observes as the end of the stream.
+pub trait Link: Write {
+    /// Shuts down writes to the stream.
+    fn shutdown_write(&mut self) -> ::std::io::Result<()>;
+}
+
+impl Link for TcpStream {
+    fn shutdown_write(&mut self) -> ::std::io::Result<()> {
+        self.shutdown(Shutdown::Write)
+    }
+}
+
+/// A stream whose writes wait until it is ready, even if it is non-blocking.
+///
+/// A socket read from without blocking is also written to without blocking, as the two share
+/// the socket's mode. Send loops expect writes to block, and so retry those that would block.
+pub struct BlockingWrites<W: Link>(pub W);
+
+impl<W: Link> Write for BlockingWrites<W> {
+    fn write(&mut self, buf: &[u8]) -> ::std::io::Result<usize> {
+        loop {
+            match self.0.write(buf) {
+                Err(error) if error.kind() == ::std::io::ErrorKind::WouldBlock => ::std::thread::yield_now(),
+                result => return result,
+            }
+        }
+    }
+    fn flush(&mut self) -> ::std::io::Result<()> {
+        loop {
+            match self.0.flush() {
+                Err(error) if error.kind() == ::std::io::ErrorKind::WouldBlock => ::std::thread::yield_now(),
+                result => return result,
+            }
+        }
+    }
+}
+
+impl<W: Link> Link for BlockingWrites<W> {
+    fn shutdown_write(&mut self) -> ::std::io::Result<()> {
+        self.0.shutdown_write()
+    }
+}
+
+/// Repeatedly reads from a TcpStream, or another byte stream, and carves out messages.
 ///
 /// The intended communication pattern is a sequence of (header, message)^* for valid
 /// messages, followed by a header for a zero length message indicating the end of stream.
 /// If the stream ends without being shut down, the receive thread panics in an attempt to
 /// take down the computation and cause the failures to cascade.
-pub fn recv_loop(
-    mut reader: TcpStream,
+pub fn recv_loop<R: Read>(
+    reader: R,
+    targets: Vec<Receiver<MergeQueue>>,
+    worker_offset: usize,
+    process: usize,
+    remote: usize,
+    logger: Option<Logger<CommunicationEvent, CommunicationSetup>>)
+{
+    recv_loop_authenticated(reader, targets, worker_offset, process, remote, None, logger);
+}
+
+/// Repeatedly reads from a byte stream and carves out messages, checking their tags with `key`.
+///
+/// As `recv_loop`, except that when `key` is supplied each message must be followed by its tag,
+/// and the receive thread panics at the first message whose tag does not match, before any of
+/// its bytes are passed to workers, or whose length exceeds `MAX_MESSAGE_BYTES`, before its
+/// bytes are buffered.
+pub fn recv_loop_authenticated<R: Read>(
+    reader: R,
     targets: Vec<Receiver<MergeQueue>>,
     worker_offset: usize,
     process: usize,
     remote: usize,
-    mut logger: Option<Logger<CommunicationEvent, CommunicationSetup>>)
+    key: Option<Key>,
+    logger: Option<Logger<CommunicationEvent, CommunicationSetup>>)
 {
-    // Log the receive thread's start.
-    logger.as_mut().map(|l| l.log(StateEvent { send: false, process, remote, start: true }));
+    recv_loop_shared(vec![RecvConnection { reader, targets, remote, key, logger }], worker_offset, process);
+}
 
-    let mut targets: Vec<MergeQueue> = targets.into_iter().map(|x| x.recv().expect("Failed to receive MergeQueue")).collect();
+/// A connection served by `recv_loop_shared`.
+pub struct RecvConnection<R: Read=TcpStream> {
+    /// The stream to read from.
+    pub reader: R,
+    /// Promises of the queues of the workers receiving from the connection.
+    pub targets: Vec<Receiver<MergeQueue>>,
+    /// The index of the remote process.
+    pub remote: usize,
+    /// The key with which to check the tag of each message, if any.
+    pub key: Option<Key>,
+    /// The logger of the connection.
+    pub logger: Option<Logger<CommunicationEvent, CommunicationSetup>>,
+}
 
-    let mut buffer = BytesSlab::new(20);
+// The state of a connection in `recv_loop_shared`.
+struct RecvState<R: Read> {
+    reader: R,
+    targets: Vec<MergeQueue>,
+    remote: usize,
+    // the key with which to check tags, and the number of messages checked.
+    key: Option<Key>,
+    received: u64,
+    logger: Option<Logger<CommunicationEvent, CommunicationSetup>>,
+    buffer: BytesSlab,
+    // where we stash Bytes before handing them off.
+    stageds: Vec<Vec<Bytes>>,
+    active: bool,
+}
 
-    // Where we stash Bytes before handing them off.
-    let mut stageds = Vec::with_capacity(targets.len());
-    for _ in 0 .. targets.len() {
-        stageds.push(Vec::new());
+/// Repeatedly reads from several TcpStreams, or other byte streams, from one thread.
+///
+/// With more than one connection the readers must be non-blocking, returning an error of kind
+/// `WouldBlock` when no bytes are available, so that one idle connection does not delay the
+/// others of the thread. The thread backs off while none of its connections have bytes.
+pub fn recv_loop_shared<R: Read>(connections: Vec<RecvConnection<R>>, worker_offset: usize, process: usize) {
+
+    let mut connections: Vec<RecvState<R>> = connections.into_iter().map(|connection| {
+
+        let RecvConnection { reader, targets, remote, key, mut logger } = connection;
+
+        // Log the receive thread's start.
+        logger.as_mut().map(|l| l.log(StateEvent { send: false, process, remote, start: true }));
+
+        let targets: Vec<MergeQueue> = targets.into_iter().map(|x| x.recv().expect("Failed to receive MergeQueue")).collect();
+        let stageds = targets.iter().map(|_| Vec::new()).collect();
+        RecvState { reader, targets, remote, key, received: 0, logger, buffer: BytesSlab::new(20), stageds, active: true }
+    }).collect();
+
+    let mut backoff = MIN_BACKOFF;
+    while !connections.is_empty() {
+
+        let mut idle = true;
+        for connection in connections.iter_mut() {
+            idle &= !connection.receive(worker_offset);
+        }
+
+        for connection in connections.iter_mut().filter(|connection| !connection.active) {
+            // Log the receive thread's end.
+            let (remote, logger) = (connection.remote, &mut connection.logger);
+            logger.as_mut().map(|l| l.log(StateEvent { send: false, process, remote, start: false, }));
+        }
+        connections.retain(|connection| connection.active);
+
+        if idle && !connections.is_empty() {
+            ::std::thread::sleep(backoff);
+            backoff = ::std::cmp::min(backoff * 2, MAX_BACKOFF);
+        }
+        else {
+            backoff = MIN_BACKOFF;
+        }
     }
+}
+
+// The least and greatest times for which `recv_loop_shared` sleeps while its connections are idle.
+const MIN_BACKOFF: ::std::time::Duration = ::std::time::Duration::from_micros(10);
+const MAX_BACKOFF: ::std::time::Duration = ::std::time::Duration::from_millis(1);
+
+impl<R: Read> RecvState<R> {
 
-    // Each loop iteration adds to `self.Bytes` and consumes all complete messages.
-    // At the start of each iteration, `self.buffer[..self.length]` represents valid
-    // data, and the remaining capacity is available for reading from the reader.
+    // Reads available bytes, passes complete messages along to their targets, and reports
+    // whether any bytes were read.
     //
-    // Once the buffer fills, we need to copy uncomplete messages to a new shared
-    // allocation and place the existing Bytes into `self.in_progress`, so that it
-    // can be recovered once all readers have read what they need to.
-    let mut active = true;
-    while active {
+    // At the start of each call, `self.buffer.valid()` represents valid data, and the remaining
+    // capacity is available for reading from the reader. Once the buffer fills, incomplete
+    // messages are copied to a new shared allocation, so that the existing allocation can be
+    // recovered once all readers have read what they need to.
+    fn receive(&mut self, worker_offset: usize) -> bool {
 
+        let buffer = &mut self.buffer;
         buffer.ensure_capacity(1);
 
         assert!(!buffer.empty().is_empty());
 
         // Attempt to read some more bytes into self.buffer.
-        let read = match reader.read(&mut buffer.empty()) {
+        let read = match self.reader.read(&mut buffer.empty()) {
             Ok(n) => n,
+            Err(x) if x.kind() == ::std::io::ErrorKind::WouldBlock => return false,
             Err(x) => {
                 // We don't expect this, as socket closure results in Ok(0) reads.
                 println!("Error: {:?}", x);
@@ -72,94 +209,152 @@ pub fn recv_loop(
 
             // TODO: Consolidate message sequences sent to the same worker?
             let peeled_bytes = header.required_bytes();
+            if let Some(key) = self.key.as_ref() {
+                let valid = buffer.valid();
+                if valid.len() < peeled_bytes + TAG_BYTES {
+                    break;
+                }
+                if !key.verify(self.received, &valid[.. peeled_bytes], &valid[peeled_bytes .. peeled_bytes + TAG_BYTES]) {
+                    panic!("Message {} from process {} failed authentication; do the processes share a key?", self.received, self.remote);
+                }
+                self.received += 1;
+            }
             let bytes = buffer.extract(peeled_bytes);
+            if self.key.is_some() {
+                buffer.extract(TAG_BYTES);
+            }
 
             // Record message receipt.
-            logger.as_mut().map(|logger| {
+            self.logger.as_mut().map(|logger| {
                 logger.log(MessageEvent { is_send: false, header, });
             });
 
             if header.length > 0 {
-                stageds[header.target - worker_offset].push(bytes);
+                self.stageds[header.target - worker_offset].push(bytes);
             }
             else {
                 // Shutting down; confirm absence of subsequent data.
-                active = false;
+                self.active = false;
                 if !buffer.valid().is_empty() {
                     panic!("Clean shutdown followed by data.");
                 }
                 buffer.ensure_capacity(1);
-                if reader.read(&mut buffer.empty()).expect("read failure") > 0 {
-                    panic!("Clean shutdown followed by data.");
+                loop {
+                    match self.reader.read(&mut buffer.empty()) {
+                        Ok(0) => break,
+                        Ok(_) => panic!("Clean shutdown followed by data."),
+                        Err(x) if x.kind() == ::std::io::ErrorKind::WouldBlock => ::std::thread::yield_now(),
+                        Err(x) => panic!("read failure: {:?}", x),
+                    }
+                }
+            }
+        }
+
+        // Reject an incomplete message too long to buffer, as its tag is checked only once complete.
+        if self.key.is_some() {
+            if let Some((header, _)) = unsafe { ::abomonation::decode::<MessageHeader>(buffer.valid()) } {
+                if header.length > MAX_MESSAGE_BYTES {
+                    panic!("Message {} from process {} claims {} bytes, more than authenticated messages may have", self.received, self.remote, header.length);
                 }
             }
         }
 
         // Pass bytes along to targets.
-        for (index, staged) in stageds.iter_mut().enumerate() {
+        for (index, staged) in self.stageds.iter_mut().enumerate() {
             // FIXME: try to merge `staged` before handing it to BytesPush::extend
             use crate::allocator::zero_copy::bytes_exchange::BytesPush;
-            targets[index].extend(staged.drain(..));
+            self.targets[index].extend(staged.drain(..));
         }
-    }
 
-    // Log the receive thread's end.
-    logger.as_mut().map(|l| l.log(StateEvent { send: false, process, remote, start: false, }));
+        true
+    }
 }
 
-/// Repeatedly sends messages into a TcpStream.
+/// Repeatedly sends messages into a TcpStream, or another byte stream.
 ///
 /// The intended communication pattern is a sequence of (header, message)^* for valid
 /// messages, followed by a header for a zero length message indicating the end of stream.
-pub fn send_loop(
+pub fn send_loop<W: Link>(
     // TODO: Maybe we don't need BufWriter with consolidation in writes.
-    writer: TcpStream,
+    writer: W,
     sources: Vec<Sender<MergeQueue>>,
     process: usize,
     remote: usize,
-    mut logger: Option<Logger<CommunicationEvent, CommunicationSetup>>)
+    logger: Option<Logger<CommunicationEvent, CommunicationSetup>>)
 {
+    send_loop_shared(vec![SendConnection { writer, sources, remote, key: None, logger }], process, 1 << 16);
+}
 
-    // Log the send thread's start.
-    logger.as_mut().map(|l| l.log(StateEvent { send: true, process, remote, start: true, }));
+/// A connection served by `send_loop_shared`.
+pub struct SendConnection<W: Link=TcpStream> {
+    /// The stream to write to.
+    pub writer: W,
+    /// Promises of the queues of the workers sending on the connection.
+    pub sources: Vec<Sender<MergeQueue>>,
+    /// The index of the remote process.
+    pub remote: usize,
+    /// The key with which to tag each message, if any.
+    pub key: Option<Key>,
+    /// The logger of the connection.
+    pub logger: Option<Logger<CommunicationEvent, CommunicationSetup>>,
+}
+
+// The state of a connection in `send_loop_shared`.
+struct SendState<W: Link> {
+    writer: ::std::io::BufWriter<W>,
+    sources: Vec<MergeQueue>,
+    remote: usize,
+    // the key with which to tag messages, and the number of messages tagged.
+    key: Option<Key>,
+    sent: u64,
+    logger: Option<Logger<CommunicationEvent, CommunicationSetup>>,
+}
+
+/// Repeatedly sends messages into several TcpStreams, or other byte streams, from one thread.
+///
+/// Messages are gathered in a buffer of `write_buffer` bytes for each connection, so that many
+/// small messages are written with one system call; a `write_buffer` of zero writes each message
+/// directly. The writes are blocking, so a slow connection delays the others of the thread.
+pub fn send_loop_shared<W: Link>(connections: Vec<SendConnection<W>>, process: usize, write_buffer: usize) {
 
-    let mut sources: Vec<MergeQueue> = sources.into_iter().map(|x| {
-        let buzzer = crate::buzzer::Buzzer::new();
-        let queue = MergeQueue::new(buzzer);
-        x.send(queue.clone()).expect("failed to send MergeQueue");
-        queue
+    let mut connections: Vec<SendState<W>> = connections.into_iter().map(|connection| {
+
+        let SendConnection { writer, sources, remote, key, mut logger } = connection;
+
+        // Log the send thread's start.
+        logger.as_mut().map(|l| l.log(StateEvent { send: true, process, remote, start: true, }));
+
+        let sources = sources.into_iter().map(|x| {
+            let buzzer = crate::buzzer::Buzzer::new();
+            let queue = MergeQueue::new(buzzer);
+            x.send(queue.clone()).expect("failed to send MergeQueue");
+            queue
+        }).collect();
+
+        let writer = ::std::io::BufWriter::with_capacity(write_buffer, writer);
+        SendState { writer, sources, remote, key, sent: 0, logger }
     }).collect();
 
-    let mut writer = ::std::io::BufWriter::with_capacity(1 << 16, writer);
     let mut stash = Vec::new();
 
-    while !sources.is_empty() {
+    while !connections.is_empty() {
 
-        // TODO: Round-robin better, to release resources fairly when overloaded.
-        for source in sources.iter_mut() {
-            use crate::allocator::zero_copy::bytes_exchange::BytesPull;
-            source.drain_into(&mut stash);
-        }
+        let mut idle = true;
+        for connection in connections.iter_mut() {
 
-        if stash.is_empty() {
-            // No evidence of records to read, but sources not yet empty (at start of loop).
-            // We are going to flush our writer (to move buffered data), double check on the
-            // sources for emptiness and wait on a signal only if we are sure that there will
-            // still be a signal incoming.
-            //
-            // We could get awoken by more data, a channel closing, or spuriously perhaps.
-            writer.flush().expect("Failed to flush writer.");
-            sources.retain(|source| !source.is_complete());
-            if !sources.is_empty() {
-                std::thread::park();
+            // TODO: Round-robin better, to release resources fairly when overloaded.
+            for source in connection.sources.iter_mut() {
+                use crate::allocator::zero_copy::bytes_exchange::BytesPull;
+                source.drain_into(&mut stash);
             }
-        }
-        else {
+
+            idle &= stash.is_empty();
+
             // TODO: Could do scatter/gather write here.
             for mut bytes in stash.drain(..) {
 
                 // Record message sends.
-                logger.as_mut().map(|logger| {
+                connection.logger.as_mut().map(|logger| {
                     let mut offset = 0;
                     while let Some(header) = MessageHeader::try_read(&mut bytes[offset..]) {
                         logger.log(MessageEvent { is_send: true, header, });
@@ -167,10 +362,51 @@ pub fn send_loop(
                     }
                 });
 
-                writer.write_all(&bytes[..]).expect("Write failure in send_loop.");
+                match connection.key.as_ref() {
+                    Some(key) => {
+                        // follow each message with its tag.
+                        let mut offset = 0;
+                        while let Some(header) = MessageHeader::try_read(&mut bytes[offset..]) {
+                            let message = &bytes[offset .. offset + header.required_bytes()];
+                            connection.writer.write_all(message).expect("Write failure in send_loop.");
+                            connection.writer.write_all(&key.tag(connection.sent, message)).expect("Write failure in send_loop.");
+                            connection.sent += 1;
+                            offset += header.required_bytes();
+                        }
+                    },
+                    None => connection.writer.write_all(&bytes[..]).expect("Write failure in send_loop."),
+                }
+            }
+        }
+
+        if idle {
+            // No evidence of records to read, but sources not yet empty (at start of loop).
+            // We are going to flush our writers (to move buffered data), double check on the
+            // sources for emptiness and wait on a signal only if we are sure that there will
+            // still be a signal incoming.
+            //
+            // We could get awoken by more data, a channel closing, or spuriously perhaps.
+            for connection in connections.iter_mut() {
+                connection.writer.flush().expect("Failed to flush writer.");
+                connection.sources.retain(|source| !source.is_complete());
+            }
+            for connection in connections.iter_mut().filter(|connection| connection.sources.is_empty()) {
+                finish(connection, process);
+            }
+            connections.retain(|connection| !connection.sources.is_empty());
+            if !connections.is_empty() {
+                std::thread::park();
             }
         }
     }
+}
+
+// Writes the final zero-length header of a connection, and shuts it down.
+fn finish<W: Link>(connection: &mut SendState<W>, process: usize) {
+
+    let writer = &mut connection.writer;
+    let remote = connection.remote;
+    let logger = &mut connection.logger;
 
     // Write final zero-length header.
     // Would be better with meaningful metadata, but as this stream merges many
@@ -182,9 +418,14 @@ pub fn send_loop(
         length:     0,
         seqno:      0,
     };
-    header.write_to(&mut writer).expect("Failed to write header!");
+    header.write_to(writer).expect("Failed to write header!");
+    if let Some(key) = connection.key.as_ref() {
+        let mut message = Vec::new();
+        header.write_to(&mut message).expect("Failed to write header!");
+        writer.write_all(&key.tag(connection.sent, &message)).expect("Failed to write tag!");
+    }
     writer.flush().expect("Failed to flush writer.");
-    writer.get_mut().shutdown(::std::net::Shutdown::Write).expect("Write shutdown failed");
+    writer.get_mut().shutdown_write().expect("Write shutdown failed");
     logger.as_mut().map(|logger| logger.log(MessageEvent { is_send: true, header }));
 
     // Log the send thread's end.
@@ -0,0 +1,280 @@
+//! A serializing allocator over a user-supplied transport of bytes.
+//!
+//! Transports that timely does not provide itself, for example RDMA, MPI, or the message ports of
+//! a browser, need only move buffers of bytes between workers. A `TransportAllocator` serializes
+//! the messages of each channel into such buffers, with headers identifying their channel and
+//! workers, and routes received buffers to their channels. Transports implement `BytesPush` for
+//! the endpoint sending to each worker and `BytesPull` for the endpoints receiving from them, and
+//! an `AllocateBuilder` that constructs the allocator in each worker thread, for use with
+//! `initialize_from`.
+
+use std::rc::Rc;
+use std::cell::RefCell;
+use std::collections::{VecDeque, HashMap};
+
+use bytes::arc::Bytes;
+
+use crate::networking::MessageHeader;
+
+use crate::{Allocate, Message, Data, Push, Pull};
+use crate::allocator::Event;
+use crate::allocator::canary::Canary;
+
+use super::bytes_exchange::{BytesPush, BytesPull, SendEndpoint};
+
+use super::reorder::ReorderBuffer;
+use super::push_pull::{Pusher, Puller};
+
+/// A serializing allocator over endpoints that push and pull bytes.
+///
+/// Each buffer pushed at a `BytesPush` endpoint contains an integral number of messages, each a
+/// `MessageHeader` followed by its payload, and the `BytesPull` endpoints must present the buffers
+/// sent to this worker unchanged, though buffers from different workers may interleave. Buffers
+/// from one worker on one channel may be reordered only if the allocator is constructed to restore
+/// their order. When waiting for events the allocator parks its thread, and transports should
+/// unpark it when they receive bytes.
+///
+/// # Examples
+/// ```
+/// use std::sync::{Arc, Mutex};
+/// use timely_bytes::arc::Bytes;
+/// use timely_communication::{Allocate, Message};
+/// use timely_communication::allocator::AllocateBuilder;
+/// use timely_communication::allocator::zero_copy::bytes_exchange::{BytesPush, BytesPull};
+/// use timely_communication::allocator::zero_copy::transport::TransportAllocator;
+///
+/// // a transport of shared queues, one for each worker.
+/// #[derive(Clone, Default)]
+/// struct Queue(Arc<Mutex<Vec<Bytes>>>);
+/// impl BytesPush for Queue {
+///     fn extend<I: IntoIterator<Item=Bytes>>(&mut self, iter: I) { self.0.lock().unwrap().extend(iter); }
+/// }
+/// impl BytesPull for Queue {
+///     fn drain_into(&mut self, vec: &mut Vec<Bytes>) { vec.extend(self.0.lock().unwrap().drain(..)); }
+/// }
+///
+/// struct QueueBuilder { index: usize, queues: Vec<Queue> }
+/// impl AllocateBuilder for QueueBuilder {
+///     type Allocator = TransportAllocator<Queue, Queue>;
+///     fn build(self) -> Self::Allocator {
+///         let recv = self.queues[self.index].clone();
+///         TransportAllocator::new(self.index, self.queues.len(), self.queues, vec![recv], false)
+///     }
+/// }
+///
+/// let queues = vec![Queue::default(), Queue::default()];
+/// let builders = (0 .. 2).map(|index| QueueBuilder { index, queues: queues.clone() }).collect();
+///
+/// let guards = timely_communication::initialize_from(builders, Box::new(()), |mut allocator| {
+///     // send each worker its own index.
+///     let (mut senders, mut receiver) = allocator.allocate(0);
+///     for (index, sender) in senders.iter_mut().enumerate() {
+///         sender.send(Message::from_typed(index));
+///     }
+///     allocator.release();
+///
+///     let mut expecting = 2;
+///     while expecting > 0 {
+///         allocator.receive();
+///         if let Some(message) = receiver.recv() {
+///             assert_eq!(*message, allocator.index());
+///             expecting -= 1;
+///         }
+///         allocator.release();
+///     }
+/// }).unwrap();
+///
+/// assert!(guards.join().into_iter().all(|result| result.is_ok()));
+/// ```
+pub struct TransportAllocator<S: BytesPush, R: BytesPull> {
+
+    index:      usize,                              // number out of peers
+    peers:      usize,                              // number of peer allocators (for typed channel allocation).
+
+    events: Rc<RefCell<VecDeque<(usize, Event)>>>,
+
+    canaries: Rc<RefCell<Vec<usize>>>,
+
+    channel_id_bound: Option<usize>,
+
+    // sending, receiving, and responding to binary buffers.
+    staged:     Vec<Bytes>,
+    sends:      Vec<Rc<RefCell<SendEndpoint<S>>>>,   // sends[x] -> goes to worker x.
+    recvs:      Vec<R>,                             // recvs[x] <- from some workers.
+    to_local:   HashMap<usize, Rc<RefCell<VecDeque<Bytes>>>>,          // to worker-local typed pullers.
+    reorder:    Option<ReorderBuffer>,                          // restores the send order of received messages, if needed.
+}
+
+impl<S: BytesPush, R: BytesPull> TransportAllocator<S, R> {
+    /// Creates an allocator for worker `index` of `peers`, sending to worker `x` through `sends[x]`
+    /// and receiving through `recvs`.
+    ///
+    /// If `reorder` is set, the messages received from each worker on each channel are delivered in
+    /// the order they were sent, as transports that may reorder buffers require. Transports that
+    /// deliver the buffers from each worker in order should not set it, and avoid its cost.
+    ///
+    /// # Panics
+    ///
+    /// Panics if there is not one send endpoint for each worker.
+    pub fn new(index: usize, peers: usize, sends: Vec<S>, recvs: Vec<R>, reorder: bool) -> Self {
+        assert_eq!(sends.len(), peers, "expected one send endpoint for each of {} workers", peers);
+        TransportAllocator {
+            index,
+            peers,
+            events: Rc::new(RefCell::new(VecDeque::new())),
+            canaries: Rc::new(RefCell::new(Vec::new())),
+            channel_id_bound: None,
+            staged: Vec::new(),
+            sends: sends.into_iter().map(|send| Rc::new(RefCell::new(SendEndpoint::new(send)))).collect(),
+            recvs,
+            to_local: HashMap::new(),
+            reorder: if reorder { Some(ReorderBuffer::default()) } else { None },
+        }
+    }
+}
+
+impl<S: BytesPush+'static, R: BytesPull> Allocate for TransportAllocator<S, R> {
+    fn index(&self) -> usize { self.index }
+    fn peers(&self) -> usize { self.peers }
+    fn allocate<T: Data>(&mut self, identifier: usize) -> (Vec<Box<dyn Push<Message<T>>>>, Box<dyn Pull<Message<T>>>) {
+
+        // Assume and enforce in-order identifier allocation.
+        if let Some(bound) = self.channel_id_bound {
+            assert!(bound < identifier);
+        }
+        self.channel_id_bound = Some(identifier);
+
+        let mut pushes = Vec::<Box<dyn Push<Message<T>>>>::with_capacity(self.peers());
+
+        for target_index in 0 .. self.peers() {
+
+            // message header template.
+            let header = MessageHeader {
+                channel:    identifier,
+                source:     self.index,
+                target:     target_index,
+                length:     0,
+                seqno:      0,
+            };
+
+            // create, box, and stash new process_binary pusher.
+            pushes.push(Box::new(Pusher::new(header, self.sends[target_index].clone())));
+        }
+
+        let channel =
+        self.to_local
+            .entry(identifier)
+            .or_insert_with(|| Rc::new(RefCell::new(VecDeque::new())))
+            .clone();
+
+        use crate::allocator::counters::Puller as CountPuller;
+        let canary = Canary::new(identifier, self.canaries.clone());
+        let puller = Box::new(CountPuller::new(Puller::new(channel, canary), identifier, self.events().clone()));
+
+        (pushes, puller)
+    }
+
+    // Perform preparatory work, most likely reading binary buffers from self.recv.
+    #[inline(never)]
+    fn receive(&mut self) {
+
+        // Check for channels whose `Puller` has been dropped.
+        let mut canaries = self.canaries.borrow_mut();
+        for dropped_channel in canaries.drain(..) {
+            let _dropped =
+            self.to_local
+                .remove(&dropped_channel)
+                .expect("non-existent channel dropped");
+            if let Some(reorder) = self.reorder.as_mut() {
+                reorder.drop_channel(dropped_channel);
+            }
+            // Borrowed channels may be non-empty, if the dataflow was forcibly
+            // dropped. The contract is that if a dataflow is dropped, all other
+            // workers will drop the dataflow too, without blocking indefinitely
+            // on events from it.
+            // assert!(dropped.borrow().is_empty());
+        }
+        std::mem::drop(canaries);
+
+        let mut events = self.events.borrow_mut();
+        let mut ready = Vec::new();
+
+        for recv in self.recvs.iter_mut() {
+            recv.drain_into(&mut self.staged);
+        }
+
+        for mut bytes in self.staged.drain(..) {
+
+            // We expect that `bytes` contains an integral number of messages.
+            // No splitting occurs across allocations.
+            while bytes.len() > 0 {
+
+                if let Some(header) = MessageHeader::try_read(&mut bytes[..]) {
+
+                    // Get the header and payload, ditch the header.
+                    let mut peel = bytes.extract_to(header.required_bytes());
+                    let _ = peel.extract_to(::std::mem::size_of::<MessageHeader>());
+
+                    // Discard messages for channels that have been dropped.
+                    let dropped = self.channel_id_bound.map(|b| header.channel <= b).unwrap_or(false) && !self.to_local.contains_key(&header.channel);
+                    if dropped { continue; }
+
+                    // Deliver messages in the order they were sent.
+                    match self.reorder.as_mut() {
+                        Some(reorder) => {
+                            if let Err(error) = reorder.accept(header, peel, &mut ready) {
+                                panic!("transport delivered a message twice: {}", error);
+                            }
+                        },
+                        None => ready.push((header, peel)),
+                    }
+                    for (header, peel) in ready.drain(..) {
+                        // Increment message count for channel.
+                        events.push_back((header.channel, Event::Pushed(1)));
+
+                        // Ensure that a queue exists; we may receive data before allocating, and shouldn't block.
+                        self.to_local
+                            .entry(header.channel)
+                            .or_insert_with(|| Rc::new(RefCell::new(VecDeque::new())))
+                            .borrow_mut()
+                            .push_back(peel);
+                    }
+                }
+                else {
+                    println!("failed to read full header!");
+                }
+            }
+        }
+    }
+
+    // Perform postparatory work, most likely sending un-full binary buffers.
+    fn release(&mut self) {
+        // Publish outgoing byte ledgers.
+        for send in self.sends.iter_mut() {
+            send.borrow_mut().publish();
+        }
+
+        // OPTIONAL: Tattle on channels sitting on borrowed data.
+        // OPTIONAL: Perhaps copy borrowed data into owned allocation.
+        // for (index, list) in self.to_local.iter() {
+        //     let len = list.borrow_mut().len();
+        //     if len > 0 {
+        //         eprintln!("Warning: worker {}, undrained channel[{}].len() = {}", self.index, index, len);
+        //     }
+        // }
+    }
+
+    fn events(&self) -> &Rc<RefCell<VecDeque<(usize, Event)>>> {
+        &self.events
+    }
+    fn await_events(&self, duration: Option<std::time::Duration>) {
+        if self.events.borrow().is_empty() {
+            if let Some(duration) = duration {
+                std::thread::park_timeout(duration);
+            }
+            else {
+                std::thread::park();
+            }
+        }
+    }
+}
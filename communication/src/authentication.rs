@@ -0,0 +1,94 @@
+//! Authentication of the messages exchanged between processes.
+//!
+//! Processes that share a key attach a tag to each message they send to each other, computed
+//! with HMAC-SHA256 over the message and its position on its connection. The receiving process
+//! checks the tag of each message before delivering it to workers, and stops the computation at
+//! the first message whose tag does not match, whether it was altered, dropped, reordered, or
+//! sent by a process with a different key, for example one of another computation that connected
+//! to the same ports. This authenticates messages without encrypting them.
+
+use std::fmt;
+use std::path::Path;
+
+use hmac::{Hmac, Mac, NewMac};
+use sha2::Sha256;
+
+/// The number of bytes of the tag attached to each message.
+pub const TAG_BYTES: usize = 32;
+
+/// The largest authenticated message, in bytes, that a receiving process will buffer.
+///
+/// The length of a message is read from its header before its tag can be checked, and a longer
+/// claimed length stops the computation rather than being buffered.
+pub const MAX_MESSAGE_BYTES: usize = 1 << 30;
+
+/// A key shared by the processes of a computation, with which they authenticate their messages.
+///
+/// # Examples
+/// ```
+/// use timely_communication::authentication::Key;
+///
+/// let key = Key::new(b"shared secret".to_vec());
+/// let tag = key.tag(0, b"message");
+/// assert!(key.verify(0, b"message", &tag));
+/// assert!(!key.verify(1, b"message", &tag));
+/// assert!(!key.verify(0, b"massage", &tag));
+/// assert!(!Key::new(b"other secret".to_vec()).verify(0, b"message", &tag));
+/// ```
+#[derive(Clone)]
+pub struct Key {
+    bytes: Vec<u8>,
+    // the MAC state after absorbing the key.
+    mac: Hmac<Sha256>,
+}
+
+impl Key {
+    /// A key from its bytes.
+    pub fn new(key: Vec<u8>) -> Self {
+        let mac = Hmac::new_from_slice(&key).expect("HMAC accepts keys of any length");
+        Key { bytes: key, mac }
+    }
+
+    /// A key from the contents of the file at `path`.
+    pub fn from_file<P: AsRef<Path>>(path: P) -> ::std::io::Result<Self> {
+        let key = ::std::fs::read(path)?;
+        if key.is_empty() {
+            return Err(::std::io::Error::new(::std::io::ErrorKind::InvalidData, "empty key file"));
+        }
+        Ok(Key::new(key))
+    }
+
+    /// The tag of `message`, the `position`th message sent on its connection.
+    pub fn tag(&self, position: u64, message: &[u8]) -> [u8; TAG_BYTES] {
+        let mut tag = [0u8; TAG_BYTES];
+        tag.copy_from_slice(&self.mac(position, message).finalize().into_bytes());
+        tag
+    }
+
+    /// True if `tag` is the tag of `message`, the `position`th message received on its connection.
+    pub fn verify(&self, position: u64, message: &[u8], tag: &[u8]) -> bool {
+        // `verify` compares in constant time, so that the time taken does not reveal where the tags differ.
+        self.mac(position, message).verify(tag).is_ok()
+    }
+
+    fn mac(&self, position: u64, message: &[u8]) -> Hmac<Sha256> {
+        let mut mac = self.mac.clone();
+        mac.update(&position.to_be_bytes());
+        mac.update(message);
+        mac
+    }
+}
+
+impl PartialEq for Key {
+    fn eq(&self, other: &Self) -> bool {
+        self.bytes == other.bytes
+    }
+}
+
+impl Eq for Key { }
+
+impl fmt::Debug for Key {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("Key(..)")
+    }
+}
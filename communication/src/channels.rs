@@ -0,0 +1,123 @@
+//! Adapters that compose `Push` implementors into channel plumbing.
+//!
+//! Each adapter wraps one or more pushers and is itself a pusher, so that fan-out, conversion, and
+//! filtering can be assembled around the pushers of an allocator without new `Push` implementations.
+//! Flushes, signalled by pushing `None`, are forwarded to every wrapped pusher.
+//!
+//! # Examples
+//! ```
+//! use std::rc::Rc;
+//! use std::cell::RefCell;
+//! use timely_communication::Push;
+//! use timely_communication::channels::{Tee, Map, Filter};
+//!
+//! // A pusher that records the elements pushed to it.
+//! struct Record(Rc<RefCell<Vec<u64>>>);
+//! impl Push<u64> for Record {
+//!     fn push(&mut self, element: &mut Option<u64>) {
+//!         self.0.borrow_mut().extend(element.take());
+//!     }
+//! }
+//!
+//! let all = Rc::new(RefCell::new(Vec::new()));
+//! let even = Rc::new(RefCell::new(Vec::new()));
+//!
+//! // parse strings, and deliver all values to one pusher and the even values to another.
+//! let mut pusher = Map::new(Tee::new(vec![
+//!     Box::new(Record(all.clone())) as Box<dyn Push<u64>>,
+//!     Box::new(Filter::new(Record(even.clone()), |x: &u64| x % 2 == 0)),
+//! ]), |text: &str| text.parse::<u64>().unwrap());
+//!
+//! for text in ["1", "2", "3", "4"].iter() {
+//!     pusher.send(*text);
+//! }
+//! pusher.done();
+//!
+//! assert_eq!(*all.borrow(), vec![1, 2, 3, 4]);
+//! assert_eq!(*even.borrow(), vec![2, 4]);
+//! ```
+
+use crate::Push;
+
+/// Pushes each element to all of a list of pushers.
+///
+/// All but the last pusher receive a clone of the element; the last receives the element itself,
+/// and any value it returns is returned to the caller.
+pub struct Tee<P> {
+    pushers: Vec<P>,
+}
+
+impl<P> Tee<P> {
+    /// Wraps `pushers`, which each receive every element.
+    pub fn new(pushers: Vec<P>) -> Self {
+        Tee { pushers }
+    }
+
+    /// Adds `pusher` to the pushers receiving each element.
+    pub fn add_pusher(&mut self, pusher: P) {
+        self.pushers.push(pusher);
+    }
+}
+
+impl<T: Clone, P: Push<T>> Push<T> for Tee<P> {
+    #[inline]
+    fn push(&mut self, element: &mut Option<T>) {
+        if let Some((last, rest)) = self.pushers.split_last_mut() {
+            for pusher in rest {
+                pusher.push(&mut element.clone());
+            }
+            last.push(element);
+        }
+        else {
+            *element = None;
+        }
+    }
+}
+
+/// Converts each element with `logic` before pushing it.
+///
+/// Values returned by the wrapped pusher have the converted type and are dropped.
+pub struct Map<P, F> {
+    pusher: P,
+    logic: F,
+}
+
+impl<P, F> Map<P, F> {
+    /// Wraps `pusher`, which receives each element converted by `logic`.
+    pub fn new(pusher: P, logic: F) -> Self {
+        Map { pusher, logic }
+    }
+}
+
+impl<T, U, P: Push<U>, F: FnMut(T)->U> Push<T> for Map<P, F> {
+    #[inline]
+    fn push(&mut self, element: &mut Option<T>) {
+        let mut converted = element.take().map(&mut self.logic);
+        self.pusher.push(&mut converted);
+    }
+}
+
+/// Pushes only the elements satisfying `predicate`.
+///
+/// Rejected elements are dropped, and flushes are always forwarded.
+pub struct Filter<P, F> {
+    pusher: P,
+    predicate: F,
+}
+
+impl<P, F> Filter<P, F> {
+    /// Wraps `pusher`, which receives the elements for which `predicate` holds.
+    pub fn new(pusher: P, predicate: F) -> Self {
+        Filter { pusher, predicate }
+    }
+}
+
+impl<T, P: Push<T>, F: FnMut(&T)->bool> Push<T> for Filter<P, F> {
+    #[inline]
+    fn push(&mut self, element: &mut Option<T>) {
+        match element {
+            Some(value) if !(self.predicate)(value) => { *element = None; },
+            _ => self.pusher.push(element),
+        }
+    }
+}
@@ -8,11 +8,15 @@ use getopts;
 use std::sync::Arc;
 
 use std::any::Any;
+use std::time::Duration;
 
 use crate::allocator::thread::ThreadBuilder;
 use crate::allocator::{AllocateBuilder, Process, Generic, GenericBuilder};
 use crate::allocator::zero_copy::allocator_process::ProcessBuilder;
-use crate::allocator::zero_copy::initialize::initialize_networking;
+use crate::allocator::zero_copy::initialize::{initialize_networking, NetworkConfig};
+use crate::allocator::zero_copy::simulated::initialize_simulation;
+#[cfg(feature = "getopts")]
+use crate::authentication::Key;
 
 use crate::logging::{CommunicationSetup, CommunicationEvent};
 use logging_core::Logger;
@@ -27,6 +31,9 @@ pub enum Config {
     /// Use one process with an indicated number of threads. Use zero-copy exchange channels.
     ProcessBinary(usize),
     /// Expect multiple processes.
+    ///
+    /// Construct with `Config::cluster`, as further fields may be added.
+    #[non_exhaustive]
     Cluster {
         /// Number of per-process worker threads
         threads: usize,
@@ -34,14 +41,78 @@ pub enum Config {
         process: usize,
         /// Addresses of all processes
         addresses: Vec<String>,
+        /// Number of worker threads of each process, if they differ; otherwise each has `threads`
+        process_threads: Option<Vec<usize>>,
+        /// Tuning of the connections between processes
+        network: NetworkConfig,
         /// Verbosely report connection process
         report: bool,
         /// Closure to create a new logger for a communication thread
         log_fn: Box<dyn Fn(CommunicationSetup) -> Option<Logger<CommunicationEvent, CommunicationSetup>> + Send + Sync>,
-    }
+    },
+    /// Simulate multiple processes within this process, connected by in-memory pipes.
+    ///
+    /// Workers of different simulated processes exchange serialized data through send and
+    /// receive threads, as they would over a network, so that distributed behavior can be
+    /// tested without sockets. Construct with `Config::simulated`, as further fields may be added.
+    #[non_exhaustive]
+    Simulated {
+        /// Number of worker threads of each simulated process
+        process_threads: Vec<usize>,
+        /// Delay of the bytes sent between simulated processes
+        latency: Duration,
+        /// Configuration of the connections between simulated processes
+        network: NetworkConfig,
+    },
 }
 
 impl Config {
+    /// A cluster of processes at `addresses`, of which this is `process`, each with `threads` worker threads.
+    ///
+    /// The connections between processes have the default `NetworkConfig`, and are established
+    /// without reporting their progress or logging.
+    ///
+    /// # Examples
+    /// ```
+    /// use timely_communication::{Config, NetworkConfig};
+    ///
+    /// let addresses = vec!["localhost:2101".to_owned(), "localhost:2102".to_owned()];
+    /// let config = Config::cluster(2, 0, addresses).network(NetworkConfig::default().connections(2));
+    /// ```
+    pub fn cluster(threads: usize, process: usize, addresses: Vec<String>) -> Config {
+        Config::Cluster {
+            threads,
+            process,
+            addresses,
+            process_threads: None,
+            network: NetworkConfig::default(),
+            report: false,
+            log_fn: Box::new(|_| None),
+        }
+    }
+
+    /// Simulated processes, process `i` with `process_threads[i]` worker threads, whose bytes are delayed by `latency`.
+    ///
+    /// The connections between simulated processes have the default `NetworkConfig`.
+    pub fn simulated(process_threads: Vec<usize>, latency: Duration) -> Config {
+        Config::Simulated {
+            process_threads,
+            latency,
+            network: NetworkConfig::default(),
+        }
+    }
+
+    /// Sets the configuration of the connections between processes.
+    ///
+    /// This has no effect on configurations of a single process.
+    pub fn network(mut self, config: NetworkConfig) -> Self {
+        match &mut self {
+            Config::Cluster { network, .. } | Config::Simulated { network, .. } => *network = config,
+            _ => { },
+        }
+        self
+    }
+
     /// Installs options into a [`getopts::Options`] struct that corresponds
     /// to the parameters in the configuration.
     ///
@@ -58,6 +129,15 @@ impl Config {
         opts.optopt("n", "processes", "number of processes", "NUM");
         opts.optopt("h", "hostfile", "text file whose lines are process addresses", "FILE");
         opts.optflag("r", "report", "reports connection progress");
+        opts.optopt("", "process-threads", "comma-separated numbers of worker threads of each process", "NUMS");
+        opts.optopt("", "connections", "number of connections to each other process", "NUM");
+        opts.optopt("", "send-threads", "number of threads sending to other processes (default: one per connection)", "NUM");
+        opts.optopt("", "recv-threads", "number of threads receiving from other processes (default: one per connection)", "NUM");
+        opts.optopt("", "write-buffer", "bytes of messages gathered before each network write", "BYTES");
+        opts.optopt("", "nodelay", "whether to send small network writes immediately (default: true)", "BOOL");
+        opts.optopt("", "authentication-key", "file whose contents authenticate the messages between processes", "FILE");
+        opts.optflag("", "simulate", "simulate the -n processes within this process, without sockets");
+        opts.optopt("", "simulated-latency", "milliseconds by which simulated processes delay the bytes between them", "MILLIS");
     }
 
     /// Instantiates a configuration based upon the parsed options in `matches`.
@@ -70,12 +150,56 @@ impl Config {
     /// it is by default.
     #[cfg(feature = "getopts")]
     pub fn from_matches(matches: &getopts::Matches) -> Result<Config, String> {
-        let threads = matches.opt_get_default("w", 1_usize).map_err(|e| e.to_string())?;
+        let mut threads = matches.opt_get_default("w", 1_usize).map_err(|e| e.to_string())?;
         let process = matches.opt_get_default("p", 0_usize).map_err(|e| e.to_string())?;
         let processes = matches.opt_get_default("n", 1_usize).map_err(|e| e.to_string())?;
+        let process_threads = match matches.opt_str("process-threads") {
+            Some(counts) => {
+                let counts = counts.split(',').map(|count| count.trim().parse::<usize>().map_err(|e| e.to_string())).collect::<Result<Vec<_>, _>>()?;
+                if counts.len() != processes {
+                    return Err(format!("--process-threads lists {} processes, but -n: {}", counts.len(), processes));
+                }
+                let mine = *counts.get(process).ok_or_else(|| format!("-p: {} is not a process", process))?;
+                if matches.opt_present("w") && mine != threads {
+                    return Err(format!("--process-threads lists {} threads for this process, but -w: {}", mine, threads));
+                }
+                threads = mine;
+                Some(counts)
+            },
+            None => None,
+        };
         let report = matches.opt_present("report");
+        let defaults = NetworkConfig::default();
+        let network = NetworkConfig {
+            connections: matches.opt_get_default("connections", defaults.connections).map_err(|e| e.to_string())?,
+            send_threads: matches.opt_get::<usize>("send-threads").map_err(|e| e.to_string())?,
+            recv_threads: matches.opt_get::<usize>("recv-threads").map_err(|e| e.to_string())?,
+            write_buffer: matches.opt_get_default("write-buffer", defaults.write_buffer).map_err(|e| e.to_string())?,
+            nodelay: matches.opt_get_default("nodelay", defaults.nodelay).map_err(|e| e.to_string())?,
+            key: match matches.opt_str("authentication-key") {
+                Some(path) => Some(Key::from_file(&path).map_err(|e| format!("failed to read key from {}: {}", path, e))?),
+                None => None,
+            },
+        };
+        if network.connections == 0 {
+            return Err("--connections must be at least 1".to_owned());
+        }
+        if network.send_threads == Some(0) {
+            return Err("--send-threads must be at least 1".to_owned());
+        }
+        if network.recv_threads == Some(0) {
+            return Err("--recv-threads must be at least 1".to_owned());
+        }
 
-        if processes > 1 {
+        if matches.opt_present("simulate") {
+            let latency = matches.opt_get_default("simulated-latency", 0_u64).map_err(|e| e.to_string())?;
+            Ok(Config::Simulated {
+                process_threads: process_threads.unwrap_or_else(|| vec![threads; processes]),
+                latency: Duration::from_millis(latency),
+                network,
+            })
+        }
+        else if processes > 1 {
             let mut addresses = Vec::new();
             if let Some(hosts) = matches.opt_str("h") {
                 let file = ::std::fs::File::open(hosts.clone()).map_err(|e| e.to_string())?;
@@ -98,6 +222,8 @@ impl Config {
                 threads,
                 process,
                 addresses,
+                process_threads,
+                network,
                 report,
                 log_fn: Box::new( | _ | None),
             })
@@ -134,8 +260,23 @@ impl Config {
             Config::ProcessBinary(threads) => {
                 Ok((ProcessBuilder::new_vector(threads).into_iter().map(|x| GenericBuilder::ProcessBinary(x)).collect(), Box::new(())))
             },
-            Config::Cluster { threads, process, addresses, report, log_fn } => {
-                match initialize_networking(addresses, process, threads, report, log_fn) {
+            Config::Simulated { process_threads, latency, network } => {
+                if process_threads.is_empty() || process_threads.contains(&0) {
+                    return Err(format!("cannot simulate processes with worker threads {:?}", process_threads));
+                }
+                match initialize_simulation(process_threads, latency, &network) {
+                    Ok((stuff, guards)) => {
+                        Ok((stuff.into_iter().map(GenericBuilder::ZeroCopy).collect(), Box::new(guards)))
+                    },
+                    Err(err) => Err(format!("failed to initialize simulation: {}", err))
+                }
+            },
+            Config::Cluster { threads, process, addresses, process_threads, network, report, log_fn } => {
+                let process_threads = process_threads.unwrap_or_else(|| vec![threads; addresses.len()]);
+                if process_threads.len() != addresses.len() || process_threads.get(process) != Some(&threads) {
+                    return Err(format!("worker threads {:?} do not match {} processes with {} threads in process {}", process_threads, addresses.len(), threads, process));
+                }
+                match initialize_networking(addresses, process, process_threads, &network, report, log_fn) {
                     Ok((stuff, guard)) => {
                         Ok((stuff.into_iter().map(|x| GenericBuilder::ZeroCopy(x)).collect(), Box::new(guard)))
                     },
@@ -315,10 +456,16 @@ impl<T:Send+'static> WorkerGuards<T> {
     }
 
     /// Waits on the worker threads and returns the results they produce.
+    ///
+    /// A worker that panicked with a `&str` or `String` payload reports its message as the error.
     pub fn join(mut self) -> Vec<Result<T, String>> {
         self.guards
             .drain(..)
-            .map(|guard| guard.join().map_err(|e| format!("{:?}", e)))
+            .map(|guard| guard.join().map_err(|e| {
+                e.downcast_ref::<&str>().map(|message| message.to_string())
+                    .or_else(|| e.downcast_ref::<String>().cloned())
+                    .unwrap_or_else(|| format!("{:?}", e))
+            }))
             .collect()
     }
 }
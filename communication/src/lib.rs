@@ -94,6 +94,8 @@ pub mod initialize;
 pub mod logging;
 pub mod message;
 pub mod buzzer;
+pub mod channels;
+pub mod authentication;
 
 use std::any::Any;
 
@@ -105,6 +107,7 @@ use abomonation::Abomonation;
 pub use allocator::Generic as Allocator;
 pub use allocator::Allocate;
 pub use initialize::{initialize, initialize_from, Config, WorkerGuards};
+pub use allocator::zero_copy::initialize::NetworkConfig;
 pub use message::Message;
 
 /// A composite trait for types that may be used with channels.
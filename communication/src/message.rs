@@ -1,6 +1,7 @@
 //! Types wrapping typed data.
 
-use std::sync::Arc;
+use std::cell::UnsafeCell;
+use std::sync::{Arc, Once};
 use bytes::arc::Bytes;
 use abomonation;
 use crate::Data;
@@ -43,6 +44,25 @@ impl<'a, T: Clone+'a> RefOrMut<'a, T> {
 }
 
 /// A wrapped message which may be either typed or binary data.
+///
+/// Messages are cloned cheaply once shared, as by `from_arc`, and clones of a shared message are
+/// encoded at most once however many of them are sent to other processes. This suits sending the
+/// same data to several workers.
+///
+/// # Examples
+/// ```
+/// use timely_communication::Message;
+///
+/// let shared = Message::from_arc(std::sync::Arc::new(vec![1u64, 2, 3]));
+/// let copies = (0 .. 3).map(|_| shared.clone()).collect::<Vec<_>>();
+///
+/// let mut bytes = Vec::new();
+/// for copy in copies.iter() {
+///     copy.into_bytes(&mut bytes);
+/// }
+/// assert_eq!(bytes.len(), 3 * shared.length_in_bytes());
+/// assert!(copies.iter().all(|copy| **copy == vec![1, 2, 3]));
+/// ```
 pub struct Message<T> {
     payload: MessageContents<T>,
 }
@@ -51,10 +71,49 @@ pub struct Message<T> {
 enum MessageContents<T> {
     /// Binary representation. Only available as a reference.
     Binary(abomonation::abomonated::Abomonated<T, Bytes>),
+    /// Binary representation, decoded on first access, and written unchanged if sent on.
+    #[cfg(feature = "bincode")]
+    Encoded(Bytes, OnceCell<T>, fn(&[u8]) -> T),
     /// Rust typed instance. Available for ownership.
     Owned(T),
-    /// Atomic reference counted. Only available as a reference.
-    Arc(Arc<T>),
+    /// Atomic reference counted, with the binary representation shared by its clones once
+    /// computed. Only available as a reference.
+    Arc(Arc<T>, Arc<OnceCell<Vec<u8>>>),
+}
+
+/// A value computed at most once, and read through shared references once computed.
+struct OnceCell<T> {
+    once: Once,
+    value: UnsafeCell<Option<T>>,
+}
+
+// The value is written only within `once.call_once`, and read only once `once` has completed.
+unsafe impl<T: Send + Sync> Sync for OnceCell<T> { }
+
+impl<T> OnceCell<T> {
+    fn new() -> Self {
+        OnceCell { once: Once::new(), value: UnsafeCell::new(None) }
+    }
+    /// The value, if it has been computed.
+    fn get(&self) -> Option<&T> {
+        if self.once.is_completed() { unsafe { (*self.value.get()).as_ref() } }
+        else { None }
+    }
+    /// The value, computed by `init` if it has not yet been computed.
+    fn get_or_init<F: FnOnce() -> T>(&self, init: F) -> &T {
+        self.once.call_once(|| unsafe { *self.value.get() = Some(init()); });
+        self.get().expect("OnceCell value taken")
+    }
+    /// Removes and returns the value, if it has been computed.
+    #[cfg(feature = "bincode")]
+    fn take(&mut self) -> Option<T> {
+        ::std::mem::replace(self, OnceCell::new()).into_inner()
+    }
+    /// Returns the value, if it has been computed.
+    #[cfg(feature = "bincode")]
+    fn into_inner(self) -> Option<T> {
+        self.value.into_inner()
+    }
 }
 
 impl<T> Message<T> {
@@ -64,38 +123,86 @@ impl<T> Message<T> {
     }
     /// Wrap a shared typed item as a message.
     pub fn from_arc(typed: Arc<T>) -> Self {
-        Message { payload: MessageContents::Arc(typed) }
+        Message { payload: MessageContents::Arc(typed, Arc::new(OnceCell::new())) }
     }
     /// Destructures and returns any typed data.
     pub fn if_typed(self) -> Option<T> {
         match self.payload {
             MessageContents::Binary(_) => None,
+            #[cfg(feature = "bincode")]
+            MessageContents::Encoded(..) => None,
             MessageContents::Owned(typed) => Some(typed),
-            MessageContents::Arc(_) => None,
+            MessageContents::Arc(..) => None,
         }
     }
     /// Returns a mutable reference, if typed.
     pub fn if_mut(&mut self) -> Option<&mut T> {
         match &mut self.payload {
             MessageContents::Binary(_) => None,
+            #[cfg(feature = "bincode")]
+            MessageContents::Encoded(..) => None,
             MessageContents::Owned(typed) => Some(typed),
-            MessageContents::Arc(_) => None,
+            MessageContents::Arc(..) => None,
         }
     }
     /// Returns an immutable or mutable typed reference.
     ///
     /// This method returns a mutable reference if the underlying data are typed Rust
     /// instances, which admit mutation, and it returns an immutable reference if the
-    /// data are serialized binary data.
+    /// data are serialized binary data. Binary data decoded on access are decoded into typed
+    /// Rust instances, and so admit mutation.
     pub fn as_ref_or_mut(&mut self) -> RefOrMut<T> {
+        #[cfg(feature = "bincode")]
+        self.decode();
         match &mut self.payload {
             MessageContents::Binary(bytes) => { RefOrMut::Ref(bytes) },
+            #[cfg(feature = "bincode")]
+            MessageContents::Encoded(..) => unreachable!(),
             MessageContents::Owned(typed) => { RefOrMut::Mut(typed) },
-            MessageContents::Arc(typed) => { RefOrMut::Ref(typed) },
+            MessageContents::Arc(typed, _) => { RefOrMut::Ref(typed) },
+        }
+    }
+
+    /// Replaces binary data decoded on access with the typed instances they decode to.
+    #[cfg(feature = "bincode")]
+    fn decode(&mut self) {
+        if let MessageContents::Encoded(bytes, typed, decode) = &mut self.payload {
+            let typed = typed.take().unwrap_or_else(|| decode(&bytes[..]));
+            self.payload = MessageContents::Owned(typed);
         }
     }
 }
 
+/// The binary representation of `typed`, computed by `encode` once for all of its clones.
+///
+/// The representation is retained only if the message has been cloned, or a clone has already
+/// retained it, as otherwise it would be written only once.
+fn shared_bytes<'a, T, E: FnOnce(&T, &mut Vec<u8>)>(typed: &Arc<T>, bytes: &'a Arc<OnceCell<Vec<u8>>>, encode: E) -> Option<&'a [u8]> {
+    if Arc::strong_count(bytes) > 1 || bytes.get().is_some() {
+        Some(bytes.get_or_init(|| {
+            let mut buffer = Vec::new();
+            encode(typed, &mut buffer);
+            buffer
+        }))
+    }
+    else {
+        None
+    }
+}
+
+impl<T: Clone> Clone for Message<T> {
+    /// Clones the message, cheaply if it is atomic reference counted.
+    ///
+    /// Binary data are cloned as typed Rust instances.
+    fn clone(&self) -> Self {
+        let payload = match &self.payload {
+            MessageContents::Arc(typed, bytes) => MessageContents::Arc(typed.clone(), bytes.clone()),
+            _ => MessageContents::Owned((**self).clone()),
+        };
+        Message { payload }
+    }
+}
+
 // These methods require `T` to implement `Abomonation`, for serialization functionality.
 #[cfg(not(feature = "bincode"))]
 impl<T: Data> Message<T> {
@@ -116,11 +223,20 @@ impl<T: Data> Message<T> {
         match &self.payload {
             MessageContents::Binary(bytes) => { bytes.as_bytes().len() },
             MessageContents::Owned(typed) => { abomonation::measure(typed) },
-            MessageContents::Arc(typed) =>{ abomonation::measure::<T>(&**typed) } ,
+            MessageContents::Arc(typed, bytes) => {
+                match shared_bytes(typed, bytes, |typed, buffer| unsafe { abomonation::encode(typed, buffer).expect("Abomonation::encode failed") }) {
+                    Some(bytes) => bytes.len(),
+                    None => abomonation::measure::<T>(&**typed),
+                }
+            },
         }
     }
 
     /// Writes the binary representation into `writer`.
+    ///
+    /// The elements of a vector are written by a single copy of their memory, followed by any
+    /// memory the elements own. For types that own no memory, for example `u64`, `(u32, u32)`,
+    /// or `f64`, encoding a batch is a single copy, and decoding it performs no copies at all.
     pub fn into_bytes<W: ::std::io::Write>(&self, writer: &mut W) {
         match &self.payload {
             MessageContents::Binary(bytes) => {
@@ -129,8 +245,11 @@ impl<T: Data> Message<T> {
             MessageContents::Owned(typed) => {
                 unsafe { abomonation::encode(typed, writer).expect("Message::into_bytes(): Abomonation::encode failed"); }
             },
-            MessageContents::Arc(typed) => {
-                unsafe { abomonation::encode(&**typed, writer).expect("Message::into_bytes(): Abomonation::encode failed"); }
+            MessageContents::Arc(typed, bytes) => {
+                match shared_bytes(typed, bytes, |typed, buffer| unsafe { abomonation::encode(typed, buffer).expect("Abomonation::encode failed") }) {
+                    Some(bytes) => writer.write_all(bytes).expect("Message::into_bytes(): write_all failed."),
+                    None => unsafe { abomonation::encode(&**typed, writer).expect("Message::into_bytes(): Abomonation::encode failed"); },
+                }
             },
         }
     }
@@ -139,20 +258,27 @@ impl<T: Data> Message<T> {
 #[cfg(feature = "bincode")]
 impl<T: Data> Message<T> {
     /// Wrap bytes as a message.
+    ///
+    /// The bytes are decoded when the message is first accessed, and a message sent on without
+    /// being accessed is written as the same bytes.
     pub fn from_bytes(bytes: Bytes) -> Self {
-        let typed = ::bincode::deserialize(&bytes[..]).expect("bincode::deserialize() failed");
-        Message { payload: MessageContents::Owned(typed) }
+        let decode: fn(&[u8]) -> T = |bytes| ::bincode::deserialize(bytes).expect("bincode::deserialize() failed");
+        Message { payload: MessageContents::Encoded(bytes, OnceCell::new(), decode) }
     }
 
     /// The number of bytes required to serialize the data.
     pub fn length_in_bytes(&self) -> usize {
         match &self.payload {
             MessageContents::Binary(bytes) => { bytes.as_bytes().len() },
+            MessageContents::Encoded(bytes, _, _) => { bytes.len() },
             MessageContents::Owned(typed) => {
                 ::bincode::serialized_size(&typed).expect("bincode::serialized_size() failed") as usize
             },
-            MessageContents::Arc(typed) => {
-                ::bincode::serialized_size(&**typed).expect("bincode::serialized_size() failed") as usize
+            MessageContents::Arc(typed, bytes) => {
+                match shared_bytes(typed, bytes, |typed, buffer| ::bincode::serialize_into(buffer, typed).expect("bincode::serialize_into() failed")) {
+                    Some(bytes) => bytes.len(),
+                    None => ::bincode::serialized_size(&**typed).expect("bincode::serialized_size() failed") as usize,
+                }
             },
         }
     }
@@ -163,11 +289,17 @@ impl<T: Data> Message<T> {
             MessageContents::Binary(bytes) => {
                 writer.write_all(bytes.as_bytes()).expect("Message::into_bytes(): write_all failed.");
             },
+            MessageContents::Encoded(bytes, _, _) => {
+                writer.write_all(&bytes[..]).expect("Message::into_bytes(): write_all failed.");
+            },
             MessageContents::Owned(typed) => {
                 ::bincode::serialize_into(writer, &typed).expect("bincode::serialize_into() failed");
             },
-            MessageContents::Arc(typed) => {
-                ::bincode::serialize_into(writer, &**typed).expect("bincode::serialize_into() failed");
+            MessageContents::Arc(typed, bytes) => {
+                match shared_bytes(typed, bytes, |typed, buffer| ::bincode::serialize_into(buffer, typed).expect("bincode::serialize_into() failed")) {
+                    Some(bytes) => writer.write_all(bytes).expect("Message::into_bytes(): write_all failed."),
+                    None => ::bincode::serialize_into(writer, &**typed).expect("bincode::serialize_into() failed"),
+                }
             },
         }
     }
@@ -179,8 +311,10 @@ impl<T> ::std::ops::Deref for Message<T> {
         // TODO: In principle we have aready decoded, but let's go again
         match &self.payload {
             MessageContents::Binary(bytes) => { bytes },
+            #[cfg(feature = "bincode")]
+            MessageContents::Encoded(bytes, typed, decode) => { typed.get_or_init(|| decode(&bytes[..])) },
             MessageContents::Owned(typed) => { typed },
-            MessageContents::Arc(typed) => { typed },
+            MessageContents::Arc(typed, _) => { typed },
         }
     }
 }
@@ -190,19 +324,25 @@ impl<T: Clone> Message<T> {
     pub fn into_typed(self) -> T {
         match self.payload {
             MessageContents::Binary(bytes) => bytes.clone(),
+            #[cfg(feature = "bincode")]
+            MessageContents::Encoded(bytes, typed, decode) => typed.into_inner().unwrap_or_else(|| decode(&bytes[..])),
             MessageContents::Owned(instance) => instance,
-            // TODO: Could attempt `Arc::try_unwrap()` here.
-            MessageContents::Arc(instance) => (*instance).clone(),
+            MessageContents::Arc(instance, _) => Arc::try_unwrap(instance).unwrap_or_else(|instance| (*instance).clone()),
         }
     }
     /// Ensures the message is typed data and returns a mutable reference to it.
     pub fn as_mut(&mut self) -> &mut T {
 
+        #[cfg(feature = "bincode")]
+        self.decode();
+
         let cloned: Option<T> = match &self.payload {
             MessageContents::Binary(bytes) => Some((*bytes).clone()),
+            #[cfg(feature = "bincode")]
+            MessageContents::Encoded(..) => unreachable!(),
             MessageContents::Owned(_) => None,
             // TODO: Could attempt `Arc::try_unwrap()` here.
-            MessageContents::Arc(typed) => Some((**typed).clone()),
+            MessageContents::Arc(typed, _) => Some((**typed).clone()),
         };
 
         if let Some(cloned) = cloned {
@@ -64,15 +64,26 @@ impl MessageHeader {
 /// The item at index i in the resulting vec, is a Some(TcpSocket) to process i, except
 /// for item `my_index` which is None (no socket to self).
 pub fn create_sockets(addresses: Vec<String>, my_index: usize, noisy: bool) -> Result<Vec<Option<TcpStream>>> {
+    let connections = create_connections(addresses, my_index, 1, noisy)?;
+    Ok(connections.into_iter().map(|mut streams| streams.pop()).collect())
+}
+
+/// Creates `connections` socket connections to each process from a list of host addresses.
+///
+/// The item at index i in the resulting vec contains the sockets to process i, in order of
+/// their connection index, except for item `my_index` which is empty (no sockets to self).
+pub fn create_connections(addresses: Vec<String>, my_index: usize, connections: usize, noisy: bool) -> Result<Vec<Vec<TcpStream>>> {
+
+    assert!(connections > 0, "at least one connection per process is required");
 
     let hosts1 = Arc::new(addresses);
     let hosts2 = hosts1.clone();
 
-    let start_task = thread::spawn(move || start_connections(hosts1, my_index, noisy));
-    let await_task = thread::spawn(move || await_connections(hosts2, my_index, noisy));
+    let start_task = thread::spawn(move || start_connections(hosts1, my_index, connections, noisy));
+    let await_task = thread::spawn(move || await_connections(hosts2, my_index, connections, noisy));
 
     let mut results = start_task.join().unwrap()?;
-    results.push(None);
+    results.push(Vec::new());
     let to_extend = await_task.join().unwrap()?;
     results.extend(to_extend.into_iter());
 
@@ -82,48 +93,60 @@ pub fn create_sockets(addresses: Vec<String>, my_index: usize, noisy: bool) -> R
 }
 
 
-/// Result contains connections [0, my_index - 1].
-pub fn start_connections(addresses: Arc<Vec<String>>, my_index: usize, noisy: bool) -> Result<Vec<Option<TcpStream>>> {
+/// Result contains `connections` connections to each of [0, my_index - 1].
+pub fn start_connections(addresses: Arc<Vec<String>>, my_index: usize, connections: usize, noisy: bool) -> Result<Vec<Vec<TcpStream>>> {
     let results = addresses.iter().take(my_index).enumerate().map(|(index, address)| {
-        loop {
-            match TcpStream::connect(address) {
-                Ok(mut stream) => {
-                    stream.set_nodelay(true).expect("set_nodelay call failed");
-                    unsafe { encode(&HANDSHAKE_MAGIC, &mut stream) }.expect("failed to encode/send handshake magic");
-                    unsafe { encode(&(my_index as u64), &mut stream) }.expect("failed to encode/send worker index");
-                    if noisy { println!("worker {}:\tconnection to worker {}", my_index, index); }
-                    break Some(stream);
-                },
-                Err(error) => {
-                    println!("worker {}:\terror connecting to worker {}: {}; retrying", my_index, index, error);
-                    sleep(Duration::from_secs(1));
-                },
+        (0 .. connections).map(|connection| {
+            loop {
+                match TcpStream::connect(address) {
+                    Ok(mut stream) => {
+                        stream.set_nodelay(true).expect("set_nodelay call failed");
+                        unsafe { encode(&HANDSHAKE_MAGIC, &mut stream) }.expect("failed to encode/send handshake magic");
+                        unsafe { encode(&(my_index as u64), &mut stream) }.expect("failed to encode/send worker index");
+                        unsafe { encode(&(connection as u64), &mut stream) }.expect("failed to encode/send connection index");
+                        if noisy { println!("worker {}:\tconnection {} to worker {}", my_index, connection, index); }
+                        break stream;
+                    },
+                    Err(error) => {
+                        println!("worker {}:\terror connecting to worker {}: {}; retrying", my_index, index, error);
+                        sleep(Duration::from_secs(1));
+                    },
+                }
             }
-        }
+        }).collect()
     }).collect();
 
     Ok(results)
 }
 
-/// Result contains connections [my_index + 1, addresses.len() - 1].
-pub fn await_connections(addresses: Arc<Vec<String>>, my_index: usize, noisy: bool) -> Result<Vec<Option<TcpStream>>> {
-    let mut results: Vec<_> = (0..(addresses.len() - my_index - 1)).map(|_| None).collect();
+/// Result contains `connections` connections to each of [my_index + 1, addresses.len() - 1].
+pub fn await_connections(addresses: Arc<Vec<String>>, my_index: usize, connections: usize, noisy: bool) -> Result<Vec<Vec<TcpStream>>> {
+    let mut results: Vec<Vec<Option<TcpStream>>> = (0..(addresses.len() - my_index - 1)).map(|_| (0 .. connections).map(|_| None).collect()).collect();
     let listener = TcpListener::bind(&addresses[my_index][..])?;
 
-    for _ in (my_index + 1) .. addresses.len() {
+    for _ in 0 .. (addresses.len() - my_index - 1) * connections {
         let mut stream = listener.accept()?.0;
         stream.set_nodelay(true).expect("set_nodelay call failed");
-        let mut buffer = [0u8;16];
+        let mut buffer = [0u8;24];
         stream.read_exact(&mut buffer)?;
-        let (magic, mut buffer) = unsafe { decode::<u64>(&mut buffer) }.expect("failed to decode magic");
+        let (magic, buffer) = unsafe { decode::<u64>(&mut buffer) }.expect("failed to decode magic");
         if magic != &HANDSHAKE_MAGIC {
             return Err(io::Error::new(io::ErrorKind::InvalidData,
                 "received incorrect timely handshake"));
         }
-        let identifier = unsafe { decode::<u64>(&mut buffer) }.expect("failed to decode worker index").0.clone() as usize;
-        results[identifier - my_index - 1] = Some(stream);
-        if noisy { println!("worker {}:\tconnection from worker {}", my_index, identifier); }
+        let (identifier, buffer) = unsafe { decode::<u64>(buffer) }.expect("failed to decode worker index");
+        let identifier = *identifier as usize;
+        let connection = *unsafe { decode::<u64>(buffer) }.expect("failed to decode connection index").0 as usize;
+        let slot = if identifier > my_index && connection < connections {
+            results.get_mut(identifier - my_index - 1).map(|streams| &mut streams[connection])
+        } else { None };
+        match slot {
+            Some(slot) if slot.is_none() => { *slot = Some(stream); },
+            _ => return Err(io::Error::new(io::ErrorKind::InvalidData,
+                "received unexpected timely handshake")),
+        }
+        if noisy { println!("worker {}:\tconnection {} from worker {}", my_index, connection, identifier); }
     }
 
-    Ok(results)
+    Ok(results.into_iter().map(|streams| streams.into_iter().map(|stream| stream.expect("connection not established")).collect()).collect())
 }
@@ -4,8 +4,11 @@ use timely::dataflow::operators::Capability;
 use timely::dataflow::operators::generic::OutputHandle;
 use timely::dataflow::channels::pushers::Tee;
 
+use timely::dataflow::operators::replayable::ReplayableSource;
+
 use rdkafka::Message;
-use rdkafka::consumer::{ConsumerContext, BaseConsumer};
+use rdkafka::consumer::{Consumer, ConsumerContext, BaseConsumer};
+use rdkafka::topic_partition_list::{Offset, TopicPartitionList};
 
 /// Constructs a stream of data from a Kafka consumer.
 ///
@@ -135,4 +138,63 @@ where
         }
 
     })
-}
\ No newline at end of file
+}
+/// Reads the payloads of the messages of one partition of a Kafka topic, as a replayable source.
+///
+/// The offset is the Kafka offset of the next message to read. The source is exhausted once a poll
+/// finds no further message within `POLL_TIMEOUT`, and so reads the messages already in the
+/// partition rather than polling it for as long as its dataflow runs.
+pub struct PartitionSource<C: ConsumerContext> {
+    consumer: BaseConsumer<C>,
+    topic: String,
+    partition: i32,
+    offset: i64,
+    messages: usize,
+}
+
+/// The time for which `PartitionSource` waits for a message before considering itself exhausted.
+pub const POLL_TIMEOUT: std::time::Duration = std::time::Duration::from_millis(100);
+
+impl<C: ConsumerContext> PartitionSource<C> {
+    /// Reads `partition` of `topic` from its beginning, at most `messages` messages per read.
+    ///
+    /// The consumer is assigned the partition, replacing any previous assignment or subscription.
+    pub fn new(consumer: BaseConsumer<C>, topic: &str, partition: i32, messages: usize) -> std::io::Result<Self> {
+        let mut source = PartitionSource { consumer, topic: topic.to_owned(), partition, offset: 0, messages };
+        source.assign(Offset::Beginning)?;
+        Ok(source)
+    }
+
+    fn assign(&mut self, offset: Offset) -> std::io::Result<()> {
+        let mut assignment = TopicPartitionList::new();
+        assignment.add_partition_offset(&self.topic, self.partition, offset);
+        self.consumer
+            .assign(&assignment)
+            .map_err(|error| std::io::Error::new(std::io::ErrorKind::Other, error))
+    }
+}
+
+impl<C: ConsumerContext> ReplayableSource for PartitionSource<C> {
+    type Item = Vec<u8>;
+    type Offset = i64;
+    fn seek(&mut self, offset: i64) -> std::io::Result<()> {
+        self.offset = offset;
+        self.assign(Offset::Offset(offset))
+    }
+    fn read(&mut self, buffer: &mut Vec<Vec<u8>>) -> std::io::Result<bool> {
+        for _ in 0 .. self.messages {
+            match self.consumer.poll(POLL_TIMEOUT) {
+                Some(Ok(message)) => {
+                    self.offset = message.offset() + 1;
+                    buffer.push(message.payload().map(|payload| payload.to_vec()).unwrap_or_default());
+                },
+                Some(Err(error)) => return Err(std::io::Error::new(std::io::ErrorKind::Other, error)),
+                None => return Ok(false),
+            }
+        }
+        Ok(true)
+    }
+    fn offset(&self) -> i64 {
+        self.offset
+    }
+}
@@ -14,6 +14,7 @@ use rdkafka::config::FromClientConfigAndContext;
 
 pub mod kafka_source;
 pub use kafka_source::kafka_source as source;
+pub use kafka_source::PartitionSource;
 
 struct OutstandingCounterContext {
     outstanding: Arc<AtomicIsize>,
@@ -3,9 +3,13 @@ use std::rc::Rc;
 use std::cell::RefCell;
 use std::any::Any;
 use std::collections::HashMap;
-use std::time::{Instant, Duration};
+use std::time::Duration;
 use std::fmt::{self, Debug};
 
+pub mod time;
+
+use crate::time::Instant;
+
 pub struct Registry<Id> {
     /// A worker-specific identifier.
     id: Id,
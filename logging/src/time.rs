@@ -0,0 +1,124 @@
+//! A monotonic clock and a wall clock available on all targets.
+//!
+//! This is `std::time::Instant`, except on `wasm32-unknown-unknown`, where the standard library has
+//! no clock and `std::time::Instant::now` panics. There, `Instant` measures the time reported by a
+//! clock installed with `set_clock`, for example one backed by `performance.now()` in a browser,
+//! and until one is installed time stands still: delayed activations are not delivered.
+//!
+//! Likewise `since_unix_epoch` reads `std::time::SystemTime`, except on `wasm32-unknown-unknown`,
+//! where it reads a clock installed with `set_unix_clock`, for example one backed by `Date.now()`,
+//! and until one is installed reports the Unix epoch itself.
+
+#[cfg(not(all(target_arch = "wasm32", target_os = "unknown")))]
+pub use std::time::Instant;
+
+#[cfg(all(target_arch = "wasm32", target_os = "unknown"))]
+pub use self::installed::{Instant, set_clock, since_unix_epoch, set_unix_clock};
+
+/// The time elapsed since the Unix epoch, or zero if the system clock is set before it.
+#[cfg(not(all(target_arch = "wasm32", target_os = "unknown")))]
+pub fn since_unix_epoch() -> std::time::Duration {
+    std::time::SystemTime::now().duration_since(std::time::UNIX_EPOCH).unwrap_or_default()
+}
+
+#[cfg(all(target_arch = "wasm32", target_os = "unknown"))]
+mod installed {
+
+    use std::cell::Cell;
+    use std::ops::{Add, AddAssign, Sub, SubAssign};
+    use std::time::Duration;
+
+    fn stopped() -> Duration { Duration::default() }
+
+    thread_local! {
+        static CLOCK: Cell<fn() -> Duration> = Cell::new(stopped as fn() -> Duration);
+        static UNIX_CLOCK: Cell<fn() -> Duration> = Cell::new(stopped as fn() -> Duration);
+    }
+
+    /// Installs `clock`, which reports the time since some fixed moment, as the source of
+    /// `Instant::now` for the current thread.
+    ///
+    /// The clock should be installed before any instants are measured, and should not decrease.
+    pub fn set_clock(clock: fn() -> Duration) {
+        CLOCK.with(|installed| installed.set(clock));
+    }
+
+    /// Installs `clock`, which reports the time since the Unix epoch, as the source of
+    /// `since_unix_epoch` for the current thread.
+    pub fn set_unix_clock(clock: fn() -> Duration) {
+        UNIX_CLOCK.with(|installed| installed.set(clock));
+    }
+
+    /// The time elapsed since the Unix epoch, as reported by the clock installed with `set_unix_clock`.
+    pub fn since_unix_epoch() -> Duration {
+        UNIX_CLOCK.with(|installed| installed.get())()
+    }
+
+    /// A measurement of the clock installed with `set_clock`.
+    #[derive(Copy, Clone, Debug, PartialEq, Eq, PartialOrd, Ord, Hash)]
+    pub struct Instant(Duration);
+
+    impl Instant {
+        /// The current time of the installed clock.
+        pub fn now() -> Instant {
+            Instant(CLOCK.with(|installed| installed.get())())
+        }
+        /// The time elapsed from `earlier` to `self`, or zero if `earlier` is later.
+        pub fn duration_since(&self, earlier: Instant) -> Duration {
+            self.saturating_duration_since(earlier)
+        }
+        /// The time elapsed from `earlier` to `self`, if `earlier` is not later.
+        pub fn checked_duration_since(&self, earlier: Instant) -> Option<Duration> {
+            self.0.checked_sub(earlier.0)
+        }
+        /// The time elapsed from `earlier` to `self`, or zero if `earlier` is later.
+        pub fn saturating_duration_since(&self, earlier: Instant) -> Duration {
+            self.checked_duration_since(earlier).unwrap_or_default()
+        }
+        /// The time elapsed since `self`.
+        pub fn elapsed(&self) -> Duration {
+            Instant::now().duration_since(*self)
+        }
+        /// The instant `duration` after `self`, if representable.
+        pub fn checked_add(&self, duration: Duration) -> Option<Instant> {
+            self.0.checked_add(duration).map(Instant)
+        }
+        /// The instant `duration` before `self`, if representable.
+        pub fn checked_sub(&self, duration: Duration) -> Option<Instant> {
+            self.0.checked_sub(duration).map(Instant)
+        }
+    }
+
+    impl Add<Duration> for Instant {
+        type Output = Instant;
+        fn add(self, duration: Duration) -> Instant {
+            self.checked_add(duration).expect("overflow when adding duration to instant")
+        }
+    }
+
+    impl AddAssign<Duration> for Instant {
+        fn add_assign(&mut self, duration: Duration) {
+            *self = *self + duration;
+        }
+    }
+
+    impl Sub<Duration> for Instant {
+        type Output = Instant;
+        fn sub(self, duration: Duration) -> Instant {
+            self.checked_sub(duration).expect("overflow when subtracting duration from instant")
+        }
+    }
+
+    impl SubAssign<Duration> for Instant {
+        fn sub_assign(&mut self, duration: Duration) {
+            *self = *self - duration;
+        }
+    }
+
+    impl Sub<Instant> for Instant {
+        type Output = Duration;
+        fn sub(self, earlier: Instant) -> Duration {
+            self.duration_since(earlier)
+        }
+    }
+}
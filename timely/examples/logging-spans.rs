@@ -0,0 +1,66 @@
+extern crate timely;
+
+use std::collections::HashMap;
+use std::time::Duration;
+
+use timely::dataflow::{InputHandle, ProbeHandle};
+use timely::dataflow::operators::{Input, Exchange, Probe};
+use timely::logging::{TimelyEvent, StartStop};
+
+// Pairs operator scheduling events into spans carrying the operator name and address, and
+// reports channel sends as events within the span of the sending operator. The output has
+// the shape expected by span-based tooling, e.g. for flamegraphs and latency analysis.
+fn main() {
+    timely::execute_from_args(std::env::args(), |worker| {
+
+        let rounds = std::env::args().nth(1).unwrap_or_else(|| "3".to_owned()).parse::<usize>().unwrap();
+
+        let index = worker.index();
+        let mut operators = HashMap::new();
+        let mut open = Vec::<(usize, Duration)>::new();
+
+        worker.log_register().insert::<TimelyEvent,_>("timely", move |_time, data|
+            for (time, _worker, event) in data.drain(..) {
+                match event {
+                    TimelyEvent::Operates(event) => {
+                        operators.insert(event.id, (event.name, event.addr));
+                    },
+                    TimelyEvent::Schedule(event) => match event.start_stop {
+                        StartStop::Start => open.push((event.id, time)),
+                        StartStop::Stop => {
+                            if let Some((id, start)) = open.pop() {
+                                assert_eq!(id, event.id);
+                                if let Some((name, addr)) = operators.get(&id) {
+                                    println!("worker {}: span {} {:?} [{:?}, {:?}) {:?}", index, name, addr, start, time, time - start);
+                                }
+                            }
+                        },
+                    },
+                    TimelyEvent::Messages(event) if event.is_send => {
+                        if let Some((name, addr)) = open.last().and_then(|(id, _)| operators.get(id)) {
+                            println!("worker {}: event in {} {:?}: sent {} records on channel {}", index, name, addr, event.length, event.channel);
+                        }
+                    },
+                    _ => { },
+                }
+            }
+        );
+
+        let mut input = InputHandle::new();
+        let mut probe = ProbeHandle::new();
+
+        worker.dataflow(|scope| {
+            scope
+                .input_from(&mut input)
+                .exchange(|&x| x as u64)
+                .probe_with(&mut probe);
+        });
+
+        for round in 0 .. rounds {
+            input.send(round);
+            input.advance_to(round + 1);
+            worker.step_while(|| probe.less_than(input.time()));
+        }
+
+    }).unwrap();
+}
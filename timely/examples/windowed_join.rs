@@ -0,0 +1,51 @@
+extern crate rand;
+extern crate timely;
+
+use rand::{Rng, SeedableRng, StdRng};
+
+use timely::dataflow::{InputHandle, ProbeHandle};
+use timely::dataflow::operators::{Delay, Accumulate, Inspect, Probe};
+use timely::dataflow::operators::keyed::KeyBy;
+
+fn main() {
+
+    // command-line args: numbers of keys, records per round, rounds per window, and rounds.
+    let keys: u64 = std::env::args().nth(1).unwrap().parse().unwrap();
+    let batch: usize = std::env::args().nth(2).unwrap().parse().unwrap();
+    let width: u64 = std::env::args().nth(3).unwrap().parse().unwrap();
+    let rounds: u64 = std::env::args().nth(4).unwrap().parse().unwrap();
+
+    timely::execute_from_args(std::env::args().skip(5), move |worker| {
+
+        let index = worker.index();
+
+        let mut orders = InputHandle::new();
+        let mut payments = InputHandle::new();
+        let mut probe = ProbeHandle::new();
+
+        worker.dataflow::<u64,_,_>(|scope| {
+
+            // each record is held until the end of its window, where it meets the other records of the window.
+            let orders = orders.to_stream(scope).delay_batch(move |time| time - time % width + width).key_by();
+            let payments = payments.to_stream(scope).delay_batch(move |time| time - time % width + width).key_by();
+
+            orders.join(&payments)
+                  .count()
+                  .inspect_time(move |window, count| println!("worker {}: window ending {}: {} matches", index, window, count))
+                  .probe_with(&mut probe);
+        });
+
+        let seed: &[_] = &[1, 2, 3, index];
+        let mut rng: StdRng = SeedableRng::from_seed(seed);
+
+        for round in 0 .. rounds {
+            for _ in 0 .. batch {
+                orders.send((rng.gen_range(0, keys), round));
+                payments.send((rng.gen_range(0, keys), rng.gen_range(0, 100u64)));
+            }
+            orders.advance_to(round + 1);
+            payments.advance_to(round + 1);
+            worker.step_while(|| probe.less_than(orders.time()));
+        }
+    }).unwrap();
+}
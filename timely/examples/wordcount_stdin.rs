@@ -0,0 +1,43 @@
+extern crate timely;
+
+use std::io::BufRead;
+
+use timely::dataflow::{InputHandle, ProbeHandle};
+use timely::dataflow::operators::{Map, Inspect, Probe};
+use timely::dataflow::operators::keyed::KeyBy;
+
+fn main() {
+    // reads lines from standard input at the first worker, each as its own round, and
+    // reports the count of each word as of each line in which it appears.
+    timely::execute_from_args(std::env::args(), |worker| {
+
+        let mut input = InputHandle::new();
+        let mut probe = ProbeHandle::new();
+
+        worker.dataflow::<u64,_,_>(|scope| {
+            input.to_stream(scope)
+                 .flat_map(|line: String|
+                    line.split_whitespace()
+                        .map(|word| (word.to_owned(), 1u64))
+                        .collect::<Vec<_>>()
+                 )
+                 .key_by()
+                 .state_machine(|word: &String, diff: u64, count: &mut u64| {
+                     *count += diff;
+                     (false, Some((word.clone(), *count)))
+                 })
+                 .inspect_time(|line, (word, count)| println!("line {}: {:?} seen {} times", line, word, count))
+                 .probe_with(&mut probe);
+        });
+
+        // other workers close their inputs, and process the words routed to them.
+        if worker.index() == 0 {
+            let stdin = std::io::stdin();
+            for (line, text) in stdin.lock().lines().enumerate() {
+                input.send(text.expect("failed to read from standard input"));
+                input.advance_to(line as u64 + 1);
+                worker.step_while(|| probe.less_than(input.time()));
+            }
+        }
+    }).unwrap();
+}
@@ -0,0 +1,101 @@
+//! An instrumented global allocator, for attributing allocation to workers and operators.
+//!
+//! Installing `CountingAllocator` as the global allocator counts the bytes each thread allocates.
+//! Workers report the bytes allocated by each step through `Worker::allocated`, and scopes the
+//! bytes allocated while scheduling each operator through `OperatorMetrics::allocated`, when
+//! metrics are enabled with `WorkerConfig::metrics`.
+//!
+//! # Examples
+//! ```
+//! use timely::allocator::CountingAllocator;
+//! use timely::dataflow::InputHandle;
+//! use timely::dataflow::operators::{Input, Map, Probe};
+//!
+//! #[global_allocator]
+//! static ALLOCATOR: CountingAllocator = CountingAllocator::system();
+//!
+//! fn main() {
+//!     let mut config = timely::Config::thread();
+//!     config.worker = timely::WorkerConfig::default().metrics(true);
+//!     timely::execute(config, |worker| {
+//!         let mut input = InputHandle::new();
+//!         let probe = worker.dataflow::<usize,_,_>(|scope| {
+//!             scope
+//!                 .input_from(&mut input)
+//!                 .map(|x: usize| vec![x; 1000])
+//!                 .probe()
+//!         });
+//!
+//!         input.send(0);
+//!         input.advance_to(1);
+//!         worker.step_while(|| probe.less_than(input.time()));
+//!
+//!         let map = worker.metrics().get(&[0, 2]).unwrap();
+//!         assert!(map.allocated >= 1000 * std::mem::size_of::<usize>() as u64);
+//!     }).unwrap();
+//! }
+//! ```
+
+use std::alloc::{GlobalAlloc, Layout, System};
+use std::cell::Cell;
+
+thread_local! {
+    static ALLOCATED: Cell<u64> = Cell::new(0);
+}
+
+/// The number of bytes allocated by the current thread.
+///
+/// The count is cumulative, and does not decrease when memory is freed; differences between two
+/// readings are the bytes allocated between them, as counted by [`CountingAllocator`].
+pub fn allocated() -> u64 {
+    ALLOCATED.try_with(|allocated| allocated.get()).unwrap_or(0)
+}
+
+fn record(bytes: usize) {
+    // The counter is unavailable while the thread's locals are being destroyed.
+    let _ = ALLOCATED.try_with(|allocated| allocated.set(allocated.get() + bytes as u64));
+}
+
+/// A global allocator that counts the bytes allocated by each thread.
+///
+/// Allocation is delegated to an inner allocator, by default the system allocator. Counts are
+/// kept only once this is installed as the global allocator, and until then `allocated`,
+/// `Worker::allocated`, and `OperatorMetrics::allocated` all report zero.
+pub struct CountingAllocator<A = System> {
+    inner: A,
+}
+
+impl CountingAllocator<System> {
+    /// Counts allocations made by the system allocator.
+    pub const fn system() -> Self {
+        CountingAllocator { inner: System }
+    }
+}
+
+impl<A> CountingAllocator<A> {
+    /// Counts allocations made by `inner`.
+    pub const fn new(inner: A) -> Self {
+        CountingAllocator { inner }
+    }
+}
+
+unsafe impl<A: GlobalAlloc> GlobalAlloc for CountingAllocator<A> {
+    unsafe fn alloc(&self, layout: Layout) -> *mut u8 {
+        record(layout.size());
+        self.inner.alloc(layout)
+    }
+
+    unsafe fn alloc_zeroed(&self, layout: Layout) -> *mut u8 {
+        record(layout.size());
+        self.inner.alloc_zeroed(layout)
+    }
+
+    unsafe fn dealloc(&self, ptr: *mut u8, layout: Layout) {
+        self.inner.dealloc(ptr, layout)
+    }
+
+    unsafe fn realloc(&self, ptr: *mut u8, layout: Layout, new_size: usize) -> *mut u8 {
+        record(new_size.saturating_sub(layout.size()));
+        self.inner.realloc(ptr, layout, new_size)
+    }
+}
@@ -0,0 +1,290 @@
+//! The subset of the flatbuffers encoding used by Arrow IPC metadata.
+//!
+//! Objects are written front to back: each table is preceded by its vtable and followed by the
+//! objects it refers to, so that the unsigned offsets to referenced objects always point forward.
+
+use std::io::{Error, ErrorKind, Result};
+
+/// A scalar or referenced value of a table field.
+pub(crate) enum Value {
+    U8(u8),
+    I16(i16),
+    I32(i32),
+    I64(i64),
+    Offset(Object),
+}
+
+impl Value {
+    fn size(&self) -> usize {
+        match self {
+            Value::U8(_) => 1,
+            Value::I16(_) => 2,
+            Value::I32(_) => 4,
+            Value::I64(_) => 8,
+            Value::Offset(_) => 4,
+        }
+    }
+}
+
+/// An object that table fields may refer to.
+pub(crate) enum Object {
+    /// A table, with a value for each of its fields that are present, indexed by field id.
+    Table(Vec<Option<Value>>),
+    /// A string.
+    String(String),
+    /// A vector of tables.
+    Tables(Vec<Object>),
+    /// A vector of structs of 8-byte aligned fields, as their encoded bytes.
+    Structs(usize, Vec<u8>),
+}
+
+/// Encodes `root` as a flatbuffer, padded to a multiple of eight bytes.
+pub(crate) fn encode(root: Object) -> Vec<u8> {
+    let mut buffer = vec![0u8; 4];
+    let position = write(&mut buffer, root);
+    patch(&mut buffer, 0, position);
+    pad(&mut buffer, 8, 0);
+    buffer
+}
+
+fn pad(buffer: &mut Vec<u8>, align: usize, offset: usize) {
+    while (buffer.len() + offset) % align != 0 {
+        buffer.push(0);
+    }
+}
+
+/// The least multiple of `align` at least `value`.
+pub(crate) fn round_up(value: usize, align: usize) -> usize {
+    (value + align - 1) / align * align
+}
+
+fn patch(buffer: &mut [u8], at: usize, target: usize) {
+    let offset = (target - at) as u32;
+    buffer[at .. at + 4].copy_from_slice(&offset.to_le_bytes());
+}
+
+// Writes `object` and the objects it refers to, returning the position offsets should refer to.
+fn write(buffer: &mut Vec<u8>, object: Object) -> usize {
+    match object {
+        Object::Table(fields) => {
+            pad(buffer, 2, 0);
+            let vtable = buffer.len();
+            let table = round_up(vtable + 4 + 2 * fields.len(), 8);
+
+            // lay out the fields after the offset to the vtable, each aligned to its size.
+            let mut offsets = Vec::with_capacity(fields.len());
+            let mut cursor = table + 4;
+            for field in fields.iter() {
+                match field {
+                    Some(value) => {
+                        cursor = round_up(cursor, value.size());
+                        offsets.push(cursor - table);
+                        cursor += value.size();
+                    },
+                    None => offsets.push(0),
+                }
+            }
+
+            buffer.extend_from_slice(&((4 + 2 * fields.len()) as u16).to_le_bytes());
+            buffer.extend_from_slice(&((cursor - table) as u16).to_le_bytes());
+            for offset in offsets.iter() {
+                buffer.extend_from_slice(&(*offset as u16).to_le_bytes());
+            }
+            buffer.resize(table, 0);
+            buffer.extend_from_slice(&((table - vtable) as i32).to_le_bytes());
+
+            let mut referenced = Vec::new();
+            for (field, offset) in fields.into_iter().zip(offsets) {
+                if let Some(value) = field {
+                    buffer.resize(table + offset, 0);
+                    match value {
+                        Value::U8(value) => buffer.push(value),
+                        Value::I16(value) => buffer.extend_from_slice(&value.to_le_bytes()),
+                        Value::I32(value) => buffer.extend_from_slice(&value.to_le_bytes()),
+                        Value::I64(value) => buffer.extend_from_slice(&value.to_le_bytes()),
+                        Value::Offset(object) => {
+                            referenced.push((buffer.len(), object));
+                            buffer.extend_from_slice(&[0; 4]);
+                        },
+                    }
+                }
+            }
+            buffer.resize(cursor, 0);
+
+            for (at, object) in referenced {
+                let position = write(buffer, object);
+                patch(buffer, at, position);
+            }
+            table
+        },
+        Object::String(string) => {
+            pad(buffer, 4, 0);
+            let position = buffer.len();
+            buffer.extend_from_slice(&(string.len() as u32).to_le_bytes());
+            buffer.extend_from_slice(string.as_bytes());
+            buffer.push(0);
+            position
+        },
+        Object::Tables(tables) => {
+            pad(buffer, 4, 0);
+            let position = buffer.len();
+            buffer.extend_from_slice(&(tables.len() as u32).to_le_bytes());
+            let slots = buffer.len();
+            buffer.resize(slots + 4 * tables.len(), 0);
+            for (index, table) in tables.into_iter().enumerate() {
+                let target = write(buffer, table);
+                patch(buffer, slots + 4 * index, target);
+            }
+            position
+        },
+        Object::Structs(count, bytes) => {
+            // the elements follow the length, and must be 8-byte aligned.
+            pad(buffer, 8, 4);
+            let position = buffer.len();
+            buffer.extend_from_slice(&(count as u32).to_le_bytes());
+            buffer.extend_from_slice(&bytes);
+            position
+        },
+    }
+}
+
+fn invalid(message: &str) -> Error {
+    Error::new(ErrorKind::InvalidData, format!("malformed flatbuffer: {}", message))
+}
+
+/// A table within an encoded flatbuffer.
+#[derive(Clone, Copy)]
+pub(crate) struct Table<'a> {
+    buffer: &'a [u8],
+    position: usize,
+}
+
+impl<'a> Table<'a> {
+    /// The root table of `buffer`.
+    pub(crate) fn root(buffer: &'a [u8]) -> Result<Self> {
+        let position = read_u32(buffer, 0)? as usize;
+        Ok(Table { buffer, position })
+    }
+
+    // The position of field `id`, if it is present.
+    fn field(&self, id: usize) -> Result<Option<usize>> {
+        let vtable = self.position as i64 - read_i32(self.buffer, self.position)? as i64;
+        if vtable < 0 {
+            return Err(invalid("vtable out of bounds"));
+        }
+        let vtable = vtable as usize;
+        let length = read_u16(self.buffer, vtable)? as usize;
+        if 4 + 2 * id + 2 > length {
+            return Ok(None);
+        }
+        match read_u16(self.buffer, vtable + 4 + 2 * id)? as usize {
+            0 => Ok(None),
+            offset => Ok(Some(self.position + offset)),
+        }
+    }
+
+    /// The value of the byte field `id`, or `default` if absent.
+    pub(crate) fn u8(&self, id: usize, default: u8) -> Result<u8> {
+        match self.field(id)? {
+            Some(position) => self.buffer.get(position).cloned().ok_or_else(|| invalid("field out of bounds")),
+            None => Ok(default),
+        }
+    }
+
+    /// The value of the 16-bit field `id`, or `default` if absent.
+    pub(crate) fn i16(&self, id: usize, default: i16) -> Result<i16> {
+        match self.field(id)? {
+            Some(position) => Ok(read_u16(self.buffer, position)? as i16),
+            None => Ok(default),
+        }
+    }
+
+    /// The value of the 32-bit field `id`, or `default` if absent.
+    pub(crate) fn i32(&self, id: usize, default: i32) -> Result<i32> {
+        match self.field(id)? {
+            Some(position) => read_i32(self.buffer, position),
+            None => Ok(default),
+        }
+    }
+
+    /// The value of the 64-bit field `id`, or `default` if absent.
+    pub(crate) fn i64(&self, id: usize, default: i64) -> Result<i64> {
+        match self.field(id)? {
+            Some(position) => read_i64(self.buffer, position),
+            None => Ok(default),
+        }
+    }
+
+    // The position of the object field `id` refers to, if it is present.
+    fn object(&self, id: usize) -> Result<Option<usize>> {
+        match self.field(id)? {
+            Some(position) => Ok(Some(position + read_u32(self.buffer, position)? as usize)),
+            None => Ok(None),
+        }
+    }
+
+    /// The table field `id` refers to, if it is present.
+    pub(crate) fn table(&self, id: usize) -> Result<Option<Table<'a>>> {
+        Ok(self.object(id)?.map(|position| Table { buffer: self.buffer, position }))
+    }
+
+    /// The string field `id` refers to, or the empty string if absent.
+    pub(crate) fn string(&self, id: usize) -> Result<String> {
+        match self.object(id)? {
+            Some(position) => {
+                let length = read_u32(self.buffer, position)? as usize;
+                let bytes = self.buffer.get(position + 4 .. position + 4 + length).ok_or_else(|| invalid("string out of bounds"))?;
+                String::from_utf8(bytes.to_vec()).map_err(|_| invalid("string is not utf8"))
+            },
+            None => Ok(String::new()),
+        }
+    }
+
+    /// The tables of the vector field `id` refers to, or none if absent.
+    pub(crate) fn tables(&self, id: usize) -> Result<Vec<Table<'a>>> {
+        let mut tables = Vec::new();
+        if let Some(position) = self.object(id)? {
+            let length = read_u32(self.buffer, position)? as usize;
+            for index in 0 .. length {
+                let slot = position + 4 + 4 * index;
+                let target = slot + read_u32(self.buffer, slot)? as usize;
+                tables.push(Table { buffer: self.buffer, position: target });
+            }
+        }
+        Ok(tables)
+    }
+
+    /// The structs of the vector field `id` refers to, as slices of `size` bytes, or none if absent.
+    pub(crate) fn structs(&self, id: usize, size: usize) -> Result<Vec<&'a [u8]>> {
+        let mut structs = Vec::new();
+        if let Some(position) = self.object(id)? {
+            let length = read_u32(self.buffer, position)? as usize;
+            let end = length.checked_mul(size).and_then(|bytes| bytes.checked_add(position + 4)).ok_or_else(|| invalid("vector out of bounds"))?;
+            let bytes = self.buffer.get(position + 4 .. end).ok_or_else(|| invalid("vector out of bounds"))?;
+            structs.extend(bytes.chunks(size));
+        }
+        Ok(structs)
+    }
+}
+
+fn read(buffer: &[u8], position: usize, length: usize) -> Result<&[u8]> {
+    buffer.get(position .. position + length).ok_or_else(|| invalid("read out of bounds"))
+}
+
+fn read_u16(buffer: &[u8], position: usize) -> Result<u16> {
+    let bytes = read(buffer, position, 2)?;
+    Ok(u16::from_le_bytes([bytes[0], bytes[1]]))
+}
+fn read_u32(buffer: &[u8], position: usize) -> Result<u32> {
+    let bytes = read(buffer, position, 4)?;
+    Ok(u32::from_le_bytes([bytes[0], bytes[1], bytes[2], bytes[3]]))
+}
+fn read_i32(buffer: &[u8], position: usize) -> Result<i32> {
+    read_u32(buffer, position).map(|value| value as i32)
+}
+/// Reads a little-endian `i64` at `position`.
+pub(crate) fn read_i64(buffer: &[u8], position: usize) -> Result<i64> {
+    let mut bytes = [0u8; 8];
+    bytes.copy_from_slice(read(buffer, position, 8)?);
+    Ok(i64::from_le_bytes(bytes))
+}
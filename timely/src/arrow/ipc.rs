@@ -0,0 +1,317 @@
+//! Reading and writing the Arrow IPC streaming format.
+//!
+//! A stream is a schema message followed by record batch messages and an end-of-stream marker.
+//! Each message is a continuation marker, the length of its flatbuffer metadata, the metadata,
+//! and a body holding the buffers of the batch's columns. Columns are written without validity
+//! buffers, as timely records have no nulls, and batches with nulls, dictionaries, or compression
+//! are rejected when read.
+
+use std::convert::{TryFrom, TryInto};
+use std::io::{Read, Write, Error, ErrorKind, Result};
+
+use super::{ArrowRecord, Column, DataType, Field};
+use super::flatbuffers::{self, Object, Table, Value};
+
+const CONTINUATION: u32 = 0xFFFF_FFFF;
+const METADATA_VERSION: i16 = 4;
+
+const HEADER_SCHEMA: u8 = 1;
+const HEADER_RECORD_BATCH: u8 = 3;
+
+const TYPE_INT: u8 = 2;
+const TYPE_FLOATING_POINT: u8 = 3;
+const TYPE_UTF8: u8 = 5;
+const PRECISION_DOUBLE: i16 = 2;
+
+fn invalid(message: String) -> Error {
+    Error::new(ErrorKind::InvalidData, message)
+}
+
+// Converts a length or offset read from a stream, which may be negative or too large.
+fn to_usize(value: i64, what: &str) -> Result<usize> {
+    usize::try_from(value).map_err(|_| invalid(format!("invalid {} {}", what, value)))
+}
+
+/// Writes record batches in the Arrow IPC streaming format.
+pub struct StreamWriter<W: Write> {
+    writer: W,
+    fields: Vec<Field>,
+}
+
+impl<W: Write> StreamWriter<W> {
+    /// Writes the schema of a stream with columns described by `fields`.
+    pub fn new(mut writer: W, fields: Vec<Field>) -> Result<Self> {
+        let fields_object = fields.iter().map(|field| {
+            let (type_type, type_table) = match field.data_type {
+                DataType::Int64 => (TYPE_INT, vec![Some(Value::I32(64)), Some(Value::U8(1))]),
+                DataType::UInt64 => (TYPE_INT, vec![Some(Value::I32(64)), Some(Value::U8(0))]),
+                DataType::Float64 => (TYPE_FLOATING_POINT, vec![Some(Value::I16(PRECISION_DOUBLE))]),
+                DataType::Utf8 => (TYPE_UTF8, vec![]),
+            };
+            Object::Table(vec![
+                Some(Value::Offset(Object::String(field.name.clone()))),
+                Some(Value::U8(0)),
+                Some(Value::U8(type_type)),
+                Some(Value::Offset(Object::Table(type_table))),
+                None,
+                Some(Value::Offset(Object::Tables(Vec::new()))),
+            ])
+        }).collect();
+        let schema = Object::Table(vec![None, Some(Value::Offset(Object::Tables(fields_object)))]);
+        write_message(&mut writer, HEADER_SCHEMA, schema, &[])?;
+        Ok(StreamWriter { writer, fields })
+    }
+
+    /// Writes a record batch of `columns`, which must match the schema.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the columns do not match the types of the schema, or differ in length.
+    pub fn write(&mut self, columns: &[Column]) -> Result<()> {
+        assert_eq!(columns.len(), self.fields.len(), "record batch has {} columns, schema has {}", columns.len(), self.fields.len());
+        let rows = columns.first().map(|column| column.len()).unwrap_or(0);
+
+        let mut nodes = Vec::new();
+        let mut buffers = Vec::new();
+        let mut body = Vec::new();
+        for (column, field) in columns.iter().zip(self.fields.iter()) {
+            assert_eq!(column.data_type(), field.data_type, "column {:?} has the wrong type", field.name);
+            assert_eq!(column.len(), rows, "column {:?} has the wrong length", field.name);
+            nodes.extend_from_slice(&(rows as i64).to_le_bytes());
+            nodes.extend_from_slice(&0i64.to_le_bytes());
+            // an empty validity buffer, as no values are null.
+            push_buffer(&mut buffers, &mut body, &[]);
+            match column {
+                Column::Int64(values) => {
+                    let mut bytes = Vec::with_capacity(8 * values.len());
+                    for value in values.iter() {
+                        bytes.extend_from_slice(&value.to_le_bytes());
+                    }
+                    push_buffer(&mut buffers, &mut body, &bytes);
+                },
+                Column::UInt64(values) => {
+                    let mut bytes = Vec::with_capacity(8 * values.len());
+                    for value in values.iter() {
+                        bytes.extend_from_slice(&value.to_le_bytes());
+                    }
+                    push_buffer(&mut buffers, &mut body, &bytes);
+                },
+                Column::Float64(values) => {
+                    let mut bytes = Vec::with_capacity(8 * values.len());
+                    for value in values.iter() {
+                        bytes.extend_from_slice(&value.to_le_bytes());
+                    }
+                    push_buffer(&mut buffers, &mut body, &bytes);
+                },
+                Column::Utf8(values) => {
+                    let mut offsets = Vec::with_capacity(4 * (values.len() + 1));
+                    let mut data = Vec::new();
+                    offsets.extend_from_slice(&0i32.to_le_bytes());
+                    for value in values.iter() {
+                        data.extend_from_slice(value.as_bytes());
+                        offsets.extend_from_slice(&(data.len() as i32).to_le_bytes());
+                    }
+                    push_buffer(&mut buffers, &mut body, &offsets);
+                    push_buffer(&mut buffers, &mut body, &data);
+                },
+            }
+        }
+
+        let batch = Object::Table(vec![
+            Some(Value::I64(rows as i64)),
+            Some(Value::Offset(Object::Structs(columns.len(), nodes))),
+            Some(Value::Offset(Object::Structs(buffers.len() / 16, buffers))),
+        ]);
+        write_message(&mut self.writer, HEADER_RECORD_BATCH, batch, &body)
+    }
+
+    /// Writes `records` as a record batch, which must match the schema.
+    pub fn write_records<D: ArrowRecord>(&mut self, records: &[D]) -> Result<()> {
+        let mut columns = self.fields.iter().map(|field| Column::new(field.data_type)).collect::<Vec<_>>();
+        for record in records.iter() {
+            record.push(&mut columns);
+        }
+        self.write(&columns)
+    }
+
+    /// Writes the end-of-stream marker, and returns the underlying writer.
+    pub fn finish(mut self) -> Result<W> {
+        self.writer.write_all(&CONTINUATION.to_le_bytes())?;
+        self.writer.write_all(&0u32.to_le_bytes())?;
+        self.writer.flush()?;
+        Ok(self.writer)
+    }
+}
+
+// Appends `bytes` to `body`, padded to a multiple of eight bytes, and describes it in `buffers`.
+fn push_buffer(buffers: &mut Vec<u8>, body: &mut Vec<u8>, bytes: &[u8]) {
+    buffers.extend_from_slice(&(body.len() as i64).to_le_bytes());
+    buffers.extend_from_slice(&(bytes.len() as i64).to_le_bytes());
+    body.extend_from_slice(bytes);
+    body.resize(flatbuffers::round_up(body.len(), 8), 0);
+}
+
+fn write_message<W: Write>(writer: &mut W, header_type: u8, header: Object, body: &[u8]) -> Result<()> {
+    let metadata = flatbuffers::encode(Object::Table(vec![
+        Some(Value::I16(METADATA_VERSION)),
+        Some(Value::U8(header_type)),
+        Some(Value::Offset(header)),
+        Some(Value::I64(body.len() as i64)),
+    ]));
+    writer.write_all(&CONTINUATION.to_le_bytes())?;
+    writer.write_all(&(metadata.len() as u32).to_le_bytes())?;
+    writer.write_all(&metadata)?;
+    writer.write_all(body)
+}
+
+/// Reads record batches in the Arrow IPC streaming format.
+pub struct StreamReader<R: Read> {
+    reader: R,
+    fields: Vec<Field>,
+}
+
+impl<R: Read> StreamReader<R> {
+    /// Reads the schema of a stream.
+    pub fn new(mut reader: R) -> Result<Self> {
+        let (metadata, _body) = read_message(&mut reader)?.ok_or_else(|| invalid("stream has no schema".to_owned()))?;
+        let message = Table::root(&metadata)?;
+        if message.u8(1, 0)? != HEADER_SCHEMA {
+            return Err(invalid("stream does not begin with a schema".to_owned()));
+        }
+        let schema = message.table(2)?.ok_or_else(|| invalid("schema message has no schema".to_owned()))?;
+        let mut fields = Vec::new();
+        for field in schema.tables(1)? {
+            let name = field.string(0)?;
+            let type_table = field.table(3)?;
+            let data_type = match (field.u8(2, 0)?, type_table) {
+                (TYPE_INT, Some(table)) if table.i32(0, 0)? == 64 && table.u8(1, 0)? == 1 => DataType::Int64,
+                (TYPE_INT, Some(table)) if table.i32(0, 0)? == 64 => DataType::UInt64,
+                (TYPE_FLOATING_POINT, Some(table)) if table.i16(0, 0)? == PRECISION_DOUBLE => DataType::Float64,
+                (TYPE_UTF8, _) => DataType::Utf8,
+                (type_type, _) => return Err(invalid(format!("field {:?} has unsupported type {}", name, type_type))),
+            };
+            if field.table(4)?.is_some() {
+                return Err(invalid(format!("field {:?} is dictionary encoded", name)));
+            }
+            fields.push(Field { name, data_type });
+        }
+        Ok(StreamReader { reader, fields })
+    }
+
+    /// The fields of the stream's schema.
+    pub fn fields(&self) -> &[Field] {
+        &self.fields
+    }
+
+    /// Reads the columns of the next record batch, or `None` at the end of the stream.
+    pub fn read(&mut self) -> Result<Option<Vec<Column>>> {
+        let (metadata, body) = match read_message(&mut self.reader)? {
+            Some(message) => message,
+            None => return Ok(None),
+        };
+        let message = Table::root(&metadata)?;
+        if message.u8(1, 0)? != HEADER_RECORD_BATCH {
+            return Err(invalid(format!("unsupported message type {}", message.u8(1, 0)?)));
+        }
+        let batch = message.table(2)?.ok_or_else(|| invalid("record batch message has no record batch".to_owned()))?;
+        if batch.table(3)?.is_some() {
+            return Err(invalid("compressed record batches are not supported".to_owned()));
+        }
+        let rows = to_usize(batch.i64(0, 0)?, "row count")?;
+        let nodes = batch.structs(1, 16)?;
+        let mut buffers = batch.structs(2, 16)?.into_iter().map(|buffer| {
+            let offset = to_usize(flatbuffers::read_i64(buffer, 0)?, "buffer offset")?;
+            let length = to_usize(flatbuffers::read_i64(buffer, 8)?, "buffer length")?;
+            offset.checked_add(length)
+                .and_then(|end| body.get(offset .. end))
+                .ok_or_else(|| invalid("buffer out of bounds".to_owned()))
+        });
+        if nodes.len() != self.fields.len() {
+            return Err(invalid(format!("record batch has {} columns, schema has {}", nodes.len(), self.fields.len())));
+        }
+
+        let mut columns = Vec::with_capacity(self.fields.len());
+        for (field, node) in self.fields.iter().zip(nodes) {
+            if to_usize(flatbuffers::read_i64(node, 0)?, "column length")? != rows {
+                return Err(invalid(format!("column {:?} has the wrong length", field.name)));
+            }
+            if flatbuffers::read_i64(node, 8)? != 0 {
+                return Err(invalid(format!("column {:?} has nulls", field.name)));
+            }
+            let mut next = || buffers.next().unwrap_or_else(|| Err(invalid("too few buffers".to_owned())));
+            let _validity = next()?;
+            let values = next()?;
+            let width = if field.data_type == DataType::Utf8 { 4 } else { 8 };
+            // writers may omit the single offset of an empty column of strings.
+            let count = if field.data_type == DataType::Utf8 && rows > 0 { rows + 1 } else { rows };
+            if count.checked_mul(width).map(|bytes| values.len() < bytes).unwrap_or(true) {
+                return Err(invalid(format!("column {:?} has too few values", field.name)));
+            }
+            let column = match field.data_type {
+                DataType::Int64 => Column::Int64(values.chunks(8).take(rows).map(|bytes| i64::from_le_bytes(bytes.try_into().unwrap())).collect()),
+                DataType::UInt64 => Column::UInt64(values.chunks(8).take(rows).map(|bytes| u64::from_le_bytes(bytes.try_into().unwrap())).collect()),
+                DataType::Float64 => Column::Float64(values.chunks(8).take(rows).map(|bytes| f64::from_le_bytes(bytes.try_into().unwrap())).collect()),
+                DataType::Utf8 => {
+                    let data = next()?;
+                    let offsets = values.chunks(4).take(rows + 1).map(|bytes| i32::from_le_bytes(bytes.try_into().unwrap()) as usize).collect::<Vec<_>>();
+                    let mut strings = Vec::with_capacity(rows);
+                    for bounds in offsets.windows(2) {
+                        let bytes = data.get(bounds[0] .. bounds[1]).ok_or_else(|| invalid(format!("column {:?} has strings out of bounds", field.name)))?;
+                        strings.push(String::from_utf8(bytes.to_vec()).map_err(|_| invalid(format!("column {:?} has invalid utf8", field.name)))?);
+                    }
+                    Column::Utf8(strings)
+                },
+            };
+            columns.push(column);
+        }
+        Ok(Some(columns))
+    }
+
+    /// Reads the next record batch as records of type `D`, or `None` at the end of the stream.
+    ///
+    /// Returns an error if the schema does not have the columns of `D`.
+    pub fn read_records<D: ArrowRecord>(&mut self) -> Result<Option<Vec<D>>> {
+        let mut types = Vec::new();
+        D::data_types(&mut types);
+        if self.fields.iter().map(|field| field.data_type).ne(types.iter().cloned()) {
+            return Err(invalid(format!("schema {:?} does not match record types {:?}", self.fields, types)));
+        }
+        Ok(self.read()?.map(|columns| {
+            let rows = columns.first().map(|column| column.len()).unwrap_or(0);
+            (0 .. rows).map(|row| D::read(&columns, row).0).collect()
+        }))
+    }
+}
+
+// Reads the metadata and body of the next message, or `None` at the end of the stream.
+fn read_message<R: Read>(reader: &mut R) -> Result<Option<(Vec<u8>, Vec<u8>)>> {
+    let mut word = [0u8; 4];
+    match reader.read_exact(&mut word) {
+        Ok(()) => { },
+        // streams may end without an end-of-stream marker.
+        Err(error) if error.kind() == ErrorKind::UnexpectedEof => return Ok(None),
+        Err(error) => return Err(error),
+    }
+    // streams written before the continuation marker begin messages with their length.
+    if u32::from_le_bytes(word) == CONTINUATION {
+        reader.read_exact(&mut word)?;
+    }
+    let length = u32::from_le_bytes(word) as usize;
+    if length == 0 {
+        return Ok(None);
+    }
+    let metadata = read_bytes(reader, length)?;
+    let body_length = to_usize(Table::root(&metadata)?.i64(3, 0)?, "body length")?;
+    let body = read_bytes(reader, body_length)?;
+    Ok(Some((metadata, body)))
+}
+
+// Reads `length` bytes, allocating only as they arrive, as the length is read from the stream.
+fn read_bytes<R: Read>(reader: &mut R, length: usize) -> Result<Vec<u8>> {
+    let mut bytes = Vec::new();
+    reader.take(length as u64).read_to_end(&mut bytes)?;
+    if bytes.len() < length {
+        return Err(Error::new(ErrorKind::UnexpectedEof, "stream ended within a message"));
+    }
+    Ok(bytes)
+}
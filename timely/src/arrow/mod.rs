@@ -0,0 +1,281 @@
+//! Exchanges streams with other systems in the Arrow IPC streaming format.
+//!
+//! `CaptureArrow::capture_arrow` writes each batch of a stream's records as an Arrow record batch,
+//! with a column for the timestamp, and `source` produces the records of an Arrow stream, so that
+//! outputs can be read by pandas or DataFusion, and columnar inputs need no conversion to rows.
+//! Records are converted to and from columns through `ArrowRecord`, which is implemented for
+//! 64-bit integers and floats, strings, and tuples of these, each contributing one column.
+//!
+//! # Examples
+//! ```
+//! use std::fs::File;
+//! use timely::dataflow::operators::{ToStream, Capture};
+//! use timely::dataflow::operators::capture::Extract;
+//! use timely::arrow::{CaptureArrow, StreamReader};
+//!
+//! let path = std::env::temp_dir().join(format!("timely-arrow-{}.arrows", std::process::id()));
+//!
+//! let file = File::create(&path).unwrap();
+//! timely::example(move |scope| {
+//!     vec![(1u64, "one".to_string()), (2, "two".to_string())]
+//!         .to_stream(scope)
+//!         .capture_arrow(file);
+//! });
+//!
+//! let mut reader = StreamReader::new(File::open(&path).unwrap()).unwrap();
+//! let names = reader.fields().iter().map(|field| field.name.clone()).collect::<Vec<_>>();
+//! assert_eq!(names, vec!["time", "0", "1"]);
+//! let records = reader.read_records::<(u64, u64, String)>().unwrap().unwrap();
+//! assert_eq!(records, vec![(0, 1, "one".to_string()), (0, 2, "two".to_string())]);
+//! assert!(reader.read().unwrap().is_none());
+//!
+//! // replay the records at their times.
+//! let path2 = path.clone();
+//! let captured = timely::example(move |scope| {
+//!     let reader = StreamReader::new(File::open(&path2).unwrap()).unwrap();
+//!     timely::arrow::source::<_, (u64, String), _>(scope, reader)
+//!         .capture()
+//! });
+//! assert_eq!(captured.extract(), vec![(0, vec![(1, "one".to_string()), (2, "two".to_string())])]);
+//!
+//! std::fs::remove_file(&path).unwrap();
+//! ```
+
+mod flatbuffers;
+mod ipc;
+
+pub use self::ipc::{StreamReader, StreamWriter};
+
+use std::io::{Read, Write};
+
+use crate::Data;
+use crate::dataflow::{Stream, Scope};
+use crate::dataflow::channels::pact::Pipeline;
+use crate::dataflow::operators::generic::operator::{self, Operator};
+
+/// The types of column supported.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DataType {
+    /// Signed 64-bit integers.
+    Int64,
+    /// Unsigned 64-bit integers.
+    UInt64,
+    /// 64-bit floating point numbers.
+    Float64,
+    /// UTF-8 strings.
+    Utf8,
+}
+
+/// A named column of a schema.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Field {
+    /// The name of the column.
+    pub name: String,
+    /// The type of the column.
+    pub data_type: DataType,
+}
+
+/// The values of a column of a record batch.
+#[derive(Debug, Clone, PartialEq)]
+pub enum Column {
+    /// Signed 64-bit integers.
+    Int64(Vec<i64>),
+    /// Unsigned 64-bit integers.
+    UInt64(Vec<u64>),
+    /// 64-bit floating point numbers.
+    Float64(Vec<f64>),
+    /// UTF-8 strings.
+    Utf8(Vec<String>),
+}
+
+impl Column {
+    /// An empty column of type `data_type`.
+    pub fn new(data_type: DataType) -> Self {
+        match data_type {
+            DataType::Int64 => Column::Int64(Vec::new()),
+            DataType::UInt64 => Column::UInt64(Vec::new()),
+            DataType::Float64 => Column::Float64(Vec::new()),
+            DataType::Utf8 => Column::Utf8(Vec::new()),
+        }
+    }
+    /// The type of the column.
+    pub fn data_type(&self) -> DataType {
+        match self {
+            Column::Int64(_) => DataType::Int64,
+            Column::UInt64(_) => DataType::UInt64,
+            Column::Float64(_) => DataType::Float64,
+            Column::Utf8(_) => DataType::Utf8,
+        }
+    }
+    /// The number of values in the column.
+    pub fn len(&self) -> usize {
+        match self {
+            Column::Int64(values) => values.len(),
+            Column::UInt64(values) => values.len(),
+            Column::Float64(values) => values.len(),
+            Column::Utf8(values) => values.len(),
+        }
+    }
+    /// True if the column has no values.
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+}
+
+/// Records that can be converted to and from a sequence of columns.
+pub trait ArrowRecord: Sized {
+    /// Appends the types of the record's columns to `types`.
+    fn data_types(types: &mut Vec<DataType>);
+    /// Appends the record to the leading columns of `columns`, returning the remaining columns.
+    fn push<'a>(&self, columns: &'a mut [Column]) -> &'a mut [Column];
+    /// Reads the record at `row` of the leading columns of `columns`, with the remaining columns.
+    fn read(columns: &[Column], row: usize) -> (Self, &[Column]);
+}
+
+macro_rules! implement_scalar {
+    ($type:ty, $variant:ident) => {
+        impl ArrowRecord for $type {
+            fn data_types(types: &mut Vec<DataType>) {
+                types.push(DataType::$variant);
+            }
+            fn push<'a>(&self, columns: &'a mut [Column]) -> &'a mut [Column] {
+                let (column, rest) = columns.split_first_mut().expect("too few columns");
+                match column {
+                    Column::$variant(values) => values.push(self.clone()),
+                    column => panic!("expected {:?} column, found {:?}", DataType::$variant, column.data_type()),
+                }
+                rest
+            }
+            fn read(columns: &[Column], row: usize) -> (Self, &[Column]) {
+                let (column, rest) = columns.split_first().expect("too few columns");
+                match column {
+                    Column::$variant(values) => (values[row].clone(), rest),
+                    column => panic!("expected {:?} column, found {:?}", DataType::$variant, column.data_type()),
+                }
+            }
+        }
+    }
+}
+
+implement_scalar!(i64, Int64);
+implement_scalar!(u64, UInt64);
+implement_scalar!(f64, Float64);
+implement_scalar!(String, Utf8);
+
+macro_rules! implement_tuple {
+    ($($name:ident)+) => {
+        #[allow(non_snake_case)]
+        impl<$($name: ArrowRecord),+> ArrowRecord for ($($name,)+) {
+            fn data_types(types: &mut Vec<DataType>) {
+                $($name::data_types(types);)+
+            }
+            fn push<'a>(&self, columns: &'a mut [Column]) -> &'a mut [Column] {
+                let ($($name,)+) = self;
+                $(let columns = $name.push(columns);)+
+                columns
+            }
+            fn read(columns: &[Column], row: usize) -> (Self, &[Column]) {
+                $(let ($name, columns) = $name::read(columns, row);)+
+                (($($name,)+), columns)
+            }
+        }
+    }
+}
+
+implement_tuple!(A B);
+implement_tuple!(A B C);
+implement_tuple!(A B C D);
+implement_tuple!(A B C D E);
+
+/// Writes a stream's records in the Arrow IPC streaming format.
+pub trait CaptureArrow<G: Scope, D: Data+ArrowRecord> where G::Timestamp: ArrowRecord {
+    /// Writes each batch of records received by this worker to `writer` as a record batch, and
+    /// ends the Arrow stream once the input is complete.
+    ///
+    /// The first columns hold the timestamp of the records, named `time` if it is one column or
+    /// `time.0`, `time.1`, and so on if it is several. The remaining columns hold the records,
+    /// named `0`, `1`, and so on.
+    ///
+    /// # Panics
+    ///
+    /// Panics if writing to `writer` fails.
+    fn capture_arrow<W: Write+'static>(&self, writer: W);
+}
+
+impl<G: Scope, D: Data+ArrowRecord> CaptureArrow<G, D> for Stream<G, D> where G::Timestamp: ArrowRecord {
+    fn capture_arrow<W: Write+'static>(&self, writer: W) {
+
+        let mut time_types = Vec::new();
+        G::Timestamp::data_types(&mut time_types);
+        let mut data_types = Vec::new();
+        D::data_types(&mut data_types);
+
+        let mut fields = Vec::new();
+        for (index, data_type) in time_types.iter().enumerate() {
+            let name = if time_types.len() == 1 { "time".to_owned() } else { format!("time.{}", index) };
+            fields.push(Field { name, data_type: *data_type });
+        }
+        for (index, data_type) in data_types.iter().enumerate() {
+            fields.push(Field { name: index.to_string(), data_type: *data_type });
+        }
+
+        let mut writer = Some(StreamWriter::new(writer, fields.clone()).expect("failed to write Arrow schema"));
+        let mut vector = Vec::new();
+        self.sink(Pipeline, "CaptureArrow", move |input| {
+            input.for_each(|time, data| {
+                data.swap(&mut vector);
+                let mut columns = fields.iter().map(|field| Column::new(field.data_type)).collect::<Vec<_>>();
+                for record in vector.drain(..) {
+                    let rest = time.time().push(&mut columns);
+                    record.push(rest);
+                }
+                if let Some(writer) = writer.as_mut() {
+                    writer.write(&columns).expect("failed to write Arrow record batch");
+                }
+            });
+            if input.frontier().is_empty() {
+                if let Some(writer) = writer.take() {
+                    writer.finish().expect("failed to write Arrow end of stream");
+                }
+            }
+        });
+    }
+}
+
+/// Produces the records of an Arrow stream, at the times held in their leading columns.
+///
+/// The columns of the stream must be those of the timestamp followed by those of `D`, as written
+/// by `capture_arrow`. Each worker produces the records of the reader it supplies, one record
+/// batch each time it is scheduled. As batches may hold any times, the worker retains the minimum
+/// timestamp until its reader is exhausted.
+///
+/// # Panics
+///
+/// Panics if reading from `reader` fails, or its columns are not those of the timestamp and `D`.
+pub fn source<G: Scope, D: Data+ArrowRecord, R: Read+'static>(scope: &G, mut reader: StreamReader<R>) -> Stream<G, D>
+where G::Timestamp: ArrowRecord {
+    operator::source(scope, "ArrowSource", |capability, info| {
+        let activator = scope.activator_for(&info.address[..]);
+        let mut capability = Some(capability);
+        move |output| {
+            if let Some(minimum) = capability.as_ref() {
+                match reader.read_records::<(G::Timestamp, D)>().expect("failed to read Arrow record batch") {
+                    Some(mut records) => {
+                        records.sort_by(|x, y| x.0.cmp(&y.0));
+                        let mut records = records.into_iter().peekable();
+                        while let Some((time, record)) = records.next() {
+                            let time = minimum.delayed(&time);
+                            let mut session = output.session(&time);
+                            session.give(record);
+                            while records.peek().map(|(next, _)| next == time.time()).unwrap_or(false) {
+                                session.give(records.next().unwrap().1);
+                            }
+                        }
+                        activator.activate();
+                    },
+                    None => capability = None,
+                }
+            }
+        }
+    })
+}
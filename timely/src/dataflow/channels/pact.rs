@@ -6,6 +6,35 @@
 //!
 //! The only requirement of a pact is that it not alter the number of `D` records at each time `T`.
 //! The progress tracking logic assumes that this number is independent of the pact used.
+//!
+//! Operator builders take a pact for each input, and so the same operator logic can be connected
+//! either within each worker by `Pipeline` or between workers by `Exchange`, as its caller chooses.
+//!
+//! # Examples
+//! ```
+//! use timely::dataflow::{Scope, Stream};
+//! use timely::dataflow::channels::pact::{Exchange, ParallelizationContract, Pipeline};
+//! use timely::dataflow::operators::{ToStream, Operator, Inspect};
+//!
+//! // forwards records, through whichever contract the caller supplies.
+//! fn forward<G: Scope, P: ParallelizationContract<G::Timestamp, u64>>(stream: &Stream<G, u64>, pact: P) -> Stream<G, u64> {
+//!     stream.unary(pact, "Forward", |_, _| {
+//!         let mut vector = Vec::new();
+//!         move |input, output| {
+//!             input.for_each(|time, data| {
+//!                 data.swap(&mut vector);
+//!                 output.session(&time).give_vec(&mut vector);
+//!             });
+//!         }
+//!     })
+//! }
+//!
+//! timely::example(|scope| {
+//!     let stream = (0 .. 10u64).to_stream(scope);
+//!     forward(&stream, Pipeline).inspect(|x| println!("pipelined: {:?}", x));
+//!     forward(&stream, Exchange::new(|x| *x)).inspect(|x| println!("exchanged: {:?}", x));
+//! });
+//! ```
 
 use std::{fmt::{self, Debug}, marker::PhantomData};
 
@@ -14,6 +43,11 @@ use crate::communication::allocator::thread::{ThreadPusher, ThreadPuller};
 
 use crate::worker::AsWorker;
 use crate::dataflow::channels::pushers::Exchange as ExchangePusher;
+use crate::dataflow::channels::pushers::Balance as BalancePusher;
+use crate::dataflow::channels::pushers::Distribute as DistributePusher;
+use crate::dataflow::channels::pullers::Balance as BalancePuller;
+use crate::dataflow::channels::partitioner::{Partitioner, HashModulo};
+use crate::dataflow::memory::Account;
 use super::{Bundle, Message};
 
 use crate::logging::{TimelyLogger as Logger, MessagesEvent};
@@ -37,10 +71,11 @@ impl<T: 'static, D: 'static> ParallelizationContract<T, D> for Pipeline {
     type Puller = LogPuller<T, D, ThreadPuller<Bundle<T, D>>>;
     fn connect<A: AsWorker>(self, allocator: &mut A, identifier: usize, address: &[usize], logging: Option<Logger>) -> (Self::Pusher, Self::Puller) {
         let (pusher, puller) = allocator.pipeline::<Message<T, D>>(identifier, address);
+        let account = allocator.memory().channels(address[0]);
         // // ignore `&mut A` and use thread allocator
         // let (pusher, puller) = Thread::new::<Bundle<T, D>>();
-        (LogPusher::new(pusher, allocator.index(), allocator.index(), identifier, logging.clone()),
-         LogPuller::new(puller, allocator.index(), identifier, logging))
+        (LogPusher::new(pusher, allocator.index(), allocator.index(), identifier, logging.clone()).with_account(account.clone()),
+         LogPuller::new(puller, allocator.index(), identifier, logging).with_account(account))
     }
 }
 
@@ -63,10 +98,8 @@ impl<T: Eq+Data+Clone, D: Data+Clone, F: FnMut(&D)->u64+'static> Parallelization
     //       Could specialize `ExchangePusher` to a time-free version.
     type Pusher = Box<dyn Push<Bundle<T, D>>>;
     type Puller = Box<dyn Pull<Bundle<T, D>>>;
-    fn connect<A: AsWorker>(mut self, allocator: &mut A, identifier: usize, address: &[usize], logging: Option<Logger>) -> (Self::Pusher, Self::Puller) {
-        let (senders, receiver) = allocator.allocate::<Message<T, D>>(identifier, address);
-        let senders = senders.into_iter().enumerate().map(|(i,x)| LogPusher::new(x, allocator.index(), i, identifier, logging.clone())).collect::<Vec<_>>();
-        (Box::new(ExchangePusher::new(senders, move |_, d| (self.hash_func)(d))), Box::new(LogPuller::new(receiver, allocator.index(), identifier, logging.clone())))
+    fn connect<A: AsWorker>(self, allocator: &mut A, identifier: usize, address: &[usize], logging: Option<Logger>) -> (Self::Pusher, Self::Puller) {
+        Partitioned::new(HashModulo::new(self.hash_func)).connect(allocator, identifier, address, logging)
     }
 }
 
@@ -76,6 +109,111 @@ impl<D, F> Debug for Exchange<D, F> {
     }
 }
 
+/// An exchange between multiple observers, as assigned by a `Partitioner`.
+///
+/// # Examples
+/// ```
+/// use timely::dataflow::channels::pact::Partitioned;
+/// use timely::dataflow::channels::partitioner::Explicit;
+/// use timely::dataflow::operators::{ToStream, Operator, Inspect};
+///
+/// timely::execute(timely::Config::process(3), |worker| {
+///     let index = worker.index();
+///     worker.dataflow::<u64,_,_>(|scope| {
+///         (0 .. 10u64)
+///             .to_stream(scope)
+///             .unary(Partitioned::new(Explicit::new(|x: &u64| (*x % 3) as usize)), "Route", |_, _| {
+///                 let mut vector = Vec::new();
+///                 move |input, output| {
+///                     input.for_each(|time, data| {
+///                         data.swap(&mut vector);
+///                         output.session(&time).give_vec(&mut vector);
+///                     });
+///                 }
+///             })
+///             .inspect(move |x| assert_eq!((*x % 3) as usize, index));
+///     });
+/// }).unwrap();
+/// ```
+pub struct Partitioned<D, P> { partitioner: P, phantom: PhantomData<D> }
+
+impl<D, P: Partitioner<D>+'static> Partitioned<D, P> {
+    /// Allocates a new `Partitioned` pact from a partitioner.
+    pub fn new(partitioner: P) -> Partitioned<D, P> {
+        Partitioned {
+            partitioner,
+            phantom: PhantomData,
+        }
+    }
+}
+
+impl<T: Eq+Data+Clone, D: Data+Clone, P: Partitioner<D>+'static> ParallelizationContract<T, D> for Partitioned<D, P> {
+    type Pusher = Box<dyn Push<Bundle<T, D>>>;
+    type Puller = Box<dyn Pull<Bundle<T, D>>>;
+    fn connect<A: AsWorker>(mut self, allocator: &mut A, identifier: usize, address: &[usize], logging: Option<Logger>) -> (Self::Pusher, Self::Puller) {
+        let (senders, receiver) = allocate(allocator, identifier, address, logging);
+        let peers = senders.len();
+        (Box::new(ExchangePusher::new(senders, move |_, d| self.partitioner.partition(d, peers) as u64)), Box::new(receiver))
+    }
+}
+
+impl<D, P> Debug for Partitioned<D, P> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Partitioned").finish()
+    }
+}
+
+/// An exchange sending each batch of records to the worker with the fewest unconsumed records.
+///
+/// Each worker acknowledges the records it consumes to the workers that sent them, and senders
+/// route each batch to the worker with the fewest records sent but not yet acknowledged. This
+/// suits operators that may process any record on any worker, when some workers fall behind.
+#[derive(Debug)]
+pub struct Balanced;
+
+impl<T: Eq+Data+Clone, D: Data+Clone> ParallelizationContract<T, D> for Balanced {
+    type Pusher = Box<dyn Push<Bundle<T, D>>>;
+    type Puller = Box<dyn Pull<Bundle<T, D>>>;
+    fn connect<A: AsWorker>(self, allocator: &mut A, identifier: usize, address: &[usize], logging: Option<Logger>) -> (Self::Pusher, Self::Puller) {
+        let (senders, receiver) = allocate(allocator, identifier, address, logging);
+        let acks = allocator.new_identifier();
+        let (ack_senders, ack_receiver) = allocator.allocate::<(usize, usize)>(acks, address);
+        (Box::new(BalancePusher::new(senders, ack_receiver)), Box::new(BalancePuller::new(receiver, allocator.index(), ack_senders)))
+    }
+}
+
+/// An exchange sending each batch of records to the worker following the one that received the
+/// previous batch.
+///
+/// Used to broadcast shared batches, one copy to each worker, without cloning or encoding each copy.
+#[derive(Debug)]
+pub(crate) struct Distributed;
+
+impl<T: Eq+Data+Clone, D: Data+Clone> ParallelizationContract<T, D> for Distributed {
+    type Pusher = DistributePusher<T, D, LogPusher<T, D, Box<dyn Push<Bundle<T, D>>>>>;
+    type Puller = Receiver<T, D>;
+    fn connect<A: AsWorker>(self, allocator: &mut A, identifier: usize, address: &[usize], logging: Option<Logger>) -> (Self::Pusher, Self::Puller) {
+        let (senders, receiver) = allocate(allocator, identifier, address, logging);
+        (DistributePusher::new(senders), receiver)
+    }
+}
+
+/// A logged and accounted pusher to each worker, as allocated by `allocate`.
+type Senders<T, D> = Vec<LogPusher<T, D, Box<dyn Push<Bundle<T, D>>>>>;
+/// A logged and accounted puller from all workers, as allocated by `allocate`.
+type Receiver<T, D> = LogPuller<T, D, Box<dyn Pull<Bundle<T, D>>>>;
+
+/// Allocates logged and accounted channels from this worker to each worker, and to this worker from each worker.
+///
+/// Contracts that exchange data between workers build on these channels, so that logging and
+/// memory accounting are applied the same way for all of them.
+fn allocate<T: Data, D: Data, A: AsWorker>(allocator: &mut A, identifier: usize, address: &[usize], logging: Option<Logger>) -> (Senders<T, D>, Receiver<T, D>) {
+    let (senders, receiver) = allocator.allocate::<Message<T, D>>(identifier, address);
+    let account = allocator.memory().channels(address[0]);
+    let senders = senders.into_iter().enumerate().map(|(i,x)| LogPusher::new(x, allocator.index(), i, identifier, logging.clone()).with_account(account.clone())).collect::<Vec<_>>();
+    (senders, LogPuller::new(receiver, allocator.index(), identifier, logging).with_account(account))
+}
+
 /// Wraps a `Message<T,D>` pusher to provide a `Push<(T, Content<D>)>`.
 #[derive(Debug)]
 pub struct LogPusher<T, D, P: Push<Bundle<T, D>>> {
@@ -86,6 +224,7 @@ pub struct LogPusher<T, D, P: Push<Bundle<T, D>>> {
     target: usize,
     phantom: PhantomData<(T, D)>,
     logging: Option<Logger>,
+    account: Option<Account>,
 }
 
 impl<T, D, P: Push<Bundle<T, D>>> LogPusher<T, D, P> {
@@ -99,8 +238,15 @@ impl<T, D, P: Push<Bundle<T, D>>> LogPusher<T, D, P> {
             target,
             phantom: PhantomData,
             logging,
+            account: None,
         }
     }
+
+    /// Accounts for the bytes of records queued for this worker in `account`.
+    pub fn with_account(mut self, account: Account) -> Self {
+        self.account = Some(account);
+        self
+    }
 }
 
 impl<T, D, P: Push<Bundle<T, D>>> Push<Bundle<T, D>> for LogPusher<T, D, P> {
@@ -126,6 +272,16 @@ impl<T, D, P: Push<Bundle<T, D>>> Push<Bundle<T, D>> for LogPusher<T, D, P> {
                     length: bundle.data.len(),
                 })
             }
+
+            #[cfg(feature = "tracing")]
+            tracing_dep::trace!(channel = self.channel, source = self.source, target = self.target, seq_no = self.counter - 1, length = bundle.data.len(), "send");
+
+            // Records sent to other workers are accounted for by the communication layer.
+            if let Some(account) = self.account.as_ref() {
+                if self.source == self.target {
+                    account.add(bundle.data.len() * ::std::mem::size_of::<D>());
+                }
+            }
         }
 
         self.pusher.push(pair);
@@ -140,6 +296,7 @@ pub struct LogPuller<T, D, P: Pull<Bundle<T, D>>> {
     index: usize,
     phantom: PhantomData<(T, D)>,
     logging: Option<Logger>,
+    account: Option<Account>,
 }
 
 impl<T, D, P: Pull<Bundle<T, D>>> LogPuller<T, D, P> {
@@ -151,8 +308,15 @@ impl<T, D, P: Pull<Bundle<T, D>>> LogPuller<T, D, P> {
             index,
             phantom: PhantomData,
             logging,
+            account: None,
         }
     }
+
+    /// Accounts for the bytes of records queued for this worker in `account`.
+    pub fn with_account(mut self, account: Account) -> Self {
+        self.account = Some(account);
+        self
+    }
 }
 
 impl<T, D, P: Pull<Bundle<T, D>>> Pull<Bundle<T, D>> for LogPuller<T, D, P> {
@@ -173,6 +337,12 @@ impl<T, D, P: Pull<Bundle<T, D>>> Pull<Bundle<T, D>> for LogPuller<T, D, P> {
                     length: bundle.data.len(),
                 });
             }
+
+            if let Some(account) = self.account.as_ref() {
+                if bundle.from == target {
+                    account.sub(bundle.data.len() * ::std::mem::size_of::<D>());
+                }
+            }
         }
 
         result
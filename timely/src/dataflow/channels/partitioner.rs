@@ -0,0 +1,168 @@
+//! Strategies assigning records to the workers that should receive them.
+
+use std::collections::hash_map::DefaultHasher;
+use std::hash::{Hash, Hasher};
+
+use crate::hash::mix;
+
+/// The hash by which the built-in keyed operators route records with `key`.
+///
+/// Streams routed by this hash modulo the number of workers, for example by `key_by`, are
+/// partitioned as these operators expect.
+pub fn hash<K: Hash>(key: &K) -> u64 {
+    let mut hasher = DefaultHasher::new();
+    key.hash(&mut hasher);
+    hasher.finish()
+}
+
+/// Assigns records to workers.
+pub trait Partitioner<D> {
+    /// The index of the worker, less than `peers`, that should receive `datum`.
+    fn partition(&mut self, datum: &D, peers: usize) -> usize;
+}
+
+/// Assigns each record to the worker indicated by its hash, modulo the number of workers.
+///
+/// This is the strategy used by the `Exchange` contract.
+pub struct HashModulo<F> {
+    hash: F,
+}
+
+impl<F> HashModulo<F> {
+    /// Assigns records by the hash computed by `hash`.
+    pub fn new(hash: F) -> Self {
+        HashModulo { hash }
+    }
+}
+
+impl<D, F: FnMut(&D)->u64> Partitioner<D> for HashModulo<F> {
+    fn partition(&mut self, datum: &D, peers: usize) -> usize {
+        ((self.hash)(datum) % peers as u64) as usize
+    }
+}
+
+/// Assigns records to workers by ranges of their keys.
+///
+/// Records with keys less than `splitters[0]` are assigned to worker 0, those with keys at least
+/// `splitters[i-1]` and less than `splitters[i]` to worker `i`, and those with keys at least the
+/// last splitter to the worker after it. The splitters should be sorted. Records that would be
+/// assigned beyond the last worker are assigned to the last worker.
+pub struct Range<K, F> {
+    splitters: Vec<K>,
+    key: F,
+}
+
+impl<K, F> Range<K, F> {
+    /// Assigns records by the keys computed by `key`, in the ranges defined by `splitters`.
+    pub fn new(splitters: Vec<K>, key: F) -> Self {
+        Range { splitters, key }
+    }
+}
+
+impl<D, K: Ord, F: FnMut(&D)->K> Partitioner<D> for Range<K, F> {
+    fn partition(&mut self, datum: &D, peers: usize) -> usize {
+        let key = (self.key)(datum);
+        let range = match self.splitters.binary_search(&key) {
+            Ok(index) => index + 1,
+            Err(index) => index,
+        };
+        ::std::cmp::min(range, peers - 1)
+    }
+}
+
+/// Assigns records to workers by rendezvous hashing of their hash.
+///
+/// Each record is assigned to the worker for which a combination of the record's hash and the
+/// worker's index is greatest. Unlike `HashModulo`, a change in the number of workers reassigns
+/// only the records of the added or removed workers.
+pub struct Rendezvous<F> {
+    hash: F,
+}
+
+impl<F> Rendezvous<F> {
+    /// Assigns records by the hash computed by `hash`.
+    pub fn new(hash: F) -> Self {
+        Rendezvous { hash }
+    }
+}
+
+impl<D, F: FnMut(&D)->u64> Partitioner<D> for Rendezvous<F> {
+    fn partition(&mut self, datum: &D, peers: usize) -> usize {
+        let hash = (self.hash)(datum);
+        (0 .. peers)
+            .max_by_key(|&worker| mix(hash ^ mix(worker as u64)))
+            .expect("no workers")
+    }
+}
+
+/// Assigns each record to the worker whose index is computed by a function.
+pub struct Explicit<F> {
+    worker: F,
+}
+
+impl<F> Explicit<F> {
+    /// Assigns records to the workers computed by `worker`.
+    pub fn new(worker: F) -> Self {
+        Explicit { worker }
+    }
+}
+
+impl<D, F: FnMut(&D)->usize> Partitioner<D> for Explicit<F> {
+    fn partition(&mut self, datum: &D, peers: usize) -> usize {
+        let worker = (self.worker)(datum);
+        assert!(worker < peers, "record assigned to worker {} of {}", worker, peers);
+        worker
+    }
+}
+
+/// Assigns records only to a subset of the workers, as the subset is assigned by another partitioner.
+///
+/// An operator whose inputs are routed by a `Subset` runs with reduced parallelism: instances at
+/// workers outside the subset receive no records. This suits operators that must run at a single
+/// worker, like a sink writing to one file, or that should run only at some workers, like a join
+/// whose state should be held by the workers of larger machines. An operator built with
+/// `OperatorBuilder::set_workers` for the same subset runs only at those workers, and progress
+/// tracking accounts only for its instances there.
+///
+/// # Panics
+///
+/// Panics when partitioning a record if the subset is empty, or names a worker not less than
+/// the number of workers.
+///
+/// # Examples
+/// ```
+/// use timely::dataflow::operators::{ToStream, Exchange, Inspect};
+/// use timely::dataflow::channels::partitioner::{Subset, HashModulo, Explicit};
+///
+/// timely::execute(timely::Config::process(3), |worker| {
+///     let index = worker.index();
+///     worker.dataflow::<u64,_,_>(|scope| {
+///         let stream = (0 .. 10u64).to_stream(scope);
+///         // a singleton stage at the first worker.
+///         stream.exchange_with(Subset::new(vec![0], Explicit::new(|_: &u64| 0)))
+///               .inspect(move |_| assert_eq!(index, 0));
+///         // a stage at the last two workers, by hash.
+///         stream.exchange_with(Subset::new(vec![1, 2], HashModulo::new(|x: &u64| *x)))
+///               .inspect(move |_| assert!(index > 0));
+///     });
+/// }).unwrap();
+/// ```
+pub struct Subset<P> {
+    workers: Vec<usize>,
+    partitioner: P,
+}
+
+impl<P> Subset<P> {
+    /// Assigns records among `workers`, as `partitioner` assigns them among that many workers.
+    pub fn new(workers: Vec<usize>, partitioner: P) -> Self {
+        Subset { workers, partitioner }
+    }
+}
+
+impl<D, P: Partitioner<D>> Partitioner<D> for Subset<P> {
+    fn partition(&mut self, datum: &D, peers: usize) -> usize {
+        let worker = self.workers[self.partitioner.partition(datum, self.workers.len())];
+        assert!(worker < peers, "record assigned to worker {} of {}", worker, peers);
+        worker
+    }
+}
@@ -0,0 +1,33 @@
+//! Acknowledges the records pulled from a balanced channel to the workers that sent them.
+
+use crate::communication::{Push, Pull, Message};
+use crate::dataflow::channels::Bundle;
+
+/// Sends `(worker, records)` acknowledgements to a worker.
+pub type AckPusher = Box<dyn Push<Message<(usize, usize)>>>;
+
+/// Acknowledges each batch of records pulled to the worker that sent it, for a `pushers::Balance`.
+pub struct Balance<T, D, P: Pull<Bundle<T, D>>> {
+    puller: P,
+    index: usize,
+    acks: Vec<AckPusher>,
+    phantom: ::std::marker::PhantomData<(T, D)>,
+}
+
+impl<T, D, P: Pull<Bundle<T, D>>> Balance<T, D, P> {
+    /// Allocates a new `Balance` acknowledging records pulled by worker `index` through `acks`.
+    pub fn new(puller: P, index: usize, acks: Vec<AckPusher>) -> Self {
+        Balance { puller, index, acks, phantom: ::std::marker::PhantomData }
+    }
+}
+
+impl<T, D, P: Pull<Bundle<T, D>>> Pull<Bundle<T, D>> for Balance<T, D, P> {
+    #[inline]
+    fn pull(&mut self) -> &mut Option<Bundle<T, D>> {
+        let result = self.puller.pull();
+        if let Some(bundle) = result {
+            self.acks[bundle.from].send(Message::from_typed((self.index, bundle.data.len())));
+        }
+        result
+    }
+}
@@ -6,18 +6,33 @@ use std::cell::RefCell;
 use crate::dataflow::channels::Bundle;
 use crate::progress::ChangeBatch;
 use crate::communication::Pull;
+#[cfg(feature = "fuzz")]
+use crate::scheduling::fuzz::Deliveries;
 
 /// A wrapper which accounts records pulled past in a shared count map.
 pub struct Counter<T: Ord+Clone+'static, D, P: Pull<Bundle<T, D>>> {
     pullable: P,
     consumed: Rc<RefCell<ChangeBatch<T>>>,
     phantom: ::std::marker::PhantomData<D>,
+    #[cfg(feature = "fuzz")]
+    fuzz: Option<Deliveries<Bundle<T, D>>>,
 }
 
 impl<T:Ord+Clone+'static, D, P: Pull<Bundle<T, D>>> Counter<T, D, P> {
     /// Retrieves the next timestamp and batch of data.
     #[inline]
     pub fn next(&mut self) -> Option<&mut Bundle<T, D>> {
+        #[cfg(feature = "fuzz")]
+        if let Some(fuzz) = self.fuzz.as_mut() {
+            while let Some(message) = self.pullable.pull().take() {
+                if !message.data.is_empty() {
+                    fuzz.hold(message);
+                }
+            }
+            let message = fuzz.next()?;
+            self.consumed.borrow_mut().update(message.time.clone(), message.data.len() as i64);
+            return Some(message);
+        }
         if let Some(message) = self.pullable.pull() {
             if message.data.len() > 0 {
                 self.consumed.borrow_mut().update(message.time.clone(), message.data.len() as i64);
@@ -36,8 +51,15 @@ impl<T:Ord+Clone+'static, D, P: Pull<Bundle<T, D>>> Counter<T, D, P> {
             phantom: ::std::marker::PhantomData,
             pullable,
             consumed: Rc::new(RefCell::new(ChangeBatch::new())),
+            #[cfg(feature = "fuzz")]
+            fuzz: None,
         }
     }
+    /// Delivers pulled batches after random delays and in random order.
+    #[cfg(feature = "fuzz")]
+    pub(crate) fn fuzz(&mut self, deliveries: Deliveries<Bundle<T, D>>) {
+        self.fuzz = Some(deliveries);
+    }
     /// A references to shared changes in counts, for cloning or draining.
     pub fn consumed(&self) -> &Rc<RefCell<ChangeBatch<T>>> {
         &self.consumed
@@ -1,5 +1,7 @@
 pub use self::counter::Counter;
+pub use self::balance::Balance;
 pub mod counter;
+pub mod balance;
 
 
 // pub trait Pullable<T, D> {
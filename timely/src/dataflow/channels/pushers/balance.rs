@@ -0,0 +1,42 @@
+//! The balance pattern sends each batch of records to the worker with the fewest unconsumed records.
+
+use crate::communication::{Push, Pull};
+use crate::dataflow::channels::Bundle;
+
+/// Sends each batch of records to the worker with the fewest records sent but not yet consumed.
+///
+/// Workers acknowledge the records they consume from this worker through `acks`, as
+/// `(worker, records)` pairs, by way of a `pullers::Balance`.
+pub struct Balance<T, D, P: Push<Bundle<T, D>>> {
+    pushers: Vec<P>,
+    outstanding: Vec<usize>,
+    acks: Box<dyn Pull<crate::communication::Message<(usize, usize)>>>,
+    phantom: ::std::marker::PhantomData<(T, D)>,
+}
+
+impl<T, D, P: Push<Bundle<T, D>>> Balance<T, D, P> {
+    /// Allocates a new `Balance` from a supplied set of pushers and a source of acknowledgements.
+    pub fn new(pushers: Vec<P>, acks: Box<dyn Pull<crate::communication::Message<(usize, usize)>>>) -> Self {
+        let outstanding = vec![0; pushers.len()];
+        Balance { pushers, outstanding, acks, phantom: ::std::marker::PhantomData }
+    }
+}
+
+impl<T, D, P: Push<Bundle<T, D>>> Push<Bundle<T, D>> for Balance<T, D, P> {
+    fn push(&mut self, message: &mut Option<Bundle<T, D>>) {
+        while let Some(ack) = self.acks.recv() {
+            let (worker, records) = *ack;
+            self.outstanding[worker] = self.outstanding[worker].saturating_sub(records);
+        }
+        if let Some(bundle) = message {
+            let target = (0 .. self.pushers.len()).min_by_key(|&index| self.outstanding[index]).expect("no workers");
+            self.outstanding[target] += bundle.data.len();
+            self.pushers[target].push(message);
+        }
+        else {
+            for pusher in self.pushers.iter_mut() {
+                pusher.push(&mut None);
+            }
+        }
+    }
+}
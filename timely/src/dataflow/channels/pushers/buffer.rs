@@ -49,6 +49,12 @@ impl<T, D, P: Push<Bundle<T, D>>> Buffer<T, D, P> where T: Eq+Clone {
     /// This is currently used internally, and should not be used without some care.
     pub fn inner(&mut self) -> &mut P { &mut self.pusher }
 
+    /// Sends any buffered records, and then `bundle` as it is.
+    pub(crate) fn give_bundle(&mut self, bundle: Bundle<T, D>) {
+        self.flush();
+        self.pusher.push(&mut Some(bundle));
+    }
+
     /// Flushes all data and pushes a `None` to `self.pusher`, indicating a flush.
     pub fn cease(&mut self) {
         self.flush();
@@ -0,0 +1,35 @@
+//! The distribute pattern sends consecutive batches of records to consecutive workers.
+
+use crate::communication::Push;
+use crate::dataflow::channels::Bundle;
+
+/// Sends each batch of records to the worker following the one that received the previous batch.
+///
+/// A sender of as many copies of a batch as there are workers, in succession, delivers one copy to
+/// each worker. Copies of a shared message are not cloned, and are encoded at most once.
+pub struct Distribute<T, D, P: Push<Bundle<T, D>>> {
+    pushers: Vec<P>,
+    next: usize,
+    phantom: ::std::marker::PhantomData<(T, D)>,
+}
+
+impl<T, D, P: Push<Bundle<T, D>>> Distribute<T, D, P> {
+    /// Allocates a new `Distribute` from a supplied set of pushers.
+    pub fn new(pushers: Vec<P>) -> Self {
+        Distribute { pushers, next: 0, phantom: ::std::marker::PhantomData }
+    }
+}
+
+impl<T, D, P: Push<Bundle<T, D>>> Push<Bundle<T, D>> for Distribute<T, D, P> {
+    fn push(&mut self, message: &mut Option<Bundle<T, D>>) {
+        if message.is_some() {
+            self.pushers[self.next].push(message);
+            self.next = (self.next + 1) % self.pushers.len();
+        }
+        else {
+            for pusher in self.pushers.iter_mut() {
+                pusher.push(&mut None);
+            }
+        }
+    }
+}
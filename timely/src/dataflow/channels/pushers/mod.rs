@@ -1,8 +1,12 @@
 pub use self::tee::{Tee, TeeHelper};
 pub use self::exchange::Exchange;
 pub use self::counter::Counter;
+pub use self::balance::Balance;
+pub use self::distribute::Distribute;
 
 pub mod tee;
 pub mod exchange;
 pub mod counter;
 pub mod buffer;
+pub mod balance;
+pub mod distribute;
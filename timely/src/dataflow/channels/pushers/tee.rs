@@ -12,6 +12,11 @@ use crate::communication::Push;
 type PushList<T, D> = Rc<RefCell<Vec<Box<dyn Push<Bundle<T, D>>>>>>;
 
 /// Wraps a shared list of `Box<Push>` to forward pushes to. Owned by `Stream`.
+///
+/// Each bundle is moved to the last pusher in the list, and the other pushers receive copies of
+/// its records, so that a stream with a single consumer copies no records. Each pusher is the
+/// start of a distinct edge of the dataflow graph, whose records are counted separately for
+/// progress tracking.
 pub struct Tee<T: 'static, D: 'static> {
     buffer: Vec<D>,
     shared: PushList<T, D>,
@@ -89,6 +94,16 @@ impl<T, D> TeeHelper<T, D> {
     pub fn add_pusher<P: Push<Bundle<T, D>>+'static>(&self, pusher: P) {
         self.shared.borrow_mut().push(Box::new(pusher));
     }
+
+    /// The number of `Push` implementors in the list.
+    pub fn len(&self) -> usize {
+        self.shared.borrow().len()
+    }
+
+    /// True if the list has no `Push` implementors.
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
 }
 
 impl<T, D> Clone for TeeHelper<T, D> {
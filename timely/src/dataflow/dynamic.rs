@@ -0,0 +1,206 @@
+//! Type-erased streams, for dataflows assembled from a description available only at runtime.
+//!
+//! A `Registry` maps runtime type tags, for example `"u64"`, to record types, and operator names
+//! to typed operators. A `DynStream` carries type-erased records along with their tag, so that
+//! a frontend can connect registered operators by name, for example following a JSON description
+//! of a pipeline, checking tags rather than types. Registered operators recover the typed stream
+//! from their inputs, and erase the type of their output. Records are moved between operators in
+//! boxes, and are neither serialized nor copied.
+
+use std::any::{Any, TypeId};
+use std::collections::HashMap;
+use std::rc::Rc;
+
+use crate::Data;
+use crate::dataflow::{Scope, Stream};
+use crate::dataflow::operators::Map;
+
+/// A record of some type, which can be cloned.
+trait AnyData: Any {
+    fn clone_box(&self) -> Box<dyn AnyData>;
+    fn into_any(self: Box<Self>) -> Box<dyn Any>;
+}
+
+impl<D: Data> AnyData for D {
+    fn clone_box(&self) -> Box<dyn AnyData> {
+        Box::new(self.clone())
+    }
+    fn into_any(self: Box<Self>) -> Box<dyn Any> {
+        self
+    }
+}
+
+/// A type-erased record.
+pub struct DynRecord {
+    record: Box<dyn AnyData>,
+}
+
+impl DynRecord {
+    /// Erases the type of `record`.
+    pub fn new<D: Data>(record: D) -> Self {
+        DynRecord { record: Box::new(record) }
+    }
+    /// Recovers the record, if it is of type `D`.
+    pub fn downcast<D: Data>(self) -> Result<D, Self> {
+        if (*self.record).type_id() == TypeId::of::<D>() {
+            Ok(*self.record.into_any().downcast::<D>().expect("record of checked type"))
+        }
+        else {
+            Err(self)
+        }
+    }
+}
+
+impl Clone for DynRecord {
+    fn clone(&self) -> Self {
+        DynRecord { record: self.record.clone_box() }
+    }
+}
+
+/// A stream of type-erased records of a registered type.
+#[derive(Clone)]
+pub struct DynStream<G: Scope> {
+    tag: String,
+    stream: Stream<G, DynRecord>,
+}
+
+impl<G: Scope> DynStream<G> {
+    /// The tag of the type of the stream's records.
+    pub fn tag(&self) -> &str {
+        &self.tag
+    }
+    /// The stream of type-erased records.
+    pub fn stream(&self) -> &Stream<G, DynRecord> {
+        &self.stream
+    }
+}
+
+type Operator<G> = Rc<dyn Fn(&Registry<G>, &[DynStream<G>])->Result<DynStream<G>, String>>;
+
+/// Record types and operators, by name.
+///
+/// # Examples
+/// ```
+/// use timely::dataflow::operators::{ToStream, Map, Filter, Capture};
+/// use timely::dataflow::operators::capture::Extract;
+/// use timely::dataflow::dynamic::Registry;
+///
+/// let captured = timely::example(|scope| {
+///
+///     let mut registry = Registry::new();
+///     registry.register_type::<String>("string");
+///     registry.register_type::<u64>("u64");
+///     registry.register_unary("parse", |stream| stream.map(|text: String| text.parse::<u64>().unwrap()));
+///     registry.register_unary("even", |stream| stream.filter(|x: &u64| x % 2 == 0));
+///
+///     // a pipeline described at runtime.
+///     let pipeline = vec!["parse", "even"];
+///
+///     let input = vec!["1", "2", "3", "4"].into_iter().map(|x| x.to_string()).to_stream(scope);
+///     let mut stream = registry.erase(&input).unwrap();
+///     for name in pipeline {
+///         stream = registry.apply(name, &[stream]).unwrap();
+///     }
+///     assert_eq!(stream.tag(), "u64");
+///     registry.recover::<u64>(&stream).unwrap().capture()
+/// });
+///
+/// assert_eq!(captured.extract(), vec![(0, vec![2, 4])]);
+/// ```
+pub struct Registry<G: Scope> {
+    tags: HashMap<TypeId, String>,
+    types: HashMap<String, TypeId>,
+    operators: HashMap<String, Operator<G>>,
+}
+
+impl<G: Scope> Default for Registry<G> {
+    fn default() -> Self {
+        Registry { tags: HashMap::new(), types: HashMap::new(), operators: HashMap::new() }
+    }
+}
+
+impl<G: Scope> Registry<G> {
+    /// An empty registry.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Registers records of type `D` under `tag`.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `tag` or `D` is already registered.
+    pub fn register_type<D: Data>(&mut self, tag: &str) {
+        assert!(!self.types.contains_key(tag), "type tag {:?} already registered", tag);
+        let previous = self.tags.insert(TypeId::of::<D>(), tag.to_owned());
+        assert!(previous.is_none(), "type of tag {:?} already registered as {:?}", tag, previous);
+        self.types.insert(tag.to_owned(), TypeId::of::<D>());
+    }
+
+    /// The tag registered for records of type `D`, if any.
+    pub fn tag<D: Data>(&self) -> Option<&str> {
+        self.tags.get(&TypeId::of::<D>()).map(|tag| &tag[..])
+    }
+
+    /// Erases the type of the records of `stream`, which must be of a registered type.
+    pub fn erase<D: Data>(&self, stream: &Stream<G, D>) -> Result<DynStream<G>, String> {
+        let tag = self.tag::<D>().ok_or_else(|| format!("type {} is not registered", ::std::any::type_name::<D>()))?;
+        let stream = stream.map(DynRecord::new);
+        Ok(DynStream { tag: tag.to_owned(), stream })
+    }
+
+    /// Recovers the records of `stream`, which must be of type `D`.
+    pub fn recover<D: Data>(&self, stream: &DynStream<G>) -> Result<Stream<G, D>, String> {
+        if self.types.get(&stream.tag) != Some(&TypeId::of::<D>()) {
+            return Err(format!("stream of {:?} is not of type {}", stream.tag, ::std::any::type_name::<D>()));
+        }
+        // the records were erased by `erase` from records of type `D`.
+        Ok(stream.stream.map(|record| record.downcast::<D>().ok().expect("record of registered type")))
+    }
+
+    /// Registers `logic`, from a stream of `I` to a stream of `O`, as the operator `name`.
+    ///
+    /// The types need not be registered until the operator is applied.
+    pub fn register_unary<I, O, L>(&mut self, name: &str, logic: L)
+    where
+        I: Data,
+        O: Data,
+        L: Fn(&Stream<G, I>)->Stream<G, O>+'static,
+    {
+        let operator: Operator<G> = Rc::new(move |registry, inputs| {
+            match inputs {
+                [input] => registry.erase(&logic(&registry.recover::<I>(input)?)),
+                _ => Err(format!("expected one input, found {}", inputs.len())),
+            }
+        });
+        self.operators.insert(name.to_owned(), operator);
+    }
+
+    /// Registers `logic`, from streams of `I1` and `I2` to a stream of `O`, as the operator `name`.
+    ///
+    /// The types need not be registered until the operator is applied.
+    pub fn register_binary<I1, I2, O, L>(&mut self, name: &str, logic: L)
+    where
+        I1: Data,
+        I2: Data,
+        O: Data,
+        L: Fn(&Stream<G, I1>, &Stream<G, I2>)->Stream<G, O>+'static,
+    {
+        let operator: Operator<G> = Rc::new(move |registry, inputs| {
+            match inputs {
+                [input1, input2] => registry.erase(&logic(&registry.recover::<I1>(input1)?, &registry.recover::<I2>(input2)?)),
+                _ => Err(format!("expected two inputs, found {}", inputs.len())),
+            }
+        });
+        self.operators.insert(name.to_owned(), operator);
+    }
+
+    /// Applies the operator `name` to `inputs`.
+    ///
+    /// Returns an error if there is no such operator, or if the inputs do not have the number and
+    /// types of records the operator expects.
+    pub fn apply(&self, name: &str, inputs: &[DynStream<G>]) -> Result<DynStream<G>, String> {
+        let operator = self.operators.get(name).ok_or_else(|| format!("operator {:?} is not registered", name))?;
+        operator(self, inputs).map_err(|error| format!("operator {:?}: {}", name, error))
+    }
+}
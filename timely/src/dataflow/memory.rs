@@ -0,0 +1,153 @@
+//! Accounting of the memory held by the dataflows installed in a worker.
+//!
+//! The worker accounts for the bytes of records queued in channels between its operators, the
+//! bytes of the output buffers of its operators, and the bytes of managed operator state. Sizes
+//! are estimated from the sizes of the types of records, keys, and values, and do not include
+//! memory the records own indirectly, for example the contents of strings or vectors.
+//!
+//! When the worker is configured with `Config::memory_budget`, it compares its usage against the
+//! budget after each step. Above the soft limit it signals `Pressure`, to which sources respond by
+//! pausing the introduction of new records, and above the hard limit it panics with a report of
+//! its usage.
+
+use std::rc::Rc;
+use std::cell::Cell;
+use std::collections::BTreeMap;
+use std::fmt;
+
+/// A count of bytes, shared by the components that hold them.
+#[derive(Clone, Debug, Default)]
+pub struct Account {
+    bytes: Rc<Cell<usize>>,
+}
+
+impl Account {
+    /// Records that `bytes` more bytes are held.
+    #[inline]
+    pub fn add(&self, bytes: usize) {
+        self.bytes.set(self.bytes.get() + bytes);
+    }
+    /// Records that `bytes` fewer bytes are held.
+    #[inline]
+    pub fn sub(&self, bytes: usize) {
+        self.bytes.set(self.bytes.get().saturating_sub(bytes));
+    }
+    /// The number of bytes held.
+    pub fn bytes(&self) -> usize {
+        self.bytes.get()
+    }
+}
+
+/// Indicates whether the worker's memory usage exceeds its soft limit.
+///
+/// Sources should refrain from introducing new records while pressure is signaled.
+#[derive(Clone, Debug, Default)]
+pub struct Pressure {
+    signaled: Rc<Cell<bool>>,
+}
+
+impl Pressure {
+    /// Indicates whether pressure is signaled.
+    pub fn signaled(&self) -> bool {
+        self.signaled.get()
+    }
+}
+
+/// The bytes held by a worker, by category.
+#[derive(Clone, Copy, Debug, Default, Eq, PartialEq)]
+pub struct MemoryUsage {
+    /// Bytes of records queued in channels between operators of the worker.
+    pub channels: usize,
+    /// Bytes of the output buffers of operators.
+    pub buffers: usize,
+    /// Bytes of managed operator state held in memory.
+    pub state: usize,
+}
+
+impl MemoryUsage {
+    /// The total bytes held.
+    pub fn total(&self) -> usize {
+        self.channels + self.buffers + self.state
+    }
+}
+
+impl fmt::Display for MemoryUsage {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{} bytes (channels: {}, output buffers: {}, state: {})", self.total(), self.channels, self.buffers, self.state)
+    }
+}
+
+/// The accounts of the dataflows installed in a worker.
+///
+/// # Examples
+/// ```
+/// timely::execute_directly(|worker| {
+///
+///     use timely::dataflow::InputHandle;
+///     use timely::dataflow::operators::{Input, Map, Probe};
+///
+///     let mut input = InputHandle::new();
+///     let probe = worker.dataflow::<usize,_,_>(|scope| {
+///         scope
+///             .input_from(&mut input)
+///             .map(|x: usize| x + 1)
+///             .probe()
+///     });
+///
+///     // each output has a buffer.
+///     assert!(worker.memory_usage().buffers > 0);
+///
+///     for round in 0 .. 10 {
+///         input.send(round);
+///     }
+///     input.advance_to(1);
+///     worker.step_while(|| probe.less_than(input.time()));
+///
+///     // drained channels hold no records.
+///     assert_eq!(worker.memory_usage().channels, 0);
+/// });
+/// ```
+#[derive(Default)]
+pub struct Memory {
+    channels: BTreeMap<usize, Account>,
+    buffers: BTreeMap<Vec<usize>, usize>,
+    pressure: Pressure,
+}
+
+impl Memory {
+    /// The account of records queued in the channels of the dataflow with the supplied index.
+    pub fn channels(&mut self, dataflow_index: usize) -> Account {
+        self.channels.entry(dataflow_index).or_default().clone()
+    }
+
+    /// Records `bytes` of output buffers for the operator at `address`.
+    pub fn insert_buffers(&mut self, address: &[usize], bytes: usize) {
+        *self.buffers.entry(address.to_vec()).or_insert(0) += bytes;
+    }
+
+    /// A handle reporting whether memory usage exceeds the soft limit.
+    pub fn pressure(&self) -> Pressure {
+        self.pressure.clone()
+    }
+
+    /// Signals or clears pressure.
+    pub(crate) fn set_pressure(&self, signaled: bool) {
+        self.pressure.signaled.set(signaled);
+    }
+
+    /// Bytes of records queued in channels, over all dataflows.
+    pub fn channel_bytes(&self) -> usize {
+        self.channels.values().map(|account| account.bytes()).sum()
+    }
+
+    /// Bytes of output buffers, over all dataflows.
+    pub fn buffer_bytes(&self) -> usize {
+        self.buffers.values().sum()
+    }
+
+    /// Discards the accounts of the dataflow with the supplied index.
+    pub fn remove_dataflow(&mut self, dataflow_index: usize) {
+        self.channels.remove(&dataflow_index);
+        self.buffers.retain(|address, _| address.first() != Some(&dataflow_index));
+    }
+}
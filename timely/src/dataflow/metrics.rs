@@ -0,0 +1,131 @@
+//! Counters describing the work performed by the operators of installed dataflows.
+//!
+//! When enabled with `WorkerConfig::metrics`, each scope registers counters for its operators
+//! with the worker when the scope is built, and updates them as it schedules the operators and
+//! collects their progress information. The worker retains the counters for as long as the
+//! dataflow is installed.
+
+use std::rc::Rc;
+use std::cell::RefCell;
+use std::collections::BTreeMap;
+use std::time::Duration;
+
+/// Counters for a single operator.
+///
+/// Records and batches are counted from the progress information the operator reports to its
+/// scope. Records are the number of messages consumed or produced at each port, and batches
+/// are the number of distinct timestamps at which messages were reported, summed over each
+/// time the scope collected the operator's progress information.
+#[derive(Clone, Debug, Default, Eq, PartialEq)]
+pub struct OperatorMetrics {
+    /// A helpful name.
+    pub name: String,
+    /// The number of times the operator has been scheduled.
+    pub schedules: usize,
+    /// The cumulative time spent scheduling the operator, including any operators it contains.
+    pub elapsed: Duration,
+    /// The bytes allocated while scheduling the operator, including any operators it contains.
+    ///
+    /// See [`CountingAllocator`](crate::allocator::CountingAllocator).
+    pub allocated: u64,
+    /// The number of records consumed at each input.
+    pub records_in: Vec<i64>,
+    /// The number of batches consumed at each input.
+    pub batches_in: Vec<usize>,
+    /// The number of records produced at each output.
+    pub records_out: Vec<i64>,
+    /// The number of batches produced at each output.
+    pub batches_out: Vec<usize>,
+}
+
+impl OperatorMetrics {
+    /// Allocates zeroed counters for an operator with the supplied name and shape.
+    pub fn new(name: String, inputs: usize, outputs: usize) -> Self {
+        OperatorMetrics {
+            name,
+            schedules: 0,
+            elapsed: Duration::default(),
+            allocated: 0,
+            records_in: vec![0; inputs],
+            batches_in: vec![0; inputs],
+            records_out: vec![0; outputs],
+            batches_out: vec![0; outputs],
+        }
+    }
+
+    /// The number of records consumed at all inputs and not matched by records produced at any
+    /// output, or zero if the operator produced more records than it consumed.
+    ///
+    /// For operators like `filter` and `flat_map`, this is the number of records they dropped.
+    pub fn records_dropped(&self) -> i64 {
+        let consumed: i64 = self.records_in.iter().sum();
+        let produced: i64 = self.records_out.iter().sum();
+        (consumed - produced).max(0)
+    }
+}
+
+/// The counters of the operators of the dataflows installed in a worker, indexed by address.
+///
+/// # Examples
+/// ```
+/// let mut config = timely::Config::thread();
+/// config.worker = timely::WorkerConfig::default().metrics(true);
+/// timely::execute(config, |worker| {
+///
+///     use timely::dataflow::InputHandle;
+///     use timely::dataflow::operators::{Input, Map, Filter, Probe};
+///
+///     let mut input = InputHandle::new();
+///     let probe = worker.dataflow::<usize,_,_>(|scope| {
+///         scope
+///             .input_from(&mut input)
+///             .map(|x: usize| x + 1)
+///             .filter(|x| x % 2 == 0)
+///             .probe()
+///     });
+///
+///     for round in 0 .. 10 {
+///         input.send(round);
+///         input.advance_to(round + 1);
+///         worker.step_while(|| probe.less_than(input.time()));
+///     }
+///
+///     let map = worker.metrics().get(&[0, 2]).unwrap();
+///     assert_eq!(map.name, "Map");
+///     assert!(map.schedules > 0);
+///     assert_eq!(map.records_in, vec![10]);
+///     assert_eq!(map.records_out, vec![10]);
+///     assert_eq!(map.records_dropped(), 0);
+///
+///     let filter = worker.metrics().get(&[0, 3]).unwrap();
+///     assert_eq!(filter.name, "Filter");
+///     assert_eq!(filter.records_dropped(), 5);
+/// }).unwrap();
+/// ```
+#[derive(Clone, Debug, Default)]
+pub struct Metrics {
+    operators: BTreeMap<Vec<usize>, Rc<RefCell<OperatorMetrics>>>,
+}
+
+impl Metrics {
+
+    /// Registers the shared counters of the operator at `address`, replacing any existing counters.
+    pub fn insert(&mut self, address: Vec<usize>, metrics: Rc<RefCell<OperatorMetrics>>) {
+        self.operators.insert(address, metrics);
+    }
+
+    /// Removes the counters of the operators of the dataflow with the supplied index.
+    pub fn remove_dataflow(&mut self, dataflow_index: usize) {
+        self.operators.retain(|address, _| address.first() != Some(&dataflow_index));
+    }
+
+    /// A snapshot of the counters of the operator at `address`, if it exists.
+    pub fn get(&self, address: &[usize]) -> Option<OperatorMetrics> {
+        self.operators.get(address).map(|metrics| metrics.borrow().clone())
+    }
+
+    /// Iterates over snapshots of the counters of all operators, with their addresses.
+    pub fn iter(&self) -> impl Iterator<Item=(&Vec<usize>, OperatorMetrics)> {
+        self.operators.iter().map(|(address, metrics)| (address, metrics.borrow().clone()))
+    }
+}
@@ -23,3 +23,10 @@ pub mod operators;
 pub mod channels;
 pub mod scopes;
 pub mod stream;
+pub mod topology;
+pub mod metrics;
+pub mod memory;
+pub mod state;
+pub mod resources;
+pub mod dynamic;
+pub mod plan;
@@ -5,7 +5,7 @@ use std::collections::HashMap;
 use crate::{Data, ExchangeData};
 use crate::dataflow::{Stream, Scope};
 use crate::dataflow::operators::generic::operator::Operator;
-use crate::dataflow::channels::pact::Exchange;
+use crate::dataflow::channels::pact::{Exchange, ParallelizationContract, Pipeline};
 
 /// Generic intra-timestamp aggregation
 ///
@@ -65,6 +65,39 @@ pub trait Aggregate<S: Scope, K: ExchangeData+Hash, V: ExchangeData> {
         fold: F,
         emit: E,
         hash: H) -> Stream<S, R> where S::Timestamp: Eq;
+
+    /// Aggregates data of the form `(key, val)`, first within each worker and then across workers.
+    ///
+    /// Each worker folds its records into a partial aggregate for each key and time, as `aggregate`
+    /// does, but before exchanging any data. Once the time is complete the partial aggregates are
+    /// exchanged by key, combined using `merge`, and passed to `emit`. When many records share each
+    /// key, as in word count, this exchanges one partial aggregate per key from each worker rather
+    /// than every record, at the cost of producing results one notification later.
+    ///
+    /// # Examples
+    /// ```
+    /// use timely::dataflow::operators::{ToStream, Map, Inspect};
+    /// use timely::dataflow::operators::aggregation::Aggregate;
+    ///
+    /// timely::example(|scope| {
+    ///
+    ///     (0..10).to_stream(scope)
+    ///         .map(|x| (x % 2, x))
+    ///         .aggregate_hierarchical(
+    ///             |_key, val, agg| { *agg += val; },
+    ///             |_key, partial, agg| { *agg += partial; },
+    ///             |key, agg: i32| (key, agg),
+    ///             |key| *key as u64
+    ///         )
+    ///         .inspect(|x| assert!(*x == (0, 20) || *x == (1, 25)));
+    /// });
+    /// ```
+    fn aggregate_hierarchical<R: Data, D: ExchangeData+Default, F: Fn(&K, V, &mut D)+'static, M: Fn(&K, D, &mut D)+'static, E: Fn(K, D)->R+'static, H: Fn(&K)->u64+'static>(
+        &self,
+        fold: F,
+        merge: M,
+        emit: E,
+        hash: H) -> Stream<S, R> where S::Timestamp: Eq;
 }
 
 impl<S: Scope, K: ExchangeData+Hash+Eq, V: ExchangeData> Aggregate<S, K, V> for Stream<S, (K, V)> {
@@ -75,14 +108,24 @@ impl<S: Scope, K: ExchangeData+Hash+Eq, V: ExchangeData> Aggregate<S, K, V> for
         emit: E,
         hash: H) -> Stream<S, R> where S::Timestamp: Eq {
 
-        let mut aggregates = HashMap::new();
+        aggregate(self, Exchange::new(move |&(ref k, _)| hash(k)), fold, emit)
+    }
+
+    fn aggregate_hierarchical<R: Data, D: ExchangeData+Default, F: Fn(&K, V, &mut D)+'static, M: Fn(&K, D, &mut D)+'static, E: Fn(K, D)->R+'static, H: Fn(&K)->u64+'static>(
+        &self,
+        fold: F,
+        merge: M,
+        emit: E,
+        hash: H) -> Stream<S, R> where S::Timestamp: Eq {
+
+        let mut partials = HashMap::new();
         let mut vector = Vec::new();
-        self.unary_notify(Exchange::new(move |&(ref k, _)| hash(k)), "Aggregate", vec![], move |input, output, notificator| {
+        self.unary_notify(Pipeline, "AggregateLocal", vec![], move |input, output, notificator| {
 
-            // read each input, fold into aggregates
+            // fold each input into per-worker partial aggregates
             input.for_each(|time, data| {
                 data.swap(&mut vector);
-                let agg_time = aggregates.entry(time.time().clone()).or_insert_with(HashMap::new);
+                let agg_time = partials.entry(time.time().clone()).or_insert_with(HashMap::new);
                 for (key, val) in vector.drain(..) {
                     let agg = agg_time.entry(key.clone()).or_insert_with(Default::default);
                     fold(&key, val, agg);
@@ -90,16 +133,54 @@ impl<S: Scope, K: ExchangeData+Hash+Eq, V: ExchangeData> Aggregate<S, K, V> for
                 notificator.notify_at(time.retain());
             });
 
-            // pop completed aggregates, send along whatever
+            // send completed partial aggregates along to be merged
             notificator.for_each(|time,_,_| {
-                if let Some(aggs) = aggregates.remove(time.time()) {
-                    let mut session = output.session(&time);
-                    for (key, agg) in aggs {
-                        session.give(emit(key, agg));
-                    }
+                if let Some(aggs) = partials.remove(time.time()) {
+                    output.session(&time).give_iterator(aggs.into_iter());
                 }
             });
         })
-
+        .aggregate(merge, emit, hash)
     }
 }
+
+/// Folds the records of each key received through `pact` within each timestamp, and produces
+/// `emit` of each aggregate once its timestamp is complete.
+pub(crate) fn aggregate<S, K, V, R, D, F, E, P>(stream: &Stream<S, (K, V)>, pact: P, fold: F, emit: E) -> Stream<S, R>
+where
+    S: Scope,
+    S::Timestamp: Eq,
+    K: Data+Hash+Eq,
+    V: Data,
+    R: Data,
+    D: Default+'static,
+    F: Fn(&K, V, &mut D)+'static,
+    E: Fn(K, D)->R+'static,
+    P: ParallelizationContract<S::Timestamp, (K, V)>,
+{
+    let mut aggregates = HashMap::new();
+    let mut vector = Vec::new();
+    stream.unary_notify(pact, "Aggregate", vec![], move |input, output, notificator| {
+
+        // read each input, fold into aggregates
+        input.for_each(|time, data| {
+            data.swap(&mut vector);
+            let agg_time = aggregates.entry(time.time().clone()).or_insert_with(HashMap::new);
+            for (key, val) in vector.drain(..) {
+                let agg = agg_time.entry(key.clone()).or_insert_with(Default::default);
+                fold(&key, val, agg);
+            }
+            notificator.notify_at(time.retain());
+        });
+
+        // pop completed aggregates, send along whatever
+        notificator.for_each(|time,_,_| {
+            if let Some(aggs) = aggregates.remove(time.time()) {
+                let mut session = output.session(&time);
+                for (key, agg) in aggs {
+                    session.give(emit(key, agg));
+                }
+            }
+        });
+    })
+}
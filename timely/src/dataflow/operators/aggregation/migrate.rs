@@ -0,0 +1,258 @@
+//! A state machine whose per-key states migrate between workers as keys are reassigned.
+//!
+//! The `state_machine` operator holds the state of each key at the worker to which the key's
+//! hash assigns it, and this assignment is fixed for the life of the dataflow. The migrating
+//! state machine instead assigns keys among a set of workers that may change at any timestamp,
+//! for example to move work to added workers or away from departing ones. At the timestamp of
+//! each change, every worker ships the states of the keys it no longer owns to their new owners,
+//! and new owners process the records of each timestamp only once all states assigned to them as
+//! of that timestamp have arrived, so that results are as if the keys never moved.
+
+use std::hash::Hash;
+use std::collections::HashMap;
+use std::rc::Rc;
+
+use crate::{Data, ExchangeData};
+use crate::order::TotalOrder;
+use crate::progress::{Antichain, Timestamp, PathSummary};
+use crate::dataflow::{Stream, Scope};
+use crate::dataflow::channels::pact::{Exchange, Pipeline};
+use crate::dataflow::operators::{Feedback, ConnectLoop};
+use crate::dataflow::operators::capability::Capability;
+use crate::dataflow::operators::generic::operator::Operator;
+use crate::dataflow::operators::generic::builder_rc::OperatorBuilder;
+
+// Records of one timestamp.
+type Stashed<T, D> = (Capability<T>, Vec<D>);
+// Records of one timestamp, each with the worker that owns its key.
+type Assigned<T, K, V> = Stashed<T, (usize, (K, V))>;
+
+/// Provides the `migrating_state_machine` method.
+pub trait MigratingStateMachine<S: Scope, K: ExchangeData+Hash+Eq, V: ExchangeData> where S::Timestamp: TotalOrder {
+    /// Tracks a state for each presented key, as `state_machine` does, at workers that change over time.
+    ///
+    /// Each record of `routing` is the set of workers among which keys are assigned by `hash`,
+    /// from its timestamp onwards, in place of all workers. The `routing` stream must be the same
+    /// at every worker, as a `command_stream` is. Migrated states are stamped with the timestamp
+    /// of the change advanced by `delay`, which must be non-zero, and the records of each
+    /// timestamp are processed once the states stamped up to it advanced by `delay` have arrived.
+    ///
+    /// # Panics
+    ///
+    /// Panics if a record of `routing` is an empty set of workers.
+    ///
+    /// # Examples
+    /// ```
+    /// use std::sync::{Arc, Mutex};
+    /// use timely::dataflow::InputHandle;
+    /// use timely::dataflow::operators::{Input, Inspect, Probe};
+    /// use timely::dataflow::operators::command::CommandInput;
+    /// use timely::dataflow::operators::aggregation::migrate::MigratingStateMachine;
+    ///
+    /// let totals = Arc::new(Mutex::new(Vec::new()));
+    /// let shared = totals.clone();
+    /// timely::execute(timely::Config::process(2), move |worker| {
+    ///     let index = worker.index();
+    ///     let mut input = InputHandle::new();
+    ///     let mut routing = InputHandle::new();
+    ///     let shared = shared.clone();
+    ///     let probe = worker.dataflow::<u64,_,_>(|scope| {
+    ///         let routing = scope.command_stream(&mut routing);
+    ///         scope.input_from(&mut input)
+    ///              .migrating_state_machine(&routing, 1, |key: &u64, val: u64, total: &mut u64| {
+    ///                  *total += val;
+    ///                  (false, Some((*key, *total)))
+    ///              }, |key| *key)
+    ///              .inspect(move |x| shared.lock().unwrap().push(*x))
+    ///              .probe()
+    ///     });
+    ///
+    ///     for round in 0 .. 4u64 {
+    ///         // from the second round, only the second worker holds states.
+    ///         if round == 1 && index == 0 { routing.send(vec![1]); }
+    ///         input.send((index as u64, 1));
+    ///         input.advance_to(round + 1);
+    ///         routing.advance_to(round + 1);
+    ///         worker.step_while(|| probe.less_than(input.time()));
+    ///     }
+    /// }).unwrap();
+    ///
+    /// let mut totals = totals.lock().unwrap().clone();
+    /// totals.sort();
+    /// assert_eq!(totals, vec![(0, 1), (0, 2), (0, 3), (0, 4), (1, 1), (1, 2), (1, 3), (1, 4)]);
+    /// ```
+    fn migrating_state_machine<
+        R: Data,                                    // output type
+        D: ExchangeData+Default,                    // per-key state (data)
+        I: IntoIterator<Item=R>,                    // type of output iterator
+        F: Fn(&K, V, &mut D)->(bool, I)+'static,    // state update logic
+        H: Fn(&K)->u64+'static,                     // "hash" function for keys
+    >(&self, routing: &Stream<S, Vec<usize>>, delay: <S::Timestamp as Timestamp>::Summary, fold: F, hash: H) -> Stream<S, R>;
+}
+
+impl<S: Scope, K: ExchangeData+Hash+Eq, V: ExchangeData> MigratingStateMachine<S, K, V> for Stream<S, (K, V)> where S::Timestamp: TotalOrder {
+    fn migrating_state_machine<
+        R: Data,
+        D: ExchangeData+Default,
+        I: IntoIterator<Item=R>,
+        F: Fn(&K, V, &mut D)->(bool, I)+'static,
+        H: Fn(&K)->u64+'static,
+    >(&self, routing: &Stream<S, Vec<usize>>, delay: <S::Timestamp as Timestamp>::Summary, fold: F, hash: H) -> Stream<S, R> {
+
+        let mut scope = self.scope();
+        let index = scope.index();
+        let peers = scope.peers();
+        let hash = Rc::new(hash);
+
+        // assign each record to the owner of its key as of its timestamp.
+        let route_hash = hash.clone();
+        let mut assignments = Assignments::new(peers);
+        let mut stash: Vec<Stashed<S::Timestamp, (K, V)>> = Vec::new();
+        let assigned = self.binary_frontier(routing, Pipeline, Pipeline, "AssignKeys", move |_capability, _info| {
+            move |input, routing, output| {
+                routing.for_each(|time, data| {
+                    for workers in data.iter() {
+                        assignments.insert(time.time().clone(), workers.clone());
+                    }
+                });
+                input.for_each(|time, data| {
+                    let mut vector = Vec::new();
+                    data.swap(&mut vector);
+                    stash.push((time.retain(), vector));
+                });
+
+                // records can be assigned once the assignment at their timestamp is known.
+                let frontier = routing.frontier();
+                let (ready, waiting) = stash.drain(..).partition::<Vec<_>, _>(|(capability, _)| !frontier.less_equal(capability.time()));
+                stash = waiting;
+                for (capability, records) in ready {
+                    let time = capability.time().clone();
+                    output.session(&capability).give_iterator(records.into_iter().map(|(key, val)| {
+                        (assignments.owner(route_hash(&key), &time), (key, val))
+                    }));
+                }
+            }
+        });
+
+        let (handle, migrated) = scope.feedback::<(usize, K, D)>(delay.clone());
+
+        let mut builder = OperatorBuilder::new("MigratingStateMachine".to_owned(), scope.clone());
+        let (mut results_out, results) = builder.new_output();
+        let (mut states_out, states_stream) = builder.new_output();
+        let identity = Antichain::from_elem(Default::default());
+        // records produce results, changes of assignment ship states, and shipped states are installed.
+        let mut input = builder.new_input_connection(&assigned, Exchange::new(|(owner, _): &(usize, (K, V))| *owner as u64), vec![identity.clone(), Antichain::new()]);
+        let mut routing = builder.new_input_connection(routing, Pipeline, vec![identity.clone(), identity.clone()]);
+        let mut installs = builder.new_input_connection(&migrated, Exchange::new(|(owner, _, _): &(usize, K, D)| *owner as u64), vec![identity, Antichain::new()]);
+        states_stream.connect_loop(handle);
+
+        builder.build(move |_capabilities| {
+
+            let mut assignments = Assignments::new(peers);
+            let mut changes: Vec<Capability<S::Timestamp>> = Vec::new();
+            let mut stash: Vec<Assigned<S::Timestamp, K, V>> = Vec::new();
+            let mut states: HashMap<K, D> = HashMap::new();
+
+            move |frontiers| {
+                let mut results_out = results_out.activate();
+                let mut states_out = states_out.activate();
+
+                routing.for_each(|time, data| {
+                    for workers in data.iter() {
+                        assignments.insert(time.time().clone(), workers.clone());
+                    }
+                    if changes.iter().all(|capability| capability.time() != time.time()) {
+                        changes.push(time.retain_for_output(1));
+                    }
+                });
+                installs.for_each(|_time, data| {
+                    let mut vector = Vec::new();
+                    data.swap(&mut vector);
+                    states.extend(vector.into_iter().map(|(_, key, state)| (key, state)));
+                });
+                input.for_each(|time, data| {
+                    let mut vector = Vec::new();
+                    data.swap(&mut vector);
+                    stash.push((time.retain_for_output(0), vector));
+                });
+
+                // ship states and process records in timestamp order, shipping first at equal times.
+                changes.sort_by(|x, y| x.time().cmp(y.time()));
+                stash.sort_by(|x, y| x.0.time().cmp(y.0.time()));
+                let (records, routed, installed) = (&frontiers[0], &frontiers[1], &frontiers[2]);
+                let (mut shipped, mut processed) = (0, 0);
+                loop {
+                    let change = changes.get(shipped).map(|capability| capability.time().clone());
+                    let next = stash.get(processed).map(|(capability, _)| capability.time().clone());
+                    match (change, next) {
+                        (Some(change), next) if next.as_ref().map(|next| &change <= next).unwrap_or(true) => {
+                            // all records before the change must be processed, and the new assignment known.
+                            if records.less_than(&change) || routed.less_equal(&change) { break; }
+                            let moving = states.keys().filter(|key| assignments.owner(hash(key), &change) != index).cloned().collect::<Vec<_>>();
+                            let mut session = states_out.session(&changes[shipped]);
+                            for key in moving {
+                                let owner = assignments.owner(hash(&key), &change);
+                                let state = states.remove(&key).expect("key just observed");
+                                session.give((owner, key, state));
+                            }
+                            shipped += 1;
+                        },
+                        (_, Some(time)) => {
+                            // all records and all states migrated as of the time must have arrived.
+                            let arrived = match delay.results_in(&time) {
+                                Some(later) => !installed.less_equal(&later),
+                                None => installed.is_empty(),
+                            };
+                            if records.less_equal(&time) || routed.less_equal(&time) || !arrived { break; }
+                            let (capability, vector) = &mut stash[processed];
+                            let mut session = results_out.session(capability);
+                            for (_, (key, val)) in vector.drain(..) {
+                                let (remove, output) = {
+                                    let state = states.entry(key.clone()).or_default();
+                                    fold(&key, val, state)
+                                };
+                                if remove { states.remove(&key); }
+                                session.give_iterator(output.into_iter());
+                            }
+                            processed += 1;
+                        },
+                        _ => break,
+                    }
+                }
+                changes.drain(.. shipped);
+                stash.drain(.. processed);
+            }
+        });
+
+        results
+    }
+}
+
+/// The workers among which keys are assigned, from each timestamp at which they changed.
+struct Assignments<T> {
+    peers: usize,
+    changes: Vec<(T, Vec<usize>)>,
+}
+
+impl<T: Timestamp+TotalOrder> Assignments<T> {
+    fn new(peers: usize) -> Self {
+        Assignments { peers, changes: Vec::new() }
+    }
+
+    /// Assigns keys among `workers` from `time` onwards, replacing any assignment at `time`.
+    fn insert(&mut self, time: T, workers: Vec<usize>) {
+        assert!(!workers.is_empty(), "keys assigned to no workers");
+        match self.changes.binary_search_by(|(other, _)| other.cmp(&time)) {
+            Ok(position) => self.changes[position].1 = workers,
+            Err(position) => self.changes.insert(position, (time, workers)),
+        }
+    }
+
+    /// The worker owning keys with `hash` at `time`.
+    fn owner(&self, hash: u64, time: &T) -> usize {
+        match self.changes.iter().rev().find(|(changed, _)| changed <= time) {
+            Some((_, workers)) => workers[(hash % workers.len() as u64) as usize],
+            None => (hash % self.peers as u64) as usize,
+        }
+    }
+}
@@ -10,9 +10,19 @@
 //!
 //! The two methods are often combined, using first `Aggregate` to reduce the volume of information, and then
 //! `StateMachine` to track an accumulation across timestamps.
+//!
+//! `CountByKey` and `SumByKey` are `Aggregate`s specialized to counting and summing, which count and sum
+//! the records at each worker before exchanging them.
+//!
+//! `MigratingStateMachine` is a `StateMachine` whose keys may be reassigned among workers over time, moving
+//! their states to their new owners.
 
 pub use self::aggregate::Aggregate;
 pub use self::state_machine::StateMachine;
+pub use self::migrate::MigratingStateMachine;
+pub use self::sum::{CountByKey, SumByKey};
 
 pub mod state_machine;
 pub mod aggregate;
+pub mod migrate;
+pub mod sum;
@@ -5,7 +5,8 @@ use std::collections::HashMap;
 use crate::{Data, ExchangeData};
 use crate::dataflow::{Stream, Scope};
 use crate::dataflow::operators::generic::operator::Operator;
-use crate::dataflow::channels::pact::Exchange;
+use crate::dataflow::channels::pact::{Exchange, ParallelizationContract};
+use crate::dataflow::state::SpillBuffer;
 
 /// Generic state-transition machinery: each key has a state, and receives a sequence of events.
 /// Events are applied in time-order, but no other promises are made. Each state transition can
@@ -15,6 +16,8 @@ use crate::dataflow::channels::pact::Exchange;
 /// updates for the current time reflected in the notificator, though. In the case of partially
 /// ordered times, the only guarantee is that updates are not applied out of order, not that there
 /// is some total order on times respecting the total order (updates may be interleaved).
+///
+/// Buffered inputs spill to disk as configured by `WorkerConfig::spill_to`.
 
 /// Provides the `state_machine` method.
 pub trait StateMachine<S: Scope, K: ExchangeData+Hash+Eq, V: ExchangeData> {
@@ -63,51 +66,68 @@ impl<S: Scope, K: ExchangeData+Hash+Eq, V: ExchangeData> StateMachine<S, K, V> f
             H: Fn(&K)->u64+'static,                     // "hash" function for keys
         >(&self, fold: F, hash: H) -> Stream<S, R> where S::Timestamp : Hash+Eq {
 
-        let mut pending: HashMap<_, Vec<(K, V)>> = HashMap::new();   // times -> (keys -> state)
-        let mut states = HashMap::new();    // keys -> state
+        state_machine(self, Exchange::new(move |&(ref k, _)| hash(k)), fold)
+    }
+}
+
+/// Applies `fold` to the records of each key received through `pact`, in timestamp order.
+pub(crate) fn state_machine<S, K, V, R, D, I, F, P>(stream: &Stream<S, (K, V)>, pact: P, fold: F) -> Stream<S, R>
+where
+    S: Scope,
+    S::Timestamp: Hash+Eq,
+    K: ExchangeData+Hash+Eq,
+    V: ExchangeData,
+    R: Data,
+    D: Default+'static,
+    I: IntoIterator<Item=R>,
+    F: Fn(&K, V, &mut D)->(bool, I)+'static,
+    P: ParallelizationContract<S::Timestamp, (K, V)>,
+{
+    let mut pending: HashMap<_, SpillBuffer<(K, V)>> = HashMap::new();   // times -> (keys -> state)
+    let mut states = HashMap::new();    // keys -> state
 
-        let mut vector = Vec::new();
+    let mut vector = Vec::new();
+    let config = stream.scope().config().clone();
 
-        self.unary_notify(Exchange::new(move |&(ref k, _)| hash(k)), "StateMachine", vec![], move |input, output, notificator| {
+    stream.unary_notify(pact, "StateMachine", vec![], move |input, output, notificator| {
 
-            // go through each time with data, process each (key, val) pair.
-            notificator.for_each(|time,_,_| {
-                if let Some(pend) = pending.remove(time.time()) {
-                    let mut session = output.session(&time);
-                    for (key, val) in pend {
-                        let (remove, output) = {
-                            let state = states.entry(key.clone()).or_insert_with(Default::default);
-                            fold(&key, val, state)
-                        };
-                        if remove { states.remove(&key); }
-                        session.give_iterator(output.into_iter());
-                    }
+        // go through each time with data, process each (key, val) pair.
+        notificator.for_each(|time,_,_| {
+            if let Some(mut pend) = pending.remove(time.time()) {
+                let mut session = output.session(&time);
+                for (key, val) in pend.drain().flatten() {
+                    let (remove, output) = {
+                        let state = states.entry(key.clone()).or_insert_with(Default::default);
+                        fold(&key, val, state)
+                    };
+                    if remove { states.remove(&key); }
+                    session.give_iterator(output.into_iter());
                 }
-            });
+            }
+        });
 
-            // stash each input and request a notification when ready
-            input.for_each(|time, data| {
+        // stash each input and request a notification when ready
+        input.for_each(|time, data| {
 
-                data.swap(&mut vector);
+            data.swap(&mut vector);
 
-                // stash if not time yet
-                if notificator.frontier(0).less_than(time.time()) {
-                    pending.entry(time.time().clone()).or_insert_with(Vec::new).extend(vector.drain(..));
-                    notificator.notify_at(time.retain());
-                }
-                else {
-                    // else we can process immediately
-                    let mut session = output.session(&time);
-                    for (key, val) in vector.drain(..) {
-                        let (remove, output) = {
-                            let state = states.entry(key.clone()).or_insert_with(Default::default);
-                            fold(&key, val, state)
-                        };
-                        if remove { states.remove(&key); }
-                        session.give_iterator(output.into_iter());
-                    }
+            // stash if not time yet
+            if notificator.frontier(0).less_than(time.time()) {
+                pending.entry(time.time().clone()).or_insert_with(|| SpillBuffer::from_config(&config)).append(&mut vector);
+                notificator.notify_at(time.retain());
+            }
+            else {
+                // else we can process immediately
+                let mut session = output.session(&time);
+                for (key, val) in vector.drain(..) {
+                    let (remove, output) = {
+                        let state = states.entry(key.clone()).or_insert_with(Default::default);
+                        fold(&key, val, state)
+                    };
+                    if remove { states.remove(&key); }
+                    session.give_iterator(output.into_iter());
                 }
-            });
-        })
-    }
+            }
+        });
+    })
 }
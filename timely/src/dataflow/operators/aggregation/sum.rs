@@ -0,0 +1,91 @@
+//! Counting and summing the records of each key within each timestamp.
+use std::hash::Hash;
+use std::ops::AddAssign;
+
+use crate::{Data, ExchangeData};
+use crate::dataflow::{Stream, Scope};
+use crate::dataflow::operators::Map;
+use crate::dataflow::channels::partitioner::hash;
+use crate::dataflow::operators::aggregation::Aggregate;
+
+/// Counts the occurrences of each key within each timestamp.
+pub trait CountByKey<S: Scope, K: ExchangeData+Hash+Eq> {
+    /// Counts the occurrences of each key within each timestamp, producing `(key, count)` once the timestamp is complete.
+    ///
+    /// Records are counted at each worker before any are exchanged, as by `aggregate_hierarchical`,
+    /// so that each worker sends one count per key and timestamp rather than each record.
+    ///
+    /// # Examples
+    /// ```
+    /// use timely::dataflow::operators::{ToStream, Capture};
+    /// use timely::dataflow::operators::aggregation::CountByKey;
+    /// use timely::dataflow::operators::capture::Extract;
+    ///
+    /// let captured = timely::example(|scope| {
+    ///     vec![1, 2, 1, 3, 1]
+    ///         .to_stream(scope)
+    ///         .count_by_key()
+    ///         .capture()
+    /// });
+    ///
+    /// assert_eq!(captured.extract(), vec![(0, vec![(1, 3), (2, 1), (3, 1)])]);
+    /// ```
+    fn count_by_key(&self) -> Stream<S, (K, usize)> where S::Timestamp: Eq;
+}
+
+impl<S: Scope, K: ExchangeData+Hash+Eq> CountByKey<S, K> for Stream<S, K> {
+    fn count_by_key(&self) -> Stream<S, (K, usize)> where S::Timestamp: Eq {
+        sum_by(self, |key| (key, 1))
+    }
+}
+
+/// Sums the values of each key within each timestamp.
+pub trait SumByKey<S: Scope, K: ExchangeData+Hash+Eq, V: ExchangeData+AddAssign+Default> {
+    /// Sums the values of each key within each timestamp, producing `(key, sum)` once the timestamp is complete.
+    ///
+    /// Records are summed at each worker before any are exchanged, as by `aggregate_hierarchical`,
+    /// so that each worker sends one partial sum per key and timestamp rather than each record.
+    ///
+    /// # Examples
+    /// ```
+    /// use timely::dataflow::operators::{ToStream, Capture};
+    /// use timely::dataflow::operators::aggregation::SumByKey;
+    /// use timely::dataflow::operators::capture::Extract;
+    ///
+    /// let captured = timely::example(|scope| {
+    ///     vec![(1, 10), (2, 20), (1, 30)]
+    ///         .to_stream(scope)
+    ///         .sum_by_key()
+    ///         .capture()
+    /// });
+    ///
+    /// assert_eq!(captured.extract(), vec![(0, vec![(1, 40), (2, 20)])]);
+    /// ```
+    fn sum_by_key(&self) -> Stream<S, (K, V)> where S::Timestamp: Eq;
+}
+
+impl<S: Scope, K: ExchangeData+Hash+Eq, V: ExchangeData+AddAssign+Default> SumByKey<S, K, V> for Stream<S, (K, V)> {
+    fn sum_by_key(&self) -> Stream<S, (K, V)> where S::Timestamp: Eq {
+        sum_by(self, |pair| pair)
+    }
+}
+
+/// Sums the values of each key of `logic` of the records of `stream` within each timestamp.
+fn sum_by<S, D, K, V, L>(stream: &Stream<S, D>, logic: L) -> Stream<S, (K, V)>
+where
+    S: Scope,
+    S::Timestamp: Eq,
+    D: Data,
+    K: ExchangeData+Hash+Eq,
+    V: ExchangeData+AddAssign+Default,
+    L: Fn(D)->(K, V)+'static,
+{
+    stream
+        .map(logic)
+        .aggregate_hierarchical(
+            |_key, val, sum: &mut V| *sum += val,
+            |_key, partial, sum: &mut V| *sum += partial,
+            |key, sum| (key, sum),
+            |key| hash(key),
+        )
+}
@@ -0,0 +1,304 @@
+//! Arranges keyed streams into indices shared by downstream operators.
+//!
+//! An arrangement is a per-worker index of the records of a keyed stream, maintained by a single
+//! operator and read by any number of others. Operators that would otherwise each build a hash
+//! table of the same relation, for example several lookups against it, can instead share one
+//! arrangement.
+//!
+//! The index is compacted as the arrangement's input frontier advances: once no registered reader
+//! reads at times before the frontier, the times of earlier values are advanced to it and their
+//! copies consolidated, so that the index grows with the distinct values of each key rather than
+//! with every record.
+
+use std::rc::Rc;
+use std::cell::RefCell;
+use std::collections::HashMap;
+use std::hash::Hash;
+
+use crate::ExchangeData;
+use crate::order::PartialOrder;
+use crate::progress::Timestamp;
+use crate::progress::frontier::{Antichain, AntichainRef, MutableAntichain};
+use crate::dataflow::{Stream, Scope};
+use crate::dataflow::channels::pact::{Exchange, ParallelizationContract, Pipeline};
+use crate::dataflow::channels::partitioner::hash;
+use crate::dataflow::operators::generic::operator::Operator;
+use crate::dataflow::operators::generic::FrontierStash;
+use crate::dataflow::operators::keyed::KeyedStream;
+
+/// The values of each key, with the times at which they were introduced and their counts.
+type Index<K, V, T> = HashMap<K, Vec<(V, T, usize)>>;
+
+/// A shared, per-worker index of the values and times of the keys of a stream.
+///
+/// Handles are cheaply cloned, and clones share the same index. Each worker indexes the keys
+/// routed to it, as determined by the hash of the key.
+///
+/// The times of values are advanced as the index is compacted, and so are exact only at times in
+/// advance of the frontiers of the registered readers. Operators that read the trace at past
+/// times should hold a `TraceReader`.
+pub struct Trace<K, V, T> {
+    index: Rc<RefCell<Index<K, V, T>>>,
+    readers: Rc<RefCell<MutableAntichain<T>>>,
+}
+
+impl<K, V, T> Clone for Trace<K, V, T> {
+    fn clone(&self) -> Self {
+        Trace { index: self.index.clone(), readers: self.readers.clone() }
+    }
+}
+
+impl<K: Hash+Eq, V, T: PartialOrder> Trace<K, V, T> {
+    /// Applies `logic` to each value of `key`, with the time at which it was introduced.
+    pub fn for_each(&self, key: &K, mut logic: impl FnMut(&V, &T)) {
+        if let Some(values) = self.index.borrow().get(key) {
+            for (value, time, count) in values.iter() {
+                for _ in 0 .. *count {
+                    logic(value, time);
+                }
+            }
+        }
+    }
+
+    /// Applies `logic` to each value of `key` introduced at a time less or equal to `time`.
+    pub fn for_each_at(&self, key: &K, time: &T, mut logic: impl FnMut(&V)) {
+        self.for_each(key, |value, introduced| {
+            if introduced.less_equal(time) {
+                logic(value);
+            }
+        });
+    }
+
+    /// The number of keys in the index.
+    pub fn keys(&self) -> usize {
+        self.index.borrow().len()
+    }
+}
+
+impl<K, V, T: Timestamp> Trace<K, V, T> {
+    /// Registers a reader, which holds back the compaction of the trace until it advances.
+    pub fn reader(&self) -> TraceReader<T> {
+        self.readers.borrow_mut().update_iter(Some((T::minimum(), 1)));
+        TraceReader { readers: self.readers.clone(), frontier: Antichain::from_elem(T::minimum()) }
+    }
+}
+
+/// A reader of a `Trace`, which prevents the compaction of times in advance of its frontier.
+///
+/// The reader starts at the minimal time, and releases its hold on the trace when dropped.
+pub struct TraceReader<T: Timestamp> {
+    readers: Rc<RefCell<MutableAntichain<T>>>,
+    frontier: Antichain<T>,
+}
+
+impl<T: Timestamp> TraceReader<T> {
+    /// Indicates that the reader will only read at times in advance of `frontier`.
+    pub fn set_frontier(&mut self, frontier: Antichain<T>) {
+        if frontier != self.frontier {
+            let released = self.frontier.elements().iter().map(|time| (time.clone(), -1));
+            let held = frontier.elements().iter().map(|time| (time.clone(), 1));
+            self.readers.borrow_mut().update_iter(released.chain(held));
+            self.frontier = frontier;
+        }
+    }
+}
+
+impl<T: Timestamp> Drop for TraceReader<T> {
+    fn drop(&mut self) {
+        self.set_frontier(Antichain::new());
+    }
+}
+
+/// A keyed stream and the shared index of its records.
+pub struct Arranged<G: Scope, K, V> {
+    /// The records of the stream, produced once they are in the index.
+    pub stream: Stream<G, (K, V)>,
+    /// The index of the records of the stream.
+    pub trace: Trace<K, V, G::Timestamp>,
+}
+
+impl<G: Scope, K: ExchangeData+Hash+Eq, V: ExchangeData> Arranged<G, K, V> {
+    /// Matches each record of `probes` with the values of its key in the arrangement.
+    ///
+    /// A probe at time `time` is matched with the values introduced at times less or equal to
+    /// `time`, once the arrangement is complete through `time`. The index is shared, and any
+    /// number of lookups may read the same arrangement.
+    ///
+    /// # Examples
+    /// ```
+    /// use timely::dataflow::operators::{ToStream, Capture};
+    /// use timely::dataflow::operators::arrange::ArrangeByKey;
+    /// use timely::dataflow::operators::capture::Extract;
+    ///
+    /// let (names, ages) = timely::example(|scope| {
+    ///     let people = vec![(1u64, "alice".to_string()), (2, "bob".to_string())]
+    ///         .to_stream(scope)
+    ///         .arrange_by_key();
+    ///     let names = vec![(2u64, ())].to_stream(scope);
+    ///     let ages = vec![(1u64, 30u32), (3, 40)].to_stream(scope);
+    ///     (people.lookup(&names).capture(), people.lookup(&ages).capture())
+    /// });
+    ///
+    /// assert_eq!(names.extract(), vec![(0, vec![(2, "bob".to_string(), ())])]);
+    /// assert_eq!(ages.extract(), vec![(0, vec![(1, "alice".to_string(), 30)])]);
+    /// ```
+    pub fn lookup<V2: ExchangeData>(&self, probes: &Stream<G, (K, V2)>) -> Stream<G, (K, V, V2)> {
+        self.lookup_through(probes, Exchange::new(|(key, _): &(K, V2)| hash(key)))
+    }
+
+    /// Matches each record of `probes`, already partitioned by key, with the values of its key.
+    ///
+    /// As `lookup`, but the probes are not exchanged, as `probes` is partitioned as the
+    /// arrangement is.
+    ///
+    /// # Examples
+    /// ```
+    /// use timely::dataflow::operators::{ToStream, Capture};
+    /// use timely::dataflow::operators::arrange::ArrangeByKey;
+    /// use timely::dataflow::operators::keyed::KeyBy;
+    /// use timely::dataflow::operators::capture::Extract;
+    ///
+    /// let captured = timely::example(|scope| {
+    ///     let people = vec![(1u64, "alice".to_string())].to_stream(scope).arrange_by_key();
+    ///     let ages = vec![(1u64, 30u32)].to_stream(scope).key_by();
+    ///     people.lookup_keyed(&ages).capture()
+    /// });
+    ///
+    /// assert_eq!(captured.extract(), vec![(0, vec![(1, "alice".to_string(), 30)])]);
+    /// ```
+    pub fn lookup_keyed<V2: ExchangeData>(&self, probes: &KeyedStream<G, K, V2>) -> Stream<G, (K, V, V2)> {
+        self.lookup_through(probes.stream(), Pipeline)
+    }
+
+    /// Matches each record of `probes`, received through `pact`, with the values of its key.
+    fn lookup_through<V2, P>(&self, probes: &Stream<G, (K, V2)>, pact: P) -> Stream<G, (K, V, V2)>
+    where
+        V2: ExchangeData,
+        P: ParallelizationContract<G::Timestamp, (K, V2)>,
+    {
+        let trace = self.trace.clone();
+        let mut reader = self.trace.reader();
+        let mut stash = FrontierStash::new();
+
+        probes.binary_frontier(&self.stream, pact, Pipeline, "Lookup", move |_capability, _info| {
+            move |probes, arranged, output| {
+
+                // the arrangement's records are already indexed, and need not be read.
+                arranged.for_each(|_time, _data| { });
+
+                probes.for_each(|time, data| stash.push(time, data));
+
+                // match probes at times through which the arrangement is complete.
+                stash.release(arranged.frontier(), |capability, probes| {
+                    let mut session = output.session(capability);
+                    for (key, value2) in probes.iter() {
+                        trace.for_each_at(key, capability.time(), |value| {
+                            session.give((key.clone(), value.clone(), value2.clone()));
+                        });
+                    }
+                });
+
+                // the probes yet to be matched are at times in advance of the probe frontier and the stash.
+                let lower = stash.lower(probes.frontier());
+                reader.set_frontier(lower);
+            }
+        })
+    }
+}
+
+/// Arranges a keyed stream into a shared index.
+pub trait ArrangeByKey<G: Scope, K: ExchangeData+Hash+Eq, V: ExchangeData+Hash+Eq> {
+    /// Routes each record by the hash of its key, and indexes it by key in each worker.
+    ///
+    /// The index retains all records, with the times at which they were introduced, advanced to
+    /// the frontier of the arrangement's input and readers as the index is compacted.
+    ///
+    /// # Examples
+    /// ```
+    /// use timely::dataflow::operators::{ToStream, Inspect};
+    /// use timely::dataflow::operators::arrange::ArrangeByKey;
+    ///
+    /// timely::example(|scope| {
+    ///     let arranged = (0 .. 10u64).map(|x| (x % 3, x)).to_stream(scope).arrange_by_key();
+    ///     let trace = arranged.trace.clone();
+    ///     arranged.stream.inspect(move |(key, _)| {
+    ///         let mut values = 0;
+    ///         trace.for_each(key, |_, _| values += 1);
+    ///         assert!(values > 0);
+    ///     });
+    /// });
+    /// ```
+    fn arrange_by_key(&self) -> Arranged<G, K, V>;
+}
+
+impl<G: Scope, K: ExchangeData+Hash+Eq, V: ExchangeData+Hash+Eq> ArrangeByKey<G, K, V> for Stream<G, (K, V)> {
+    fn arrange_by_key(&self) -> Arranged<G, K, V> {
+        arrange(self, Exchange::new(|(key, _): &(K, V)| hash(key)))
+    }
+}
+
+/// Indexes the records received through `pact` by key in each worker.
+pub(crate) fn arrange<G, K, V, P>(stream: &Stream<G, (K, V)>, pact: P) -> Arranged<G, K, V>
+where
+    G: Scope,
+    K: ExchangeData+Hash+Eq,
+    V: ExchangeData+Hash+Eq,
+    P: ParallelizationContract<G::Timestamp, (K, V)>,
+{
+    let trace = Trace { index: Rc::new(RefCell::new(HashMap::new())), readers: Rc::new(RefCell::new(MutableAntichain::new())) };
+    let index = trace.index.clone();
+    let readers = trace.readers.clone();
+    let mut vector = Vec::new();
+
+    // the frontier of the last compaction, and the entries of the index then and since.
+    let mut compacted = Antichain::from_elem(G::Timestamp::minimum());
+    let mut retained = 0;
+    let mut entries = 0;
+
+    let stream = stream.unary_frontier(pact, "ArrangeByKey", move |_capability, _info| {
+        move |input, output| {
+            input.for_each(|time, data| {
+                data.swap(&mut vector);
+                let mut index = index.borrow_mut();
+                for (key, value) in vector.iter() {
+                    index.entry(key.clone()).or_insert_with(Vec::new).push((value.clone(), time.time().clone(), 1));
+                }
+                entries += vector.len();
+                output.session(&time).give_vec(&mut vector);
+            });
+
+            // compact once the frontier has advanced and the index has doubled since the last compaction.
+            let mut frontier = Antichain::new();
+            for time in input.frontier().frontier().iter().chain(readers.borrow().frontier().iter()) {
+                frontier.insert(time.clone());
+            }
+            if frontier != compacted && entries >= 2 * retained {
+                compact(&mut index.borrow_mut(), frontier.borrow());
+                retained = index.borrow().values().map(|values| values.len()).sum();
+                entries = retained;
+                compacted = frontier;
+            }
+        }
+    });
+
+    Arranged { stream, trace }
+}
+
+/// Advances the times of `index` to `frontier`, if it has a single element, and consolidates the
+/// copies of each value at each time.
+fn compact<K, V: Hash+Eq, T: Timestamp>(index: &mut Index<K, V, T>, frontier: AntichainRef<T>) {
+    for values in index.values_mut() {
+        if let [lower] = &frontier[..] {
+            for (_, time, _) in values.iter_mut() {
+                if time.less_than(lower) {
+                    *time = lower.clone();
+                }
+            }
+        }
+        let mut counts = HashMap::with_capacity(values.len());
+        for (value, time, count) in values.drain(..) {
+            *counts.entry((value, time)).or_insert(0) += count;
+        }
+        values.extend(counts.into_iter().map(|((value, time), count)| (value, time, count)));
+    }
+}
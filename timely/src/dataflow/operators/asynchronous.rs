@@ -0,0 +1,163 @@
+//! Adapters between timely streams and asynchronous code.
+//!
+//! `from_async_stream` feeds the timestamped records of an asynchronous stream into an input
+//! handle, and `IntoAsyncChannel::into_async_channel` delivers the records of each completed
+//! timestamp to an asynchronous consumer, which may run on any thread. Together they let a
+//! dataflow sit inside an async application without a dedicated thread per edge.
+//!
+//! This module is available with the `async` feature. The future of `from_async_stream` must run
+//! on a local executor on the thread of its worker, while receivers may be used from any thread.
+
+use std::collections::VecDeque;
+use std::pin::Pin;
+use std::sync::{Arc, Mutex};
+use std::task::{Context, Poll, Waker};
+
+use futures_util::stream::{Stream as AsyncStream, StreamExt};
+
+use crate::Data;
+use crate::progress::Timestamp;
+use crate::dataflow::{Stream, Scope};
+use crate::dataflow::operators::input::Handle;
+use crate::dataflow::operators::collect::complete_epochs;
+
+/// Sends the records of `source` into `handle`, advancing it to the time of each record.
+///
+/// The times of the records should not decrease, as the handle cannot send at times it has
+/// advanced beyond. The handle is closed once `source` ends.
+///
+/// # Executors
+///
+/// The future holds the input handle, which is tied to the thread of its worker, and so the
+/// future is not `Send`. It must run on a local executor on the worker's thread, for example a
+/// `futures::executor::LocalPool` or a `tokio::task::LocalSet`, rather than be spawned onto a
+/// multi-threaded executor. The worker only makes progress when it is stepped, so the executor
+/// should be run until it stalls between steps of the worker, as `run_until_stalled` does.
+///
+/// # Panics
+///
+/// Panics if a record has a time earlier than that of a previous record.
+///
+/// # Examples
+/// ```
+/// use std::future::Future;
+/// use std::task::{Context, Poll};
+///
+/// use futures_util::stream;
+/// use timely::dataflow::InputHandle;
+/// use timely::dataflow::operators::{Input, Capture};
+/// use timely::dataflow::operators::asynchronous::from_async_stream;
+/// use timely::dataflow::operators::capture::Extract;
+///
+/// let captured = timely::execute_directly(|worker| {
+///     let mut input = InputHandle::new();
+///     let captured = worker.dataflow::<u64,_,_>(|scope| scope.input_from(&mut input).capture());
+///
+///     let records = stream::iter(vec![(0, 'a'), (0, 'b'), (2, 'c')]);
+///     let mut feed = Box::pin(from_async_stream(records, input));
+///
+///     // the records are all ready, and the future completes when first polled.
+///     let waker = futures_util::task::noop_waker();
+///     assert_eq!(feed.as_mut().poll(&mut Context::from_waker(&waker)), Poll::Ready(()));
+///     captured
+/// });
+///
+/// assert_eq!(captured.extract(), vec![(0, vec!['a', 'b']), (2, vec!['c'])]);
+/// ```
+pub async fn from_async_stream<T, D, S>(mut source: S, mut handle: Handle<T, D>)
+where
+    T: Timestamp,
+    D: Data,
+    S: AsyncStream<Item=(T, D)>+Unpin,
+{
+    while let Some((time, datum)) = source.next().await {
+        if &time != handle.time() {
+            assert!(handle.time().less_than(&time), "record at {:?} sent after {:?}", time, handle.time());
+            handle.advance_to(time);
+        }
+        handle.send(datum);
+    }
+}
+
+/// The records of completed timestamps, and whether more may follow.
+struct Shared<T, D> {
+    epochs: VecDeque<(T, Vec<D>)>,
+    closed: bool,
+    waker: Option<Waker>,
+}
+
+/// An asynchronous stream of the completed timestamps of a timely stream, with their records.
+///
+/// Each worker's receiver yields the timestamps completed at that worker, in order, with the
+/// records that arrived at the worker. The receiver ends when the timely stream is complete.
+pub struct EpochReceiver<T, D> {
+    shared: Arc<Mutex<Shared<T, D>>>,
+}
+
+impl<T, D> AsyncStream for EpochReceiver<T, D> {
+    type Item = (T, Vec<D>);
+    fn poll_next(self: Pin<&mut Self>, context: &mut Context<'_>) -> Poll<Option<Self::Item>> {
+        let mut shared = self.shared.lock().expect("epoch channel poisoned");
+        if let Some(epoch) = shared.epochs.pop_front() {
+            Poll::Ready(Some(epoch))
+        }
+        else if shared.closed {
+            Poll::Ready(None)
+        }
+        else {
+            shared.waker = Some(context.waker().clone());
+            Poll::Pending
+        }
+    }
+}
+
+/// Delivers the completed timestamps of a stream to asynchronous consumers.
+pub trait IntoAsyncChannel<T: Timestamp, D: Data> {
+    /// Collects the records of each timestamp, and delivers them once the timestamp is complete.
+    ///
+    /// # Examples
+    /// ```
+    /// use futures_util::stream::StreamExt;
+    /// use futures_util::task::noop_waker;
+    /// use std::task::{Context, Poll};
+    /// use timely::dataflow::InputHandle;
+    /// use timely::dataflow::operators::Input;
+    /// use timely::dataflow::operators::asynchronous::IntoAsyncChannel;
+    ///
+    /// let mut epochs = timely::execute_directly(|worker| {
+    ///     let mut input = InputHandle::new();
+    ///     let epochs = worker.dataflow::<u64,_,_>(|scope| scope.input_from(&mut input).into_async_channel());
+    ///     input.send("hello");
+    ///     input.advance_to(1);
+    ///     input.send("world");
+    ///     epochs
+    /// });
+    ///
+    /// let waker = noop_waker();
+    /// let mut context = Context::from_waker(&waker);
+    /// assert_eq!(epochs.poll_next_unpin(&mut context), Poll::Ready(Some((0, vec!["hello"]))));
+    /// assert_eq!(epochs.poll_next_unpin(&mut context), Poll::Ready(Some((1, vec!["world"]))));
+    /// assert_eq!(epochs.poll_next_unpin(&mut context), Poll::Ready(None));
+    /// ```
+    #[allow(clippy::wrong_self_convention)]
+    fn into_async_channel(&self) -> EpochReceiver<T, D>;
+}
+
+impl<G: Scope, D: Data+Send> IntoAsyncChannel<G::Timestamp, D> for Stream<G, D> {
+    fn into_async_channel(&self) -> EpochReceiver<G::Timestamp, D> {
+
+        let shared = Arc::new(Mutex::new(Shared { epochs: VecDeque::new(), closed: false, waker: None }));
+        let sender = shared.clone();
+
+        complete_epochs(self, "IntoAsyncChannel", move |complete, closed| {
+            let mut shared = sender.lock().expect("epoch channel poisoned");
+            shared.epochs.extend(complete);
+            shared.closed = closed;
+            if let Some(waker) = shared.waker.take() {
+                waker.wake();
+            }
+        });
+
+        EpochReceiver { shared }
+    }
+}
@@ -0,0 +1,147 @@
+//! Accounting of the records a segment of a dataflow consumes and produces at each timestamp.
+//!
+//! When records go missing between the inputs and outputs of a dataflow, for example because
+//! filters or flat maps drop them, the counts of records at each timestamp on either side of a
+//! segment of the dataflow show where they vanish. An `audit` surrounds a segment with operators
+//! counting the records entering and leaving it, and once a timestamp is complete reports the
+//! counts to the `"timely/audit"` logger, if one is registered.
+
+use std::cell::RefCell;
+use std::collections::HashMap;
+use std::rc::Rc;
+
+use crate::Data;
+use crate::dataflow::{Stream, Scope};
+use crate::dataflow::channels::pact::Pipeline;
+use crate::dataflow::operators::generic::operator::Operator;
+use crate::progress::Timestamp;
+
+/// The records entering and leaving an audited segment of a dataflow at one timestamp.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct AuditEvent<T> {
+    /// The name of the audit.
+    pub name: String,
+    /// The completed timestamp.
+    pub time: T,
+    /// The number of records entering the segment.
+    pub records_in: i64,
+    /// The number of records leaving the segment.
+    pub records_out: i64,
+}
+
+impl<T> AuditEvent<T> {
+    /// The number of records leaving the segment less the number entering it.
+    ///
+    /// This is negative for segments that dropped records.
+    pub fn delta(&self) -> i64 {
+        self.records_out - self.records_in
+    }
+}
+
+/// Counts the records entering and leaving a segment of a dataflow.
+pub trait Audit<G: Scope, D: Data> {
+    /// Applies `logic` to the stream, and passes through its output, counting the records of
+    /// each timestamp entering and leaving it.
+    ///
+    /// Once a timestamp is complete at the output of the segment, an `AuditEvent` with its counts
+    /// is logged to the `"timely/audit"` logger of the worker, if one is registered when the
+    /// dataflow is built. Timestamps without any records are not reported. Each worker counts
+    /// the records it sees.
+    ///
+    /// # Examples
+    /// ```
+    /// use std::cell::RefCell;
+    /// use std::rc::Rc;
+    ///
+    /// use timely::dataflow::InputHandle;
+    /// use timely::dataflow::operators::{Input, Filter, Probe};
+    /// use timely::dataflow::operators::audit::{Audit, AuditEvent};
+    ///
+    /// timely::execute_directly(|worker| {
+    ///
+    ///     let events = Rc::new(RefCell::new(Vec::new()));
+    ///     let events2 = events.clone();
+    ///     worker.log_register().insert::<AuditEvent<u64>,_>("timely/audit", move |_time, data| {
+    ///         events2.borrow_mut().extend(data.drain(..).map(|(_, _, event)| event));
+    ///     });
+    ///
+    ///     let mut input = InputHandle::new();
+    ///     let probe = worker.dataflow::<u64,_,_>(|scope| {
+    ///         scope.input_from(&mut input)
+    ///              .audit("evens", |stream| stream.filter(|x: &u64| x % 2 == 0))
+    ///              .probe()
+    ///     });
+    ///
+    ///     for round in 0 .. 3 {
+    ///         for record in 0 .. 10 {
+    ///             input.send(round * 10 + record);
+    ///         }
+    ///         input.advance_to(round + 1);
+    ///         worker.step_while(|| probe.less_than(input.time()));
+    ///     }
+    ///     worker.step();
+    ///
+    ///     let events = events.borrow();
+    ///     assert_eq!(events.len(), 3);
+    ///     for (round, event) in events.iter().enumerate() {
+    ///         assert_eq!(event.name, "evens");
+    ///         assert_eq!(event.time, round as u64);
+    ///         assert_eq!((event.records_in, event.records_out), (10, 5));
+    ///         assert_eq!(event.delta(), -5);
+    ///     }
+    /// });
+    /// ```
+    fn audit<D2, L>(&self, name: &str, logic: L) -> Stream<G, D2>
+    where
+        D2: Data,
+        L: FnOnce(&Stream<G, D>) -> Stream<G, D2>;
+}
+
+impl<G: Scope, D: Data> Audit<G, D> for Stream<G, D> {
+    fn audit<D2, L>(&self, name: &str, logic: L) -> Stream<G, D2>
+    where
+        D2: Data,
+        L: FnOnce(&Stream<G, D>) -> Stream<G, D2>,
+    {
+        let logger = self.scope().log_register().get::<AuditEvent<G::Timestamp>>("timely/audit");
+        let counts = Rc::new(RefCell::new(HashMap::<G::Timestamp, (i64, i64)>::new()));
+
+        let counts_in = counts.clone();
+        let mut vector = Vec::new();
+        let entering = self.unary(Pipeline, &format!("AuditIn({})", name), move |_capability, _info| move |input, output| {
+            input.for_each(|time, data| {
+                data.swap(&mut vector);
+                counts_in.borrow_mut().entry(time.time().clone()).or_insert((0, 0)).0 += vector.len() as i64;
+                output.session(&time).give_vec(&mut vector);
+            });
+        });
+
+        let name = name.to_owned();
+        let mut vector = Vec::new();
+        logic(&entering).unary_frontier(Pipeline, &format!("AuditOut({})", name), move |_capability, _info| move |input, output| {
+            input.for_each(|time, data| {
+                data.swap(&mut vector);
+                counts.borrow_mut().entry(time.time().clone()).or_insert((0, 0)).1 += vector.len() as i64;
+                output.session(&time).give_vec(&mut vector);
+            });
+            report(&name, &mut counts.borrow_mut(), |time| !input.frontier().less_equal(time), logger.as_ref());
+        })
+    }
+}
+
+// Removes the counts of times satisfying `complete`, and logs them in order of time.
+fn report<T: Timestamp>(
+    name: &str,
+    counts: &mut HashMap<T, (i64, i64)>,
+    complete: impl Fn(&T) -> bool,
+    logger: Option<&crate::logging_core::Logger<AuditEvent<T>, crate::logging::WorkerIdentifier>>,
+) {
+    let mut completed = counts.keys().filter(|time| complete(time)).cloned().collect::<Vec<_>>();
+    completed.sort();
+    for time in completed {
+        let (records_in, records_out) = counts.remove(&time).expect("completed time present");
+        if let Some(logger) = logger {
+            logger.log(AuditEvent { name: name.to_owned(), time, records_in, records_out });
+        }
+    }
+}
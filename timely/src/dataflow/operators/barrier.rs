@@ -0,0 +1,68 @@
+//! Synchronizes workers on the completion of each timestamp.
+
+use crate::Data;
+use crate::dataflow::{Stream, Scope};
+use crate::dataflow::channels::pact::Pipeline;
+use crate::dataflow::operators::Broadcast;
+use crate::dataflow::operators::generic::operator::Operator;
+
+/// Synchronizes workers on the completion of each timestamp of a stream.
+pub trait Barrier<G: Scope, D: Data> {
+    /// Produces each timestamp of the stream once all workers have completed it.
+    ///
+    /// Each worker produces one record for each timestamp at which any worker received records,
+    /// containing the timestamp, once no worker may receive further records at that timestamp.
+    /// This is useful to coordinate side effects, for example to flush an external system once
+    /// all workers have written the records of a timestamp to it.
+    ///
+    /// # Examples
+    /// ```
+    /// use timely::dataflow::operators::{ToStream, Filter, Barrier, Inspect};
+    ///
+    /// use std::rc::Rc;
+    /// use std::cell::Cell;
+    ///
+    /// timely::execute(timely::Config::process(2), |worker| {
+    ///     let index = worker.index();
+    ///     let barriers = Rc::new(Cell::new(0));
+    ///     let counter = barriers.clone();
+    ///     worker.dataflow::<u64,_,_>(|scope| {
+    ///         // only the first worker has records, but both workers see the barrier.
+    ///         (0 .. 10u64)
+    ///             .to_stream(scope)
+    ///             .filter(move |_| index == 0)
+    ///             .barrier()
+    ///             .inspect(move |time| { assert_eq!(*time, 0); counter.set(counter.get() + 1); });
+    ///     });
+    ///     while worker.step() { }
+    ///     assert_eq!(barriers.get(), 1);
+    /// }).unwrap();
+    /// ```
+    fn barrier(&self) -> Stream<G, G::Timestamp>;
+}
+
+impl<G: Scope, D: Data> Barrier<G, D> for Stream<G, D> {
+    fn barrier(&self) -> Stream<G, G::Timestamp> {
+
+        // announce each time with records to all workers, once per batch.
+        let mut vector = Vec::new();
+        let announced = self.unary(Pipeline, "BarrierAnnounce", move |_,_| move |input, output| {
+            input.for_each(|time, data| {
+                data.swap(&mut vector);
+                output.session(&time).give(());
+            });
+        });
+
+        // produce each announced time once it is complete at all workers.
+        announced
+            .broadcast()
+            .unary_notify(Pipeline, "Barrier", vec![], move |input, output, notificator| {
+                input.for_each(|time, _data| {
+                    notificator.notify_at(time.retain());
+                });
+                notificator.for_each(|time, _count, _notificator| {
+                    output.session(&time).give(time.time().clone());
+                });
+            })
+    }
+}
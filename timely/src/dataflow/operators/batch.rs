@@ -0,0 +1,116 @@
+//! Create new `Streams` from batches, each introduced once the dataflow has finished with the last.
+//!
+//! A finite batch job, processed in several rounds, need not be driven by the worker: an input
+//! of batches introduces each batch as its own epoch, and introduces the next batch only once
+//! the dataflow is quiescent, having finished all work for the previous epochs. Once the batches
+//! are exhausted the input closes, and the dataflow completes without the driver stepping the
+//! worker, advancing epochs, or waiting on probes.
+
+use crate::Data;
+use crate::progress::Antichain;
+use crate::dataflow::{Stream, Scope};
+use crate::dataflow::channels::pact::Pipeline;
+use crate::dataflow::operators::{Feedback, ConnectLoop};
+use crate::dataflow::operators::feedback::Handle;
+use crate::dataflow::operators::generic::operator::Operator;
+use crate::dataflow::operators::generic::builder_rc::OperatorBuilder;
+
+/// Create a new `Stream` and `BatchHandle` introducing batches of records one epoch at a time.
+pub trait BatchInput : Scope<Timestamp=u64> {
+    /// Create a new `Stream` introducing each of `batches` at its own epoch, and a `BatchHandle`
+    /// through which to indicate when the dataflow has finished with an epoch.
+    ///
+    /// The first batch is introduced at epoch zero, and each later batch at the following epoch,
+    /// once the stream supplied to `BatchHandle::completed_by` is complete through the previous
+    /// epoch. The input closes once the batches are exhausted. Each worker supplies its own
+    /// batches, and the dataflow advances to the next epoch once all workers are finished with
+    /// the last.
+    ///
+    /// # Examples
+    /// ```
+    /// use std::sync::{Arc, Mutex};
+    /// use timely::dataflow::operators::{Accumulate, Inspect};
+    /// use timely::dataflow::operators::batch::BatchInput;
+    ///
+    /// let counts = Arc::new(Mutex::new(Vec::new()));
+    /// let shared = counts.clone();
+    /// timely::execute(timely::Config::process(2), move |worker| {
+    ///     let shared = shared.clone();
+    ///     worker.dataflow(|scope| {
+    ///         let batches = vec![vec![1u64, 2, 3], vec![4, 5], vec![6]];
+    ///         let (handle, stream) = scope.new_batch_input(batches);
+    ///         let counted = stream.count()
+    ///                             .inspect_time(move |epoch, count| shared.lock().unwrap().push((*epoch, *count)));
+    ///         handle.completed_by(&counted);
+    ///     });
+    ///     // no further work by the driver: the worker runs the batches to completion.
+    /// }).unwrap();
+    ///
+    /// let mut counts = counts.lock().unwrap().clone();
+    /// counts.sort();
+    /// assert_eq!(counts, vec![(0, 3), (0, 3), (1, 2), (1, 2), (2, 1), (2, 1)]);
+    /// ```
+    fn new_batch_input<D: Data, I>(&mut self, batches: I) -> (BatchHandle<Self>, Stream<Self, D>)
+    where
+        I: IntoIterator<Item=Vec<D>>,
+        I::IntoIter: 'static;
+}
+
+impl<G: Scope<Timestamp=u64>> BatchInput for G {
+    fn new_batch_input<D: Data, I>(&mut self, batches: I) -> (BatchHandle<G>, Stream<G, D>)
+    where
+        I: IntoIterator<Item=Vec<D>>,
+        I::IntoIter: 'static,
+    {
+        // the completed stream, delayed by one epoch, passes an epoch once the previous is complete.
+        let (handle, completed) = self.feedback(1);
+
+        let mut builder = OperatorBuilder::new("BatchInput".to_owned(), self.clone());
+        let (mut output, stream) = builder.new_output();
+        let mut input = builder.new_input_connection(&completed, Pipeline, vec![Antichain::new()]);
+
+        builder.build(move |mut capabilities| {
+
+            let mut capability = capabilities.pop();
+            let mut batches = batches.into_iter().peekable();
+
+            move |frontiers| {
+                input.for_each(|_time, _data| { });
+                if let Some(cap) = capability.as_mut() {
+                    if !frontiers[0].less_equal(cap.time()) {
+                        if let Some(mut batch) = batches.next() {
+                            output.activate().session(&cap).give_vec(&mut batch);
+                        }
+                        if batches.peek().is_some() {
+                            let next = *cap.time() + 1;
+                            cap.downgrade(&next);
+                        }
+                        else {
+                            capability = None;
+                        }
+                    }
+                }
+            }
+        });
+
+        (BatchHandle { handle }, stream)
+    }
+}
+
+/// A handle to a batch input `Stream`, through which to indicate when epochs are complete.
+pub struct BatchHandle<G: Scope<Timestamp=u64>> {
+    handle: Handle<G, ()>,
+}
+
+impl<G: Scope<Timestamp=u64>> BatchHandle<G> {
+    /// Introduces each batch once `stream` is complete through the epoch of the previous batch.
+    ///
+    /// The stream is typically the last stream of the dataflow, or one joining its outputs, so
+    /// that each batch is introduced once the dataflow is quiescent. Its records are discarded.
+    pub fn completed_by<D: Data>(self, stream: &Stream<G, D>) {
+        stream.unary(Pipeline, "BatchCompleted", |_capability, _info| move |input, _output| {
+            input.for_each(|_time, _data| { });
+        })
+        .connect_loop(self.handle);
+    }
+}
@@ -1,13 +1,20 @@
 //! Broadcast records to all workers.
 
+use std::sync::Arc;
+
 use crate::ExchangeData;
 use crate::dataflow::{Stream, Scope};
-use crate::dataflow::operators::{Map, Exchange};
+use crate::dataflow::channels::{Bundle, Message};
+use crate::dataflow::channels::pact::{Distributed, Pipeline};
+use crate::dataflow::operators::generic::operator::Operator;
 
 /// Broadcast records to all workers.
 pub trait Broadcast<D: ExchangeData> {
     /// Broadcast records to all workers.
     ///
+    /// Each batch of records is shared by the messages to all workers, rather than copied for each,
+    /// and is encoded at most once for workers in other processes.
+    ///
     /// # Examples
     /// ```
     /// use timely::dataflow::operators::{ToStream, Broadcast, Inspect};
@@ -24,12 +31,28 @@ pub trait Broadcast<D: ExchangeData> {
 impl<G: Scope, D: ExchangeData> Broadcast<D> for Stream<G, D> {
     fn broadcast(&self) -> Stream<G, D> {
 
-        // NOTE: Simplified implementation due to underlying motion
-        // in timely dataflow internals. Optimize once they have
-        // settled down.
-        let peers = self.scope().peers() as u64;
-        self.flat_map(move |x| (0 .. peers).map(move |i| (i,x.clone())))
-            .exchange(|ix| ix.0)
-            .map(|(_i,x)| x)
+        // produce one shared message of each batch for each worker, which `Distributed` delivers
+        // to each worker in turn.
+        let peers = self.scope().peers();
+        let index = self.scope().index();
+        let mut vector = Vec::new();
+        let shared = self.unary(Pipeline, "BroadcastShare", move |_capability, _info| move |input, output| {
+            input.for_each(|time, data| {
+                data.swap(&mut vector);
+                let message = Message::new(time.time().clone(), std::mem::take(&mut vector), index, 0);
+                let bundle = Bundle::from_arc(Arc::new(message));
+                for _ in 0 .. peers {
+                    output.give_bundle(&time, bundle.clone());
+                }
+            });
+        });
+
+        let mut vector = Vec::new();
+        shared.unary(Distributed, "Broadcast", move |_capability, _info| move |input, output| {
+            input.for_each(|time, data| {
+                data.swap(&mut vector);
+                output.session(&time).give_vec(&mut vector);
+            });
+        })
     }
 }
@@ -76,6 +76,7 @@
 pub use self::capture::Capture;
 pub use self::replay::Replay;
 pub use self::extract::Extract;
+pub use self::record::Record;
 pub use self::event::{Event, EventPusher};
 pub use self::event::link::EventLink;
 pub use self::event::binary::EventReader;
@@ -84,4 +85,5 @@ pub use self::event::binary::EventWriter;
 pub mod capture;
 pub mod replay;
 pub mod extract;
+pub mod record;
 pub mod event;
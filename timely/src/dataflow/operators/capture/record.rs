@@ -0,0 +1,88 @@
+//! Recording streams to files, and replaying the recordings deterministically.
+//!
+//! The order in which a worker receives messages, and the moments at which its input frontiers
+//! advance, can vary from run to run. A stream recorded by `record_to` captures the messages and
+//! progress information as this worker observed them, in the order observed. Replaying the
+//! recording with `replay_recording` presents the same sequence of messages and frontier changes
+//! to downstream operators, so that a misbehaving operator can be re-run deterministically, on a
+//! single worker if desired. To re-run a whole worker from a recording of all of its
+//! communication, see `execute_recorded` and `replay_worker`.
+//!
+//! Recordings are written without buffering, so that a recording remains useful when the
+//! recorded computation crashes.
+
+use std::fs::File;
+use std::io;
+use std::path::Path;
+
+use abomonation::Abomonation;
+
+use crate::Data;
+use crate::dataflow::{Scope, Stream};
+use crate::progress::Timestamp;
+
+use super::{Capture, EventReader, EventWriter, Replay};
+
+/// Records a stream to a file, for later deterministic replay.
+pub trait Record {
+    /// Records the messages and progress of the stream, as seen by this worker, to the file at `path`.
+    ///
+    /// Any existing file at `path` is replaced. Each worker should record to a distinct path.
+    ///
+    /// # Examples
+    /// ```
+    /// use timely::dataflow::operators::{Exchange, Inspect, ToStream};
+    /// use timely::dataflow::operators::capture::record::{Record, replay_recording};
+    ///
+    /// let path = std::env::temp_dir().join(format!("timely-record-{}.bin", std::process::id()));
+    ///
+    /// // record the input to an operator, as the worker received it.
+    /// let recording = path.clone();
+    /// timely::execute(timely::Config::thread(), move |worker| {
+    ///     worker.dataflow::<u64,_,_>(|scope| {
+    ///         let exchanged = (0 .. 10u64).to_stream(scope).exchange(|x| *x);
+    ///         exchanged.record_to(&recording).unwrap();
+    ///         exchanged.inspect(|x| println!("seen: {:?}", x));
+    ///     });
+    /// }).unwrap();
+    ///
+    /// // re-run the operator from the recording.
+    /// let recording = path.clone();
+    /// timely::execute_directly(move |worker| {
+    ///     worker.dataflow::<u64,_,_>(|scope| {
+    ///         replay_recording::<_, u64, _>(scope, &recording)
+    ///             .unwrap()
+    ///             .inspect(|x| println!("replayed: {:?}", x));
+    ///     });
+    /// });
+    ///
+    /// std::fs::remove_file(path).unwrap();
+    /// ```
+    fn record_to<P: AsRef<Path>>(&self, path: P) -> io::Result<()>;
+}
+
+impl<S: Scope, D: Data+Abomonation> Record for Stream<S, D>
+where
+    S::Timestamp: Abomonation,
+{
+    fn record_to<P: AsRef<Path>>(&self, path: P) -> io::Result<()> {
+        let file = File::create(path)?;
+        self.capture_into(EventWriter::new(file));
+        Ok(())
+    }
+}
+
+/// Replays a stream recorded by `Record::record_to` from the file at `path`.
+///
+/// The recording should be complete, in that the recorded stream should have finished, for the
+/// replayed stream to complete.
+pub fn replay_recording<S, D, P>(scope: &mut S, path: P) -> io::Result<Stream<S, D>>
+where
+    S: Scope,
+    S::Timestamp: Timestamp+Abomonation,
+    D: Data+Abomonation,
+    P: AsRef<Path>,
+{
+    let file = File::open(path)?;
+    Ok(Some(EventReader::<S::Timestamp, D, _>::new(file)).replay_into(scope))
+}
@@ -43,7 +43,10 @@ use crate::dataflow::{Scope, Stream};
 use crate::dataflow::channels::pushers::Counter as PushCounter;
 use crate::dataflow::channels::pushers::buffer::Buffer as PushBuffer;
 use crate::dataflow::operators::generic::builder_raw::OperatorBuilder;
+use crate::dataflow::operators::probe::Handle;
 use crate::progress::Timestamp;
+use crate::progress::frontier::{AntichainRef, MutableAntichain};
+use crate::progress::timestamp::PathSummary;
 
 use super::Event;
 use super::event::EventIterator;
@@ -60,60 +63,149 @@ pub trait Replay<T: Timestamp, D: Data> : Sized {
     /// will re-activate itself every so often. The `None` argument instructs the operator not to
     /// re-activate itself.us
     fn replay_core<S: Scope<Timestamp=T>>(self, scope: &mut S, period: Option<std::time::Duration>) -> Stream<S, D>;
+    /// Replays `self` into the provided scope, introducing each time only once `probe` is within `slack` of it.
+    ///
+    /// A message at `time` is replayed once some time in the frontier of `probe`, advanced by `slack`,
+    /// is greater or equal to `time`, or once the frontier of `probe` is empty. The replay operator stops
+    /// reading each stream at its first message that is not yet admitted, holding only that message and
+    /// the capabilities the stream has not yet released, and reads further events once it is admitted.
+    /// As a stream may report the release of its capabilities only after later messages, a message is
+    /// also admitted once the frontier of `probe` has caught up with the capabilities of the replay
+    /// itself. This bounds the data in flight between the replay and the probe, and the data read from
+    /// the streams, without any logic in the worker's driver loop.
+    ///
+    /// # Examples
+    /// ```
+    /// use std::rc::Rc;
+    /// use timely::dataflow::operators::{ToStream, Delay, Capture, Probe, Inspect};
+    /// use timely::dataflow::operators::capture::{EventLink, Replay, Extract};
+    /// use timely::dataflow::operators::probe::Handle;
+    ///
+    /// let captured = timely::execute_directly(|worker| {
+    ///     let link = Rc::new(EventLink::new());
+    ///     let replay = Some(link.clone());
+    ///     worker.dataflow::<u64,_,_>(|scope| {
+    ///         (0 .. 10u64).to_stream(scope).delay(|x, _| *x).capture_into(link);
+    ///     });
+    ///
+    ///     let mut probe = Handle::new();
+    ///     let feedback = probe.clone();
+    ///     let captured = worker.dataflow(|scope| {
+    ///         replay.replay_with_feedback(scope, feedback, 2)
+    ///               .inspect_time(|time, _| assert!(*time < 10))
+    ///               .probe_with(&mut probe)
+    ///               .capture()
+    ///     });
+    ///     while worker.step() { }
+    ///     captured
+    /// });
+    ///
+    /// assert_eq!(captured.extract().len(), 10);
+    /// ```
+    fn replay_with_feedback<S: Scope<Timestamp=T>>(self, scope: &mut S, probe: Handle<T>, slack: T::Summary) -> Stream<S, D>;
 }
 
 impl<T: Timestamp, D: Data, I> Replay<T, D> for I
 where I : IntoIterator,
       <I as IntoIterator>::Item: EventIterator<T, D>+'static {
     fn replay_core<S: Scope<Timestamp=T>>(self, scope: &mut S, period: Option<std::time::Duration>) -> Stream<S, D>{
+        replay(self, scope, period, |_, _| true)
+    }
+    fn replay_with_feedback<S: Scope<Timestamp=T>>(self, scope: &mut S, probe: Handle<T>, slack: T::Summary) -> Stream<S, D> {
+        replay(self, scope, Some(std::time::Duration::new(0, 0)), move |time, held| {
+            probe.with_frontier(|frontier| {
+                frontier.is_empty() ||
+                frontier.iter().any(|lower| match slack.results_in(lower) {
+                    Some(upper) => time.less_equal(&upper),
+                    None => true,
+                }) ||
+                // the probe waits only on the replay, which must read further to release its capabilities.
+                frontier.iter().all(|lower| held.less_equal(lower))
+            })
+        })
+    }
+}
 
-        let mut builder = OperatorBuilder::new("Replay".to_owned(), scope.clone());
+/// Replays `streams` into `scope`, stopping at each message at a time that `admit` rejects until
+/// `admit` accepts the time, given the frontier of the capabilities the replay holds.
+fn replay<T, D, I, S, A>(streams: I, scope: &mut S, period: Option<std::time::Duration>, mut admit: A) -> Stream<S, D>
+where
+    T: Timestamp,
+    D: Data,
+    I: IntoIterator,
+    I::Item: EventIterator<T, D>+'static,
+    S: Scope<Timestamp=T>,
+    A: FnMut(&T, AntichainRef<T>)->bool+'static,
+{
+    let mut builder = OperatorBuilder::new("Replay".to_owned(), scope.clone());
 
-        let address = builder.operator_info().address;
-        let activator = scope.activator_for(&address[..]);
+    let address = builder.operator_info().address;
+    let activator = scope.activator_for(&address[..]);
 
-        let (targets, stream) = builder.new_output();
+    let (targets, stream) = builder.new_output();
 
-        let mut output = PushBuffer::new(PushCounter::new(targets));
-        let mut event_streams = self.into_iter().collect::<Vec<_>>();
-        let mut started = false;
+    let mut output = PushBuffer::new(PushCounter::new(targets));
+    let mut event_streams = streams.into_iter().collect::<Vec<_>>();
+    let mut held: Vec<Option<(T, Vec<D>)>> = event_streams.iter().map(|_| None).collect();
+    let mut capabilities = MutableAntichain::new();
+    let mut started = false;
 
-        builder.build(
-            move |progress| {
+    builder.build(
+        move |progress| {
+
+            if !started {
+                // The first thing we do is modify our capabilities to match the number of streams we manage.
+                // This should be a simple change of `self.event_streams.len() - 1`. We only do this once, as
+                // our very first action.
+                progress.internals[0].update(S::Timestamp::minimum(), (event_streams.len() as i64) - 1);
+                capabilities.update_iter(Some((S::Timestamp::minimum(), event_streams.len() as i64)));
+                started = true;
+            }
 
-                if !started {
-                    // The first thing we do is modify our capabilities to match the number of streams we manage.
-                    // This should be a simple change of `self.event_streams.len() - 1`. We only do this once, as
-                    // our very first action.
-                    progress.internals[0].update(S::Timestamp::minimum(), (event_streams.len() as i64) - 1);
-                    started = true;
+            for (event_stream, held) in event_streams.iter_mut().zip(held.iter_mut()) {
+
+                // release a held message once its time is admitted, and only then read further events.
+                if let Some((time, data)) = held.take() {
+                    if admit(&time, capabilities.frontier()) {
+                        output.session(&time).give_iterator(data.into_iter());
+                    }
+                    else {
+                        *held = Some((time, data));
+                        continue;
+                    }
                 }
 
-                for event_stream in event_streams.iter_mut() {
-                    while let Some(event) = event_stream.next() {
-                        match *event {
-                            Event::Progress(ref vec) => {
-                                progress.internals[0].extend(vec.iter().cloned());
-                            },
-                            Event::Messages(ref time, ref data) => {
+                while let Some(event) = event_stream.next() {
+                    match *event {
+                        Event::Progress(ref vec) => {
+                            progress.internals[0].extend(vec.iter().cloned());
+                            capabilities.update_iter(vec.iter().cloned());
+                        },
+                        Event::Messages(ref time, ref data) => {
+                            if admit(time, capabilities.frontier()) {
                                 output.session(time).give_iterator(data.iter().cloned());
                             }
+                            else {
+                                // the stream's capabilities remain held, as its later progress events are not yet read.
+                                *held = Some((time.clone(), data.clone()));
+                                break;
+                            }
                         }
                     }
                 }
+            }
 
-                // A `None` period indicates that we do not re-activate here.
-                if let Some(delay) = period {
-                    activator.activate_after(delay);
-                }
+            // A `None` period indicates that we do not re-activate here.
+            if let Some(delay) = period {
+                activator.activate_after(delay);
+            }
 
-                output.cease();
-                output.inner().produced().borrow_mut().drain_into(&mut progress.produceds[0]);
+            output.cease();
+            output.inner().produced().borrow_mut().drain_into(&mut progress.produceds[0]);
 
-                false
-            }
-        );
+            false
+        }
+    );
 
-        stream
-    }
+    stream
 }
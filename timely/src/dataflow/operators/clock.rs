@@ -0,0 +1,203 @@
+//! Create new `Streams` whose timestamps advance with wall-clock time.
+
+use std::rc::Rc;
+use std::cell::RefCell;
+use std::time::Duration;
+
+use crate::Data;
+use crate::logging_core::time::since_unix_epoch;
+use crate::scheduling::Activator;
+use crate::dataflow::{Stream, Scope};
+use crate::dataflow::operators::generic::operator::source;
+
+/// Create a new `Stream` and `ClockHandle` whose epochs advance with wall-clock time.
+pub trait ClockInput : Scope<Timestamp=Duration> {
+    /// Create a new `Stream` and `ClockHandle` through which to supply input.
+    ///
+    /// Timestamps are durations since the Unix epoch, rounded down to a multiple of `granularity`.
+    /// Records sent through the handle are stamped with the current time, and the input advances
+    /// its frontier to the current time as the clock ticks, without any action by the driver.
+    /// As all workers read the same clock, their inputs advance in approximate lock-step.
+    /// On `wasm32-unknown-unknown` the clock is the one installed with
+    /// `timely::logging_core::time::set_unix_clock`, and time stands still until one is.
+    ///
+    /// # Panics
+    ///
+    /// This method panics if `granularity` is zero.
+    ///
+    /// # Examples
+    /// ```
+    /// use std::time::Duration;
+    /// use timely::dataflow::operators::{Inspect, Probe};
+    /// use timely::dataflow::operators::clock::ClockInput;
+    ///
+    /// timely::execute_directly(|worker| {
+    ///
+    ///     let (mut input, probe) = worker.dataflow(|scope| {
+    ///         let (input, stream) = scope.new_clock_input(Duration::from_millis(10));
+    ///         let probe = stream.inspect_time(|t, x| println!("{:?} at {:?}", x, t))
+    ///                           .probe();
+    ///         (input, probe)
+    ///     });
+    ///
+    ///     input.send("hello");
+    ///
+    ///     // the epoch completes once the clock ticks, without calls to `advance_to`.
+    ///     let time = input.time();
+    ///     worker.step_while(|| probe.less_equal(&time));
+    /// });
+    /// ```
+    fn new_clock_input<D: Data>(&mut self, granularity: Duration) -> (ClockHandle<D>, Stream<Self, D>);
+}
+
+impl<G: Scope<Timestamp=Duration>> ClockInput for G {
+    fn new_clock_input<D: Data>(&mut self, granularity: Duration) -> (ClockHandle<D>, Stream<G, D>) {
+
+        assert!(granularity > Duration::new(0, 0), "clock granularity must be non-zero");
+
+        let shared = Rc::new(RefCell::new(ClockState::new(granularity)));
+        let (activator, stream) = timed_source(self, "ClockInput", shared.clone(), |state: &mut ClockState<D>, buffer| {
+            ::std::mem::swap(buffer, &mut state.buffer);
+            if state.closed { None }
+            else { Some((state.now(), Some(state.until_next()))) }
+        });
+
+        (ClockHandle { shared, activator }, stream)
+    }
+}
+
+/// Builds a source of records with `Duration` timestamps, supplied through the shared `state`.
+///
+/// Each time the source is scheduled, `poll` moves buffered records from `state` into the supplied
+/// vector, and returns `None` if the input is closed, or else the time to which the source should
+/// advance and an optional delay after which it should be scheduled again. The records are sent at
+/// their times, which must not be less than the time to which the source last advanced. Returns
+/// an activator for the source, with which handles should schedule it when they buffer records.
+pub(crate) fn timed_source<G, D, S, P>(scope: &G, name: &str, state: Rc<RefCell<S>>, mut poll: P) -> (Activator, Stream<G, D>)
+where
+    G: Scope<Timestamp=Duration>,
+    D: Data,
+    S: 'static,
+    P: FnMut(&mut S, &mut Vec<(Duration, D)>)->Option<(Duration, Option<Duration>)>+'static,
+{
+    let mut activator = None;
+
+    let stream = source(scope, name, |capability, info| {
+
+        let operator_activator = scope.activator_for(&info.address[..]);
+        activator = Some(scope.activator_for(&info.address[..]));
+        operator_activator.activate();
+
+        let mut capability = Some(capability);
+        let mut buffer = Vec::new();
+
+        move |output| {
+            if let Some(cap) = capability.as_mut() {
+                let advance = poll(&mut state.borrow_mut(), &mut buffer);
+
+                // Send buffered records, each at its own time.
+                buffer.sort_by_key(|(time, _)| *time);
+                let mut drain = buffer.drain(..).peekable();
+                while let Some((time, datum)) = drain.next() {
+                    let delayed = cap.delayed(&time);
+                    let mut session = output.session(&delayed);
+                    session.give(datum);
+                    while drain.peek().map(|(t, _)| t == &time).unwrap_or(false) {
+                        session.give(drain.next().unwrap().1);
+                    }
+                }
+
+                match advance {
+                    None => capability = None,
+                    Some((time, delay)) => {
+                        cap.downgrade(&time);
+                        if let Some(delay) = delay {
+                            operator_activator.activate_after(delay);
+                        }
+                    },
+                }
+            }
+        }
+    });
+
+    (activator.expect("source constructor not invoked"), stream)
+}
+
+/// Clock and buffered records shared between a `ClockHandle` and its operator.
+struct ClockState<D> {
+    granularity: Duration,
+    last: Duration,
+    buffer: Vec<(Duration, D)>,
+    closed: bool,
+}
+
+impl<D> ClockState<D> {
+
+    fn new(granularity: Duration) -> Self {
+        let mut result = ClockState {
+            granularity,
+            last: Duration::new(0, 0),
+            buffer: Vec::new(),
+            closed: false,
+        };
+        result.now();
+        result
+    }
+
+    /// The current time, rounded down to the granularity.
+    ///
+    /// The result never decreases, even if the system clock is moved backwards.
+    fn now(&mut self) -> Duration {
+        let nanos = since_unix_epoch().as_nanos();
+        let nanos = nanos - nanos % self.granularity.as_nanos();
+        let time = Duration::new((nanos / 1_000_000_000) as u64, (nanos % 1_000_000_000) as u32);
+        if self.last < time {
+            self.last = time;
+        }
+        self.last
+    }
+
+    /// The time remaining until the clock next ticks.
+    fn until_next(&self) -> Duration {
+        (self.last + self.granularity).checked_sub(since_unix_epoch()).unwrap_or_default()
+    }
+}
+
+/// A handle to a clock-driven input `Stream`, used to introduce data to a timely dataflow computation.
+///
+/// Dropping the handle closes the input.
+pub struct ClockHandle<D> {
+    shared: Rc<RefCell<ClockState<D>>>,
+    activator: Activator,
+}
+
+impl<D> ClockHandle<D> {
+
+    /// Sends one record into the corresponding timely dataflow `Stream`, at the current time.
+    pub fn send(&mut self, data: D) {
+        let mut shared = self.shared.borrow_mut();
+        let time = shared.now();
+        if shared.buffer.is_empty() {
+            self.activator.activate();
+        }
+        shared.buffer.push((time, data));
+    }
+
+    /// Reports the current time, rounded down to the granularity of the clock.
+    pub fn time(&self) -> Duration {
+        self.shared.borrow_mut().now()
+    }
+
+    /// Closes the input.
+    ///
+    /// This method allows timely dataflow to issue all progress notifications blocked by this input
+    /// and to begin to shut down operators, as this input can no longer produce data.
+    pub fn close(self) { }
+}
+
+impl<D> Drop for ClockHandle<D> {
+    fn drop(&mut self) {
+        self.shared.borrow_mut().closed = true;
+        self.activator.activate();
+    }
+}
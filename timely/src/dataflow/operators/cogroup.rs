@@ -0,0 +1,234 @@
+//! Groups the values of two keyed streams by key, within each timestamp.
+
+use std::collections::HashMap;
+use std::hash::Hash;
+
+use crate::{Data, ExchangeData};
+use crate::dataflow::{Stream, Scope};
+use crate::dataflow::channels::pact::{Exchange, ParallelizationContract};
+use crate::dataflow::channels::partitioner::hash;
+use crate::dataflow::operators::generic::TimeStash;
+use crate::dataflow::operators::generic::operator::Operator;
+
+/// Groups the values of two streams of `(key, value)` pairs by key.
+pub trait CoGroup<G: Scope, K: ExchangeData+Hash+Eq, V1: ExchangeData> {
+    /// Invokes `logic` once for each key with values in either stream at a timestamp, with the
+    /// values of each stream, once the timestamp is complete for both inputs.
+    ///
+    /// Records are exchanged by the hash of their key. The values of each key are presented in
+    /// the order in which they are received, and keys in no particular order.
+    ///
+    /// # Examples
+    /// ```
+    /// use timely::dataflow::operators::{ToStream, CoGroup, Capture};
+    /// use timely::dataflow::operators::capture::Extract;
+    ///
+    /// let captured = timely::example(|scope| {
+    ///     let orders = vec![(1u64, 10u64), (2, 20), (2, 25)].to_stream(scope);
+    ///     let refunds = vec![(2u64, 5u64), (3, 7)].to_stream(scope);
+    ///     orders.cogroup(&refunds, |key, orders, refunds| {
+    ///         Some((*key, orders.iter().sum::<u64>(), refunds.iter().sum::<u64>()))
+    ///     })
+    ///     .capture()
+    /// });
+    ///
+    /// let mut result = captured.extract();
+    /// result[0].1.sort();
+    /// assert_eq!(result, vec![(0, vec![(1, 10, 0), (2, 45, 5), (3, 0, 7)])]);
+    /// ```
+    fn cogroup<V2, R, I, L>(&self, other: &Stream<G, (K, V2)>, logic: L) -> Stream<G, R>
+    where
+        V2: ExchangeData,
+        R: Data,
+        I: IntoIterator<Item=R>,
+        L: FnMut(&K, Vec<V1>, Vec<V2>)->I+'static;
+
+    /// Pairs the values of each key in `self` with the values of the key in `other`, within each
+    /// timestamp.
+    ///
+    /// # Examples
+    /// ```
+    /// use timely::dataflow::operators::{ToStream, CoGroup, Capture};
+    /// use timely::dataflow::operators::capture::Extract;
+    ///
+    /// let captured = timely::example(|scope| {
+    ///     let names = vec![(1u64, 'a'), (2, 'b')].to_stream(scope);
+    ///     let ages = vec![(1u64, 30u32), (3, 40)].to_stream(scope);
+    ///     names.join(&ages).capture()
+    /// });
+    ///
+    /// assert_eq!(captured.extract(), vec![(0, vec![(1, 'a', 30)])]);
+    /// ```
+    fn join<V2: ExchangeData>(&self, other: &Stream<G, (K, V2)>) -> Stream<G, (K, V1, V2)>;
+
+    /// As `join`, but also produces each value of a key in `self` absent from `other`, with `None`.
+    ///
+    /// # Examples
+    /// ```
+    /// use timely::dataflow::operators::{ToStream, CoGroup, Capture};
+    /// use timely::dataflow::operators::capture::Extract;
+    ///
+    /// let captured = timely::example(|scope| {
+    ///     let names = vec![(1u64, 'a'), (2, 'b')].to_stream(scope);
+    ///     let ages = vec![(1u64, 30u32), (3, 40)].to_stream(scope);
+    ///     names.left_join(&ages).capture()
+    /// });
+    ///
+    /// let mut result = captured.extract();
+    /// result[0].1.sort();
+    /// assert_eq!(result, vec![(0, vec![(1, 'a', Some(30)), (2, 'b', None)])]);
+    /// ```
+    fn left_join<V2: ExchangeData>(&self, other: &Stream<G, (K, V2)>) -> Stream<G, (K, V1, Option<V2>)>;
+
+    /// As `join`, but also produces each value of a key in `other` absent from `self`, with `None`.
+    ///
+    /// # Examples
+    /// ```
+    /// use timely::dataflow::operators::{ToStream, CoGroup, Capture};
+    /// use timely::dataflow::operators::capture::Extract;
+    ///
+    /// let captured = timely::example(|scope| {
+    ///     let names = vec![(1u64, 'a'), (2, 'b')].to_stream(scope);
+    ///     let ages = vec![(1u64, 30u32), (3, 40)].to_stream(scope);
+    ///     names.right_join(&ages).capture()
+    /// });
+    ///
+    /// let mut result = captured.extract();
+    /// result[0].1.sort();
+    /// assert_eq!(result, vec![(0, vec![(1, Some('a'), 30), (3, None, 40)])]);
+    /// ```
+    fn right_join<V2: ExchangeData>(&self, other: &Stream<G, (K, V2)>) -> Stream<G, (K, Option<V1>, V2)>;
+
+    /// As `join`, but also produces each value of a key present in only one input, with `None`
+    /// for the other input.
+    ///
+    /// # Examples
+    /// ```
+    /// use timely::dataflow::operators::{ToStream, CoGroup, Capture};
+    /// use timely::dataflow::operators::capture::Extract;
+    ///
+    /// let captured = timely::example(|scope| {
+    ///     let names = vec![(1u64, 'a'), (2, 'b')].to_stream(scope);
+    ///     let ages = vec![(1u64, 30u32), (3, 40)].to_stream(scope);
+    ///     names.full_outer_join(&ages).capture()
+    /// });
+    ///
+    /// let mut result = captured.extract();
+    /// result[0].1.sort();
+    /// assert_eq!(result, vec![(0, vec![(1, Some('a'), Some(30)), (2, Some('b'), None), (3, None, Some(40))])]);
+    /// ```
+    fn full_outer_join<V2: ExchangeData>(&self, other: &Stream<G, (K, V2)>) -> Stream<G, (K, Option<V1>, Option<V2>)>;
+}
+
+impl<G: Scope, K: ExchangeData+Hash+Eq, V1: ExchangeData> CoGroup<G, K, V1> for Stream<G, (K, V1)> {
+    fn cogroup<V2, R, I, L>(&self, other: &Stream<G, (K, V2)>, logic: L) -> Stream<G, R>
+    where
+        V2: ExchangeData,
+        R: Data,
+        I: IntoIterator<Item=R>,
+        L: FnMut(&K, Vec<V1>, Vec<V2>)->I+'static,
+    {
+        let pact1 = Exchange::new(|(key, _): &(K, V1)| hash(key));
+        let pact2 = Exchange::new(|(key, _): &(K, V2)| hash(key));
+        cogroup(self, other, pact1, pact2, logic)
+    }
+
+    fn join<V2: ExchangeData>(&self, other: &Stream<G, (K, V2)>) -> Stream<G, (K, V1, V2)> {
+        self.cogroup(other, join_values)
+    }
+
+    fn left_join<V2: ExchangeData>(&self, other: &Stream<G, (K, V2)>) -> Stream<G, (K, V1, Option<V2>)> {
+        self.cogroup(other, left_join_values)
+    }
+
+    fn right_join<V2: ExchangeData>(&self, other: &Stream<G, (K, V2)>) -> Stream<G, (K, Option<V1>, V2)> {
+        self.cogroup(other, right_join_values)
+    }
+
+    fn full_outer_join<V2: ExchangeData>(&self, other: &Stream<G, (K, V2)>) -> Stream<G, (K, Option<V1>, Option<V2>)> {
+        self.cogroup(other, full_outer_join_values)
+    }
+}
+
+/// Groups the values of two streams by key, with the supplied parallelization contracts.
+pub(crate) fn cogroup<G, K, V1, V2, R, I, L, P1, P2>(stream1: &Stream<G, (K, V1)>, stream2: &Stream<G, (K, V2)>, pact1: P1, pact2: P2, mut logic: L) -> Stream<G, R>
+where
+    G: Scope,
+    K: Data+Hash+Eq,
+    V1: Data,
+    V2: Data,
+    R: Data,
+    I: IntoIterator<Item=R>,
+    L: FnMut(&K, Vec<V1>, Vec<V2>)->I+'static,
+    P1: ParallelizationContract<G::Timestamp, (K, V1)>,
+    P2: ParallelizationContract<G::Timestamp, (K, V2)>,
+{
+    let mut groups = TimeStash::new();
+    let mut vector1 = Vec::new();
+    let mut vector2 = Vec::new();
+
+    stream1.binary_notify(stream2, pact1, pact2, "CoGroup", vec![], move |input1, input2, output, notificator| {
+
+        // group the values of each input by timestamp and key.
+        input1.for_each(|time, data| {
+            data.swap(&mut vector1);
+            let groups = groups.entry(time.retain(), notificator, HashMap::new);
+            for (key, value) in vector1.drain(..) {
+                groups.entry(key).or_insert_with(|| (Vec::new(), Vec::new())).0.push(value);
+            }
+        });
+        input2.for_each(|time, data| {
+            data.swap(&mut vector2);
+            let groups = groups.entry(time.retain(), notificator, HashMap::new);
+            for (key, value) in vector2.drain(..) {
+                groups.entry(key).or_insert_with(|| (Vec::new(), Vec::new())).1.push(value);
+            }
+        });
+
+        // present the groups of each complete timestamp.
+        groups.complete(notificator, |time, groups| {
+            let mut session = output.session(time);
+            for (key, (values1, values2)) in groups {
+                session.give_iterator(logic(&key, values1, values2).into_iter());
+            }
+        });
+    })
+}
+
+/// Pairs each value of the first input with each value of the second input.
+pub(crate) fn join_values<K: Clone, V1: Clone, V2: Clone>(key: &K, values1: Vec<V1>, values2: Vec<V2>) -> Vec<(K, V1, V2)> {
+    let mut results = Vec::with_capacity(values1.len() * values2.len());
+    for value1 in values1 {
+        for value2 in values2.iter() {
+            results.push((key.clone(), value1.clone(), value2.clone()));
+        }
+    }
+    results
+}
+
+/// As `join_values`, with `None` for the second input if it has no values.
+pub(crate) fn left_join_values<K: Clone, V1: Clone, V2: Clone>(key: &K, values1: Vec<V1>, values2: Vec<V2>) -> Vec<(K, V1, Option<V2>)> {
+    let values2 = optional(values2);
+    join_values(key, values1, values2)
+}
+
+/// As `join_values`, with `None` for the first input if it has no values.
+pub(crate) fn right_join_values<K: Clone, V1: Clone, V2: Clone>(key: &K, values1: Vec<V1>, values2: Vec<V2>) -> Vec<(K, Option<V1>, V2)> {
+    let values1 = optional(values1);
+    join_values(key, values1, values2)
+}
+
+/// As `join_values`, with `None` for either input if it has no values.
+pub(crate) fn full_outer_join_values<K: Clone, V1: Clone, V2: Clone>(key: &K, values1: Vec<V1>, values2: Vec<V2>) -> Vec<(K, Option<V1>, Option<V2>)> {
+    join_values(key, optional(values1), optional(values2))
+}
+
+// The values as options, or a single `None` if there are no values.
+fn optional<V>(values: Vec<V>) -> Vec<Option<V>> {
+    if values.is_empty() {
+        vec![None]
+    }
+    else {
+        values.into_iter().map(Some).collect()
+    }
+}
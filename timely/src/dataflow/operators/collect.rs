@@ -0,0 +1,111 @@
+//! Collects the records of completed timestamps back to the worker's driver.
+
+use std::rc::Rc;
+use std::cell::RefCell;
+use std::collections::{BTreeMap, VecDeque};
+
+use crate::Data;
+use crate::progress::Timestamp;
+use crate::dataflow::{Stream, Scope};
+use crate::dataflow::channels::pact::Pipeline;
+use crate::dataflow::operators::generic::operator::Operator;
+
+/// The completed timestamps of a stream at this worker, with their records.
+///
+/// The handle is read on the worker's thread, between steps of the worker. As an iterator it
+/// yields the timestamps completed so far, in order, and then returns `None` until the worker
+/// completes more; `is_complete` indicates that the stream is complete and all timestamps have
+/// been read.
+pub struct CollectHandle<T, D> {
+    epochs: Rc<RefCell<(VecDeque<(T, Vec<D>)>, bool)>>,
+}
+
+impl<T, D> CollectHandle<T, D> {
+    /// Indicates that the stream is complete, and every completed timestamp has been read.
+    pub fn is_complete(&self) -> bool {
+        let epochs = self.epochs.borrow();
+        epochs.0.is_empty() && epochs.1
+    }
+}
+
+impl<T, D> Iterator for CollectHandle<T, D> {
+    type Item = (T, Vec<D>);
+    fn next(&mut self) -> Option<Self::Item> {
+        self.epochs.borrow_mut().0.pop_front()
+    }
+}
+
+/// Collects the records of a stream back to the worker's driver.
+pub trait Collect<T: Timestamp, D: Data> {
+    /// Collects the records of each timestamp, and makes them available once it is complete.
+    ///
+    /// # Examples
+    /// ```
+    /// use timely::dataflow::InputHandle;
+    /// use timely::dataflow::operators::{Input, Map, Collect};
+    ///
+    /// timely::execute_directly(|worker| {
+    ///     let mut input = InputHandle::new();
+    ///     let mut results = worker.dataflow::<u64,_,_>(|scope| {
+    ///         scope.input_from(&mut input).map(|x: u64| x * 10).collect_handle()
+    ///     });
+    ///
+    ///     input.send(1);
+    ///     input.send(2);
+    ///     input.advance_to(1);
+    ///     let mut first = None;
+    ///     while first.is_none() { worker.step(); first = results.next(); }
+    ///     assert_eq!(first, Some((0, vec![10, 20])));
+    ///
+    ///     input.close();
+    ///     while !results.is_complete() { worker.step(); }
+    /// });
+    /// ```
+    fn collect_handle(&self) -> CollectHandle<T, D>;
+}
+
+impl<G: Scope, D: Data> Collect<G::Timestamp, D> for Stream<G, D> {
+    fn collect_handle(&self) -> CollectHandle<G::Timestamp, D> {
+        let epochs = Rc::new(RefCell::new((VecDeque::new(), false)));
+        let sender = epochs.clone();
+        complete_epochs(self, "CollectHandle", move |complete, closed| {
+            let mut sender = sender.borrow_mut();
+            sender.0.extend(complete);
+            sender.1 = closed;
+        });
+        CollectHandle { epochs }
+    }
+}
+
+/// Stashes the records of `stream` by timestamp, and supplies `deliver` with the timestamps
+/// that are complete, in order, and whether the stream is complete.
+///
+/// `deliver` is called whenever there are newly completed timestamps or the stream is complete.
+pub(crate) fn complete_epochs<G, D, L>(stream: &Stream<G, D>, name: &str, mut deliver: L)
+where
+    G: Scope,
+    D: Data,
+    L: FnMut(Vec<(G::Timestamp, Vec<D>)>, bool)+'static,
+{
+    let mut stash = BTreeMap::new();
+
+    stream.sink(Pipeline, name, move |input| {
+
+        input.for_each(|time, data| {
+            stash.entry(time.time().clone())
+                 .or_insert_with(Vec::new)
+                 .extend(data.replace(Vec::new()));
+        });
+
+        let frontier = input.frontier();
+        let complete = stash.keys().filter(|time| !frontier.less_equal(time)).cloned().collect::<Vec<_>>();
+        let closed = frontier.is_empty();
+        if !complete.is_empty() || closed {
+            let complete = complete.into_iter().map(|time| {
+                let records = stash.remove(&time).expect("stashed time");
+                (time, records)
+            });
+            deliver(complete.collect(), closed);
+        }
+    });
+}
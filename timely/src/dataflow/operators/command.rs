@@ -0,0 +1,163 @@
+//! Streams of commands received identically by all workers, for reconfiguring operators.
+//!
+//! A dataflow that changes its behavior at runtime, for example by changing the predicate of a
+//! filter or the keys it monitors, should change it at the same point in every worker and in
+//! every run. A `command_stream` broadcasts the commands of each worker's input to all workers,
+//! and releases the commands of each timestamp once it is complete, in timestamp order and in the
+//! same order at every worker. The `reconfigure` operator applies these commands to a state, and
+//! processes the records of each timestamp with the state once all commands at times up to and
+//! including that timestamp have been applied.
+
+use crate::{Data, ExchangeData};
+use crate::order::TotalOrder;
+use crate::dataflow::{Stream, Scope, InputHandle};
+use crate::dataflow::channels::pact::Pipeline;
+use crate::dataflow::operators::{Input, Broadcast};
+use crate::dataflow::operators::capability::Capability;
+use crate::dataflow::operators::generic::operator::Operator;
+use crate::dataflow::operators::ordered::ordered;
+
+/// Introduces commands that all workers receive identically.
+pub trait CommandInput<G: Scope> where G::Timestamp: TotalOrder {
+    /// Introduces the commands of `handle` into the scope, broadcast to all workers.
+    ///
+    /// The commands of each timestamp are produced once the timestamp is complete, in order of
+    /// timestamp, and within a timestamp in order of the index of the issuing worker and then in
+    /// the order it issued them. Every worker receives the same commands in the same order, and
+    /// as the commands of a timestamp are produced at once, operators that apply them in the order
+    /// received reach the same state at every worker.
+    ///
+    /// # Examples
+    /// ```
+    /// use timely::dataflow::InputHandle;
+    /// use timely::dataflow::operators::{Inspect, Probe};
+    /// use timely::dataflow::operators::command::CommandInput;
+    ///
+    /// timely::execute(timely::Config::process(2), |worker| {
+    ///     let mut commands = InputHandle::new();
+    ///     let probe = worker.dataflow::<u64,_,_>(|scope| {
+    ///         scope.command_stream(&mut commands)
+    ///              .inspect(|command: &String| println!("command: {}", command))
+    ///              .probe()
+    ///     });
+    ///     if worker.index() == 0 {
+    ///         commands.send("start".to_string());
+    ///     }
+    ///     commands.advance_to(1);
+    ///     worker.step_while(|| probe.less_than(commands.time()));
+    /// }).unwrap();
+    /// ```
+    fn command_stream<C: ExchangeData>(&mut self, handle: &mut InputHandle<G::Timestamp, C>) -> Stream<G, C>;
+}
+
+impl<G: Scope> CommandInput<G> for G where G::Timestamp: TotalOrder {
+    fn command_stream<C: ExchangeData>(&mut self, handle: &mut InputHandle<G::Timestamp, C>) -> Stream<G, C> {
+
+        let commands = self.input_from(handle);
+        ordered(&commands, "CommandStream", |tagged| tagged.broadcast(), |capability, commands, output| {
+            output.session(capability).give_iterator(commands.into_iter());
+        })
+    }
+}
+
+/// Processes records with a state reconfigured by commands.
+pub trait Reconfigure<G: Scope, D: Data> where G::Timestamp: TotalOrder {
+    /// Applies the records of `commands` to `state` with `apply`, and each record of the stream to
+    /// the state with `logic`, producing the records `logic` returns.
+    ///
+    /// The records of a timestamp are held until `commands` is complete through the timestamp,
+    /// and are then processed with the state once all commands at times less or equal to the
+    /// timestamp have been applied, in the order they were received. With commands from a
+    /// `command_stream`, every worker processes each timestamp with the same state.
+    ///
+    /// # Examples
+    /// ```
+    /// use timely::dataflow::InputHandle;
+    /// use timely::dataflow::operators::{Input, Inspect, Probe};
+    /// use timely::dataflow::operators::command::{CommandInput, Reconfigure};
+    ///
+    /// timely::execute_directly(|worker| {
+    ///
+    ///     let mut commands = InputHandle::new();
+    ///     let mut input = InputHandle::new();
+    ///     let (probe, seen) = worker.dataflow::<u64,_,_>(|scope| {
+    ///         let seen = std::rc::Rc::new(std::cell::RefCell::new(Vec::new()));
+    ///         let seen2 = seen.clone();
+    ///         // the modulus by which records are filtered.
+    ///         let modulus = scope.command_stream(&mut commands);
+    ///         let probe = scope.input_from(&mut input)
+    ///             .reconfigure(&modulus, 1u64, |modulus, command| *modulus = command, |modulus, x: u64| {
+    ///                 if x % modulus == 0 { Some(x) } else { None }
+    ///             })
+    ///             .inspect(move |x| seen2.borrow_mut().push(*x))
+    ///             .probe();
+    ///         (probe, seen)
+    ///     });
+    ///
+    ///     for round in 0 .. 4u64 {
+    ///         if round == 2 { commands.send(2); }
+    ///         for x in 0 .. 4 { input.send(round * 4 + x); }
+    ///         commands.advance_to(round + 1);
+    ///         input.advance_to(round + 1);
+    ///         worker.step_while(|| probe.less_than(input.time()));
+    ///     }
+    ///
+    ///     assert_eq!(*seen.borrow(), vec![0, 1, 2, 3, 4, 5, 6, 7, 8, 10, 12, 14]);
+    /// });
+    /// ```
+    fn reconfigure<C, S, D2, I, A, L>(&self, commands: &Stream<G, C>, state: S, apply: A, logic: L) -> Stream<G, D2>
+    where
+        C: Data,
+        S: 'static,
+        D2: Data,
+        I: IntoIterator<Item=D2>,
+        A: FnMut(&mut S, C)+'static,
+        L: FnMut(&S, D)->I+'static;
+}
+
+impl<G: Scope, D: Data> Reconfigure<G, D> for Stream<G, D> where G::Timestamp: TotalOrder {
+    fn reconfigure<C, S, D2, I, A, L>(&self, commands: &Stream<G, C>, mut state: S, mut apply: A, mut logic: L) -> Stream<G, D2>
+    where
+        C: Data,
+        S: 'static,
+        D2: Data,
+        I: IntoIterator<Item=D2>,
+        A: FnMut(&mut S, C)+'static,
+        L: FnMut(&S, D)->I+'static,
+    {
+        let mut stash: Vec<(Capability<G::Timestamp>, Vec<D>)> = Vec::new();
+        let mut pending: Vec<(G::Timestamp, C)> = Vec::new();
+
+        self.binary_frontier(commands, Pipeline, Pipeline, "Reconfigure", move |_capability, _info| {
+            move |input, commands, output| {
+                commands.for_each(|time, data| {
+                    let mut vector = Vec::new();
+                    data.swap(&mut vector);
+                    pending.extend(vector.into_iter().map(|command| (time.time().clone(), command)));
+                });
+                input.for_each(|time, data| {
+                    let mut vector = Vec::new();
+                    data.swap(&mut vector);
+                    stash.push((time.retain(), vector));
+                });
+
+                // process records at times through which the commands are complete, in order of time.
+                let frontier = commands.frontier();
+                let (mut ready, waiting) = stash.drain(..).partition::<Vec<_>, _>(|(capability, _)| !frontier.less_equal(capability.time()));
+                stash = waiting;
+                ready.sort_by(|x, y| x.0.time().cmp(y.0.time()));
+                pending.sort_by(|x, y| x.0.cmp(&y.0));
+                for (capability, records) in ready {
+                    let applied = pending.iter().take_while(|(time, _)| time <= capability.time()).count();
+                    for (_, command) in pending.drain(.. applied) {
+                        apply(&mut state, command);
+                    }
+                    let mut session = output.session(&capability);
+                    for record in records {
+                        session.give_iterator(logic(&state, record).into_iter());
+                    }
+                }
+            }
+        })
+    }
+}
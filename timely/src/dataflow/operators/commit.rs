@@ -0,0 +1,102 @@
+//! Commits the contents of a stream once per timestamp, when complete and durable.
+
+use std::collections::BTreeMap;
+use std::time::Duration;
+
+use crate::Data;
+use crate::dataflow::channels::pact::Pipeline;
+use crate::dataflow::operators::generic::builder_rc::OperatorBuilder;
+use crate::dataflow::{Scope, Stream};
+
+/// Commits the contents of a stream once per timestamp.
+pub trait Commit<G: Scope, D: Data> {
+    /// Buffers the records of each timestamp, and passes them to `logic` once the timestamp is
+    /// complete and `durable` confirms it may be committed.
+    ///
+    /// Timestamps are committed in order, each exactly once per worker, with all of the records
+    /// the worker received for that timestamp. Used with `Worker::durability` and a source that
+    /// can be replayed from the restored epoch, committed output is neither lost nor duplicated
+    /// on restoration: an epoch is committed only once a consistent checkpoint contains it, and
+    /// restoration resumes from after such a checkpoint.
+    ///
+    /// While a complete timestamp awaits durability the operator re-activates itself
+    /// periodically to check again.
+    ///
+    /// # Examples
+    /// ```
+    /// use std::rc::Rc;
+    /// use std::cell::RefCell;
+    /// use timely::dataflow::InputHandle;
+    /// use timely::dataflow::operators::{Input, Commit};
+    ///
+    /// timely::execute_directly(|worker| {
+    ///
+    ///     let committed = Rc::new(RefCell::new(Vec::new()));
+    ///     let durability = worker.durability();
+    ///
+    ///     let mut input = InputHandle::new();
+    ///     let sink = committed.clone();
+    ///     worker.dataflow::<u64,_,_>(|scope| {
+    ///         scope
+    ///             .input_from(&mut input)
+    ///             .commit(move |time| durability.is_durable(*time), move |time, data| {
+    ///                 sink.borrow_mut().push((*time, data));
+    ///             });
+    ///     });
+    ///
+    ///     for round in 0 .. 3 {
+    ///         input.send(round);
+    ///         input.send(round + 10);
+    ///         input.advance_to(round + 1);
+    ///     }
+    ///     input.close();
+    ///     while worker.step() { }
+    ///
+    ///     assert_eq!(*committed.borrow(), vec![(0, vec![0, 10]), (1, vec![1, 11]), (2, vec![2, 12])]);
+    /// });
+    /// ```
+    fn commit<P, L>(&self, durable: P, logic: L)
+    where
+        P: FnMut(&G::Timestamp)->bool+'static,
+        L: FnMut(&G::Timestamp, Vec<D>)+'static;
+}
+
+impl<G: Scope, D: Data> Commit<G, D> for Stream<G, D> {
+
+    fn commit<P, L>(&self, mut durable: P, mut logic: L)
+    where
+        P: FnMut(&G::Timestamp)->bool+'static,
+        L: FnMut(&G::Timestamp, Vec<D>)+'static,
+    {
+        let mut builder = OperatorBuilder::new("Commit".to_owned(), self.scope());
+        let activator = self.scope().activator_for(&builder.operator_info().address[..]);
+        let mut input = builder.new_input(self, Pipeline);
+
+        let mut pending = BTreeMap::<G::Timestamp, Vec<D>>::new();
+        let mut vector = Vec::new();
+
+        builder.build(move |_capabilities| {
+            move |frontiers| {
+
+                input.for_each(|time, data| {
+                    data.swap(&mut vector);
+                    pending.entry(time.time().clone()).or_insert_with(Vec::new).append(&mut vector);
+                });
+
+                // Commit complete and durable timestamps, in order.
+                let frontier = &frontiers[0];
+                while let Some(time) = pending.keys().next().cloned() {
+                    if frontier.less_equal(&time) {
+                        break;
+                    }
+                    if !durable(&time) {
+                        activator.activate_after(Duration::from_millis(10));
+                        break;
+                    }
+                    let data = pending.remove(&time).expect("pending time absent");
+                    logic(&time, data);
+                }
+            }
+        });
+    }
+}
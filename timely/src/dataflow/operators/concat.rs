@@ -47,6 +47,22 @@ pub trait Concatenate<G: Scope, D: Data> {
     ///          .inspect(|x| println!("seen: {:?}", x));
     /// });
     /// ```
+    ///
+    /// Called on a scope, the streams may come from any operators in it, and may be none at all,
+    /// in which case the result is an empty stream.
+    ///
+    /// ```
+    /// use timely::dataflow::Stream;
+    /// use timely::dataflow::operators::{Concatenate, Probe};
+    ///
+    /// timely::execute_directly(|worker| {
+    ///     let probe = worker.dataflow::<u64,_,_>(|scope| {
+    ///         scope.concatenate(Vec::<Stream<_, u64>>::new())
+    ///              .probe()
+    ///     });
+    ///     worker.step_while(|| !probe.done());
+    /// });
+    /// ```
     fn concatenate<I>(&self, sources: I) -> Stream<G, D>
     where
         I: IntoIterator<Item=Stream<G, D>>;
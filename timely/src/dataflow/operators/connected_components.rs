@@ -0,0 +1,165 @@
+//! Labels the nodes of a graph by their connected component.
+//!
+//! This is a complete iterative computation, combining exchange, a feedback loop in a nested
+//! scope, and per-key state that is retired as timestamps complete, and can serve as a template
+//! for other graph algorithms.
+
+use std::collections::HashMap;
+use std::hash::Hash;
+
+use crate::ExchangeData;
+use crate::order::{PartialOrder, Product};
+use crate::dataflow::{Stream, Scope};
+use crate::dataflow::channels::pact::{Exchange, Pipeline};
+use crate::dataflow::channels::partitioner::hash;
+use crate::dataflow::operators::{Enter, Leave, Concat, Map, LoopVariable, ConnectLoop};
+use crate::dataflow::operators::generic::operator::Operator;
+
+/// Labels the nodes of a graph by their connected component.
+pub trait ConnectedComponents<G: Scope, N: ExchangeData+Hash+Ord> {
+    /// Produces `(node, label)` for each node of the edges at each timestamp, where `label` is
+    /// the least node in the connected component of `node`.
+    ///
+    /// The graph at each timestamp is formed by the edges with that timestamp alone, and edges are
+    /// undirected. Labels are propagated along edges in an iterative scope until none improve, and
+    /// the label of each node is produced once, by the worker the node is exchanged to, once the
+    /// timestamp is complete.
+    ///
+    /// # Examples
+    /// ```
+    /// use timely::dataflow::operators::{ToStream, Capture};
+    /// use timely::dataflow::operators::connected_components::ConnectedComponents;
+    /// use timely::dataflow::operators::capture::Extract;
+    ///
+    /// let captured = timely::example(|scope| {
+    ///     vec![(1u64, 2u64), (3, 2), (5, 4)]
+    ///         .to_stream(scope)
+    ///         .connected_components()
+    ///         .capture()
+    /// });
+    ///
+    /// assert_eq!(captured.extract(), vec![(0, vec![(1, 1), (2, 1), (3, 1), (4, 4), (5, 4)])]);
+    /// ```
+    fn connected_components(&self) -> Stream<G, (N, N)>;
+}
+
+impl<G: Scope, N: ExchangeData+Hash+Ord> ConnectedComponents<G, N> for Stream<G, (N, N)> {
+    fn connected_components(&self) -> Stream<G, (N, N)> {
+
+        let mut scope = self.scope();
+        let improvements = scope.iterative::<u64, _, _>(|inner| {
+
+            // each edge in both directions, and each node proposing itself as its label.
+            let edges = self.flat_map(|(src, dst)| vec![(src.clone(), dst.clone()), (dst, src)]).enter(inner);
+            let nodes = self.flat_map(|(src, dst)| vec![(src.clone(), src), (dst.clone(), dst)]).enter(inner);
+
+            let (handle, proposals) = inner.loop_variable(1);
+            let (proposals_out, sides) = nodes.concat(&proposals).binary_side(
+                &edges,
+                Exchange::new(|proposal: &(N, N)| hash(&proposal.0)),
+                Exchange::new(|edge: &(N, N)| hash(&edge.0)),
+                "ConnectedComponents",
+                &["improved"],
+                |_capability, _side_capabilities, _info| {
+
+                    let mut neighbors = HashMap::new();
+                    let mut labels = HashMap::new();
+                    let mut stash = HashMap::new();
+
+                    move |input1, input2, output, sides| {
+
+                        // the graph of each outer timestamp.
+                        input2.for_each(|time, data| {
+                            let graph = neighbors.entry(time.time().outer.clone()).or_insert_with(HashMap::new);
+                            for (src, dst) in data.replace(Vec::new()) {
+                                graph.entry(src).or_insert_with(Vec::new).push(dst);
+                            }
+                        });
+
+                        // proposed labels, stashed until their time is complete.
+                        input1.for_each(|time, data| {
+                            let key = time.time().clone();
+                            if !stash.contains_key(&key) {
+                                let side_time = time.delayed_for_output(&key, sides.port("improved"));
+                                stash.insert(key.clone(), (time.retain(), side_time, Vec::new()));
+                            }
+                            stash.get_mut(&key).expect("stashed time").2.extend(data.replace(Vec::new()));
+                        });
+
+                        let frontier1 = input1.frontier();
+                        let frontier2 = input2.frontier();
+                        let mut ready = stash.keys().filter(|time| !frontier1.less_equal(time) && !frontier2.less_equal(time)).cloned().collect::<Vec<_>>();
+                        ready.sort();
+
+                        for time in ready {
+                            let (capability, side_capability, proposals) = stash.remove(&time).expect("stashed time");
+                            let current = labels.entry(time.outer.clone()).or_insert_with(HashMap::new);
+
+                            // apply the least proposal for each node, noting those that improve.
+                            let mut improved = Vec::new();
+                            for (node, label) in proposals {
+                                let improves = match current.get(&node) {
+                                    Some(existing) => label < *existing,
+                                    None => true,
+                                };
+                                if improves {
+                                    current.insert(node.clone(), label);
+                                    improved.push(node);
+                                }
+                            }
+                            improved.sort();
+                            improved.dedup();
+
+                            // report each improvement, and propose it to the node's neighbors.
+                            let graph = neighbors.get(&time.outer);
+                            let mut session = output.session(&capability);
+                            let mut side_session = sides.get("improved").session(&side_capability);
+                            for node in improved {
+                                let label = current[&node].clone();
+                                if let Some(adjacent) = graph.and_then(|graph| graph.get(&node)) {
+                                    for neighbor in adjacent.iter() {
+                                        session.give((neighbor.clone(), label.clone()));
+                                    }
+                                }
+                                side_session.give((node, label));
+                            }
+                        }
+
+                        // retire the state of outer timestamps that can no longer be iterated.
+                        let active = |outer: &G::Timestamp| {
+                            frontier1.frontier().iter().chain(frontier2.frontier().iter())
+                                     .any(|time: &Product<G::Timestamp, u64>| time.outer.less_equal(outer))
+                        };
+                        neighbors.retain(|outer, _| active(outer));
+                        labels.retain(|outer, _| active(outer));
+                    }
+                }
+            );
+
+            proposals_out.connect_loop(handle);
+            sides["improved"].leave()
+        });
+
+        // the least label reported for each node is its final label.
+        let mut stash = HashMap::new();
+        improvements.unary_notify(Pipeline, "ComponentLabels", vec![], move |input, output, notificator| {
+            input.for_each(|time, data| {
+                let labels = stash.entry(time.time().clone()).or_insert_with(HashMap::new);
+                for (node, label) in data.replace(Vec::new()) {
+                    let least = labels.entry(node).or_insert_with(|| label.clone());
+                    if label < *least {
+                        *least = label;
+                    }
+                }
+                notificator.notify_at(time.retain());
+            });
+            notificator.for_each(|time, _count, _notificator| {
+                if let Some(labels) = stash.remove(time.time()) {
+                    let mut labels = labels.into_iter().collect::<Vec<_>>();
+                    labels.sort();
+                    output.session(&time).give_iterator(labels.into_iter());
+                }
+            });
+        })
+    }
+}
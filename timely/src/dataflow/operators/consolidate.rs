@@ -0,0 +1,63 @@
+//! Consolidates the differences of records within each timestamp.
+use std::collections::HashMap;
+use std::hash::Hash;
+
+use crate::ExchangeData;
+use crate::dataflow::{Stream, Scope};
+use crate::dataflow::channels::pact::Exchange;
+use crate::dataflow::channels::partitioner::hash;
+use crate::dataflow::operators::generic::operator::Operator;
+
+/// Consolidates streams of `(data, diff)` pairs.
+pub trait Consolidate<G: Scope, D: ExchangeData+Hash+Eq> {
+    /// Sums the differences of each record within each timestamp, and drops records whose sum is zero.
+    ///
+    /// Records are exchanged by their hash, so that each record is produced by at most one
+    /// worker, and are produced once their timestamp is complete.
+    ///
+    /// # Examples
+    /// ```
+    /// use timely::dataflow::operators::{ToStream, Consolidate, Capture};
+    /// use timely::dataflow::operators::capture::Extract;
+    ///
+    /// let captured = timely::example(|scope| {
+    ///     vec![(0u64, 1), (1, 2), (0, -1), (1, 1), (2, 0)]
+    ///         .to_stream(scope)
+    ///         .consolidate()
+    ///         .capture()
+    /// });
+    ///
+    /// assert_eq!(captured.extract(), vec![(0, vec![(1, 3)])]);
+    /// ```
+    fn consolidate(&self) -> Stream<G, (D, i64)>;
+}
+
+impl<G: Scope, D: ExchangeData+Hash+Eq> Consolidate<G, D> for Stream<G, (D, i64)> {
+    fn consolidate(&self) -> Stream<G, (D, i64)> {
+
+        let mut differences = HashMap::new();
+        let mut vector = Vec::new();
+        let exchange = Exchange::new(|(datum, _): &(D, i64)| hash(datum));
+
+        self.unary_notify(exchange, "Consolidate", vec![], move |input, output, notificator| {
+
+            // accumulate the differences of each record at each time.
+            input.for_each(|time, data| {
+                data.swap(&mut vector);
+                let accumulated = differences.entry(time.time().clone()).or_insert_with(HashMap::new);
+                for (datum, diff) in vector.drain(..) {
+                    *accumulated.entry(datum).or_insert(0) += diff;
+                }
+                notificator.notify_at(time.retain());
+            });
+
+            // produce the non-zero accumulations of completed times.
+            notificator.for_each(|time,_,_| {
+                if let Some(accumulated) = differences.remove(time.time()) {
+                    let mut session = output.session(&time);
+                    session.give_iterator(accumulated.into_iter().filter(|(_, diff)| *diff != 0));
+                }
+            });
+        })
+    }
+}
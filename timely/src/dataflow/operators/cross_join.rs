@@ -0,0 +1,65 @@
+//! Pairs each record of a stream with each record of a small stream, at the same timestamp.
+
+use crate::{Data, ExchangeData};
+use crate::dataflow::{Stream, Scope};
+use crate::dataflow::channels::pact::Pipeline;
+use crate::dataflow::operators::Broadcast;
+use crate::dataflow::operators::generic::TimeStash;
+use crate::dataflow::operators::generic::operator::Operator;
+
+/// Pairs each record of a stream with each record of a small stream.
+pub trait CrossJoin<G: Scope, D: Data> {
+    /// Produces every pair of a record of `self` and a record of `small` with the same timestamp.
+    ///
+    /// The records of `small` are broadcast to all workers, while the records of `self` remain
+    /// where they are; each pair is produced once, at the worker holding the record of `self`.
+    /// The pairs of a timestamp are produced once it is complete for both inputs. As every worker
+    /// holds all of `small`, it should be the smaller input, for example a dimension table or a
+    /// set of parameters to sweep.
+    ///
+    /// # Examples
+    /// ```
+    /// use timely::dataflow::operators::{ToStream, CrossJoin, Capture};
+    /// use timely::dataflow::operators::capture::Extract;
+    ///
+    /// let captured = timely::example(|scope| {
+    ///     let parameters = vec!['a', 'b'].to_stream(scope);
+    ///     (0 .. 2).to_stream(scope)
+    ///             .cross_join(&parameters)
+    ///             .capture()
+    /// });
+    ///
+    /// assert_eq!(captured.extract(), vec![(0, vec![(0, 'a'), (0, 'b'), (1, 'a'), (1, 'b')])]);
+    /// ```
+    fn cross_join<D2: ExchangeData>(&self, small: &Stream<G, D2>) -> Stream<G, (D, D2)>;
+}
+
+impl<G: Scope, D: Data> CrossJoin<G, D> for Stream<G, D> {
+    fn cross_join<D2: ExchangeData>(&self, small: &Stream<G, D2>) -> Stream<G, (D, D2)> {
+
+        let mut stash = TimeStash::new();
+
+        self.binary_notify(&small.broadcast(), Pipeline, Pipeline, "CrossJoin", vec![], move |input1, input2, output, notificator| {
+
+            // stash the records of each input by timestamp.
+            input1.for_each(|time, data| {
+                let (records, _): &mut (Vec<D>, Vec<D2>) = stash.entry(time.retain(), notificator, Default::default);
+                records.extend(data.replace(Vec::new()));
+            });
+            input2.for_each(|time, data| {
+                let (_, small) = stash.entry(time.retain(), notificator, Default::default);
+                small.extend(data.replace(Vec::new()));
+            });
+
+            // produce the pairs of each complete timestamp.
+            stash.complete(notificator, |time, (records, small)| {
+                let mut session = output.session(time);
+                for record in records {
+                    for other in small.iter() {
+                        session.give((record.clone(), other.clone()));
+                    }
+                }
+            });
+        })
+    }
+}
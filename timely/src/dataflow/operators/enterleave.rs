@@ -32,7 +32,7 @@ use crate::dataflow::channels::{Bundle, Message};
 
 use crate::worker::AsWorker;
 use crate::dataflow::{Stream, Scope};
-use crate::dataflow::scopes::{Child, ScopeParent};
+use crate::dataflow::scopes::{Child, Group, ScopeParent};
 use crate::dataflow::operators::delay::Delay;
 
 /// Extension trait to move a `Stream` into a child of its current `Scope`.
@@ -141,6 +141,38 @@ impl<'a, G: Scope, D: Data, T: Timestamp+Refines<G::Timestamp>> Leave<G, D> for
     }
 }
 
+/// Extension trait to move a `Stream` into a group of its current `Scope`.
+pub trait EnterGroup<G: Scope, D: Data> {
+    /// Moves the `Stream` argument into a group of its current `Scope`.
+    ///
+    /// The group shares the progress tracking of the scope, and no operator is added.
+    ///
+    /// # Examples
+    /// ```
+    /// use timely::dataflow::scopes::Scope;
+    /// use timely::dataflow::operators::{EnterGroup, Leave, ToStream};
+    ///
+    /// timely::example(|outer| {
+    ///     let stream = (0..9).to_stream(outer);
+    ///     let output = outer.group("Group", |inner| {
+    ///         stream.enter_group(inner).leave()
+    ///     });
+    /// });
+    /// ```
+    fn enter_group(&self, group: &Group<G>) -> Stream<Group<G>, D>;
+}
+
+impl<G: Scope, D: Data> EnterGroup<G, D> for Stream<G, D> {
+    fn enter_group(&self, group: &Group<G>) -> Stream<Group<G>, D> {
+        self.rescope(group.clone())
+    }
+}
+
+impl<G: Scope, D: Data> Leave<G, D> for Stream<Group<G>, D> {
+    fn leave(&self) -> Stream<G, D> {
+        self.rescope(self.scope().parent)
+    }
+}
 
 struct IngressNub<TOuter: Timestamp, TInner: Timestamp+Refines<TOuter>, TData: Data> {
     targets: Counter<TInner, TData, Tee<TInner, TData>>,
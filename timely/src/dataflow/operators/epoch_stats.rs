@@ -0,0 +1,79 @@
+//! Summarizes the records of each timestamp of a stream, for monitoring.
+
+use std::time::Duration;
+use crate::logging_core::time::Instant;
+
+use crate::Data;
+use crate::dataflow::{Stream, Scope};
+use crate::dataflow::channels::pact::Pipeline;
+use crate::dataflow::operators::generic::TimeStash;
+use crate::dataflow::operators::generic::operator::Operator;
+
+/// A summary of the records of one timestamp at one worker.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct EpochStats {
+    /// The number of records.
+    pub records: usize,
+    /// An estimate of the size of the records, as `records` times the size of the record type.
+    ///
+    /// Memory owned by the records, for example the contents of a `String`, is not included.
+    pub bytes: usize,
+    /// The time from the arrival of the last records to the completion of the timestamp.
+    pub min_latency: Duration,
+    /// The time from the arrival of the first records to the completion of the timestamp.
+    pub max_latency: Duration,
+}
+
+/// Summarizes the records of each timestamp of a stream.
+pub trait EpochStatistics<G: Scope, D: Data> {
+    /// Passes the stream through unchanged, and produces an `EpochStats` for each timestamp with
+    /// records, once the timestamp is complete, as a side stream.
+    ///
+    /// Each worker summarizes the records it receives. Latencies are measured from when the worker
+    /// receives a batch of records to when it observes the timestamp complete.
+    ///
+    /// # Examples
+    /// ```
+    /// use timely::dataflow::operators::{ToStream, Map, Capture};
+    /// use timely::dataflow::operators::epoch_stats::EpochStatistics;
+    /// use timely::dataflow::operators::capture::Extract;
+    ///
+    /// let (records, stats) = timely::example(|scope| {
+    ///     let (records, stats) = (0 .. 10u64).to_stream(scope).epoch_stats();
+    ///     (records.capture(), stats.map(|stats| (stats.records, stats.bytes)).capture())
+    /// });
+    ///
+    /// assert_eq!(records.extract(), vec![(0, (0 .. 10).collect::<Vec<_>>())]);
+    /// assert_eq!(stats.extract(), vec![(0, vec![(10, 80)])]);
+    /// ```
+    fn epoch_stats(&self) -> (Stream<G, D>, Stream<G, EpochStats>);
+}
+
+impl<G: Scope, D: Data> EpochStatistics<G, D> for Stream<G, D> {
+    fn epoch_stats(&self) -> (Stream<G, D>, Stream<G, EpochStats>) {
+
+        let mut pending = TimeStash::new();
+        let stats = self.unary_notify(Pipeline, "EpochStats", vec![], move |input, output, notificator| {
+
+            let arrival = Instant::now();
+            input.for_each(|time, data| {
+                let (stats, arrivals) = pending.entry(time.retain(), notificator, || {
+                    (EpochStats { records: 0, bytes: 0, min_latency: Duration::default(), max_latency: Duration::default() }, (arrival, arrival))
+                });
+                stats.records += data.len();
+                stats.bytes += data.len() * ::std::mem::size_of::<D>();
+                arrivals.1 = arrival;
+            });
+
+            // summarize each timestamp that is now complete.
+            let closed = Instant::now();
+            pending.complete(notificator, |time, (mut stats, (first, last))| {
+                stats.min_latency = closed.duration_since(last);
+                stats.max_latency = closed.duration_since(first);
+                output.session(time).give(stats);
+            });
+        });
+
+        (self.clone(), stats)
+    }
+}
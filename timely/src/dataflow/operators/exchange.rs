@@ -1,9 +1,11 @@
 //! Exchange records between workers.
 
 use crate::ExchangeData;
-use crate::dataflow::channels::pact::Exchange as ExchangePact;
+use crate::dataflow::channels::pact::{Exchange as ExchangePact, Balanced, ParallelizationContract, Partitioned};
+use crate::dataflow::channels::partitioner::Partitioner;
 use crate::dataflow::{Stream, Scope};
 use crate::dataflow::operators::generic::operator::Operator;
+use crate::dataflow::operators::ordered::ordered;
 
 /// Exchange records between workers.
 pub trait Exchange<T, D: ExchangeData> {
@@ -23,17 +25,103 @@ pub trait Exchange<T, D: ExchangeData> {
     /// });
     /// ```
     fn exchange(&self, route: impl Fn(&D)->u64+'static) -> Self;
+
+    /// Exchange records between workers, as assigned by `partitioner`.
+    ///
+    /// # Examples
+    /// ```
+    /// use timely::dataflow::operators::{ToStream, Exchange, Inspect};
+    /// use timely::dataflow::channels::partitioner::Rendezvous;
+    ///
+    /// timely::example(|scope| {
+    ///     (0..10).to_stream(scope)
+    ///            .exchange_with(Rendezvous::new(|x: &u64| *x))
+    ///            .inspect(|x| println!("seen: {:?}", x));
+    /// });
+    /// ```
+    fn exchange_with(&self, partitioner: impl Partitioner<D>+'static) -> Self;
+
+    /// Exchange batches of records between workers, sending each to the worker with the fewest
+    /// records sent to it but not yet consumed.
+    ///
+    /// Records may be routed to any worker, and so this is appropriate only when downstream
+    /// operators do not rely on records being routed by their contents.
+    ///
+    /// # Examples
+    /// ```
+    /// use timely::dataflow::operators::{ToStream, Exchange, Inspect};
+    ///
+    /// timely::execute(timely::Config::process(2), |worker| {
+    ///     worker.dataflow::<u64,_,_>(|scope| {
+    ///         (0..1000u64).to_stream(scope)
+    ///                     .balance()
+    ///                     .inspect(|x| assert!(*x < 1000));
+    ///     });
+    /// }).unwrap();
+    /// ```
+    fn balance(&self) -> Self;
+
+    /// Exchange records between workers as `exchange` does, delivering them in a deterministic order.
+    ///
+    /// The records of each timestamp are delivered once the timestamp is complete, in order of
+    /// the index of the worker that sent them, and then in the order that worker sent them, so
+    /// that the order of delivery is the same in every run. Timestamps are delivered in order of
+    /// completion, and timestamps completing together in order of timestamp. This costs tagging
+    /// each record with its sender and position, holding the records of each timestamp until it
+    /// is complete, and sorting them, and so suits testing and audit more than throughput.
+    ///
+    /// # Examples
+    /// ```
+    /// use std::sync::{Arc, Mutex};
+    /// use timely::dataflow::operators::{ToStream, Exchange, Inspect};
+    ///
+    /// let received = Arc::new(Mutex::new(Vec::new()));
+    /// let shared = received.clone();
+    /// timely::execute(timely::Config::process(2), move |worker| {
+    ///     let index = worker.index();
+    ///     let shared = shared.clone();
+    ///     worker.dataflow::<u64,_,_>(|scope| {
+    ///         (0 .. 3u64).map(move |x| (index, x))
+    ///                    .to_stream(scope)
+    ///                    .exchange_ordered(|_| 0)
+    ///                    .inspect(move |x| shared.lock().unwrap().push(*x));
+    ///     });
+    /// }).unwrap();
+    ///
+    /// let received = received.lock().unwrap().clone();
+    /// assert_eq!(received, vec![(0, 0), (0, 1), (0, 2), (1, 0), (1, 1), (1, 2)]);
+    /// ```
+    fn exchange_ordered(&self, route: impl Fn(&D)->u64+'static) -> Self;
 }
 
 // impl<T: Timestamp, G: Scope<Timestamp=T>, D: ExchangeData> Exchange<T, D> for Stream<G, D> {
 impl<G: Scope, D: ExchangeData> Exchange<G::Timestamp, D> for Stream<G, D> {
     fn exchange(&self, route: impl Fn(&D)->u64+'static) -> Stream<G, D> {
-        let mut vector = Vec::new();
-        self.unary(ExchangePact::new(route), "Exchange", move |_,_| move |input, output| {
-            input.for_each(|time, data| {
-                data.swap(&mut vector);
-                output.session(&time).give_vec(&mut vector);
-            });
+        exchange(self, ExchangePact::new(route))
+    }
+
+    fn exchange_with(&self, partitioner: impl Partitioner<D>+'static) -> Stream<G, D> {
+        exchange(self, Partitioned::new(partitioner))
+    }
+
+    fn balance(&self) -> Stream<G, D> {
+        exchange(self, Balanced)
+    }
+
+    fn exchange_ordered(&self, route: impl Fn(&D)->u64+'static) -> Stream<G, D> {
+        ordered(self, "ExchangeOrdered", |sequenced| sequenced.exchange(move |(_, _, datum)| route(datum)), |capability, records, output| {
+            output.session(capability).give_iterator(records.into_iter());
         })
     }
 }
+
+/// Forwards the records of `stream` through `pact`.
+fn exchange<G: Scope, D: ExchangeData, P: ParallelizationContract<G::Timestamp, D>>(stream: &Stream<G, D>, pact: P) -> Stream<G, D> {
+    let mut vector = Vec::new();
+    stream.unary(pact, "Exchange", move |_,_| move |input, output| {
+        input.for_each(|time, data| {
+            data.swap(&mut vector);
+            output.session(&time).give_vec(&mut vector);
+        });
+    })
+}
@@ -0,0 +1,228 @@
+//! Split streams of raw bytes into frames.
+//!
+//! Network and file sources read bytes in chunks whose boundaries have nothing to do with the
+//! records they contain. Such sources can emit the chunks as they read them, as a `ByteStream`,
+//! and leave framing and parsing to ordinary operators: a framing operator reassembles the
+//! chunks into frames, such as lines or length-prefixed messages, and the frames can then be
+//! exchanged among workers and parsed in parallel.
+
+use crate::order::TotalOrder;
+use crate::dataflow::{Stream, Scope};
+use crate::dataflow::channels::pact::Pipeline;
+use crate::dataflow::operators::capability::Capability;
+use crate::dataflow::operators::generic::operator::Operator;
+
+/// A stream of chunks of raw bytes, in the order they were read.
+pub type ByteStream<G> = Stream<G, Vec<u8>>;
+
+/// A way of splitting a sequence of bytes into frames.
+pub trait Framer {
+    /// Removes the complete frames at the front of `bytes`, appending them to `frames`.
+    fn split(&mut self, bytes: &mut Vec<u8>, frames: &mut Vec<Vec<u8>>);
+    /// The frame formed by bytes remaining once no more bytes will arrive, if any.
+    fn finish(&mut self, bytes: Vec<u8>) -> Option<Vec<u8>>;
+}
+
+/// Frames terminated by a newline, which is removed, as is a carriage return before it.
+///
+/// Bytes remaining after the last newline form a final frame.
+#[derive(Clone, Debug, Default)]
+pub struct Lines;
+
+impl Framer for Lines {
+    fn split(&mut self, bytes: &mut Vec<u8>, frames: &mut Vec<Vec<u8>>) {
+        let mut start = 0;
+        while let Some(offset) = bytes[start ..].iter().position(|byte| *byte == b'\n') {
+            let mut end = start + offset;
+            if end > start && bytes[end - 1] == b'\r' {
+                end -= 1;
+            }
+            frames.push(bytes[start .. end].to_vec());
+            start += offset + 1;
+        }
+        bytes.drain(.. start);
+    }
+    fn finish(&mut self, bytes: Vec<u8>) -> Option<Vec<u8>> {
+        Some(bytes)
+    }
+}
+
+/// Frames preceded by their length in bytes, as a four-byte big-endian integer, which is removed.
+///
+/// Bytes remaining after the last complete frame are an incomplete frame, and are discarded.
+#[derive(Clone, Debug, Default)]
+pub struct LengthPrefixed;
+
+impl Framer for LengthPrefixed {
+    fn split(&mut self, bytes: &mut Vec<u8>, frames: &mut Vec<Vec<u8>>) {
+        let mut start = 0;
+        while bytes.len() - start >= 4 {
+            let mut prefix = [0u8; 4];
+            prefix.copy_from_slice(&bytes[start .. start + 4]);
+            let length = u32::from_be_bytes(prefix) as usize;
+            if bytes.len() - start - 4 < length {
+                break;
+            }
+            frames.push(bytes[start + 4 .. start + 4 + length].to_vec());
+            start += 4 + length;
+        }
+        bytes.drain(.. start);
+    }
+    fn finish(&mut self, _bytes: Vec<u8>) -> Option<Vec<u8>> {
+        None
+    }
+}
+
+/// Frames of a fixed number of bytes.
+///
+/// Bytes remaining after the last complete frame form a shorter final frame.
+#[derive(Clone, Debug)]
+pub struct Chunks {
+    size: usize,
+}
+
+impl Chunks {
+    /// Frames of `size` bytes, which must be positive.
+    pub fn new(size: usize) -> Self {
+        assert!(size > 0, "chunk size must be positive");
+        Chunks { size }
+    }
+}
+
+impl Framer for Chunks {
+    fn split(&mut self, bytes: &mut Vec<u8>, frames: &mut Vec<Vec<u8>>) {
+        let complete = bytes.len() - bytes.len() % self.size;
+        frames.extend(bytes[.. complete].chunks(self.size).map(|chunk| chunk.to_vec()));
+        bytes.drain(.. complete);
+    }
+    fn finish(&mut self, bytes: Vec<u8>) -> Option<Vec<u8>> {
+        Some(bytes)
+    }
+}
+
+/// Splits a stream of raw bytes into frames.
+pub trait Frame<G: Scope> where G::Timestamp: TotalOrder {
+    /// Splits the bytes of this stream into frames, as determined by `framer`.
+    ///
+    /// Bytes are framed at each worker in the order in which they arrive, which is the order in
+    /// which they were sent when the stream comes from a single source at the worker. A frame is
+    /// produced at the latest timestamp of the chunks it spans. Bytes awaiting the rest of their
+    /// frame hold a capability that is downgraded as the input frontier advances, so that they
+    /// hold back the output frontier no further than the input frontier, and a frame completed
+    /// after the frontier has passed its chunks is produced at a time no earlier than the frontier.
+    /// Once the stream is complete, any remaining bytes are passed to the framer's `finish` method.
+    ///
+    /// # Examples
+    /// ```
+    /// use timely::dataflow::operators::{ToStream, Capture};
+    /// use timely::dataflow::operators::framing::{Frame, LengthPrefixed};
+    /// use timely::dataflow::operators::capture::Extract;
+    ///
+    /// let captured = timely::example(|scope| {
+    ///     vec![vec![0, 0, 0, 2, b'h'], vec![b'i', 0, 0], vec![0, 1, b'!']]
+    ///         .to_stream(scope)
+    ///         .frame(LengthPrefixed)
+    ///         .capture()
+    /// });
+    ///
+    /// assert_eq!(captured.extract(), vec![(0, vec![b"!".to_vec(), b"hi".to_vec()])]);
+    /// ```
+    fn frame<F: Framer+'static>(&self, framer: F) -> Stream<G, Vec<u8>>;
+
+    /// Splits the bytes of this stream into lines.
+    ///
+    /// # Examples
+    /// ```
+    /// use timely::dataflow::operators::{ToStream, Capture};
+    /// use timely::dataflow::operators::framing::Frame;
+    /// use timely::dataflow::operators::capture::Extract;
+    ///
+    /// let captured = timely::example(|scope| {
+    ///     vec![b"one\ntw".to_vec(), b"o\r\nthr".to_vec(), b"ee".to_vec()]
+    ///         .to_stream(scope)
+    ///         .split_lines()
+    ///         .capture()
+    /// });
+    ///
+    /// assert_eq!(captured.extract(), vec![(0, vec![b"one".to_vec(), b"three".to_vec(), b"two".to_vec()])]);
+    /// ```
+    fn split_lines(&self) -> Stream<G, Vec<u8>> {
+        self.frame(Lines)
+    }
+
+    /// Splits the bytes of this stream into frames preceded by four-byte big-endian lengths.
+    fn split_length_prefixed(&self) -> Stream<G, Vec<u8>> {
+        self.frame(LengthPrefixed)
+    }
+
+    /// Splits the bytes of this stream into frames of `size` bytes, and a shorter final frame.
+    ///
+    /// # Examples
+    /// ```
+    /// use timely::dataflow::operators::{ToStream, Capture};
+    /// use timely::dataflow::operators::framing::Frame;
+    /// use timely::dataflow::operators::capture::Extract;
+    ///
+    /// let captured = timely::example(|scope| {
+    ///     vec![vec![1, 2, 3], vec![4, 5, 6, 7]]
+    ///         .to_stream(scope)
+    ///         .split_chunks(3)
+    ///         .capture()
+    /// });
+    ///
+    /// assert_eq!(captured.extract(), vec![(0, vec![vec![1, 2, 3], vec![4, 5, 6], vec![7]])]);
+    /// ```
+    fn split_chunks(&self, size: usize) -> Stream<G, Vec<u8>> {
+        self.frame(Chunks::new(size))
+    }
+}
+
+impl<G: Scope> Frame<G> for ByteStream<G> where G::Timestamp: TotalOrder {
+    fn frame<F: Framer+'static>(&self, mut framer: F) -> Stream<G, Vec<u8>> {
+        self.unary_frontier(Pipeline, "Frame", move |_capability, _info| {
+
+            // bytes awaiting the rest of their frame, and the capability for their latest timestamp.
+            let mut pending = Vec::new();
+            let mut held: Option<Capability<G::Timestamp>> = None;
+            let mut frames = Vec::new();
+            let mut vector = Vec::new();
+
+            move |input, output| {
+                input.for_each(|time, data| {
+                    data.swap(&mut vector);
+                    let capability = match held.take() {
+                        Some(held) if time.time() < held.time() => held,
+                        _ => time.retain(),
+                    };
+                    for chunk in vector.drain(..) {
+                        pending.extend(chunk);
+                        framer.split(&mut pending, &mut frames);
+                    }
+                    output.session(&capability).give_vec(&mut frames);
+                    if !pending.is_empty() {
+                        held = Some(capability);
+                    }
+                });
+
+                match input.frontier().frontier().first() {
+                    // the rest of the pending frame arrives no earlier than the frontier.
+                    Some(frontier) => {
+                        if let Some(capability) = held.as_mut() {
+                            if capability.time() < frontier {
+                                capability.downgrade(frontier);
+                            }
+                        }
+                    },
+                    None => {
+                        if let Some(capability) = held.take() {
+                            let bytes = ::std::mem::take(&mut pending);
+                            if let Some(frame) = framer.finish(bytes) {
+                                output.session(&capability).give(frame);
+                            }
+                        }
+                    },
+                }
+            }
+        })
+    }
+}
@@ -0,0 +1,81 @@
+//! Describes the evolution of the frontier of a stream as a stream.
+
+use crate::Data;
+use crate::progress::frontier::Antichain;
+use crate::dataflow::{Stream, Scope};
+use crate::dataflow::channels::pact::Pipeline;
+use crate::dataflow::operators::CapabilitySet;
+use crate::dataflow::operators::generic::operator::Operator;
+
+/// Describes the frontier of a stream as a stream.
+pub trait FrontierStream<G: Scope, D: Data> {
+    /// Produces a record for each change to the frontier of the stream.
+    ///
+    /// When the frontier changes, a record `(time, frontier)` is produced at each `time` in the
+    /// new frontier. When the frontier becomes empty, a record `(time, frontier)` with the empty
+    /// frontier is produced at each `time` of the last non-empty frontier. Logic driven by the
+    /// frontier, for example measuring the lateness of records or triggering windows, can then
+    /// be written as ordinary operators on this stream.
+    ///
+    /// # Examples
+    /// ```
+    /// use timely::dataflow::InputHandle;
+    /// use timely::dataflow::operators::{Input, Map, FrontierStream, Capture};
+    /// use timely::dataflow::operators::capture::Extract;
+    ///
+    /// let captured = timely::execute_directly(|worker| {
+    ///     let mut input = InputHandle::<u64, ()>::new();
+    ///     let captured = worker.dataflow(|scope| {
+    ///         scope.input_from(&mut input)
+    ///              .frontier_stream()
+    ///              .map(|(time, frontier)| (time, frontier.elements().to_vec()))
+    ///              .capture()
+    ///     });
+    ///     for round in 1 .. 3 {
+    ///         input.advance_to(round);
+    ///         worker.step();
+    ///     }
+    ///     captured
+    /// });
+    ///
+    /// assert_eq!(captured.extract(), vec![
+    ///     (1, vec![(1, vec![1])]),
+    ///     (2, vec![(2, vec![]), (2, vec![2])]),
+    /// ]);
+    /// ```
+    fn frontier_stream(&self) -> Stream<G, (G::Timestamp, Antichain<G::Timestamp>)>;
+}
+
+impl<G: Scope, D: Data> FrontierStream<G, D> for Stream<G, D> {
+    fn frontier_stream(&self) -> Stream<G, (G::Timestamp, Antichain<G::Timestamp>)> {
+        self.unary_frontier(Pipeline, "FrontierStream", |capability, _info| {
+
+            let mut last = Antichain::from_elem(capability.time().clone());
+            let mut capabilities = CapabilitySet::from_elem(capability);
+
+            move |input, output| {
+
+                // the records themselves are not needed.
+                input.for_each(|_time, _data| { });
+
+                let frontier = input.frontier().frontier();
+                if frontier != last.borrow() {
+                    let frontier = frontier.to_owned();
+                    if frontier.is_empty() {
+                        for capability in capabilities.iter() {
+                            output.session(capability).give((capability.time().clone(), frontier.clone()));
+                        }
+                    }
+                    else {
+                        for time in frontier.elements().iter() {
+                            let capability = capabilities.delayed(time);
+                            output.session(&capability).give((time.clone(), frontier.clone()));
+                        }
+                    }
+                    capabilities.downgrade(&frontier.borrow()[..]);
+                    last = frontier;
+                }
+            }
+        })
+    }
+}
@@ -0,0 +1,126 @@
+//! Fuses chains of record-by-record transformations into a single operator.
+//!
+//! Each of `map`, `filter`, and `flat_map` on a `Stream` is an operator, with its own scheduling,
+//! progress tracking, and output buffers. A `Fused` chain composes the same transformations into
+//! one closure, applied by a single operator to each record as it is read.
+
+use crate::Data;
+use crate::dataflow::{Stream, Scope};
+use crate::dataflow::channels::pact::Pipeline;
+use crate::dataflow::operators::generic::operator::Operator;
+
+/// Logic supplying the records produced by a chain for each of its input records.
+pub trait Chain<D, D2>: FnMut(D, &mut dyn FnMut(D2))+'static { }
+impl<D, D2, L: FnMut(D, &mut dyn FnMut(D2))+'static> Chain<D, D2> for L { }
+
+/// The chain supplying each record unchanged.
+pub type Identity<D> = fn(D, &mut dyn FnMut(D));
+
+/// A chain of record-by-record transformations from records `D` to records `D2`, not yet built.
+///
+/// The chain is extended by `map`, `filter`, and `flat_map`, and built into one operator by
+/// `into_stream`.
+pub struct Fused<S: Scope, D, D2, L> {
+    stream: Stream<S, D>,
+    logic: L,
+    phantom: ::std::marker::PhantomData<D2>,
+}
+
+impl<S: Scope, D: Data, D2: Data, L: Chain<D, D2>> Fused<S, D, D2, L> {
+    /// Extends the chain with a transformation of each record.
+    pub fn map<D3: Data, M: FnMut(D2)->D3+'static>(self, mut map: M) -> Fused<S, D, D3, impl Chain<D, D3>> {
+        let mut logic = self.logic;
+        self.stream.fuse_with(move |datum, give: &mut dyn FnMut(D3)| logic(datum, &mut |x| give(map(x))))
+    }
+
+    /// Extends the chain with a predicate retaining only some records.
+    pub fn filter<P: FnMut(&D2)->bool+'static>(self, mut predicate: P) -> Fused<S, D, D2, impl Chain<D, D2>> {
+        let mut logic = self.logic;
+        self.stream.fuse_with(move |datum, give: &mut dyn FnMut(D2)| logic(datum, &mut |x| if predicate(&x) { give(x) }))
+    }
+
+    /// Extends the chain with a transformation of each record into any number of records.
+    pub fn flat_map<I, M>(self, mut flat_map: M) -> Fused<S, D, I::Item, impl Chain<D, I::Item>>
+    where
+        I: IntoIterator,
+        I::Item: Data,
+        M: FnMut(D2)->I+'static,
+    {
+        let mut logic = self.logic;
+        self.stream.fuse_with(move |datum, give: &mut dyn FnMut(I::Item)| logic(datum, &mut |x| flat_map(x).into_iter().for_each(&mut *give)))
+    }
+
+    /// Builds the chain into a single operator.
+    pub fn into_stream(self) -> Stream<S, D2> {
+        let mut logic = self.logic;
+        let mut vector = Vec::new();
+        self.stream.unary(Pipeline, "Fused", move |_,_| move |input, output| {
+            input.for_each(|time, data| {
+                data.swap(&mut vector);
+                let mut session = output.session(&time);
+                for datum in vector.drain(..) {
+                    logic(datum, &mut |x| session.give(x));
+                }
+            });
+        })
+    }
+}
+
+/// Extension trait for `Stream`.
+pub trait Fuse<S: Scope, D: Data> {
+    /// Starts a chain of record-by-record transformations to be applied by a single operator.
+    ///
+    /// # Examples
+    /// ```
+    /// use timely::dataflow::operators::{ToStream, Fuse, Capture};
+    /// use timely::dataflow::operators::capture::Extract;
+    ///
+    /// let captured = timely::example(|scope| {
+    ///     (0 .. 10u64)
+    ///         .to_stream(scope)
+    ///         .fuse()
+    ///         .map(|x| x + 1)
+    ///         .filter(|x| x % 2 == 0)
+    ///         .flat_map(|x| vec![x; 2])
+    ///         .into_stream()
+    ///         .capture()
+    /// });
+    ///
+    /// assert_eq!(captured.extract(), vec![(0, vec![2, 2, 4, 4, 6, 6, 8, 8, 10, 10])]);
+    /// ```
+    fn fuse(&self) -> Fused<S, D, D, Identity<D>>;
+
+    /// Starts a chain with `logic`, which supplies the records produced for each record.
+    ///
+    /// # Examples
+    /// ```
+    /// use timely::dataflow::operators::{ToStream, Fuse, Capture};
+    /// use timely::dataflow::operators::capture::Extract;
+    ///
+    /// let captured = timely::example(|scope| {
+    ///     (0 .. 4u64)
+    ///         .to_stream(scope)
+    ///         .fuse_with(|x, give| if x > 1 { give(x * 10) })
+    ///         .into_stream()
+    ///         .capture()
+    /// });
+    ///
+    /// assert_eq!(captured.extract(), vec![(0, vec![20, 30])]);
+    /// ```
+    fn fuse_with<D2: Data, L: Chain<D, D2>>(&self, logic: L) -> Fused<S, D, D2, L>;
+}
+
+impl<S: Scope, D: Data> Fuse<S, D> for Stream<S, D> {
+    fn fuse(&self) -> Fused<S, D, D, Identity<D>> {
+        self.fuse_with(identity as Identity<D>)
+    }
+
+    fn fuse_with<D2: Data, L: Chain<D, D2>>(&self, logic: L) -> Fused<S, D, D2, L> {
+        Fused { stream: self.clone(), logic, phantom: ::std::marker::PhantomData }
+    }
+}
+
+/// Supplies each record unchanged.
+fn identity<D>(datum: D, give: &mut dyn FnMut(D)) {
+    give(datum)
+}
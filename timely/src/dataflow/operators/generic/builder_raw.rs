@@ -25,7 +25,8 @@ use crate::dataflow::operators::generic::operator_info::OperatorInfo;
 pub struct OperatorShape {
     name: String,   // A meaningful name for the operator.
     notify: bool,   // Does the operator require progress notifications.
-    peers: usize,   // The total number of workers in the computation.
+    outstanding: bool, // Does the operator read the messages outstanding at its inputs.
+    peers: usize,   // The number of workers running the operator.
     inputs: usize,  // The number of input ports.
     outputs: usize, // The number of output ports.
 }
@@ -36,6 +37,7 @@ impl OperatorShape {
         OperatorShape {
             name,
             notify: true,
+            outstanding: false,
             peers,
             inputs: 0,
             outputs: 0,
@@ -95,6 +97,11 @@ impl<G: Scope> OperatorBuilder<G> {
         self.global
     }
 
+    /// The operator's identifier, the same at every worker and in every run.
+    pub fn stable_id(&self) -> u64 {
+        super::operator_info::stable_id(&self.address)
+    }
+
     /// Return a reference to the operator's shape
     pub fn shape(&self) -> &OperatorShape {
         &self.shape
@@ -105,6 +112,23 @@ impl<G: Scope> OperatorBuilder<G> {
         self.shape.notify = notify;
     }
 
+    /// Indicates whether the operator reads the counts of messages outstanding at its inputs.
+    pub fn set_outstanding(&mut self, outstanding: bool) {
+        self.shape.outstanding = outstanding;
+    }
+
+    /// Runs the operator only at the workers in `workers`, returning whether this worker is among them.
+    ///
+    /// The outputs of the operator start with capabilities for the instances at these workers
+    /// only, rather than for those at all workers, and instances at other workers must not hold
+    /// capabilities. The workers should be distinct, and the same at every worker.
+    pub fn set_workers(&mut self, workers: &[usize]) -> bool {
+        let peers = self.scope.peers();
+        assert!(workers.iter().all(|worker| *worker < peers), "operator assigned to workers {:?} of {}", workers, peers);
+        self.shape.peers = workers.len();
+        workers.contains(&self.scope.index())
+    }
+
     /// Adds a new input to a generic operator builder, returning the `Pull` implementor to use.
     pub fn new_input<D: Data, P>(&mut self, stream: &Stream<G, D>, pact: P) -> P::Puller
         where
@@ -176,7 +200,7 @@ impl<G: Scope> OperatorBuilder<G> {
 
     /// Information describing the operator.
     pub fn operator_info(&self) -> OperatorInfo {
-        OperatorInfo::new(self.index, self.global, &self.address[..])
+        OperatorInfo::for_worker(self.index, self.global, &self.address[..], self.scope.index())
     }
 }
 
@@ -237,4 +261,6 @@ where
     }
 
     fn notify_me(&self) -> bool { self.shape.notify }
+
+    fn observes_outstanding(&self) -> bool { self.shape.outstanding }
 }
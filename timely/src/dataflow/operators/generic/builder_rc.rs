@@ -11,6 +11,7 @@ use crate::progress::operate::SharedProgress;
 use crate::progress::frontier::{Antichain, MutableAntichain};
 
 use crate::dataflow::{Stream, Scope};
+use crate::dataflow::channels::Message;
 use crate::dataflow::channels::pushers::Tee;
 use crate::dataflow::channels::pushers::Counter as PushCounter;
 use crate::dataflow::channels::pushers::buffer::Buffer as PushBuffer;
@@ -20,6 +21,7 @@ use crate::dataflow::operators::capability::Capability;
 use crate::dataflow::operators::generic::handles::{InputHandle, new_input_handle, OutputWrapper};
 use crate::dataflow::operators::generic::operator_info::OperatorInfo;
 use crate::dataflow::operators::generic::builder_raw::OperatorShape;
+use crate::dataflow::state::Compaction;
 
 use crate::logging::TimelyLogger as Logger;
 
@@ -33,7 +35,10 @@ pub struct OperatorBuilder<G: Scope> {
     consumed: Vec<Rc<RefCell<ChangeBatch<G::Timestamp>>>>,
     internal: Rc<RefCell<Vec<Rc<RefCell<ChangeBatch<G::Timestamp>>>>>>,
     produced: Vec<Rc<RefCell<ChangeBatch<G::Timestamp>>>>,
+    outstanding: OutstandingCounts<G::Timestamp>,
+    compaction: Compaction<G::Timestamp>,
     logging: Option<Logger>,
+    active: bool,
 }
 
 impl<G: Scope> OperatorBuilder<G> {
@@ -47,7 +52,10 @@ impl<G: Scope> OperatorBuilder<G> {
             consumed: Vec::new(),
             internal: Rc::new(RefCell::new(Vec::new())),
             produced: Vec::new(),
+            outstanding: OutstandingCounts { counts: Rc::new(RefCell::new(Vec::new())) },
+            compaction: Compaction::default(),
             logging,
+            active: true,
         }
     }
 
@@ -56,6 +64,41 @@ impl<G: Scope> OperatorBuilder<G> {
         self.builder.set_notify(notify);
     }
 
+    /// Runs the operator only at the workers in `workers`, which should be distinct and the same at every worker.
+    ///
+    /// At other workers the logic constructor is not invoked and the operator holds no
+    /// capabilities, and progress tracking accounts only for the instances at `workers`. Records
+    /// must be routed only to these workers, for example by a `Subset` partitioner: records sent
+    /// to other workers are never received, and hold back the frontiers of the operator's outputs.
+    ///
+    /// # Examples
+    /// ```
+    /// use timely::dataflow::operators::ToStream;
+    /// use timely::dataflow::operators::generic::builder_rc::OperatorBuilder;
+    /// use timely::dataflow::channels::pact::Partitioned;
+    /// use timely::dataflow::channels::partitioner::{Subset, Explicit};
+    ///
+    /// timely::execute(timely::Config::process(3), |worker| {
+    ///     let index = worker.index();
+    ///     worker.dataflow::<u64,_,_>(|scope| {
+    ///         let stream = (0 .. 10u64).to_stream(scope);
+    ///         // a sink at the first worker only.
+    ///         let mut builder = OperatorBuilder::new("Singleton".to_owned(), scope.clone());
+    ///         let mut input = builder.new_input(&stream, Partitioned::new(Subset::new(vec![0], Explicit::new(|_: &u64| 0))));
+    ///         builder.set_workers(&[0]);
+    ///         builder.build(move |_capabilities| {
+    ///             assert_eq!(index, 0);
+    ///             move |_frontiers| {
+    ///                 input.for_each(|time, data| println!("{:?}: {:?}", time.time(), &data[..]));
+    ///             }
+    ///         });
+    ///     });
+    /// }).unwrap();
+    /// ```
+    pub fn set_workers(&mut self, workers: &[usize]) {
+        self.active = self.builder.set_workers(workers);
+    }
+
     /// Adds a new input to a generic operator builder, returning the `Pull` implementor to use.
     pub fn new_input<D: Data, P>(&mut self, stream: &Stream<G, D>, pact: P) -> InputHandle<G::Timestamp, D, P::Puller>
     where
@@ -79,9 +122,21 @@ impl<G: Scope> OperatorBuilder<G> {
 
         let puller = self.builder.new_input_connection(stream, pact, connection);
 
-        let input = PullCounter::new(puller);
+        #[allow(unused_mut)]
+        let mut input = PullCounter::new(puller);
+        #[cfg(feature = "fuzz")]
+        {
+            use crate::scheduling::fuzz::{Rng, Deliveries};
+            let scope = stream.scope();
+            if let Some(seed) = scope.config().fuzz {
+                let address = self.builder.operator_info().address;
+                let salt = address.iter().cloned().chain(Some(scope.index())).chain(Some(self.frontier.len())).collect::<Vec<_>>();
+                input.fuzz(Deliveries::new(Rng::new(seed, &salt), scope.activator_for(&address[..])));
+            }
+        }
         self.frontier.push(MutableAntichain::new());
         self.consumed.push(input.consumed().clone());
+        self.outstanding.counts.borrow_mut().push(ChangeBatch::new());
 
         new_input_handle(input, self.internal.clone(), self.logging.clone())
     }
@@ -104,6 +159,9 @@ impl<G: Scope> OperatorBuilder<G> {
 
         let (tee, stream) = self.builder.new_output_connection(connection);
 
+        let bytes = Message::<G::Timestamp, D>::default_length() * ::std::mem::size_of::<D>();
+        stream.scope().memory().insert_buffers(&self.builder.operator_info().address, bytes);
+
         let internal = Rc::new(RefCell::new(ChangeBatch::new()));
         self.internal.borrow_mut().push(internal.clone());
 
@@ -136,6 +194,16 @@ impl<G: Scope> OperatorBuilder<G> {
         B: FnOnce(Vec<Capability<G::Timestamp>>) -> L,
         L: FnMut(&[MutableAntichain<G::Timestamp>])->bool+'static
     {
+        if !self.active {
+            // this worker does not run the operator, and discards the progress information offered to it.
+            self.builder.build(|progress| {
+                progress.frontiers.iter_mut().for_each(|frontier| frontier.clear());
+                progress.outstanding.iter_mut().for_each(|outstanding| outstanding.clear());
+                false
+            });
+            return;
+        }
+
         // create capabilities, discard references to their creation.
         let mut capabilities = Vec::with_capacity(self.internal.borrow().len());
         for batch in self.internal.borrow().iter() {
@@ -150,18 +218,32 @@ impl<G: Scope> OperatorBuilder<G> {
         let self_consumed = self.consumed;
         let self_internal = self.internal;
         let self_produced = self.produced;
+        let self_outstanding = self.outstanding;
+        let self_compaction = self.compaction;
 
         let raw_logic =
         move |progress: &mut SharedProgress<G::Timestamp>| {
 
             // drain frontier changes
+            let mut advanced = false;
             for (progress, frontier) in progress.frontiers.iter_mut().zip(self_frontier.iter_mut()) {
-                frontier.update_iter(progress.drain());
+                advanced |= frontier.update_iter(progress.drain()).next().is_some();
+            }
+
+            // replace outstanding message counts.
+            for (progress, outstanding) in progress.outstanding.iter_mut().zip(self_outstanding.counts.borrow_mut().iter_mut()) {
+                outstanding.clear();
+                progress.drain_into(outstanding);
             }
 
             // invoke supplied logic
             let result = logic(&self_frontier[..]);
 
+            // reclaim state for times the frontiers have passed.
+            if advanced && !self_compaction.is_empty() {
+                self_compaction.compact(&self_frontier[..]);
+            }
+
             // move batches of consumed changes.
             for (progress, consumed) in progress.consumeds.iter_mut().zip(self_consumed.iter()) {
                 consumed.borrow_mut().drain_into(progress);
@@ -195,6 +277,11 @@ impl<G: Scope> OperatorBuilder<G> {
         self.builder.global()
     }
 
+    /// The operator's identifier, the same at every worker and in every run.
+    pub fn stable_id(&self) -> u64 {
+        self.builder.stable_id()
+    }
+
     /// Return a reference to the operator's shape
     pub fn shape(&self) -> &OperatorShape {
         self.builder.shape()
@@ -204,6 +291,77 @@ impl<G: Scope> OperatorBuilder<G> {
     pub fn operator_info(&self) -> OperatorInfo {
         self.builder.operator_info()
     }
+
+    /// Hooks invoked once the frontiers of the operator's inputs pass given times.
+    ///
+    /// The hooks are invoked after the operator's logic, when its input frontiers have changed.
+    pub fn compaction(&self) -> Compaction<G::Timestamp> {
+        self.compaction.clone()
+    }
+
+    /// Counts of the messages outstanding at the operator's inputs, updated when it is scheduled.
+    ///
+    /// # Examples
+    /// ```
+    /// use timely::dataflow::operators::{ToStream, Inspect};
+    /// use timely::dataflow::operators::generic::builder_rc::OperatorBuilder;
+    /// use timely::dataflow::channels::pact::Pipeline;
+    ///
+    /// timely::example(|scope| {
+    ///     let stream = (0 .. 10u64).to_stream(scope);
+    ///
+    ///     let mut builder = OperatorBuilder::new("Adaptive".to_owned(), scope.clone());
+    ///     let mut input = builder.new_input(&stream, Pipeline);
+    ///     let outstanding = builder.outstanding();
+    ///     builder.build(move |_capabilities| {
+    ///         move |_frontiers| {
+    ///             if outstanding.count(0) > 1_000_000 {
+    ///                 // for example, grow a hash table before a large backlog arrives.
+    ///             }
+    ///             input.for_each(|_time, _data| { });
+    ///         }
+    ///     });
+    /// });
+    /// ```
+    pub fn outstanding(&mut self) -> OutstandingCounts<G::Timestamp> {
+        self.builder.set_outstanding(true);
+        self.outstanding.clone()
+    }
+}
+
+/// Counts of the messages sent to each input of an operator but not yet received.
+///
+/// The counts are taken from the progress information of the operator's scope, and so include
+/// messages sent to the input at all workers, as known to this worker when the operator was last
+/// scheduled. They lag behind the messages actually in flight, and include messages the operator
+/// has received but whose receipt is not yet reported. Across workers, receipts may be known
+/// before the corresponding sends, so counts may be briefly negative.
+#[derive(Debug)]
+pub struct OutstandingCounts<T: Timestamp> {
+    counts: Rc<RefCell<Vec<ChangeBatch<T>>>>,
+}
+
+impl<T: Timestamp> Clone for OutstandingCounts<T> {
+    fn clone(&self) -> Self {
+        OutstandingCounts { counts: self.counts.clone() }
+    }
+}
+
+impl<T: Timestamp> OutstandingCounts<T> {
+    /// The number of messages outstanding at `input`, across all timestamps.
+    pub fn count(&self, input: usize) -> i64 {
+        self.counts.borrow_mut()[input].iter().map(|(_, count)| *count).sum()
+    }
+
+    /// The number of messages outstanding at `input` with timestamp `time`.
+    pub fn count_for(&self, input: usize, time: &T) -> i64 {
+        self.counts.borrow_mut()[input].iter().filter(|(t, _)| t == time).map(|(_, count)| *count).sum()
+    }
+
+    /// The timestamps with messages outstanding at `input`, and their counts.
+    pub fn counts(&self, input: usize) -> Vec<(T, i64)> {
+        self.counts.borrow_mut()[input].iter().cloned().collect()
+    }
 }
 
 
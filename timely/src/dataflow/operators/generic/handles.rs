@@ -221,6 +221,15 @@ impl<'a, T: Timestamp, D, P: Push<Bundle<T, D>>> OutputHandle<'a, T, D, P> {
         assert!(cap.valid_for_output(&self.internal_buffer), "Attempted to open output session with invalid capability");
         self.push_buffer.session(cap.time())
     }
+
+    /// Sends `bundle`, at the timestamp of capability `cap`, as one message.
+    ///
+    /// Unlike sessions, this does not copy the records of `bundle`, which may be shared.
+    pub(crate) fn give_bundle<C: CapabilityTrait<T>>(&mut self, cap: &C, bundle: Bundle<T, D>) {
+        assert!(cap.valid_for_output(self.internal_buffer), "Attempted to send bundle with invalid capability");
+        assert!(&bundle.time == cap.time(), "Attempted to send bundle at a time other than that of its capability");
+        self.push_buffer.give_bundle(bundle);
+    }
 }
 
 impl<'a, T: Timestamp, D, P: Push<Bundle<T, D>>> Drop for OutputHandle<'a, T, D, P> {
@@ -228,3 +237,50 @@ impl<'a, T: Timestamp, D, P: Push<Bundle<T, D>>> Drop for OutputHandle<'a, T, D,
         self.push_buffer.cease();
     }
 }
+
+/// Handles to an operator's named side outputs.
+///
+/// Side outputs follow the operator's output, and so the side output named `sides[i]` when the
+/// operator was constructed is output port `i + 1`, as reported by `port`.
+pub struct SideOutputs<'a, T: Timestamp, D: 'a, P: Push<Bundle<T, D>>+'a> {
+    names: &'a [String],
+    handles: Vec<OutputHandle<'a, T, D, P>>,
+}
+
+impl<'a, T: Timestamp, D, P: Push<Bundle<T, D>>> SideOutputs<'a, T, D, P> {
+    /// Creates handles to side outputs from their names and handles, in port order.
+    pub(crate) fn new(names: &'a [String], handles: Vec<OutputHandle<'a, T, D, P>>) -> Self {
+        SideOutputs {
+            names,
+            handles,
+        }
+    }
+
+    /// The names of the side outputs, in port order.
+    pub fn names(&self) -> &[String] {
+        self.names
+    }
+
+    /// The output port of the side output `name`, for use with `delayed_for_output` and
+    /// `retain_for_output`.
+    ///
+    /// # Panics
+    ///
+    /// Panics if there is no side output named `name`.
+    pub fn port(&self, name: &str) -> usize {
+        match self.names.iter().position(|side| side == name) {
+            Some(index) => index + 1,
+            None => panic!("no side output named {:?}", name),
+        }
+    }
+
+    /// The handle to the side output `name`.
+    ///
+    /// # Panics
+    ///
+    /// Panics if there is no side output named `name`.
+    pub fn get(&mut self, name: &str) -> &mut OutputHandle<'a, T, D, P> {
+        let port = self.port(name);
+        &mut self.handles[port - 1]
+    }
+}
@@ -8,8 +8,9 @@ mod handles;
 mod notificator;
 mod operator_info;
 
-pub use self::handles::{InputHandle, FrontieredInputHandle, OutputHandle, OutputWrapper};
+pub use self::handles::{InputHandle, FrontieredInputHandle, OutputHandle, OutputWrapper, SideOutputs};
 pub use self::notificator::{Notificator, FrontierNotificator};
+pub(crate) use self::notificator::{TimeStash, FrontierStash};
 
-pub use self::operator::{Operator, source};
-pub use self::operator_info::OperatorInfo;
+pub use self::operator::{Operator, source, external_source};
+pub use self::operator_info::{OperatorInfo, stable_id};
@@ -1,6 +1,7 @@
-use crate::progress::frontier::{AntichainRef, MutableAntichain};
+use crate::communication::message::RefOrMut;
+use crate::progress::frontier::{Antichain, AntichainRef, MutableAntichain};
 use crate::progress::Timestamp;
-use crate::dataflow::operators::Capability;
+use crate::dataflow::operators::{Capability, CapabilityRef};
 use crate::logging::TimelyLogger as Logger;
 
 /// Tracks requests for notification and delivers available notifications.
@@ -104,6 +105,85 @@ impl<'a, T: Timestamp> Iterator for Notificator<'a, T> {
     }
 }
 
+/// State kept for each timestamp at which an operator received records, until it is complete.
+///
+/// Operators that hold or fold the records of each timestamp until the timestamp is complete
+/// keep their state here, requesting a notification for each timestamp as they first update its
+/// state, and take the states of timestamps as the notifications are delivered.
+pub(crate) struct TimeStash<T, S> {
+    states: ::std::collections::HashMap<T, S>,
+}
+
+impl<T: Timestamp, S> TimeStash<T, S> {
+    /// An empty stash.
+    pub(crate) fn new() -> Self {
+        TimeStash { states: ::std::collections::HashMap::new() }
+    }
+
+    /// The state of the timestamp of `capability`, from `init` if it has none, with a notification requested.
+    pub(crate) fn entry<F: FnOnce()->S>(&mut self, capability: Capability<T>, notificator: &mut Notificator<T>, init: F) -> &mut S {
+        let state = self.states.entry(capability.time().clone()).or_insert_with(init);
+        notificator.notify_at(capability);
+        state
+    }
+
+    /// Passes the state of each timestamp that is complete to `logic`, with a capability for the timestamp.
+    pub(crate) fn complete<F: FnMut(&Capability<T>, S)>(&mut self, notificator: &mut Notificator<T>, mut logic: F) {
+        let states = &mut self.states;
+        notificator.for_each(|capability, _count, _notificator| {
+            if let Some(state) = states.remove(capability.time()) {
+                logic(&capability, state);
+            }
+        });
+    }
+}
+
+/// Batches of records held, with capabilities for their times, until the frontier of another input passes them.
+///
+/// Operators that answer the records of one input from the state built by another, once that state is
+/// complete through each record's time, hold the records here, and report the times of held records and
+/// of those yet to be received as the times at which the state is still to be read.
+pub(crate) struct FrontierStash<T: Timestamp, D> {
+    batches: Vec<(Capability<T>, Vec<D>)>,
+}
+
+impl<T: Timestamp, D> FrontierStash<T, D> {
+    /// An empty stash.
+    pub(crate) fn new() -> Self {
+        FrontierStash { batches: Vec::new() }
+    }
+
+    /// Holds a batch of records received at time `time`.
+    pub(crate) fn push(&mut self, time: CapabilityRef<T>, data: RefOrMut<Vec<D>>) where D: Clone {
+        let mut vector = Vec::new();
+        data.swap(&mut vector);
+        self.batches.push((time.retain(), vector));
+    }
+
+    /// Passes each held batch whose time `frontier` has passed to `logic`, and releases it.
+    pub(crate) fn release<F: FnMut(&Capability<T>, &[D])>(&mut self, frontier: &MutableAntichain<T>, mut logic: F) {
+        self.batches.retain(|(capability, data)| {
+            if frontier.less_equal(capability.time()) {
+                return true;
+            }
+            logic(capability, &data[..]);
+            false
+        });
+    }
+
+    /// A lower bound on the times of held batches, and of batches yet to be received at an input with `frontier`.
+    pub(crate) fn lower(&self, frontier: &MutableAntichain<T>) -> Antichain<T> {
+        let mut lower = Antichain::new();
+        for time in frontier.frontier().iter() {
+            lower.insert(time.clone());
+        }
+        for (capability, _) in self.batches.iter() {
+            lower.insert(capability.time().clone());
+        }
+        lower
+    }
+}
+
 #[test]
 fn notificator_delivers_notifications_in_topo_order() {
     use std::rc::Rc;
@@ -4,9 +4,12 @@
 use crate::dataflow::channels::pushers::Tee;
 use crate::dataflow::channels::pact::ParallelizationContract;
 
-use crate::dataflow::operators::generic::handles::{InputHandle, FrontieredInputHandle, OutputHandle};
+use crate::dataflow::operators::generic::handles::{InputHandle, FrontieredInputHandle, OutputHandle, OutputWrapper, SideOutputs};
 use crate::dataflow::operators::capability::Capability;
 
+use std::collections::HashMap;
+use std::task::Waker;
+
 use crate::Data;
 
 use crate::dataflow::{Stream, Scope};
@@ -64,6 +67,69 @@ pub trait Operator<G: Scope, D1: Data> {
                  &mut OutputHandle<G::Timestamp, D2, Tee<G::Timestamp, D2>>)+'static,
         P: ParallelizationContract<G::Timestamp, D1>;
 
+    /// Creates a new dataflow operator with named side outputs, in addition to its output.
+    ///
+    /// As `unary_frontier`, except that `logic` may also write to the side outputs named by
+    /// `sides`, returned as a map from name to stream. Side outputs carry records that do not
+    /// belong in the output, for example records that arrived late or could not be processed,
+    /// without multiplexing them into the output and splitting them apart downstream. The
+    /// constructor receives a capability for the output, and one for each side output in the
+    /// order of `sides`. Capabilities for a side output can be obtained from the input with
+    /// `delayed_for_output` and `retain_for_output`, with the port reported by `SideOutputs::port`.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `sides` names a side output more than once.
+    ///
+    /// # Examples
+    /// ```
+    /// use timely::dataflow::operators::{ToStream, Capture};
+    /// use timely::dataflow::operators::generic::Operator;
+    /// use timely::dataflow::operators::capture::Extract;
+    /// use timely::dataflow::channels::pact::Pipeline;
+    ///
+    /// let (numbers, errors, empty) = timely::example(|scope| {
+    ///     let (numbers, sides) = vec!["1", "x", "", "3"]
+    ///         .to_stream(scope)
+    ///         .unary_side(Pipeline, "Parse", &["errors", "empty"], |_capability, _side_capabilities, _info| {
+    ///             let mut vector = Vec::new();
+    ///             move |input, output, sides| {
+    ///                 input.for_each(|time, data| {
+    ///                     data.swap(&mut vector);
+    ///                     let mut errors = Vec::new();
+    ///                     let mut empty = Vec::new();
+    ///                     let mut numbers = output.session(&time);
+    ///                     for text in vector.drain(..) {
+    ///                         match text.parse::<u64>() {
+    ///                             Ok(number) => numbers.give(number),
+    ///                             Err(_) if text.is_empty() => empty.push(text.to_string()),
+    ///                             Err(_) => errors.push(text.to_string()),
+    ///                         }
+    ///                     }
+    ///                     let errors_time = time.delayed_for_output(time.time(), sides.port("errors"));
+    ///                     sides.get("errors").session(&errors_time).give_vec(&mut errors);
+    ///                     let empty_time = time.delayed_for_output(time.time(), sides.port("empty"));
+    ///                     sides.get("empty").session(&empty_time).give_vec(&mut empty);
+    ///                 });
+    ///             }
+    ///         });
+    ///     (numbers.capture(), sides["errors"].capture(), sides["empty"].capture())
+    /// });
+    ///
+    /// assert_eq!(numbers.extract(), vec![(0, vec![1, 3])]);
+    /// assert_eq!(errors.extract(), vec![(0, vec!["x".to_string()])]);
+    /// assert_eq!(empty.extract(), vec![(0, vec!["".to_string()])]);
+    /// ```
+    fn unary_side<D2, S, B, L, P>(&self, pact: P, name: &str, sides: &[&str], constructor: B) -> (Stream<G, D2>, HashMap<String, Stream<G, S>>)
+    where
+        D2: Data,
+        S: Data,
+        B: FnOnce(Capability<G::Timestamp>, Vec<Capability<G::Timestamp>>, OperatorInfo) -> L,
+        L: FnMut(&mut FrontieredInputHandle<G::Timestamp, D1, P::Puller>,
+                 &mut OutputHandle<G::Timestamp, D2, Tee<G::Timestamp, D2>>,
+                 &mut SideOutputs<G::Timestamp, S, Tee<G::Timestamp, S>>)+'static,
+        P: ParallelizationContract<G::Timestamp, D1>;
+
     /// Creates a new dataflow operator that partitions its input stream by a parallelization
     /// strategy `pact`, and repeatedly invokes `logic`, the function returned by the function passed as `constructor`.
     /// `logic` can read from the input stream, write to the output stream, and inspect the frontier at the input.
@@ -324,6 +390,52 @@ pub trait Operator<G: Scope, D1: Data> {
     where
         L: FnMut(&mut FrontieredInputHandle<G::Timestamp, D1, P::Puller>)+'static,
         P: ParallelizationContract<G::Timestamp, D1>;
+
+    /// Creates a new dataflow operator with two inputs and named side outputs, in addition to its output.
+    ///
+    /// As `binary_frontier`, except that `logic` may also write to the side outputs named by
+    /// `sides`, returned as a map from name to stream, as for `unary_side`.
+    ///
+    /// # Examples
+    /// ```
+    /// use timely::dataflow::operators::{ToStream, Capture};
+    /// use timely::dataflow::operators::generic::Operator;
+    /// use timely::dataflow::operators::capture::Extract;
+    /// use timely::dataflow::channels::pact::Pipeline;
+    ///
+    /// let (small, large) = timely::example(|scope| {
+    ///     let first = vec![1u64, 20].to_stream(scope);
+    ///     let second = vec![3u64, 40].to_stream(scope);
+    ///     let (small, sides) = first.binary_side(&second, Pipeline, Pipeline, "Split", &["large"], |_, _, _| {
+    ///         let mut vector = Vec::new();
+    ///         move |input1, input2, output, sides| {
+    ///             let mut split = |time: timely::dataflow::operators::CapabilityRef<u64>, data: &mut Vec<u64>| {
+    ///                 let side_time = time.delayed_for_output(time.time(), sides.port("large"));
+    ///                 output.session(&time).give_iterator(data.iter().cloned().filter(|x| *x < 10));
+    ///                 sides.get("large").session(&side_time).give_iterator(data.drain(..).filter(|x| *x >= 10));
+    ///             };
+    ///             input1.for_each(|time, data| { data.swap(&mut vector); split(time, &mut vector); });
+    ///             input2.for_each(|time, data| { data.swap(&mut vector); split(time, &mut vector); });
+    ///         }
+    ///     });
+    ///     (small.capture(), sides["large"].capture())
+    /// });
+    ///
+    /// assert_eq!(small.extract(), vec![(0, vec![1, 3])]);
+    /// assert_eq!(large.extract(), vec![(0, vec![20, 40])]);
+    /// ```
+    fn binary_side<D2, D3, S, B, L, P1, P2>(&self, other: &Stream<G, D2>, pact1: P1, pact2: P2, name: &str, sides: &[&str], constructor: B) -> (Stream<G, D3>, HashMap<String, Stream<G, S>>)
+    where
+        D2: Data,
+        D3: Data,
+        S: Data,
+        B: FnOnce(Capability<G::Timestamp>, Vec<Capability<G::Timestamp>>, OperatorInfo) -> L,
+        L: FnMut(&mut FrontieredInputHandle<G::Timestamp, D1, P1::Puller>,
+                 &mut FrontieredInputHandle<G::Timestamp, D2, P2::Puller>,
+                 &mut OutputHandle<G::Timestamp, D3, Tee<G::Timestamp, D3>>,
+                 &mut SideOutputs<G::Timestamp, S, Tee<G::Timestamp, S>>)+'static,
+        P1: ParallelizationContract<G::Timestamp, D1>,
+        P2: ParallelizationContract<G::Timestamp, D2>;
 }
 
 impl<G: Scope, D1: Data> Operator<G, D1> for Stream<G, D1> {
@@ -356,6 +468,40 @@ impl<G: Scope, D1: Data> Operator<G, D1> for Stream<G, D1> {
         stream
     }
 
+    fn unary_side<D2, S, B, L, P>(&self, pact: P, name: &str, sides: &[&str], constructor: B) -> (Stream<G, D2>, HashMap<String, Stream<G, S>>)
+    where
+        D2: Data,
+        S: Data,
+        B: FnOnce(Capability<G::Timestamp>, Vec<Capability<G::Timestamp>>, OperatorInfo) -> L,
+        L: FnMut(&mut FrontieredInputHandle<G::Timestamp, D1, P::Puller>,
+                 &mut OutputHandle<G::Timestamp, D2, Tee<G::Timestamp, D2>>,
+                 &mut SideOutputs<G::Timestamp, S, Tee<G::Timestamp, S>>)+'static,
+        P: ParallelizationContract<G::Timestamp, D1> {
+
+        let mut builder = OperatorBuilder::new(name.to_owned(), self.scope());
+        let operator_info = builder.operator_info();
+
+        let mut input = builder.new_input(self, pact);
+        let (mut output, stream) = builder.new_output();
+        let names = sides.iter().map(|side| side.to_string()).collect::<Vec<_>>();
+        let (mut side_outputs, side_streams) = new_side_outputs(&mut builder, sides);
+
+        builder.build(move |mut capabilities| {
+            // `capabilities` should hold one capability for the output, then one for each side output.
+            let side_capabilities = capabilities.split_off(1);
+            let capability = capabilities.pop().unwrap();
+            let mut logic = constructor(capability, side_capabilities, operator_info);
+            move |frontiers| {
+                let mut input_handle = FrontieredInputHandle::new(&mut input, &frontiers[0]);
+                let mut output_handle = output.activate();
+                let mut side_handles = SideOutputs::new(&names, side_outputs.iter_mut().map(|side| side.activate()).collect());
+                logic(&mut input_handle, &mut output_handle, &mut side_handles);
+            }
+        });
+
+        (stream, side_streams)
+    }
+
     fn unary_notify<D2: Data,
             L: FnMut(&mut InputHandle<G::Timestamp, D1, P::Puller>,
                      &mut OutputHandle<G::Timestamp, D2, Tee<G::Timestamp, D2>>,
@@ -513,6 +659,58 @@ impl<G: Scope, D1: Data> Operator<G, D1> for Stream<G, D1> {
             }
         });
     }
+
+    fn binary_side<D2, D3, S, B, L, P1, P2>(&self, other: &Stream<G, D2>, pact1: P1, pact2: P2, name: &str, sides: &[&str], constructor: B) -> (Stream<G, D3>, HashMap<String, Stream<G, S>>)
+    where
+        D2: Data,
+        D3: Data,
+        S: Data,
+        B: FnOnce(Capability<G::Timestamp>, Vec<Capability<G::Timestamp>>, OperatorInfo) -> L,
+        L: FnMut(&mut FrontieredInputHandle<G::Timestamp, D1, P1::Puller>,
+                 &mut FrontieredInputHandle<G::Timestamp, D2, P2::Puller>,
+                 &mut OutputHandle<G::Timestamp, D3, Tee<G::Timestamp, D3>>,
+                 &mut SideOutputs<G::Timestamp, S, Tee<G::Timestamp, S>>)+'static,
+        P1: ParallelizationContract<G::Timestamp, D1>,
+        P2: ParallelizationContract<G::Timestamp, D2> {
+
+        let mut builder = OperatorBuilder::new(name.to_owned(), self.scope());
+        let operator_info = builder.operator_info();
+
+        let mut input1 = builder.new_input(self, pact1);
+        let mut input2 = builder.new_input(other, pact2);
+        let (mut output, stream) = builder.new_output();
+        let names = sides.iter().map(|side| side.to_string()).collect::<Vec<_>>();
+        let (mut side_outputs, side_streams) = new_side_outputs(&mut builder, sides);
+
+        builder.build(move |mut capabilities| {
+            // `capabilities` should hold one capability for the output, then one for each side output.
+            let side_capabilities = capabilities.split_off(1);
+            let capability = capabilities.pop().unwrap();
+            let mut logic = constructor(capability, side_capabilities, operator_info);
+            move |frontiers| {
+                let mut input1_handle = FrontieredInputHandle::new(&mut input1, &frontiers[0]);
+                let mut input2_handle = FrontieredInputHandle::new(&mut input2, &frontiers[1]);
+                let mut output_handle = output.activate();
+                let mut side_handles = SideOutputs::new(&names, side_outputs.iter_mut().map(|side| side.activate()).collect());
+                logic(&mut input1_handle, &mut input2_handle, &mut output_handle, &mut side_handles);
+            }
+        });
+
+        (stream, side_streams)
+    }
+}
+
+/// Adds an output to `builder` for each side output in `sides`, in order, returning their output
+/// wrappers and their streams by name.
+fn new_side_outputs<G: Scope, S: Data>(builder: &mut OperatorBuilder<G>, sides: &[&str]) -> (Vec<OutputWrapper<G::Timestamp, S, Tee<G::Timestamp, S>>>, HashMap<String, Stream<G, S>>) {
+    let mut outputs = Vec::new();
+    let mut streams = HashMap::new();
+    for side in sides.iter() {
+        let (output, stream) = builder.new_output();
+        assert!(streams.insert(side.to_string(), stream).is_none(), "side output {:?} named more than once", side);
+        outputs.push(output);
+    }
+    (outputs, streams)
 }
 
 /// Creates a new data stream source for a scope.
@@ -580,6 +778,61 @@ where
     stream
 }
 
+/// Creates a new data stream source for a scope, driven by external events.
+///
+/// As `source`, except that the constructor also receives a `Waker` that schedules the operator.
+/// The operator is scheduled once when the dataflow is built, and subsequently only when it is
+/// woken or otherwise activated, rather than on each step of the worker. The waker may be sent
+/// to other threads, and woken when data for the source arrives from a channel, a socket, a
+/// file, or any other external event; waking it also unparks a parked worker.
+///
+/// # Examples
+/// ```
+/// use std::sync::mpsc;
+/// use timely::dataflow::operators::{Capture, capture::Extract};
+/// use timely::dataflow::operators::generic::operator::external_source;
+///
+/// let captured = timely::example(|scope| {
+///     external_source(scope, "External", |capability, _info, waker| {
+///
+///         // an external thread produces data, and wakes the source when it has.
+///         let (senders, receiver) = mpsc::channel();
+///         std::thread::spawn(move || {
+///             for round in 0 .. 3u64 {
+///                 senders.send(round).unwrap();
+///                 waker.wake_by_ref();
+///             }
+///         });
+///
+///         let mut cap = Some(capability);
+///         let mut received = 0;
+///         move |output| {
+///             if let Some(cap) = cap.as_ref() {
+///                 while let Ok(round) = receiver.try_recv() {
+///                     output.session(cap).give(round);
+///                     received += 1;
+///                 }
+///             }
+///             if received == 3 { cap = None; }
+///         }
+///     })
+///     .capture()
+/// });
+///
+/// assert_eq!(captured.extract(), vec![(0, vec![0, 1, 2])]);
+/// ```
+pub fn external_source<G: Scope, D, B, L>(scope: &G, name: &str, constructor: B) -> Stream<G, D>
+where
+    D: Data,
+    B: FnOnce(Capability<G::Timestamp>, OperatorInfo, Waker) -> L,
+    L: FnMut(&mut OutputHandle<G::Timestamp, D, Tee<G::Timestamp, D>>)+'static {
+
+    source(scope, name, |capability, info| {
+        let waker = scope.waker_for(&info.address[..]);
+        constructor(capability, info, waker)
+    })
+}
+
 /// Constructs an empty stream.
 ///
 /// This method is useful in patterns where an input is required, but there is no
@@ -1,3 +1,4 @@
+use crate::hash::mix;
 
 /// Information about the operator being constructed
 #[derive(Clone)]
@@ -5,18 +6,120 @@ pub struct OperatorInfo {
     /// Scope-local index assigned to the operator being constructed.
     pub local_id: usize,
     /// Worker-unique identifier.
+    ///
+    /// The identifier is allocated from a counter the worker shares with its channels and
+    /// dataflows, and changes as channels are added to the dataflow; see `stable_id`.
     pub global_id: usize,
     /// Operator address.
     pub address: Vec<usize>,
+    /// Index of the worker constructing the operator.
+    worker: usize,
 }
 
 impl OperatorInfo {
     /// Construct a new `OperatorInfo`.
+    ///
+    /// The operator is attributed to worker zero; see `for_worker`.
     pub fn new(local_id: usize, global_id: usize, address: &[usize]) -> OperatorInfo {
+        OperatorInfo::for_worker(local_id, global_id, address, 0)
+    }
+
+    /// Construct a new `OperatorInfo` for an operator constructed by worker `worker`.
+    pub fn for_worker(local_id: usize, global_id: usize, address: &[usize], worker: usize) -> OperatorInfo {
         OperatorInfo {
             local_id,
             global_id,
             address: address.to_vec(),
+            worker,
         }
     }
+
+    /// An identifier derived from the operator's address, the same at every worker and in every run.
+    ///
+    /// The address reflects only the order in which dataflows, scopes, and operators are
+    /// constructed, and not the identifiers allocated to channels, so the identifier of an
+    /// operator is unchanged when channels are added to or removed from the dataflow, where its
+    /// `global_id` would change. This makes it suitable for matching operators across workers
+    /// and runs, for example when joining their metrics or restoring their checkpointed state.
+    /// Distinct addresses have distinct identifiers with overwhelming probability.
+    ///
+    /// # Examples
+    /// ```
+    /// use std::sync::{Arc, Mutex};
+    /// use timely::dataflow::operators::{ToStream, Concat, Map};
+    /// use timely::dataflow::operators::generic::operator::Operator;
+    /// use timely::dataflow::channels::pact::Pipeline;
+    ///
+    /// // the identifiers of an operator, following an operator with one or two inputs.
+    /// let identify = |merge: bool| {
+    ///     let ids = Arc::new(Mutex::new((0, 0)));
+    ///     let shared = ids.clone();
+    ///     timely::example(move |scope| {
+    ///         let stream = (0 .. 10u64).to_stream(scope);
+    ///         let stream = if merge { stream.concat(&stream) } else { stream.map(|x| x) };
+    ///         stream.unary(Pipeline, "Identified", move |_capability, info| {
+    ///             *shared.lock().unwrap() = (info.global_id, info.stable_id());
+    ///             move |input, output| {
+    ///                 input.for_each(|time, data| output.session(&time).give_vec(&mut data.replace(Vec::new())));
+    ///             }
+    ///         });
+    ///     });
+    ///     let ids = *ids.lock().unwrap();
+    ///     ids
+    /// };
+    ///
+    /// let (mapped, merged) = (identify(false), identify(true));
+    /// assert_ne!(mapped.0, merged.0);
+    /// assert_eq!(mapped.1, merged.1);
+    /// ```
+    pub fn stable_id(&self) -> u64 {
+        stable_id(&self.address)
+    }
+
+    /// A seed derived from the operator's address, the same at every worker and in every run.
+    ///
+    /// Operators that must make the same pseudo-random choices at each worker, for example the
+    /// hash functions of a sketch whose partial results are merged, can seed a generator with it.
+    pub fn shared_seed(&self) -> u64 {
+        mix(self.stable_id())
+    }
+
+    /// A seed derived from the operator's address and the worker's index, the same in every run.
+    ///
+    /// Operators that make independent pseudo-random choices at each worker, for example when
+    /// sampling their inputs, can seed a generator with it to be reproducible across runs.
+    ///
+    /// # Examples
+    /// ```
+    /// use timely::dataflow::operators::ToStream;
+    /// use timely::dataflow::operators::generic::operator::Operator;
+    /// use timely::dataflow::channels::pact::Pipeline;
+    ///
+    /// timely::example(|scope| {
+    ///     (0 .. 10u64)
+    ///         .to_stream(scope)
+    ///         .unary(Pipeline, "Sample", |_capability, info| {
+    ///             assert_ne!(info.seed(), info.shared_seed());
+    ///             let mut state = info.seed();
+    ///             move |input, output| {
+    ///                 input.for_each(|time, data| {
+    ///                     let mut session = output.session(&time);
+    ///                     for datum in data.iter() {
+    ///                         // a linear congruential generator, for illustration.
+    ///                         state = state.wrapping_mul(6364136223846793005).wrapping_add(1);
+    ///                         if state >> 63 == 0 { session.give(*datum); }
+    ///                     }
+    ///                 });
+    ///             }
+    ///         });
+    /// });
+    /// ```
+    pub fn seed(&self) -> u64 {
+        mix(self.shared_seed() ^ self.worker as u64)
+    }
+}
+
+/// The identifier of the operator at `address`, the same at every worker and in every run.
+pub fn stable_id(address: &[usize]) -> u64 {
+    address.iter().fold(0x1D, |id, index| mix(id ^ *index as u64))
 }
@@ -0,0 +1,54 @@
+//! Iterates a computation on a stream within a nested scope.
+
+use crate::Data;
+use crate::dataflow::{Stream, Scope};
+use crate::dataflow::scopes::child::Iterative;
+use crate::dataflow::operators::{Enter, Leave, Concat, Branch, LoopVariable, ConnectLoop};
+
+/// Iterates a computation on the records of a stream.
+pub trait Iterate<G: Scope, D: Data> {
+    /// Repeatedly applies `logic` to each record until it converges or reaches `limit` iterations.
+    ///
+    /// The records of the stream enter an iterative scope, whose timestamps extend those of the
+    /// stream with an iteration counter. Each record produced by `logic` for which `converged`
+    /// returns false is fed back to `logic` in the next iteration, and all other records leave
+    /// the scope. Records produced in iteration `limit - 1` leave the scope regardless of
+    /// `converged`, so that `logic` is applied at most `limit` times to each record.
+    ///
+    /// # Examples
+    /// ```
+    /// use timely::dataflow::operators::{ToStream, Map, Capture, Iterate};
+    /// use timely::dataflow::operators::capture::Extract;
+    ///
+    /// let captured = timely::example(|scope| {
+    ///     (1 .. 10u64)
+    ///         .to_stream(scope)
+    ///         .iterate(100, |x| *x == 1, |inner| inner.map(|x| if x % 2 == 0 { x / 2 } else { 3 * x + 1 }))
+    ///         .capture()
+    /// });
+    ///
+    /// assert_eq!(captured.extract(), vec![(0, vec![1; 9])]);
+    /// ```
+    fn iterate<C, L>(&self, limit: u64, converged: C, logic: L) -> Stream<G, D>
+    where
+        C: Fn(&D)->bool+'static,
+        L: for<'a> FnOnce(&Stream<Iterative<'a, G, u64>, D>)->Stream<Iterative<'a, G, u64>, D>;
+}
+
+impl<G: Scope, D: Data> Iterate<G, D> for Stream<G, D> {
+    fn iterate<C, L>(&self, limit: u64, converged: C, logic: L) -> Stream<G, D>
+    where
+        C: Fn(&D)->bool+'static,
+        L: for<'a> FnOnce(&Stream<Iterative<'a, G, u64>, D>)->Stream<Iterative<'a, G, u64>, D>,
+    {
+        assert!(limit > 0, "iteration limit must be positive");
+        let mut scope = self.scope();
+        scope.iterative::<u64, _, _>(|inner| {
+            let (handle, cycle) = inner.loop_variable(1);
+            let (done, again) = logic(&self.enter(inner).concat(&cycle))
+                .branch(move |time, datum| time.inner + 1 < limit && !converged(datum));
+            again.connect_loop(handle);
+            done.leave()
+        })
+    }
+}
@@ -0,0 +1,228 @@
+//! Keyed streams, whose partitioning by key is recorded in their type.
+//!
+//! Keyed operators such as `aggregate`, `state_machine`, and `arrange_by_key` exchange their
+//! inputs so that all records with the same key are at the same worker. When several of these
+//! operators are applied to the same stream, or to streams derived from it without changing
+//! keys, each exchange after the first moves no records between workers but still serializes
+//! and routes them. A `KeyedStream` is partitioned once, by `key_by`, and its keyed operators
+//! read it in place.
+
+use std::hash::Hash;
+
+use crate::{Data, ExchangeData};
+use crate::dataflow::{Stream, Scope};
+use crate::dataflow::channels::pact::Pipeline;
+use crate::dataflow::channels::partitioner::hash;
+use crate::dataflow::operators::{Exchange, Map};
+use crate::dataflow::operators::aggregation::aggregate::aggregate;
+use crate::dataflow::operators::aggregation::state_machine::state_machine;
+use crate::dataflow::operators::arrange::{arrange, Arranged};
+use crate::dataflow::operators::cogroup::{cogroup, join_values, left_join_values, right_join_values, full_outer_join_values};
+
+/// A stream of `(key, value)` pairs, partitioned among workers by the hash of the key.
+///
+/// The records of each key are all at the worker indicated by `partitioner::hash` of the key,
+/// modulo the number of workers, which is how the built-in keyed operators route them.
+#[derive(Clone)]
+pub struct KeyedStream<G: Scope, K, V> {
+    stream: Stream<G, (K, V)>,
+}
+
+impl<G: Scope, K: ExchangeData+Hash+Eq, V: ExchangeData> KeyedStream<G, K, V> {
+    /// The partitioned records, as a stream.
+    pub fn stream(&self) -> &Stream<G, (K, V)> {
+        &self.stream
+    }
+
+    /// Transforms the value of each record, retaining its key and so the partitioning.
+    ///
+    /// # Examples
+    /// ```
+    /// use timely::dataflow::operators::{ToStream, Capture};
+    /// use timely::dataflow::operators::keyed::KeyBy;
+    /// use timely::dataflow::operators::capture::Extract;
+    ///
+    /// let captured = timely::example(|scope| {
+    ///     vec![(0u64, 1u64), (1, 2)]
+    ///         .to_stream(scope)
+    ///         .key_by()
+    ///         .map_values(|value| value * 10)
+    ///         .stream()
+    ///         .capture()
+    /// });
+    ///
+    /// assert_eq!(captured.extract(), vec![(0, vec![(0, 10), (1, 20)])]);
+    /// ```
+    pub fn map_values<V2: ExchangeData, L: FnMut(V)->V2+'static>(&self, mut logic: L) -> KeyedStream<G, K, V2> {
+        KeyedStream { stream: self.stream.map(move |(key, value)| (key, logic(value))) }
+    }
+
+    /// Aggregates the values of each key within each timestamp, without exchanging records.
+    ///
+    /// As `Aggregate::aggregate`, with the keys already partitioned.
+    ///
+    /// # Examples
+    /// ```
+    /// use timely::dataflow::operators::{ToStream, Capture};
+    /// use timely::dataflow::operators::keyed::KeyBy;
+    /// use timely::dataflow::operators::capture::Extract;
+    ///
+    /// let captured = timely::example(|scope| {
+    ///     (0 .. 10u64)
+    ///         .map(|x| (x % 2, x))
+    ///         .to_stream(scope)
+    ///         .key_by()
+    ///         .aggregate(|_key, val, agg: &mut u64| *agg += val, |key, agg| (key, agg))
+    ///         .capture()
+    /// });
+    ///
+    /// let mut result = captured.extract();
+    /// result[0].1.sort();
+    /// assert_eq!(result, vec![(0, vec![(0, 20), (1, 25)])]);
+    /// ```
+    pub fn aggregate<R, D, F, E>(&self, fold: F, emit: E) -> Stream<G, R>
+    where
+        G::Timestamp: Eq,
+        R: Data,
+        D: Default+'static,
+        F: Fn(&K, V, &mut D)+'static,
+        E: Fn(K, D)->R+'static,
+    {
+        aggregate(&self.stream, Pipeline, fold, emit)
+    }
+
+    /// Tracks a state for each key, without exchanging records.
+    ///
+    /// As `StateMachine::state_machine`, with the keys already partitioned.
+    ///
+    /// # Examples
+    /// ```
+    /// use timely::dataflow::operators::{ToStream, Capture};
+    /// use timely::dataflow::operators::keyed::KeyBy;
+    /// use timely::dataflow::operators::capture::Extract;
+    ///
+    /// let captured = timely::example(|scope| {
+    ///     vec![(0u64, 1u64), (0, 2), (0, 3)]
+    ///         .to_stream(scope)
+    ///         .key_by()
+    ///         .state_machine(|_key, val, total: &mut u64| { *total += val; (false, Some(*total)) })
+    ///         .capture()
+    /// });
+    ///
+    /// assert_eq!(captured.extract(), vec![(0, vec![1, 3, 6])]);
+    /// ```
+    pub fn state_machine<R, D, I, F>(&self, fold: F) -> Stream<G, R>
+    where
+        G::Timestamp: Hash+Eq,
+        R: Data,
+        D: Default+'static,
+        I: IntoIterator<Item=R>,
+        F: Fn(&K, V, &mut D)->(bool, I)+'static,
+    {
+        state_machine(&self.stream, Pipeline, fold)
+    }
+
+    /// Arranges the records into a shared index, without exchanging records.
+    ///
+    /// As `ArrangeByKey::arrange_by_key`, with the keys already partitioned.
+    ///
+    /// # Examples
+    /// ```
+    /// use timely::dataflow::operators::{ToStream, Capture};
+    /// use timely::dataflow::operators::keyed::KeyBy;
+    /// use timely::dataflow::operators::capture::Extract;
+    ///
+    /// let captured = timely::example(|scope| {
+    ///     let people = vec![(1u64, "alice".to_string())].to_stream(scope).key_by();
+    ///     let ages = vec![(1u64, 30u32)].to_stream(scope).key_by();
+    ///     people.arrange().lookup_keyed(&ages).capture()
+    /// });
+    ///
+    /// assert_eq!(captured.extract(), vec![(0, vec![(1, "alice".to_string(), 30)])]);
+    /// ```
+    pub fn arrange(&self) -> Arranged<G, K, V> where V: Hash+Eq {
+        arrange(&self.stream, Pipeline)
+    }
+
+    /// Groups the values of each key with those of `other`, without exchanging records.
+    ///
+    /// As `CoGroup::cogroup`, with the keys already partitioned.
+    ///
+    /// # Examples
+    /// ```
+    /// use timely::dataflow::operators::{ToStream, Capture};
+    /// use timely::dataflow::operators::keyed::KeyBy;
+    /// use timely::dataflow::operators::capture::Extract;
+    ///
+    /// let captured = timely::example(|scope| {
+    ///     let left = vec![(1u64, 'a'), (1, 'b')].to_stream(scope).key_by();
+    ///     let right = vec![(1u64, 'c')].to_stream(scope).key_by();
+    ///     left.cogroup(&right, |key, left, right| Some((*key, left.len(), right.len()))).capture()
+    /// });
+    ///
+    /// assert_eq!(captured.extract(), vec![(0, vec![(1, 2, 1)])]);
+    /// ```
+    pub fn cogroup<V2, R, I, L>(&self, other: &KeyedStream<G, K, V2>, logic: L) -> Stream<G, R>
+    where
+        V2: ExchangeData,
+        R: Data,
+        I: IntoIterator<Item=R>,
+        L: FnMut(&K, Vec<V>, Vec<V2>)->I+'static,
+    {
+        cogroup(&self.stream, &other.stream, Pipeline, Pipeline, logic)
+    }
+
+    /// Pairs the values of each key with those of `other`, without exchanging records.
+    ///
+    /// As `CoGroup::join`, with the keys already partitioned.
+    pub fn join<V2: ExchangeData>(&self, other: &KeyedStream<G, K, V2>) -> Stream<G, (K, V, V2)> {
+        self.cogroup(other, join_values)
+    }
+
+    /// As `join`, with `None` for the values of keys absent from `other`.
+    ///
+    /// As `CoGroup::left_join`, with the keys already partitioned.
+    pub fn left_join<V2: ExchangeData>(&self, other: &KeyedStream<G, K, V2>) -> Stream<G, (K, V, Option<V2>)> {
+        self.cogroup(other, left_join_values)
+    }
+
+    /// As `join`, with `None` for the values of keys absent from `self`.
+    ///
+    /// As `CoGroup::right_join`, with the keys already partitioned.
+    pub fn right_join<V2: ExchangeData>(&self, other: &KeyedStream<G, K, V2>) -> Stream<G, (K, Option<V>, V2)> {
+        self.cogroup(other, right_join_values)
+    }
+
+    /// As `join`, with `None` for the values of keys absent from either input.
+    ///
+    /// As `CoGroup::full_outer_join`, with the keys already partitioned.
+    pub fn full_outer_join<V2: ExchangeData>(&self, other: &KeyedStream<G, K, V2>) -> Stream<G, (K, Option<V>, Option<V2>)> {
+        self.cogroup(other, full_outer_join_values)
+    }
+}
+
+/// Partitions a stream of `(key, value)` pairs by key.
+pub trait KeyBy<G: Scope, K: ExchangeData+Hash+Eq, V: ExchangeData> {
+    /// Exchanges each record to the worker indicated by the hash of its key.
+    ///
+    /// # Examples
+    /// ```
+    /// use timely::dataflow::operators::{ToStream, Inspect};
+    /// use timely::dataflow::operators::keyed::KeyBy;
+    ///
+    /// timely::execute(timely::Config::process(2), |worker| {
+    ///     worker.dataflow::<u64,_,_>(|scope| {
+    ///         let keyed = (0 .. 10u64).map(|x| (x % 3, x)).to_stream(scope).key_by();
+    ///         keyed.aggregate(|_key, _val, count: &mut u64| *count += 1, |key, count| (key, count))
+    ///              .inspect(|&(key, count)| assert_eq!(count, if key == 0 { 8 } else { 6 }));
+    ///     });
+    /// }).unwrap();
+    /// ```
+    fn key_by(&self) -> KeyedStream<G, K, V>;
+}
+
+impl<G: Scope, K: ExchangeData+Hash+Eq, V: ExchangeData> KeyBy<G, K, V> for Stream<G, (K, V)> {
+    fn key_by(&self) -> KeyedStream<G, K, V> {
+        KeyedStream { stream: self.exchange(|(key, _)| hash(key)) }
+    }
+}
@@ -0,0 +1,224 @@
+//! End-to-end latency of the timestamps of a dataflow, measured without altering its records.
+//!
+//! A `LatencyTracker` records the instant each timestamp was first seen near a source, either
+//! stamped by the driver as it introduces input or by a `stamp_latency` operator. Further along
+//! the dataflow, a `measure_latency` operator records the latency of each batch it receives since
+//! the origin of its timestamp, and the latency of the completion of each timestamp. The origins
+//! are carried by the tracker rather than by the records, and so a tracker measures the latency
+//! within one worker. The tracker holds the origins of open timestamps only, and a bounded number
+//! of the latencies of completed timestamps.
+
+use std::cell::RefCell;
+use std::collections::{HashMap, VecDeque};
+use std::rc::Rc;
+use std::time::Duration;
+use crate::logging_core::time::Instant;
+
+use crate::Data;
+use crate::dataflow::{Stream, Scope};
+use crate::dataflow::channels::pact::Pipeline;
+use crate::dataflow::operators::generic::operator::Operator;
+use crate::progress::Timestamp;
+
+/// Counts of durations, in buckets of powers of two microseconds.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Histogram {
+    // bucket `i` counts durations of less than `2^i` microseconds, and at least half that.
+    buckets: Vec<u64>,
+}
+
+impl Histogram {
+    /// Counts `duration`.
+    pub fn record(&mut self, duration: Duration) {
+        let micros = duration.as_micros().min(u64::MAX as u128) as u64;
+        let bucket = (64 - micros.leading_zeros()) as usize;
+        if self.buckets.len() <= bucket {
+            self.buckets.resize(bucket + 1, 0);
+        }
+        self.buckets[bucket] += 1;
+    }
+
+    /// The number of durations counted.
+    pub fn count(&self) -> u64 {
+        self.buckets.iter().sum()
+    }
+
+    /// The counts of each bucket, where bucket `i` counts durations of less than `2^i`
+    /// microseconds and at least half that.
+    pub fn buckets(&self) -> &[u64] {
+        &self.buckets
+    }
+
+    /// An upper bound on the duration of rank `quantile` times the number of durations, for
+    /// `quantile` between zero and one, or `None` if no durations were counted.
+    pub fn quantile(&self, quantile: f64) -> Option<Duration> {
+        let target = (quantile.clamp(0.0, 1.0) * self.count() as f64).ceil().max(1.0) as u64;
+        let mut rank = 0;
+        for (bucket, count) in self.buckets.iter().enumerate() {
+            rank += count;
+            if rank >= target {
+                return Some(Duration::from_micros(1 << bucket));
+            }
+        }
+        None
+    }
+}
+
+/// The latencies measured for one timestamp.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct EpochLatency {
+    /// The latency of each batch received, from the origin of the timestamp.
+    pub batches: Histogram,
+    /// The latency of the completion of the timestamp, from its origin.
+    pub completed: Option<Duration>,
+}
+
+/// The number of completed timestamps whose latencies a tracker retains by default.
+const COMPLETED_CAPACITY: usize = 1024;
+
+/// The origins of timestamps, and the latencies measured for them.
+///
+/// Handles are cheaply cloned, and clones share the same origins and latencies. Timestamps are
+/// evicted from the open timestamps once complete, and their latencies retained until taken with
+/// `take_completed`, discarding the oldest beyond the tracker's capacity.
+///
+/// # Examples
+/// ```
+/// use timely::dataflow::InputHandle;
+/// use timely::dataflow::operators::{Input, Map, Probe};
+/// use timely::dataflow::operators::latency::{LatencyTracker, TrackLatency};
+///
+/// timely::execute_directly(|worker| {
+///
+///     let tracker = LatencyTracker::new();
+///     let mut input = InputHandle::new();
+///     let probe = worker.dataflow::<u64,_,_>(|scope| {
+///         scope.input_from(&mut input)
+///              .stamp_latency(&tracker)
+///              .map(|x: u64| x + 1)
+///              .measure_latency(&tracker)
+///              .probe()
+///     });
+///
+///     for round in 0 .. 3 {
+///         input.send(round);
+///         input.advance_to(round + 1);
+///         worker.step_while(|| probe.less_than(input.time()));
+///     }
+///
+///     let completed = tracker.take_completed();
+///     assert_eq!(completed.len(), 3);
+///     for (_time, latency) in completed {
+///         assert_eq!(latency.batches.count(), 1);
+///         assert!(latency.completed.is_some());
+///     }
+/// });
+/// ```
+pub struct LatencyTracker<T: Timestamp> {
+    epochs: Rc<RefCell<Epochs<T>>>,
+}
+
+/// The open timestamps with their origins, and the latencies of completed timestamps.
+struct Epochs<T> {
+    open: HashMap<T, (Instant, EpochLatency)>,
+    completed: VecDeque<(T, EpochLatency)>,
+    capacity: usize,
+}
+
+impl<T: Timestamp> Clone for LatencyTracker<T> {
+    fn clone(&self) -> Self {
+        LatencyTracker { epochs: self.epochs.clone() }
+    }
+}
+
+impl<T: Timestamp> Default for LatencyTracker<T> {
+    fn default() -> Self {
+        Self::with_capacity(COMPLETED_CAPACITY)
+    }
+}
+
+impl<T: Timestamp> LatencyTracker<T> {
+    /// A tracker with no origins.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// A tracker with no origins, retaining the latencies of at most `capacity` completed timestamps.
+    pub fn with_capacity(capacity: usize) -> Self {
+        let epochs = Epochs { open: HashMap::new(), completed: VecDeque::new(), capacity };
+        LatencyTracker { epochs: Rc::new(RefCell::new(epochs)) }
+    }
+
+    /// Records the present as the origin of `time`, unless it already has an origin.
+    pub fn stamp(&self, time: T) {
+        self.epochs.borrow_mut().open.entry(time).or_insert_with(|| (Instant::now(), EpochLatency::default()));
+    }
+
+    /// The latencies measured so far for `time`, if it is open or its latencies are retained.
+    pub fn latency(&self, time: &T) -> Option<EpochLatency> {
+        let epochs = self.epochs.borrow();
+        match epochs.open.get(time) {
+            Some((_, latency)) => Some(latency.clone()),
+            None => epochs.completed.iter().find(|(completed, _)| completed == time).map(|(_, latency)| latency.clone()),
+        }
+    }
+
+    /// Removes and returns the latencies of the completed timestamps, in order of completion.
+    pub fn take_completed(&self) -> Vec<(T, EpochLatency)> {
+        self.epochs.borrow_mut().completed.drain(..).collect()
+    }
+}
+
+/// Operators that stamp and measure the latency of timestamps.
+pub trait TrackLatency<G: Scope, D: Data> {
+    /// Passes the stream through unchanged, recording the arrival of the first batch of each
+    /// timestamp as its origin.
+    fn stamp_latency(&self, tracker: &LatencyTracker<G::Timestamp>) -> Stream<G, D>;
+
+    /// Passes the stream through unchanged, recording the latency of each batch, and of the
+    /// completion of each timestamp, from the timestamp's origin.
+    ///
+    /// Timestamps without an origin are not measured.
+    fn measure_latency(&self, tracker: &LatencyTracker<G::Timestamp>) -> Stream<G, D>;
+}
+
+impl<G: Scope, D: Data> TrackLatency<G, D> for Stream<G, D> {
+    fn stamp_latency(&self, tracker: &LatencyTracker<G::Timestamp>) -> Stream<G, D> {
+        let tracker = tracker.clone();
+        let mut vector = Vec::new();
+        self.unary(Pipeline, "StampLatency", move |_capability, _info| move |input, output| {
+            input.for_each(|time, data| {
+                tracker.stamp(time.time().clone());
+                data.swap(&mut vector);
+                output.session(&time).give_vec(&mut vector);
+            });
+        })
+    }
+
+    fn measure_latency(&self, tracker: &LatencyTracker<G::Timestamp>) -> Stream<G, D> {
+        let tracker = tracker.clone();
+        let mut vector = Vec::new();
+        self.unary_frontier(Pipeline, "MeasureLatency", move |_capability, _info| move |input, output| {
+            let now = Instant::now();
+            input.for_each(|time, data| {
+                if let Some((origin, latency)) = tracker.epochs.borrow_mut().open.get_mut(time.time()) {
+                    latency.batches.record(now.duration_since(*origin));
+                }
+                data.swap(&mut vector);
+                output.session(&time).give_vec(&mut vector);
+            });
+            // evict the completed timestamps, retaining their latencies.
+            let frontier = input.frontier();
+            let mut epochs = tracker.epochs.borrow_mut();
+            let closed = epochs.open.keys().filter(|time| !frontier.less_equal(time)).cloned().collect::<Vec<_>>();
+            for time in closed {
+                let (origin, mut latency) = epochs.open.remove(&time).expect("open time present");
+                latency.completed = Some(now.duration_since(origin));
+                epochs.completed.push_back((time, latency));
+                if epochs.completed.len() > epochs.capacity {
+                    epochs.completed.pop_front();
+                }
+            }
+        })
+    }
+}
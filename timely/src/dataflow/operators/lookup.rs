@@ -0,0 +1,134 @@
+//! Enriches the records of a stream from a static table, without exchanging them.
+
+use std::collections::HashMap;
+use std::hash::Hash;
+use std::rc::Rc;
+
+use crate::{Data, ExchangeData};
+use crate::dataflow::{Stream, Scope};
+use crate::dataflow::channels::pact::Pipeline;
+use crate::dataflow::operators::{Broadcast, Map};
+use crate::dataflow::operators::generic::operator::Operator;
+
+/// Enriches the records of a stream from a static table.
+pub trait Lookup<G: Scope, D: Data> {
+    /// Pairs each record with the value `table` holds for its key, if any.
+    ///
+    /// Each worker holds its own table, for example built when the dataflow is constructed or
+    /// registered with the worker's `Resources`, and records are enriched where they are. This
+    /// suits tables that fit in memory at each worker, and is much cheaper than a join.
+    ///
+    /// # Examples
+    /// ```
+    /// use std::collections::HashMap;
+    /// use std::rc::Rc;
+    /// use timely::dataflow::operators::{ToStream, Capture};
+    /// use timely::dataflow::operators::lookup::Lookup;
+    /// use timely::dataflow::operators::capture::Extract;
+    ///
+    /// let captured = timely::example(|scope| {
+    ///     let countries = Rc::new(vec![(1u64, 'a'), (2, 'b')].into_iter().collect::<HashMap<_,_>>());
+    ///     vec![(1u64, 10u64), (3, 30)]
+    ///         .to_stream(scope)
+    ///         .lookup(&countries, |(country, _)| *country)
+    ///         .capture()
+    /// });
+    ///
+    /// assert_eq!(captured.extract(), vec![(0, vec![((1, 10), Some('a')), ((3, 30), None)])]);
+    /// ```
+    fn lookup<K, V, F>(&self, table: &Rc<HashMap<K, V>>, key: F) -> Stream<G, (D, Option<V>)>
+    where
+        K: Hash+Eq+'static,
+        V: Data,
+        F: Fn(&D)->K+'static;
+
+    /// As `lookup`, with a table built from the records of `table`, which are broadcast to all
+    /// workers.
+    ///
+    /// Records of `self` are held back until `table` is complete, that is, until its frontier is
+    /// empty; from then on they are enriched as they arrive. If several records of `table` have
+    /// the same key, one of them is retained.
+    ///
+    /// # Examples
+    /// ```
+    /// use timely::dataflow::operators::{ToStream, Capture};
+    /// use timely::dataflow::operators::lookup::Lookup;
+    /// use timely::dataflow::operators::capture::Extract;
+    ///
+    /// let captured = timely::example(|scope| {
+    ///     let countries = vec![(1u64, 'a'), (2, 'b')].to_stream(scope);
+    ///     vec![(1u64, 10u64), (3, 30)]
+    ///         .to_stream(scope)
+    ///         .lookup_broadcast(&countries, |(country, _)| *country)
+    ///         .capture()
+    /// });
+    ///
+    /// assert_eq!(captured.extract(), vec![(0, vec![((1, 10), Some('a')), ((3, 30), None)])]);
+    /// ```
+    fn lookup_broadcast<K, V, F>(&self, table: &Stream<G, (K, V)>, key: F) -> Stream<G, (D, Option<V>)>
+    where
+        K: ExchangeData+Hash+Eq,
+        V: ExchangeData,
+        F: Fn(&D)->K+'static;
+}
+
+impl<G: Scope, D: Data> Lookup<G, D> for Stream<G, D> {
+    fn lookup<K, V, F>(&self, table: &Rc<HashMap<K, V>>, key: F) -> Stream<G, (D, Option<V>)>
+    where
+        K: Hash+Eq+'static,
+        V: Data,
+        F: Fn(&D)->K+'static,
+    {
+        let table = table.clone();
+        self.map(move |record| {
+            let value = table.get(&key(&record)).cloned();
+            (record, value)
+        })
+    }
+
+    fn lookup_broadcast<K, V, F>(&self, table: &Stream<G, (K, V)>, key: F) -> Stream<G, (D, Option<V>)>
+    where
+        K: ExchangeData+Hash+Eq,
+        V: ExchangeData,
+        F: Fn(&D)->K+'static,
+    {
+        let mut entries = HashMap::new();
+        let mut stash = Vec::new();
+        let mut vector = Vec::new();
+        let mut records = Vec::new();
+
+        self.binary_frontier(&table.broadcast(), Pipeline, Pipeline, "LookupBroadcast", move |_capability, _info| move |input1, input2, output| {
+
+            // build the table from its records.
+            input2.for_each(|_time, data| {
+                data.swap(&mut vector);
+                entries.extend(vector.drain(..));
+            });
+
+            if input2.frontier().is_empty() {
+                // enrich held back and arriving records.
+                for (time, records) in stash.drain(..) {
+                    let mut session = output.session(&time);
+                    for record in records {
+                        let value = entries.get(&key(&record)).cloned();
+                        session.give((record, value));
+                    }
+                }
+                input1.for_each(|time, data| {
+                    data.swap(&mut records);
+                    let mut session = output.session(&time);
+                    for record in records.drain(..) {
+                        let value = entries.get(&key(&record)).cloned();
+                        session.give((record, value));
+                    }
+                });
+            }
+            else {
+                // hold back records until the table is complete.
+                input1.for_each(|time, data| {
+                    stash.push((time.retain(), data.replace(Vec::new())));
+                });
+            }
+        })
+    }
+}
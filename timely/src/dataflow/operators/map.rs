@@ -46,6 +46,25 @@ pub trait Map<S: Scope, D: Data> {
     /// });
     /// ```
     fn flat_map<I: IntoIterator, L: FnMut(D)->I+'static>(&self, logic: L) -> Stream<S, I::Item> where I::Item: Data;
+    /// Consumes each batch of the stream and yields the elements `logic` appends to its output.
+    ///
+    /// The logic is called with the elements of each batch as they arrived from the channel, and an
+    /// empty vector to populate, so that transformations can be written over slices of elements.
+    ///
+    /// # Examples
+    /// ```
+    /// use timely::dataflow::operators::{ToStream, Map, Capture};
+    /// use timely::dataflow::operators::capture::Extract;
+    ///
+    /// let captured = timely::example(|scope| {
+    ///     (0..10u64).to_stream(scope)
+    ///               .map_batch(|batch, output| output.extend(batch.iter().map(|x| x * 2)))
+    ///               .capture()
+    /// });
+    ///
+    /// assert_eq!(captured.extract(), vec![(0, (0..10).map(|x| x * 2).collect::<Vec<_>>())]);
+    /// ```
+    fn map_batch<D2: Data, L: FnMut(&[D], &mut Vec<D2>)+'static>(&self, logic: L) -> Stream<S, D2>;
 }
 
 impl<S: Scope, D: Data> Map<S, D> for Stream<S, D> {
@@ -80,4 +99,13 @@ impl<S: Scope, D: Data> Map<S, D> for Stream<S, D> {
             });
         })
     }
+    fn map_batch<D2: Data, L: FnMut(&[D], &mut Vec<D2>)+'static>(&self, mut logic: L) -> Stream<S, D2> {
+        let mut outputs = Vec::new();
+        self.unary(Pipeline, "MapBatch", move |_,_| move |input, output| {
+            input.for_each(|time, data| {
+                logic(&data[..], &mut outputs);
+                output.session(&time).give_vec(&mut outputs);
+            });
+        })
+    }
 }
@@ -0,0 +1,132 @@
+//! Record-by-record transformation spread across a pool of threads owned by the operator.
+//!
+//! Timely parallelizes across workers, each running its operators on one thread. For closures
+//! that are expensive per record, `map_parallel` additionally spreads each batch of records over
+//! threads owned by the operator, and waits for the results before producing them in their
+//! original order. Progress tracking is unaffected, as the operator holds its input batch and
+//! capability until the results are produced.
+
+use std::panic::{self, AssertUnwindSafe};
+use std::sync::Arc;
+use std::thread::JoinHandle;
+
+use crossbeam_channel::{Sender, Receiver};
+
+use crate::Data;
+use crate::dataflow::{Stream, Scope};
+use crate::dataflow::channels::pact::Pipeline;
+use crate::dataflow::operators::generic::operator::Operator;
+
+/// Extension trait for `Stream`.
+pub trait MapParallel<S: Scope, D: Data+Send> {
+    /// Consumes each element of the stream and yields `logic` applied to it, evaluated on a pool of
+    /// `threads` threads.
+    ///
+    /// Each batch of records is split into one chunk per thread, and the results are produced in
+    /// the order of the records once all chunks are transformed. With fewer than two threads the
+    /// records are transformed on the worker thread, as by `map`. A panic in `logic` is resumed on
+    /// the worker thread.
+    ///
+    /// # Examples
+    /// ```
+    /// use timely::dataflow::operators::{ToStream, Capture};
+    /// use timely::dataflow::operators::map_parallel::MapParallel;
+    /// use timely::dataflow::operators::capture::Extract;
+    ///
+    /// let captured = timely::example(|scope| {
+    ///     (0..10u64).to_stream(scope)
+    ///               .map_parallel(4, |x| x * x)
+    ///               .capture()
+    /// });
+    ///
+    /// assert_eq!(captured.extract(), vec![(0, vec![0, 1, 4, 9, 16, 25, 36, 49, 64, 81])]);
+    /// ```
+    fn map_parallel<D2: Data+Send, L: Fn(D)->D2+Send+Sync+'static>(&self, threads: usize, logic: L) -> Stream<S, D2>;
+}
+
+impl<S: Scope, D: Data+Send> MapParallel<S, D> for Stream<S, D> {
+    fn map_parallel<D2: Data+Send, L: Fn(D)->D2+Send+Sync+'static>(&self, threads: usize, logic: L) -> Stream<S, D2> {
+        let logic = Arc::new(logic);
+        let mut vector = Vec::new();
+        let mut results = Vec::new();
+        self.unary(Pipeline, "MapParallel", move |_,_| {
+            let mut pool = if threads > 1 { Some(Pool::new(threads, logic.clone())) } else { None };
+            move |input, output| {
+                input.for_each(|time, data| {
+                    data.swap(&mut vector);
+                    match pool.as_mut() {
+                        Some(pool) => pool.map(&mut vector, &mut results),
+                        None => results.extend(vector.drain(..).map(|x| logic(x))),
+                    }
+                    output.session(&time).give_vec(&mut results);
+                });
+            }
+        })
+    }
+}
+
+// The outcome of transforming a chunk of records: its position and results, or the panic.
+type Outcome<D2> = (usize, std::thread::Result<Vec<D2>>);
+
+// Threads transforming chunks of records, which exit once the pool is dropped.
+struct Pool<D, D2> {
+    jobs: Option<Sender<(usize, Vec<D>)>>,
+    outcomes: Receiver<Outcome<D2>>,
+    threads: Vec<JoinHandle<()>>,
+}
+
+impl<D: Send+'static, D2: Send+'static> Pool<D, D2> {
+
+    fn new<L: Fn(D)->D2+Send+Sync+'static>(threads: usize, logic: Arc<L>) -> Self {
+        let (jobs, jobs_recv) = crossbeam_channel::unbounded::<(usize, Vec<D>)>();
+        let (outcomes_send, outcomes) = crossbeam_channel::unbounded();
+        let threads = (0 .. threads).map(|index| {
+            let jobs = jobs_recv.clone();
+            let outcomes = outcomes_send.clone();
+            let logic = logic.clone();
+            std::thread::Builder::new()
+                .name(format!("timely:map-parallel-{}", index))
+                .spawn(move || {
+                    while let Ok((position, chunk)) = jobs.recv() {
+                        let result = panic::catch_unwind(AssertUnwindSafe(|| chunk.into_iter().map(|x| logic(x)).collect()));
+                        if outcomes.send((position, result)).is_err() { break; }
+                    }
+                })
+                .expect("failed to spawn map_parallel thread")
+        }).collect();
+        Pool { jobs: Some(jobs), outcomes, threads }
+    }
+
+    // Transforms the records of `input` into `output`, in order.
+    fn map(&mut self, input: &mut Vec<D>, output: &mut Vec<D2>) {
+        let threads = self.threads.len();
+        let chunk_size = (input.len() + threads - 1) / threads;
+        let mut chunks = 0;
+        while !input.is_empty() {
+            let chunk = input.drain(.. chunk_size.min(input.len())).collect();
+            self.jobs.as_ref().unwrap().send((chunks, chunk)).expect("map_parallel threads exited");
+            chunks += 1;
+        }
+        let mut results: Vec<Option<Vec<D2>>> = (0 .. chunks).map(|_| None).collect();
+        for _ in 0 .. chunks {
+            let (position, result) = self.outcomes.recv().expect("map_parallel threads exited");
+            match result {
+                Ok(result) => { results[position] = Some(result); },
+                Err(payload) => panic::resume_unwind(payload),
+            }
+        }
+        for result in results {
+            output.extend(result.unwrap());
+        }
+    }
+}
+
+impl<D, D2> Drop for Pool<D, D2> {
+    fn drop(&mut self) {
+        // closing the queue of jobs stops the threads.
+        self.jobs = None;
+        for thread in self.threads.drain(..) {
+            let _ = thread.join();
+        }
+    }
+}
@@ -8,22 +8,27 @@
 //! operators whose behavior can be supplied using closures accepting input and output handles.
 //! Most of the operators in this module are defined using these two general operators.
 
-pub use self::enterleave::{Enter, EnterAt, Leave};
+pub use self::enterleave::{Enter, EnterAt, EnterGroup, Leave};
 // pub use self::queue::*;
 pub use self::input::Input;
+pub use self::clock::ClockInput;
 pub use self::unordered_input::UnorderedInput;
 pub use self::feedback::{Feedback, LoopVariable, ConnectLoop};
 pub use self::concat::{Concat, Concatenate};
 pub use self::partition::Partition;
 pub use self::map::Map;
+pub use self::fuse::Fuse;
 pub use self::inspect::Inspect;
 pub use self::filter::Filter;
 pub use self::delay::Delay;
 pub use self::exchange::Exchange;
 pub use self::broadcast::Broadcast;
+pub use self::barrier::Barrier;
 pub use self::probe::Probe;
+pub use self::frontier::FrontierStream;
 pub use self::to_stream::{ToStream, ToStreamAsync, Event};
 pub use self::capture::Capture;
+pub use self::collect::Collect;
 pub use self::branch::{Branch, BranchWhen};
 pub use self::ok_err::OkErr;
 pub use self::result::ResultStream;
@@ -32,24 +37,39 @@ pub use self::generic::Operator;
 pub use self::generic::{Notificator, FrontierNotificator};
 
 pub use self::reclock::Reclock;
+pub use self::zip::Zip;
+pub use self::cross_join::CrossJoin;
 pub use self::count::Accumulate;
+pub use self::commit::Commit;
+pub use self::consolidate::Consolidate;
+pub use self::iterate::Iterate;
+pub use self::sort::Sort;
+pub use self::throttle::Throttle;
+pub use self::sink::Sink;
+pub use self::cogroup::CoGroup;
 
 pub mod enterleave;
 pub mod input;
+pub mod clock;
 pub mod flow_controlled;
 pub mod unordered_input;
 pub mod feedback;
 pub mod concat;
 pub mod partition;
 pub mod map;
+pub mod fuse;
 pub mod inspect;
 pub mod filter;
 pub mod delay;
 pub mod exchange;
 pub mod broadcast;
+pub mod barrier;
 pub mod probe;
+pub mod frontier;
+pub mod epoch_stats;
 pub mod to_stream;
 pub mod capture;
+pub mod collect;
 pub mod branch;
 pub mod ok_err;
 pub mod result;
@@ -58,7 +78,36 @@ pub mod aggregation;
 pub mod generic;
 
 pub mod reclock;
+pub mod zip;
+pub mod cross_join;
+pub mod connected_components;
 pub mod count;
+pub mod commit;
+pub mod consolidate;
+pub mod arrange;
+pub mod keyed;
+pub mod iterate;
+pub mod sort;
+pub mod throttle;
+pub mod sink;
+pub mod cogroup;
+pub mod set_ops;
+pub mod lookup;
+pub mod try_map;
+pub mod latency;
+pub mod map_parallel;
+pub mod audit;
+pub mod query;
+pub mod command;
+pub mod replayable;
+pub mod sketch;
+pub mod watermark;
+pub mod side_input;
+pub mod batch;
+pub mod framing;
+pub(crate) mod ordered;
+#[cfg(feature = "async")]
+pub mod asynchronous;
 
 // keep "mint" module-private
 mod capability;
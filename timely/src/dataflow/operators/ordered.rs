@@ -0,0 +1,61 @@
+//! Delivery of records in an order that is the same at every worker and in every run.
+
+use crate::{Data, ExchangeData};
+use crate::dataflow::{Stream, Scope};
+use crate::dataflow::channels::pact::Pipeline;
+use crate::dataflow::channels::pushers::Tee;
+use crate::dataflow::operators::Map;
+use crate::dataflow::operators::capability::Capability;
+use crate::dataflow::operators::generic::OutputHandle;
+use crate::dataflow::operators::generic::operator::Operator;
+
+// Records of one timestamp, each with the index of the worker that sent it and its position
+// among that worker's records.
+type Sequenced<T, D> = (Capability<T>, Vec<(usize, u64, D)>);
+
+/// Routes the records of `stream` with `route`, and passes them to `logic` in a deterministic order.
+///
+/// Each record is tagged with the index of the worker sending it and its position among that
+/// worker's records before it is routed, for example by `broadcast` or `exchange`. The records
+/// of each timestamp are held until the timestamp is complete, and are then passed to `logic` at
+/// once, in order of sender and then of position. Timestamps completing together are passed in
+/// order of timestamp.
+pub(crate) fn ordered<G, D, D2, R, L>(stream: &Stream<G, D>, name: &str, route: R, mut logic: L) -> Stream<G, D2>
+where
+    G: Scope,
+    D: ExchangeData,
+    D2: Data,
+    R: FnOnce(&Stream<G, (usize, u64, D)>)->Stream<G, (usize, u64, D)>,
+    L: FnMut(&Capability<G::Timestamp>, Vec<D>, &mut OutputHandle<G::Timestamp, D2, Tee<G::Timestamp, D2>>)+'static,
+{
+    let worker = stream.scope().index();
+    let mut sent = 0u64;
+    let sequenced = stream.map(move |datum| {
+        sent += 1;
+        (worker, sent, datum)
+    });
+
+    let mut stash: Vec<Sequenced<G::Timestamp, D>> = Vec::new();
+    route(&sequenced).unary_frontier(Pipeline, name, move |_capability, _info| {
+        move |input, output| {
+            input.for_each(|time, data| {
+                let mut vector = Vec::new();
+                data.swap(&mut vector);
+                match stash.iter_mut().find(|(capability, _)| capability.time() == time.time()) {
+                    Some((_, records)) => records.extend(vector),
+                    None => stash.push((time.retain(), vector)),
+                }
+            });
+
+            // pass along the records of completed timestamps, in order of sender and position.
+            let frontier = input.frontier();
+            let (mut ready, pending) = stash.drain(..).partition::<Vec<_>, _>(|(capability, _)| !frontier.less_equal(capability.time()));
+            stash = pending;
+            ready.sort_by(|x, y| x.0.time().cmp(y.0.time()));
+            for (capability, mut records) in ready {
+                records.sort_by_key(|(sender, position, _)| (*sender, *position));
+                logic(&capability, records.into_iter().map(|(_, _, datum)| datum).collect(), output);
+            }
+        }
+    })
+}
@@ -0,0 +1,273 @@
+//! Queries of operator state, issued by the driver at a timestamp and answered once the state is
+//! complete through that timestamp.
+//!
+//! A `QueryHandle` introduces queries into a dataflow as an input does, each tagged with the
+//! current time of the handle. The `answer_queries` operator holds each query until the frontier
+//! of a stream of the stateful operator passes its time, at which point the state reflects all
+//! records up to that time, and then answers it from the state. The answer is routed back to the
+//! worker whose driver issued the query, where it can be taken from the handle. This turns a
+//! dataflow maintaining state into a service answering consistent queries of it.
+
+use std::cell::RefCell;
+use std::collections::HashMap;
+use std::hash::Hash;
+use std::rc::Rc;
+
+use crate::ExchangeData;
+use crate::order::TotalOrder;
+use crate::dataflow::{Stream, Scope, InputHandle};
+use crate::dataflow::channels::pact::{Exchange, Pipeline};
+use crate::dataflow::channels::partitioner::hash;
+use crate::dataflow::operators::Input;
+use crate::dataflow::operators::arrange::Arranged;
+use crate::dataflow::operators::generic::operator::Operator;
+use crate::dataflow::operators::generic::FrontierStash;
+use crate::progress::Timestamp;
+use crate::progress::frontier::Antichain;
+
+/// The driver's handle for issuing queries and taking their answers.
+///
+/// Like an `InputHandle`, the handle has a current time, which it must advance for the dataflow
+/// to make progress, and which each worker's handle must advance alike. Dropping the handle
+/// closes its input of queries.
+pub struct QueryHandle<T: Timestamp, Q: ExchangeData, R: ExchangeData> {
+    // queries, with the index of the issuing worker and an identifier.
+    input: InputHandle<T, (usize, u64, Q)>,
+    // the index of the worker, once the handle is bound to a dataflow.
+    worker: Option<usize>,
+    next: u64,
+    answers: Rc<RefCell<HashMap<u64, Vec<R>>>>,
+}
+
+impl<T: Timestamp, Q: ExchangeData, R: ExchangeData> QueryHandle<T, Q, R> {
+    /// A handle not yet bound to a dataflow.
+    pub fn new() -> Self {
+        QueryHandle {
+            input: InputHandle::new(),
+            worker: None,
+            next: 0,
+            answers: Rc::new(RefCell::new(HashMap::new())),
+        }
+    }
+
+    /// Issues `query` at the current time of the handle, and returns its identifier.
+    ///
+    /// The answer is available from `answer` once the state is complete through the time.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the handle has not been bound to a dataflow by `answer_queries`.
+    pub fn query(&mut self, query: Q) -> u64 {
+        let worker = self.worker.expect("query handle is not bound to a dataflow");
+        let identifier = self.next;
+        self.next += 1;
+        self.input.send((worker, identifier, query));
+        identifier
+    }
+
+    /// Advances the time at which queries are issued to `next`.
+    pub fn advance_to(&mut self, next: T) {
+        self.input.advance_to(next);
+    }
+
+    /// The time at which queries are issued.
+    pub fn time(&self) -> &T {
+        self.input.time()
+    }
+
+    /// Removes and returns the answer to the query with identifier `query`, if it has arrived.
+    pub fn answer(&mut self, query: u64) -> Option<Vec<R>> {
+        self.answers.borrow_mut().remove(&query)
+    }
+
+    /// The number of answers that have arrived and not been taken.
+    pub fn answered(&self) -> usize {
+        self.answers.borrow().len()
+    }
+}
+
+impl<T: Timestamp, Q: ExchangeData, R: ExchangeData> Default for QueryHandle<T, Q, R> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Answers queries from the state of an operator once it is complete through their times.
+pub trait AnswerQueries<G: Scope> {
+    /// Answers the queries of `handle`, routed to workers by `route`, with `logic`.
+    ///
+    /// A query at time `time` is answered once the frontier of this stream, produced by the
+    /// operator maintaining the state, is no longer less or equal to `time`. `logic` is called
+    /// with the query and `time`, and adds the answer to the supplied vector. The operator may
+    /// have applied records at later times by then, and so `logic` should read the state as of
+    /// `time` for a consistent answer. Each query is answered at the worker indicated by `route`,
+    /// which should be the worker holding the state it reads.
+    ///
+    /// # Examples
+    /// ```
+    /// use std::collections::HashMap;
+    /// use std::rc::Rc;
+    /// use std::cell::RefCell;
+    ///
+    /// use timely::dataflow::InputHandle;
+    /// use timely::dataflow::operators::{Input, Inspect};
+    /// use timely::dataflow::operators::query::{AnswerQueries, QueryHandle};
+    ///
+    /// timely::execute_directly(|worker| {
+    ///
+    ///     // the latest value of each key, with the times of its changes.
+    ///     let latest = Rc::new(RefCell::new(HashMap::<u64, Vec<(u64, u64)>>::new()));
+    ///     let mut input = InputHandle::new();
+    ///     let mut queries = QueryHandle::new();
+    ///     worker.dataflow::<u64,_,_>(|scope| {
+    ///         let state = latest.clone();
+    ///         let updates = scope
+    ///             .input_from(&mut input)
+    ///             .inspect_time(move |time, (key, value): &(u64, u64)| {
+    ///                 state.borrow_mut().entry(*key).or_default().push((*time, *value));
+    ///             });
+    ///         let state = latest.clone();
+    ///         updates.answer_queries(&mut queries, |key| *key, move |key, time, answer| {
+    ///             let state = state.borrow();
+    ///             let changes = state.get(key).into_iter().flatten();
+    ///             answer.extend(changes.filter(|(changed, _)| changed <= time).map(|(_, value)| *value).last());
+    ///         });
+    ///     });
+    ///
+    ///     input.send((0, 10));
+    ///     let before = queries.query(0);
+    ///     input.advance_to(1);
+    ///     queries.advance_to(1);
+    ///     input.send((0, 20));
+    ///     let after = queries.query(0);
+    ///     input.advance_to(2);
+    ///     queries.advance_to(2);
+    ///
+    ///     while queries.answered() < 2 {
+    ///         worker.step();
+    ///     }
+    ///     assert_eq!(queries.answer(before), Some(vec![10]));
+    ///     assert_eq!(queries.answer(after), Some(vec![20]));
+    /// });
+    /// ```
+    fn answer_queries<Q, R, F, L>(&self, handle: &mut QueryHandle<G::Timestamp, Q, R>, route: F, logic: L)
+    where
+        G::Timestamp: TotalOrder,
+        Q: ExchangeData,
+        R: ExchangeData,
+        F: Fn(&Q)->u64+'static,
+        L: FnMut(&Q, &G::Timestamp, &mut Vec<R>)+'static;
+}
+
+impl<G: Scope, D: ExchangeData> AnswerQueries<G> for Stream<G, D> {
+    fn answer_queries<Q, R, F, L>(&self, handle: &mut QueryHandle<G::Timestamp, Q, R>, route: F, logic: L)
+    where
+        G::Timestamp: TotalOrder,
+        Q: ExchangeData,
+        R: ExchangeData,
+        F: Fn(&Q)->u64+'static,
+        L: FnMut(&Q, &G::Timestamp, &mut Vec<R>)+'static,
+    {
+        answer_queries(self, handle, route, logic, |_| { });
+    }
+}
+
+/// Answers the queries of `handle` from the state of `stream`, as `AnswerQueries::answer_queries`,
+/// and reports to `pending` a lower bound on the times of the queries yet to be answered.
+fn answer_queries<G, D, Q, R, F, L, H>(stream: &Stream<G, D>, handle: &mut QueryHandle<G::Timestamp, Q, R>, route: F, mut logic: L, mut pending: H)
+where
+    G: Scope,
+    G::Timestamp: TotalOrder,
+    D: ExchangeData,
+    Q: ExchangeData,
+    R: ExchangeData,
+    F: Fn(&Q)->u64+'static,
+    L: FnMut(&Q, &G::Timestamp, &mut Vec<R>)+'static,
+    H: FnMut(Antichain<G::Timestamp>)+'static,
+{
+    let mut scope = stream.scope();
+    handle.worker = Some(scope.index());
+    let queries = scope.input_from(&mut handle.input);
+
+    let mut stash = FrontierStash::new();
+    let answers = queries.binary_frontier(stream, Exchange::new(move |(_, _, query): &(usize, u64, Q)| route(query)), Pipeline, "AnswerQueries", move |_capability, _info| {
+        move |queries, state, output| {
+
+            // the state is read by `logic`, and its records need not be.
+            state.for_each(|_time, _data| { });
+
+            queries.for_each(|time, data| stash.push(time, data));
+
+            // answer queries at times through which the state is complete.
+            stash.release(state.frontier(), |capability, queries| {
+                let mut session = output.session(capability);
+                for (worker, identifier, query) in queries.iter() {
+                    let mut answer = Vec::new();
+                    logic(query, capability.time(), &mut answer);
+                    session.give((*worker, *identifier, answer));
+                }
+            });
+
+            let lower = stash.lower(queries.frontier());
+            pending(lower);
+        }
+    });
+
+    let delivered = handle.answers.clone();
+    let mut vector = Vec::new();
+    answers.sink(Exchange::new(|(worker, _, _): &(usize, u64, Vec<R>)| *worker as u64), "QueryAnswers", move |input| {
+        input.for_each(|_time, data| {
+            data.swap(&mut vector);
+            let mut delivered = delivered.borrow_mut();
+            for (_worker, identifier, answer) in vector.drain(..) {
+                delivered.insert(identifier, answer);
+            }
+        });
+    });
+}
+
+impl<G: Scope, K: ExchangeData+Hash+Eq, V: ExchangeData> Arranged<G, K, V>
+where
+    G::Timestamp: TotalOrder,
+{
+    /// Answers queries of `handle` for the values of a key, as of the time of the query.
+    ///
+    /// # Examples
+    /// ```
+    /// use timely::dataflow::InputHandle;
+    /// use timely::dataflow::operators::Input;
+    /// use timely::dataflow::operators::arrange::ArrangeByKey;
+    /// use timely::dataflow::operators::query::QueryHandle;
+    ///
+    /// timely::execute_directly(|worker| {
+    ///
+    ///     let mut input = InputHandle::new();
+    ///     let mut queries = QueryHandle::new();
+    ///     worker.dataflow::<u64,_,_>(|scope| {
+    ///         scope.input_from(&mut input)
+    ///              .arrange_by_key()
+    ///              .serve(&mut queries);
+    ///     });
+    ///
+    ///     input.send(("alice".to_string(), 30u32));
+    ///     input.advance_to(1);
+    ///     queries.advance_to(1);
+    ///     input.send(("alice".to_string(), 31u32));
+    ///     let query = queries.query("alice".to_string());
+    ///     input.advance_to(2);
+    ///     queries.advance_to(2);
+    ///
+    ///     while queries.answered() < 1 {
+    ///         worker.step();
+    ///     }
+    ///     assert_eq!(queries.answer(query), Some(vec![30, 31]));
+    /// });
+    /// ```
+    pub fn serve(&self, handle: &mut QueryHandle<G::Timestamp, K, V>) {
+        let trace = self.trace.clone();
+        let mut reader = self.trace.reader();
+        answer_queries(&self.stream, handle, |key| hash(key), move |key, time, answer| {
+            trace.for_each_at(key, time, |value| answer.push(value.clone()));
+        }, move |pending| reader.set_frontier(pending));
+    }
+}
@@ -16,10 +16,15 @@ pub trait Reclock<S: Scope, D: Data> {
     /// workers receive the same clock records, which can be accomplished with
     /// `broadcast`.
     ///
+    /// Each record is emitted at the earliest clock time greater or equal to its
+    /// own time, which makes `reclock` a way to coarsen fine-grained timestamps
+    /// before operators whose work is proportional to the number of distinct times.
+    /// Only the times of clock records matter, not their contents.
+    ///
     /// # Examples
     ///
     /// ```
-    /// use timely::dataflow::operators::{ToStream, Delay, Map, Reclock, Capture};
+    /// use timely::dataflow::operators::{ToStream, Delay, Reclock, Capture};
     /// use timely::dataflow::operators::capture::Extract;
     ///
     /// let captured = timely::example(|scope| {
@@ -31,8 +36,7 @@ pub trait Reclock<S: Scope, D: Data> {
     ///     // product clock ticks at three times.
     ///     let clock = vec![3, 5, 8].into_iter()
     ///                              .to_stream(scope)
-    ///                              .delay(|x,t| *x)
-    ///                              .map(|_| ());
+    ///                              .delay(|x,t| *x);
     ///
     ///     // reclock the data.
     ///     data.reclock(&clock)
@@ -45,11 +49,11 @@ pub trait Reclock<S: Scope, D: Data> {
     /// assert_eq!(extracted[1], (5, vec![4,5]));
     /// assert_eq!(extracted[2], (8, vec![6,7,8]));
     /// ```
-    fn reclock(&self, clock: &Stream<S, ()>) -> Stream<S, D>;
+    fn reclock<C: Data>(&self, clock: &Stream<S, C>) -> Stream<S, D>;
 }
 
 impl<S: Scope, D: Data> Reclock<S, D> for Stream<S, D> {
-    fn reclock(&self, clock: &Stream<S, ()>) -> Stream<S, D> {
+    fn reclock<C: Data>(&self, clock: &Stream<S, C>) -> Stream<S, D> {
 
         let mut stash = vec![];
 
@@ -0,0 +1,174 @@
+//! Sources that can resume reading from an offset, for restoration from checkpoints.
+//!
+//! A `ReplayableSource` reads records from an external system and reports its offset, a position
+//! in the system's data from which reading can resume. The `source` operator assigns each read
+//! to the next epoch and records the offset after it in checkpointed state, so that a worker
+//! restored from the checkpoint of an epoch resumes reading immediately after that epoch.
+//! Together with `Commit`, which commits output only once it is durable, records are neither
+//! lost nor duplicated across restoration.
+
+use std::collections::VecDeque;
+use std::fs::File;
+use std::io::{self, BufRead, BufReader, Seek, SeekFrom};
+use std::path::Path;
+
+use abomonation::Abomonation;
+
+use crate::Data;
+use crate::dataflow::{Scope, Stream};
+use crate::dataflow::operators::generic::operator;
+use crate::dataflow::state::checkpoint::Durability;
+
+/// A source of records that can resume reading from an offset.
+pub trait ReplayableSource {
+    /// The records read from the source.
+    type Item: Data;
+    /// A position in the source from which reading can resume.
+    type Offset: Abomonation+Clone+'static;
+    /// Positions the source to read from `offset`, as previously reported by `offset`.
+    fn seek(&mut self, offset: Self::Offset) -> io::Result<()>;
+    /// Reads some records into `buffer`, returning false once the source is exhausted.
+    fn read(&mut self, buffer: &mut Vec<Self::Item>) -> io::Result<bool>;
+    /// The offset immediately after the records read so far.
+    fn offset(&self) -> Self::Offset;
+}
+
+/// Reads the lines of a file, a bounded number at a time.
+///
+/// The offset is the position in bytes of the next line to read.
+pub struct FileSource {
+    reader: BufReader<File>,
+    position: u64,
+    lines: usize,
+}
+
+impl FileSource {
+    /// Reads the lines of the file at `path`, at most `lines` lines per read.
+    pub fn open<P: AsRef<Path>>(path: P, lines: usize) -> io::Result<Self> {
+        Ok(FileSource { reader: BufReader::new(File::open(path)?), position: 0, lines })
+    }
+}
+
+impl ReplayableSource for FileSource {
+    type Item = String;
+    type Offset = u64;
+    fn seek(&mut self, offset: u64) -> io::Result<()> {
+        self.position = self.reader.seek(SeekFrom::Start(offset))?;
+        Ok(())
+    }
+    fn read(&mut self, buffer: &mut Vec<String>) -> io::Result<bool> {
+        for _ in 0 .. self.lines {
+            let mut line = String::new();
+            let bytes = self.reader.read_line(&mut line)?;
+            if bytes == 0 {
+                return Ok(false);
+            }
+            self.position += bytes as u64;
+            if line.ends_with('\n') { line.pop(); }
+            if line.ends_with('\r') { line.pop(); }
+            buffer.push(line);
+        }
+        Ok(true)
+    }
+    fn offset(&self) -> u64 {
+        self.position
+    }
+}
+
+/// Produces the records of `replayable`, the records of each read at the next epoch.
+///
+/// The offset after each epoch is kept in checkpointed state until a later epoch is contained in a
+/// consistent checkpoint, so that only the latest committed offset and those of later epochs are
+/// retained. If `restored_epoch` is the epoch of the checkpoint the worker was restored from, as
+/// reported by `Worker::restored_epoch`, the source resumes from the offset after that epoch, and
+/// its records start at the next epoch. Each worker reads from its own source.
+///
+/// # Panics
+///
+/// Panics if reading from or seeking the source fails.
+///
+/// # Examples
+/// ```
+/// use std::io::Write;
+/// use timely::dataflow::operators::Capture;
+/// use timely::dataflow::operators::capture::Extract;
+/// use timely::dataflow::operators::replayable::{self, FileSource};
+///
+/// let path = std::env::temp_dir().join(format!("timely-replayable-{}.txt", std::process::id()));
+/// std::fs::File::create(&path).unwrap().write_all(b"a\nb\nc\n").unwrap();
+///
+/// let file = path.clone();
+/// let captured = timely::example(move |scope| {
+///     replayable::source(scope, "Lines", FileSource::open(&file, 2).unwrap(), None)
+///         .capture()
+/// });
+///
+/// let lines = |lines: &[&str]| lines.iter().map(|line| line.to_string()).collect::<Vec<_>>();
+/// assert_eq!(captured.extract(), vec![(0, lines(&["a", "b"])), (1, lines(&["c"]))]);
+///
+/// std::fs::remove_file(&path).unwrap();
+/// ```
+pub fn source<G, S>(scope: &G, name: &str, mut replayable: S, restored_epoch: Option<u64>) -> Stream<G, S::Item>
+where
+    G: Scope<Timestamp=u64>,
+    S: ReplayableSource+'static,
+{
+    operator::source(scope, name, |mut capability, info| {
+
+        let activator = scope.activator_for(&info.address[..]);
+        let offsets = scope.state().checkpointed_handle::<u64, S::Offset>(&info.address, "offsets");
+
+        // resume after the restored epoch, discarding the offsets of later epochs.
+        if let Some(restored) = restored_epoch {
+            let mut resume = None;
+            offsets.for_each(|epoch, offset| {
+                let later = match &resume {
+                    Some((latest, _)) => epoch > latest,
+                    None => true,
+                };
+                if *epoch <= restored && later {
+                    resume = Some((*epoch, offset.clone()));
+                }
+            });
+            offsets.retain(|epoch, _| *epoch <= restored);
+            if let Some((_, offset)) = resume {
+                replayable.seek(offset).expect("failed to seek replayable source");
+            }
+            capability.downgrade(&(restored + 1));
+        }
+
+        // the epochs with offsets, in increasing order.
+        let mut epochs = Vec::new();
+        offsets.for_each(|epoch, _| epochs.push(*epoch));
+        epochs.sort();
+        let mut epochs = VecDeque::from(epochs);
+        let directory = scope.config().checkpoint.as_ref().map(|(directory, _)| directory.clone());
+        let durability = Durability::new(directory, scope.peers());
+
+        let mut capability = Some(capability);
+        let mut buffer = Vec::new();
+        move |output| {
+            if let Some(time) = capability.as_mut() {
+                let more = replayable.read(&mut buffer).expect("failed to read replayable source");
+                let epoch = *time.time();
+                if !buffer.is_empty() {
+                    output.session(time).give_vec(&mut buffer);
+                    offsets.insert(epoch, replayable.offset());
+                    epochs.push_back(epoch);
+                    time.downgrade(&(epoch + 1));
+                    // discard offsets superseded by a later committed offset.
+                    while epochs.len() > 1 && durability.is_durable(epochs[1]) {
+                        offsets.remove(&epochs[0]);
+                        epochs.pop_front();
+                    }
+                }
+                if more {
+                    activator.activate();
+                }
+                else {
+                    capability = None;
+                }
+            }
+        }
+    })
+}
@@ -0,0 +1,96 @@
+//! Set operations between two streams, within each timestamp.
+
+use std::hash::Hash;
+
+use crate::ExchangeData;
+use crate::dataflow::{Stream, Scope};
+use crate::dataflow::operators::{CoGroup, Map};
+
+/// Set operations between the records of two streams at each timestamp.
+///
+/// Records are exchanged by their hash, and each distinct record of the result is produced once,
+/// once the timestamp is complete for both inputs.
+pub trait SetOps<G: Scope, D: ExchangeData+Hash+Eq> {
+    /// The distinct records present in both `self` and `other`.
+    ///
+    /// # Examples
+    /// ```
+    /// use timely::dataflow::operators::{ToStream, Capture};
+    /// use timely::dataflow::operators::set_ops::SetOps;
+    /// use timely::dataflow::operators::capture::Extract;
+    ///
+    /// let captured = timely::example(|scope| {
+    ///     let other = vec![2, 3, 3, 4].to_stream(scope);
+    ///     vec![1, 2, 3, 3].to_stream(scope).intersect(&other).capture()
+    /// });
+    ///
+    /// let mut result = captured.extract();
+    /// result[0].1.sort();
+    /// assert_eq!(result, vec![(0, vec![2, 3])]);
+    /// ```
+    fn intersect(&self, other: &Stream<G, D>) -> Stream<G, D>;
+
+    /// The distinct records present in `self` but not in `other`.
+    ///
+    /// # Examples
+    /// ```
+    /// use timely::dataflow::operators::{ToStream, Capture};
+    /// use timely::dataflow::operators::set_ops::SetOps;
+    /// use timely::dataflow::operators::capture::Extract;
+    ///
+    /// let captured = timely::example(|scope| {
+    ///     let other = vec![2, 3, 4].to_stream(scope);
+    ///     vec![1, 1, 2, 3].to_stream(scope).minus(&other).capture()
+    /// });
+    ///
+    /// assert_eq!(captured.extract(), vec![(0, vec![1])]);
+    /// ```
+    fn minus(&self, other: &Stream<G, D>) -> Stream<G, D>;
+
+    /// The distinct records present in `self` or in `other`.
+    ///
+    /// # Examples
+    /// ```
+    /// use timely::dataflow::operators::{ToStream, Capture};
+    /// use timely::dataflow::operators::set_ops::SetOps;
+    /// use timely::dataflow::operators::capture::Extract;
+    ///
+    /// let captured = timely::example(|scope| {
+    ///     let other = vec![2, 3, 3].to_stream(scope);
+    ///     vec![1, 2, 2].to_stream(scope).union_distinct(&other).capture()
+    /// });
+    ///
+    /// let mut result = captured.extract();
+    /// result[0].1.sort();
+    /// assert_eq!(result, vec![(0, vec![1, 2, 3])]);
+    /// ```
+    fn union_distinct(&self, other: &Stream<G, D>) -> Stream<G, D>;
+}
+
+impl<G: Scope, D: ExchangeData+Hash+Eq> SetOps<G, D> for Stream<G, D> {
+    fn intersect(&self, other: &Stream<G, D>) -> Stream<G, D> {
+        set_op(self, other, |in_self, in_other| in_self && in_other)
+    }
+
+    fn minus(&self, other: &Stream<G, D>) -> Stream<G, D> {
+        set_op(self, other, |in_self, in_other| in_self && !in_other)
+    }
+
+    fn union_distinct(&self, other: &Stream<G, D>) -> Stream<G, D> {
+        set_op(self, other, |in_self, in_other| in_self || in_other)
+    }
+}
+
+// Produces each distinct record for which `include` holds of its presence in each input.
+fn set_op<G, D, F>(stream1: &Stream<G, D>, stream2: &Stream<G, D>, include: F) -> Stream<G, D>
+where
+    G: Scope,
+    D: ExchangeData+Hash+Eq,
+    F: Fn(bool, bool)->bool+'static,
+{
+    stream1
+        .map(|record| (record, ()))
+        .cogroup(&stream2.map(|record| (record, ())), move |record, values1, values2| {
+            if include(!values1.is_empty(), !values2.is_empty()) { Some(record.clone()) } else { None }
+        })
+}
@@ -0,0 +1,213 @@
+//! Slowly changing state, broadcast to all workers and consulted by operators at each timestamp.
+//!
+//! Many dataflows consult state that changes rarely compared to the records they process, for
+//! example a configuration table or the version of a model used to score records. A side input
+//! broadcasts a low-rate stream of updates to all workers, applies the updates of each timestamp
+//! once the timestamp is complete, and retains the state as of each timestamp at which it
+//! changed. Operators reading the side input hold each record until the side input is complete
+//! through the record's timestamp, and then consult the state as of that timestamp, so that no
+//! record ever sees a partially applied update, and every worker sees the same state.
+
+use std::cell::RefCell;
+use std::rc::Rc;
+
+use crate::{Data, ExchangeData};
+use crate::order::TotalOrder;
+use crate::progress::Timestamp;
+use crate::dataflow::{Stream, Scope};
+use crate::dataflow::channels::pact::Pipeline;
+use crate::dataflow::operators::Broadcast;
+use crate::dataflow::operators::capability::Capability;
+use crate::dataflow::operators::generic::operator::Operator;
+use crate::dataflow::operators::ordered::ordered;
+
+/// A state broadcast to all workers, with its value as of each timestamp.
+///
+/// The side input is bound to the scope in which it was built, and is read by `with_side_input`.
+pub struct SideInput<G: Scope, S> {
+    // carries no records; its frontier indicates the timestamps through which the state is complete.
+    stream: Stream<G, ()>,
+    versions: Rc<RefCell<Versions<G::Timestamp, S>>>,
+}
+
+impl<G: Scope, S> Clone for SideInput<G, S> {
+    fn clone(&self) -> Self {
+        SideInput {
+            stream: self.stream.clone(),
+            versions: self.versions.clone(),
+        }
+    }
+}
+
+/// Builds a side input from a stream of updates.
+pub trait SideInputStream<G: Scope, U: ExchangeData> where G::Timestamp: TotalOrder {
+    /// Broadcasts the updates of this stream to all workers, applying them to `initial` with `apply`.
+    ///
+    /// The updates of each timestamp are applied once the timestamp is complete, in order of the
+    /// index of the worker that produced them and then in the order it produced them, so that
+    /// the state as of each timestamp is the same at every worker.
+    ///
+    /// # Examples
+    /// ```
+    /// use timely::dataflow::InputHandle;
+    /// use timely::dataflow::operators::{Input, Probe, Capture};
+    /// use timely::dataflow::operators::side_input::{SideInputStream, WithSideInput};
+    /// use timely::dataflow::operators::capture::Extract;
+    ///
+    /// let captured = timely::execute_directly(|worker| {
+    ///
+    ///     let mut updates = InputHandle::new();
+    ///     let mut input = InputHandle::new();
+    ///     let (probe, captured) = worker.dataflow::<u64,_,_>(|scope| {
+    ///         // the factor by which records are scaled.
+    ///         let factor = scope.input_from(&mut updates).side_input(1u64, |factor, update| *factor = update);
+    ///         let scaled = scope.input_from(&mut input)
+    ///                           .with_side_input(&factor, |factor, _time, x: u64| Some(x * factor));
+    ///         (scaled.probe(), scaled.capture())
+    ///     });
+    ///
+    ///     for round in 0 .. 3u64 {
+    ///         if round == 1 { updates.send(10); }
+    ///         input.send(round);
+    ///         updates.advance_to(round + 1);
+    ///         input.advance_to(round + 1);
+    ///         worker.step_while(|| probe.less_than(input.time()));
+    ///     }
+    ///     captured
+    /// });
+    ///
+    /// assert_eq!(captured.extract(), vec![(0, vec![0]), (1, vec![10]), (2, vec![20])]);
+    /// ```
+    fn side_input<S, F>(&self, initial: S, apply: F) -> SideInput<G, S>
+    where
+        S: Clone+'static,
+        F: FnMut(&mut S, U)+'static;
+}
+
+impl<G: Scope, U: ExchangeData> SideInputStream<G, U> for Stream<G, U> where G::Timestamp: TotalOrder {
+    fn side_input<S, F>(&self, initial: S, mut apply: F) -> SideInput<G, S>
+    where
+        S: Clone+'static,
+        F: FnMut(&mut S, U)+'static,
+    {
+        let versions = Rc::new(RefCell::new(Versions::new(initial)));
+        let shared = versions.clone();
+
+        // apply the updates of completed timestamps, holding the output until they are applied.
+        let stream = ordered(self, "SideInput", |tagged| tagged.broadcast(), move |capability, updates, _output| {
+            let mut versions = shared.borrow_mut();
+            let mut state = versions.latest().clone();
+            for update in updates {
+                apply(&mut state, update);
+            }
+            versions.insert(capability.time().clone(), state);
+            versions.compact();
+        });
+
+        SideInput { stream, versions }
+    }
+}
+
+/// Processes records with the state of a side input.
+pub trait WithSideInput<G: Scope, D: Data> where G::Timestamp: TotalOrder {
+    /// Applies `logic` to each record with the state of `side` as of the record's timestamp,
+    /// producing the records `logic` returns.
+    ///
+    /// Records are held until `side` is complete through their timestamp.
+    fn with_side_input<S, R, I, L>(&self, side: &SideInput<G, S>, logic: L) -> Stream<G, R>
+    where
+        S: 'static,
+        R: Data,
+        I: IntoIterator<Item=R>,
+        L: FnMut(&S, &G::Timestamp, D)->I+'static;
+}
+
+impl<G: Scope, D: Data> WithSideInput<G, D> for Stream<G, D> where G::Timestamp: TotalOrder {
+    fn with_side_input<S, R, I, L>(&self, side: &SideInput<G, S>, mut logic: L) -> Stream<G, R>
+    where
+        S: 'static,
+        R: Data,
+        I: IntoIterator<Item=R>,
+        L: FnMut(&S, &G::Timestamp, D)->I+'static,
+    {
+        let versions = side.versions.clone();
+        let reader = versions.borrow_mut().register();
+
+        let mut stash: Vec<(Capability<G::Timestamp>, Vec<D>)> = Vec::new();
+        self.binary_frontier(&side.stream, Pipeline, Pipeline, "WithSideInput", move |_capability, _info| {
+            move |input, side, output| {
+                side.for_each(|_time, _data| { });
+                input.for_each(|time, data| {
+                    let mut vector = Vec::new();
+                    data.swap(&mut vector);
+                    stash.push((time.retain(), vector));
+                });
+
+                // process records at timestamps through which the side input is complete.
+                let frontier = side.frontier();
+                let (ready, waiting) = stash.drain(..).partition::<Vec<_>, _>(|(capability, _)| !frontier.less_equal(capability.time()));
+                stash = waiting;
+                let mut versions = versions.borrow_mut();
+                for (capability, records) in ready {
+                    let state = versions.at(capability.time());
+                    let mut session = output.session(&capability);
+                    for record in records {
+                        session.give_iterator(logic(state, capability.time(), record).into_iter());
+                    }
+                }
+
+                // report the least timestamp at which this reader may still consult the state.
+                let least = input.frontier().frontier().iter().chain(stash.iter().map(|(capability, _)| capability.time())).min().cloned();
+                versions.bound(reader, least);
+            }
+        })
+    }
+}
+
+/// The state as of each timestamp at which it changed.
+struct Versions<T, S> {
+    // versions in order of timestamp, the first holding the initial state at the minimum timestamp.
+    versions: Vec<(T, S)>,
+    // for each reader, the least timestamp at which it may still read, if any.
+    bounds: Vec<Option<T>>,
+}
+
+impl<T: Timestamp+TotalOrder, S> Versions<T, S> {
+    fn new(initial: S) -> Self {
+        Versions { versions: vec![(T::minimum(), initial)], bounds: Vec::new() }
+    }
+
+    /// Registers a reader, which may read at any timestamp until it reports a bound.
+    fn register(&mut self) -> usize {
+        self.bounds.push(Some(T::minimum()));
+        self.bounds.len() - 1
+    }
+
+    /// Records that `reader` will only read at timestamps at least `least`, or never if `None`.
+    fn bound(&mut self, reader: usize, least: Option<T>) {
+        self.bounds[reader] = least;
+    }
+
+    fn latest(&self) -> &S {
+        &self.versions.last().expect("no versions").1
+    }
+
+    fn insert(&mut self, time: T, state: S) {
+        self.versions.push((time, state));
+    }
+
+    /// The state as of `time`.
+    fn at(&self, time: &T) -> &S {
+        &self.versions.iter().rev().find(|(changed, _)| changed <= time).expect("no version at time").1
+    }
+
+    /// Discards versions no reader can read.
+    fn compact(&mut self) {
+        let least = self.bounds.iter().flatten().min();
+        let needed = match least {
+            Some(least) => self.versions.iter().rposition(|(changed, _)| changed <= least).unwrap_or(0),
+            None => self.versions.len() - 1,
+        };
+        self.versions.drain(.. needed);
+    }
+}
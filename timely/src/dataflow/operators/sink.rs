@@ -0,0 +1,98 @@
+//! Consumes the contents of a stream, for side effects.
+
+use crate::Data;
+use crate::dataflow::channels::pact::Pipeline;
+use crate::dataflow::operators::collect::complete_epochs;
+use crate::dataflow::operators::generic::Operator;
+use crate::dataflow::{Scope, Stream};
+
+/// Consumes the contents of a stream, for side effects.
+///
+/// Unlike `Inspect`, these operators produce no output, and `sink_on_complete` observes each
+/// timestamp only once it is complete.
+pub trait Sink<G: Scope, D: Data> {
+    /// Passes each batch of records to `logic`, with its timestamp, as the batch arrives.
+    ///
+    /// A timestamp may be observed in many batches, and there is no guarantee that a timestamp
+    /// is complete when it is observed.
+    ///
+    /// # Examples
+    /// ```
+    /// use std::rc::Rc;
+    /// use std::cell::RefCell;
+    /// use timely::dataflow::operators::{ToStream, Sink};
+    ///
+    /// timely::execute_directly(|worker| {
+    ///     let seen = Rc::new(RefCell::new(Vec::new()));
+    ///     let sink = seen.clone();
+    ///     worker.dataflow::<u64,_,_>(|scope| {
+    ///         (0 .. 10)
+    ///             .to_stream(scope)
+    ///             .sink_each(move |_time, batch| sink.borrow_mut().extend(batch.drain(..)));
+    ///     });
+    ///     while worker.step() { }
+    ///
+    ///     assert_eq!(*seen.borrow(), (0 .. 10).collect::<Vec<_>>());
+    /// });
+    /// ```
+    fn sink_each<L: FnMut(&G::Timestamp, &mut Vec<D>)+'static>(&self, logic: L);
+
+    /// Passes all of the records of each timestamp to `logic` once the timestamp is complete.
+    ///
+    /// Each worker calls `logic` once for each timestamp at which it received records, with all
+    /// of those records, in order of timestamp.
+    ///
+    /// # Examples
+    /// ```
+    /// use std::rc::Rc;
+    /// use std::cell::RefCell;
+    /// use timely::dataflow::InputHandle;
+    /// use timely::dataflow::operators::{Input, Sink};
+    ///
+    /// timely::execute_directly(|worker| {
+    ///
+    ///     let complete = Rc::new(RefCell::new(Vec::new()));
+    ///     let sink = complete.clone();
+    ///     let mut input = InputHandle::new();
+    ///     worker.dataflow::<u64,_,_>(|scope| {
+    ///         scope
+    ///             .input_from(&mut input)
+    ///             .sink_on_complete(move |time, records| sink.borrow_mut().push((*time, records)));
+    ///     });
+    ///
+    ///     input.send(0);
+    ///     input.send(1);
+    ///     worker.step();
+    ///     assert!(complete.borrow().is_empty());
+    ///
+    ///     input.advance_to(1);
+    ///     input.send(2);
+    ///     input.close();
+    ///     while worker.step() { }
+    ///
+    ///     assert_eq!(*complete.borrow(), vec![(0, vec![0, 1]), (1, vec![2])]);
+    /// });
+    /// ```
+    fn sink_on_complete<L: FnMut(&G::Timestamp, Vec<D>)+'static>(&self, logic: L);
+}
+
+impl<G: Scope, D: Data> Sink<G, D> for Stream<G, D> {
+    fn sink_each<L: FnMut(&G::Timestamp, &mut Vec<D>)+'static>(&self, mut logic: L) {
+        let mut vector = Vec::new();
+        self.sink(Pipeline, "SinkEach", move |input| {
+            input.for_each(|time, data| {
+                data.swap(&mut vector);
+                logic(time.time(), &mut vector);
+                vector.clear();
+            });
+        });
+    }
+
+    fn sink_on_complete<L: FnMut(&G::Timestamp, Vec<D>)+'static>(&self, mut logic: L) {
+        complete_epochs(self, "SinkOnComplete", move |complete, _closed| {
+            for (time, records) in complete {
+                logic(&time, records);
+            }
+        });
+    }
+}
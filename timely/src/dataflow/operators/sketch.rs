@@ -0,0 +1,292 @@
+//! Approximate summaries of the records of each timestamp, in bounded space.
+//!
+//! Each worker summarizes the records it receives at a timestamp in a sketch, and once the
+//! timestamp is complete the sketches of all workers are exchanged to worker zero and merged,
+//! which reports the summary. The space used is independent of the number of records, at the
+//! cost of an approximate result.
+
+use std::hash::Hash;
+
+use crate::{Data, ExchangeData};
+use crate::dataflow::{Stream, Scope};
+use crate::dataflow::channels::pact::{Exchange, Pipeline};
+use crate::dataflow::channels::partitioner::hash;
+use crate::dataflow::operators::Map;
+use crate::dataflow::operators::generic::TimeStash;
+use crate::dataflow::operators::generic::operator::Operator;
+
+/// A HyperLogLog sketch, estimating the number of distinct keys inserted.
+///
+/// With precision `p` the sketch uses `2^p` bytes, and its estimates have a relative standard
+/// error of about `1.04 / 2^(p/2)`, for example 1.6% with precision 12. Sketches of the same
+/// precision can be merged, producing the sketch of the union of their keys.
+#[derive(Debug, Clone, PartialEq, Eq, Abomonation, Serialize, Deserialize)]
+pub struct HyperLogLog {
+    precision: u8,
+    registers: Vec<u8>,
+}
+
+impl HyperLogLog {
+    /// An empty sketch with `2^precision` registers.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `precision` is not between 4 and 16.
+    pub fn new(precision: u8) -> Self {
+        assert!((4 ..= 16).contains(&precision), "precision {} is not between 4 and 16", precision);
+        HyperLogLog { precision, registers: vec![0; 1 << precision] }
+    }
+
+    /// Inserts `key` into the sketch.
+    pub fn insert<K: Hash>(&mut self, key: &K) {
+        let hash = hash(key);
+        let index = (hash >> (64 - self.precision)) as usize;
+        let rest = hash << self.precision;
+        let rank = (rest.leading_zeros() as u8).min(64 - self.precision) + 1;
+        if self.registers[index] < rank {
+            self.registers[index] = rank;
+        }
+    }
+
+    /// Merges `other` into the sketch.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the sketches have different precisions.
+    pub fn merge(&mut self, other: &HyperLogLog) {
+        assert_eq!(self.precision, other.precision, "merging sketches of different precisions");
+        for (register, other) in self.registers.iter_mut().zip(other.registers.iter()) {
+            if *register < *other {
+                *register = *other;
+            }
+        }
+    }
+
+    /// An estimate of the number of distinct keys inserted.
+    pub fn estimate(&self) -> f64 {
+        let registers = self.registers.len() as f64;
+        let alpha = match self.registers.len() {
+            16 => 0.673,
+            32 => 0.697,
+            64 => 0.709,
+            _ => 0.7213 / (1.0 + 1.079 / registers),
+        };
+        let sum = self.registers.iter().map(|register| (-(*register as f64)).exp2()).sum::<f64>();
+        let estimate = alpha * registers * registers / sum;
+        // count empty registers instead, for small numbers of keys.
+        let zeros = self.registers.iter().filter(|register| **register == 0).count();
+        if estimate <= 2.5 * registers && zeros > 0 {
+            registers * (registers / zeros as f64).ln()
+        }
+        else {
+            estimate
+        }
+    }
+}
+
+/// A mergeable sketch of the distribution of the values inserted, answering quantile queries.
+///
+/// Values are held in levels, each of at most `capacity` values, where a value at level `l`
+/// stands for `2^l` inserted values. A full level is sorted and every other value is promoted to
+/// the next level. The rank of a value in the sketch is within about `n / capacity` times the
+/// number of levels of its rank among the `n` values inserted.
+#[derive(Debug, Clone, PartialEq, Eq, Abomonation, Serialize, Deserialize)]
+pub struct QuantileSketch<D> {
+    capacity: usize,
+    levels: Vec<Vec<D>>,
+    // alternates which of each pair of values is promoted.
+    parity: bool,
+}
+
+impl<D: Ord+Clone> QuantileSketch<D> {
+    /// An empty sketch holding at most `capacity` values per level.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `capacity` is less than two.
+    pub fn new(capacity: usize) -> Self {
+        assert!(capacity >= 2, "capacity must be at least two");
+        QuantileSketch { capacity, levels: vec![Vec::new()], parity: false }
+    }
+
+    /// Inserts `value` into the sketch.
+    pub fn insert(&mut self, value: D) {
+        self.levels[0].push(value);
+        self.compact();
+    }
+
+    /// Merges `other` into the sketch.
+    pub fn merge(&mut self, other: QuantileSketch<D>) {
+        for (level, values) in other.levels.into_iter().enumerate() {
+            if self.levels.len() <= level {
+                self.levels.push(Vec::new());
+            }
+            self.levels[level].extend(values);
+        }
+        self.compact();
+    }
+
+    // Promotes every other value of each full level to the next level.
+    fn compact(&mut self) {
+        let mut level = 0;
+        while level < self.levels.len() {
+            if self.levels[level].len() >= self.capacity {
+                let mut values = ::std::mem::take(&mut self.levels[level]);
+                values.sort();
+                // an odd value out remains at this level.
+                if values.len() % 2 == 1 {
+                    self.levels[level].push(values.pop().expect("non-empty level"));
+                }
+                let offset = if self.parity { 1 } else { 0 };
+                self.parity = !self.parity;
+                let promoted = values.into_iter().skip(offset).step_by(2);
+                if self.levels.len() == level + 1 {
+                    self.levels.push(Vec::new());
+                }
+                self.levels[level + 1].extend(promoted);
+            }
+            level += 1;
+        }
+    }
+
+    /// The number of values inserted.
+    pub fn count(&self) -> u64 {
+        self.levels.iter().enumerate().map(|(level, values)| (values.len() as u64) << level).sum()
+    }
+
+    /// An estimate of the value of rank `quantile` times the number of values inserted, for
+    /// `quantile` between zero and one, or `None` if no values were inserted.
+    pub fn quantile(&self, quantile: f64) -> Option<D> {
+        let mut weighted = self.levels
+            .iter()
+            .enumerate()
+            .flat_map(|(level, values)| values.iter().map(move |value| (value, 1u64 << level)))
+            .collect::<Vec<_>>();
+        weighted.sort_by(|x, y| x.0.cmp(y.0));
+        let target = (quantile.clamp(0.0, 1.0) * self.count() as f64).ceil().max(1.0) as u64;
+        let mut rank = 0;
+        for (value, weight) in weighted {
+            rank += weight;
+            if rank >= target {
+                return Some(value.clone());
+            }
+        }
+        None
+    }
+}
+
+/// Approximate summaries of the records of each timestamp.
+pub trait Sketch<G: Scope, D: Data> {
+    /// Estimates the number of distinct records at each timestamp, with a HyperLogLog sketch of
+    /// the given precision, reported by worker zero once the timestamp is complete.
+    ///
+    /// # Examples
+    /// ```
+    /// use timely::dataflow::operators::{ToStream, Capture};
+    /// use timely::dataflow::operators::sketch::Sketch;
+    /// use timely::dataflow::operators::capture::Extract;
+    ///
+    /// let captured = timely::example(|scope| {
+    ///     (0 .. 10000u64)
+    ///         .map(|x| x % 1000)
+    ///         .to_stream(scope)
+    ///         .approx_distinct(12)
+    ///         .capture()
+    /// });
+    ///
+    /// let estimate = captured.extract()[0].1[0];
+    /// assert!(950 <= estimate && estimate <= 1050);
+    /// ```
+    fn approx_distinct(&self, precision: u8) -> Stream<G, u64> where D: Hash;
+
+    /// Estimates the records at each of `quantiles` of the records at each timestamp, with a
+    /// quantile sketch of the given capacity, reported by worker zero once the timestamp is
+    /// complete.
+    ///
+    /// # Examples
+    /// ```
+    /// use timely::dataflow::operators::{ToStream, Capture};
+    /// use timely::dataflow::operators::sketch::Sketch;
+    /// use timely::dataflow::operators::capture::Extract;
+    ///
+    /// let captured = timely::example(|scope| {
+    ///     (0 .. 10000u64)
+    ///         .to_stream(scope)
+    ///         .approx_quantiles(200, vec![0.5, 0.99])
+    ///         .capture()
+    /// });
+    ///
+    /// let quantiles = &captured.extract()[0].1[0];
+    /// assert!(4800 <= quantiles[0] && quantiles[0] <= 5200);
+    /// assert!(9700 <= quantiles[1] && quantiles[1] <= 10000);
+    /// ```
+    fn approx_quantiles(&self, capacity: usize, quantiles: Vec<f64>) -> Stream<G, Vec<D>> where D: ExchangeData+Ord;
+}
+
+impl<G: Scope, D: Data> Sketch<G, D> for Stream<G, D> {
+    fn approx_distinct(&self, precision: u8) -> Stream<G, u64> where D: Hash {
+        merge_sketches(
+            self,
+            "ApproxDistinct",
+            move || HyperLogLog::new(precision),
+            |sketch, record| sketch.insert(&record),
+            |sketch, other| sketch.merge(&other),
+        )
+        .map(|sketch| sketch.estimate().round() as u64)
+    }
+
+    fn approx_quantiles(&self, capacity: usize, quantiles: Vec<f64>) -> Stream<G, Vec<D>> where D: ExchangeData+Ord {
+        merge_sketches(
+            self,
+            "ApproxQuantiles",
+            move || QuantileSketch::new(capacity),
+            |sketch, record| sketch.insert(record),
+            |sketch, other| sketch.merge(other),
+        )
+        .map(move |sketch| quantiles.iter().filter_map(|quantile| sketch.quantile(*quantile)).collect())
+    }
+}
+
+// Sketches the records of each timestamp at each worker, and merges the sketches at worker zero.
+fn merge_sketches<G, D, S, N, I, M>(stream: &Stream<G, D>, name: &str, new: N, insert: I, merge: M) -> Stream<G, S>
+where
+    G: Scope,
+    D: Data,
+    S: ExchangeData,
+    N: Fn()->S+'static,
+    I: Fn(&mut S, D)+'static,
+    M: Fn(&mut S, S)+'static,
+{
+    let new = ::std::rc::Rc::new(new);
+    let new2 = new.clone();
+
+    let mut sketches = TimeStash::new();
+    let mut vector = Vec::new();
+    let local = stream.unary_notify(Pipeline, name, vec![], move |input, output, notificator| {
+        input.for_each(|time, data| {
+            data.swap(&mut vector);
+            let sketch = sketches.entry(time.retain(), notificator, || new());
+            for record in vector.drain(..) {
+                insert(sketch, record);
+            }
+        });
+        sketches.complete(notificator, |time, sketch| {
+            output.session(time).give(sketch);
+        });
+    });
+
+    let mut merged = TimeStash::new();
+    let mut vector = Vec::new();
+    local.unary_notify(Exchange::new(|_| 0), &format!("{}Merge", name), vec![], move |input, output, notificator| {
+        input.for_each(|time, data| {
+            data.swap(&mut vector);
+            let sketch = merged.entry(time.retain(), notificator, || new2());
+            for other in vector.drain(..) {
+                merge(sketch, other);
+            }
+        });
+        merged.complete(notificator, |time, sketch| {
+            output.session(time).give(sketch);
+        });
+    })
+}
@@ -0,0 +1,104 @@
+//! Sorts the records of each timestamp.
+
+use std::collections::HashMap;
+
+use crate::{Data, ExchangeData};
+use crate::dataflow::{Stream, Scope};
+use crate::dataflow::channels::pact::{ParallelizationContract, Partitioned, Pipeline};
+use crate::dataflow::channels::partitioner::Range;
+use crate::dataflow::operators::generic::operator::Operator;
+
+/// Extension trait for sorting the records of each timestamp.
+pub trait Sort<G: Scope, D: Data> {
+    /// Sorts the records of each timestamp in each worker by `key`.
+    ///
+    /// Records are buffered until their timestamp is complete, and then produced in order of
+    /// their keys. Records with equal keys are produced in the order they were received.
+    ///
+    /// # Examples
+    /// ```
+    /// use timely::dataflow::operators::{ToStream, Sort, Capture};
+    /// use timely::dataflow::operators::capture::Extract;
+    ///
+    /// let captured = timely::example(|scope| {
+    ///     vec![5, 3, 8, 1].to_stream(scope)
+    ///                     .sort_by(|x| *x)
+    ///                     .capture()
+    /// });
+    ///
+    /// assert_eq!(captured.extract(), vec![(0, vec![1, 3, 5, 8])]);
+    /// ```
+    fn sort_by<K: Ord, F: Fn(&D)->K+'static>(&self, key: F) -> Stream<G, D>;
+
+    /// Sorts the records of each timestamp across workers by `key`, partitioned into ranges.
+    ///
+    /// Records with keys less than `splitters[0]` are sorted by worker 0, those with keys at
+    /// least `splitters[i-1]` and less than `splitters[i]` by worker `i`, and those with keys at
+    /// least the last splitter by the worker after it. The concatenation of the outputs of the
+    /// workers, in order of their index, is sorted. The splitters should be sorted, and there
+    /// should be one fewer than there are workers; keys that would be routed beyond the last
+    /// worker are sorted by the last worker.
+    ///
+    /// # Examples
+    /// ```
+    /// use timely::dataflow::operators::{ToStream, Sort, Inspect};
+    ///
+    /// timely::execute(timely::Config::process(2), |worker| {
+    ///     let index = worker.index();
+    ///     worker.dataflow::<u64,_,_>(|scope| {
+    ///         (0 .. 100u64).rev()
+    ///             .to_stream(scope)
+    ///             .sort_by_range(vec![50], |x| *x)
+    ///             .inspect(move |x| assert_eq!(*x < 50, index == 0));
+    ///     });
+    /// }).unwrap();
+    /// ```
+    fn sort_by_range<K: Ord+'static, F: Fn(&D)->K+'static>(&self, splitters: Vec<K>, key: F) -> Stream<G, D>
+    where
+        D: ExchangeData;
+}
+
+impl<G: Scope, D: Data> Sort<G, D> for Stream<G, D> {
+    fn sort_by<K: Ord, F: Fn(&D)->K+'static>(&self, key: F) -> Stream<G, D> {
+        sort(self, Pipeline, "SortBy", key)
+    }
+
+    fn sort_by_range<K: Ord+'static, F: Fn(&D)->K+'static>(&self, splitters: Vec<K>, key: F) -> Stream<G, D>
+    where
+        D: ExchangeData,
+    {
+        let key = ::std::rc::Rc::new(key);
+        let route = key.clone();
+        let partitioner = Range::new(splitters, move |datum: &D| route(datum));
+        sort(self, Partitioned::new(partitioner), "SortByRange", move |datum| key(datum))
+    }
+}
+
+/// Buffers the records of each timestamp received through `pact`, and produces them sorted by `key`.
+fn sort<G, D, K, F, P>(stream: &Stream<G, D>, pact: P, name: &str, key: F) -> Stream<G, D>
+where
+    G: Scope,
+    D: Data,
+    K: Ord,
+    F: Fn(&D)->K+'static,
+    P: ParallelizationContract<G::Timestamp, D>,
+{
+    let mut stash = HashMap::new();
+
+    stream.unary_notify(pact, name, vec![], move |input, output, notificator| {
+
+        input.for_each(|time, data| {
+            stash.entry(time.time().clone())
+                 .or_insert_with(Vec::new)
+                 .extend(data.replace(Vec::new()));
+            notificator.notify_at(time.retain());
+        });
+
+        notificator.for_each(|time,_,_| {
+            if let Some(mut records) = stash.remove(time.time()) {
+                records.sort_by_key(|datum| key(datum));
+                output.session(&time).give_vec(&mut records);
+            }
+        });
+    })
+}
@@ -0,0 +1,85 @@
+//! Limits the rate at which records are introduced downstream.
+
+use std::collections::{BTreeMap, VecDeque};
+
+use crate::Data;
+use crate::dataflow::{Stream, Scope};
+use crate::dataflow::channels::pact::Pipeline;
+use crate::dataflow::operators::generic::operator::Operator;
+
+/// Limits the rate at which records are introduced downstream.
+pub trait Throttle<G: Scope, D: Data> {
+    /// Releases at most `limit` records each time the operator is scheduled, oldest time first.
+    ///
+    /// Records are buffered with a capability for their time, so that downstream operators do
+    /// not see their times complete until the records are released. The operator accepts records
+    /// only while fewer than `limit` are buffered, leaving the rest in its input channel, and so
+    /// buffers at most `limit` records plus one batch. Each scheduling releases records of only
+    /// the oldest buffered time, so that downstream frontiers advance by at most one time with
+    /// records per scheduling. The operator reschedules itself while any records remain. A
+    /// driver that introduces records faster than the dataflow can absorb them then paces the
+    /// times downstream operators see complete, instead of filling their buffers.
+    ///
+    /// # Examples
+    /// ```
+    /// use timely::dataflow::operators::{ToStream, Throttle, Capture};
+    /// use timely::dataflow::operators::capture::Extract;
+    ///
+    /// let captured = timely::example(|scope| {
+    ///     (0 .. 10u64)
+    ///         .to_stream(scope)
+    ///         .throttle(3)
+    ///         .capture()
+    /// });
+    ///
+    /// assert_eq!(captured.extract(), vec![(0, (0 .. 10).collect::<Vec<_>>())]);
+    /// ```
+    fn throttle(&self, limit: usize) -> Stream<G, D>;
+}
+
+impl<G: Scope, D: Data> Throttle<G, D> for Stream<G, D> {
+    fn throttle(&self, limit: usize) -> Stream<G, D> {
+        assert!(limit > 0, "throttle limit must be positive");
+        let scope = self.scope();
+        self.unary_frontier(Pipeline, "Throttle", move |_capability, info| {
+
+            let activator = scope.activator_for(&info.address[..]);
+            let mut pending = BTreeMap::new();
+            let mut vector = Vec::new();
+            let mut buffered = 0;
+
+            move |input, output| {
+
+                // accept records while fewer than `limit` are buffered, leaving the rest queued.
+                while buffered < limit {
+                    let (time, data) = match input.next() {
+                        Some(next) => next,
+                        None => break,
+                    };
+                    data.swap(&mut vector);
+                    buffered += vector.len();
+                    pending.entry(time.time().clone())
+                           .or_insert_with(|| (time.retain(), VecDeque::new()))
+                           .1
+                           .extend(vector.drain(..));
+                }
+
+                // release up to `limit` records of the oldest time.
+                if let Some(time) = pending.keys().next().cloned() {
+                    let (capability, records) = pending.get_mut(&time).expect("pending time");
+                    let count = ::std::cmp::min(limit, records.len());
+                    output.session(capability).give_iterator(records.drain(.. count));
+                    buffered -= count;
+                    if records.is_empty() {
+                        pending.remove(&time);
+                    }
+                }
+
+                // records remain buffered, or queued in the input if the buffer was full.
+                if !pending.is_empty() {
+                    activator.activate();
+                }
+            }
+        })
+    }
+}
@@ -3,6 +3,7 @@
 use std::pin::Pin;
 use std::sync::Arc;
 use std::task::{Context, Poll};
+use std::time::Duration;
 
 use crate::dataflow::channels::Message;
 use crate::dataflow::operators::generic::operator::source;
@@ -11,6 +12,11 @@ use crate::dataflow::{Scope, Stream};
 use crate::progress::Timestamp;
 use crate::Data;
 
+/// The initial delay before a source paused by memory pressure checks the pressure again.
+const MIN_BACKOFF: Duration = Duration::from_millis(1);
+/// The longest delay between checks of memory pressure by a paused source.
+const MAX_BACKOFF: Duration = Duration::from_millis(100);
+
 /// Converts to a timely `Stream`.
 pub trait ToStream<T: Timestamp, D: Data> {
     /// Converts to a timely `Stream`.
@@ -39,13 +45,22 @@ impl<T: Timestamp, I: IntoIterator+'static> ToStream<T, I::Item> for I where I::
 
             // Acquire an activator, so that the operator can rescheduled itself.
             let activator = scope.activator_for(&info.address[..]);
+            // Pause while the worker's memory usage exceeds its soft limit, checking back after
+            // exponentially increasing delays rather than spinning.
+            let pressure = scope.memory().pressure();
+            let mut backoff = MIN_BACKOFF;
 
             let mut iterator = self.into_iter().fuse();
             let mut capability = Some(capability);
 
             move |output| {
 
-                if let Some(element) = iterator.next() {
+                if pressure.signaled() {
+                    activator.activate_after(backoff);
+                    backoff = ::std::cmp::min(backoff * 2, MAX_BACKOFF);
+                }
+                else if let Some(element) = iterator.next() {
+                    backoff = MIN_BACKOFF;
                     let mut session = output.session(capability.as_ref().unwrap());
                     session.give(element);
                     for element in iterator.by_ref().take((256 * Message::<T, I::Item>::default_length()) - 1) {
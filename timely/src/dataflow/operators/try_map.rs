@@ -0,0 +1,180 @@
+//! Fallible transformations, whose failures are routed to a stream of dead letters.
+
+use std::fmt::Display;
+
+use crate::Data;
+use crate::dataflow::{Scope, Stream};
+use crate::dataflow::channels::pact::Pipeline;
+use crate::dataflow::operators::generic::builder_rc::OperatorBuilder;
+
+/// A record whose transformation failed, with the error of its last attempt.
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord, Abomonation, Serialize, Deserialize)]
+pub struct DeadLetter<D> {
+    /// The record.
+    pub record: D,
+    /// The error of the last attempt, as a string.
+    pub error: String,
+    /// The number of attempts made.
+    pub attempts: usize,
+}
+
+/// How a fallible transformation responds to failures.
+#[derive(Debug, Clone, PartialEq)]
+pub struct FailurePolicy {
+    retries: usize,
+    max_failure_rate: Option<f64>,
+    min_records: u64,
+}
+
+impl Default for FailurePolicy {
+    fn default() -> Self {
+        FailurePolicy { retries: 0, max_failure_rate: None, min_records: 100 }
+    }
+}
+
+impl FailurePolicy {
+    /// Sets the number of further attempts made for a record before it is a dead letter.
+    pub fn retries(mut self, retries: usize) -> Self {
+        self.retries = retries;
+        self
+    }
+
+    /// Panics, aborting the dataflow, once the fraction of records that are dead letters at a
+    /// worker exceeds `rate`, after at least `min_records` records.
+    pub fn max_failure_rate(mut self, rate: f64, min_records: u64) -> Self {
+        self.max_failure_rate = Some(rate);
+        self.min_records = min_records;
+        self
+    }
+}
+
+/// Transformations that may fail for some records.
+pub trait TryMap<S: Scope, D: Data> {
+    /// Transforms each record with `logic`, producing the records it transforms and the dead
+    /// letters of those it fails to transform under `policy`.
+    ///
+    /// # Examples
+    /// ```
+    /// use timely::dataflow::operators::{ToStream, Capture};
+    /// use timely::dataflow::operators::try_map::{TryMap, FailurePolicy};
+    /// use timely::dataflow::operators::capture::Extract;
+    ///
+    /// let (parsed, dead) = timely::example(|scope| {
+    ///     let (parsed, dead) = vec!["1", "two", "3"]
+    ///         .into_iter()
+    ///         .map(|x| x.to_string())
+    ///         .to_stream(scope)
+    ///         .try_map(FailurePolicy::default(), |text: &String| text.parse::<u64>());
+    ///     (parsed.capture(), dead.capture())
+    /// });
+    ///
+    /// assert_eq!(parsed.extract(), vec![(0, vec![1, 3])]);
+    /// let dead = dead.extract();
+    /// assert_eq!(dead[0].1[0].record, "two");
+    /// assert_eq!(dead[0].1[0].attempts, 1);
+    /// ```
+    fn try_map<D2, E, L>(&self, policy: FailurePolicy, logic: L) -> (Stream<S, D2>, Stream<S, DeadLetter<D>>)
+    where
+        D2: Data,
+        E: Display,
+        L: FnMut(&D)->Result<D2, E>+'static;
+
+    /// Transforms each record into any number of records with `logic`, producing the records it
+    /// produces and the dead letters of those it fails to transform under `policy`.
+    ///
+    /// # Examples
+    /// ```
+    /// use timely::dataflow::operators::{ToStream, Capture};
+    /// use timely::dataflow::operators::try_map::{TryMap, FailurePolicy};
+    /// use timely::dataflow::operators::capture::Extract;
+    ///
+    /// let (words, dead) = timely::example(|scope| {
+    ///     let (words, dead) = vec!["a b", "", "c"]
+    ///         .into_iter()
+    ///         .map(|x| x.to_string())
+    ///         .to_stream(scope)
+    ///         .try_flat_map(FailurePolicy::default().retries(2), |line: &String| {
+    ///             if line.is_empty() { Err("empty line") }
+    ///             else { Ok(line.split(' ').map(|word| word.to_string()).collect::<Vec<_>>()) }
+    ///         });
+    ///     (words.capture(), dead.capture())
+    /// });
+    ///
+    /// assert_eq!(words.extract(), vec![(0, vec!["a".to_string(), "b".to_string(), "c".to_string()])]);
+    /// assert_eq!(dead.extract()[0].1[0].attempts, 3);
+    /// ```
+    fn try_flat_map<D2, E, I, L>(&self, policy: FailurePolicy, logic: L) -> (Stream<S, D2>, Stream<S, DeadLetter<D>>)
+    where
+        D2: Data,
+        E: Display,
+        I: IntoIterator<Item=D2>,
+        L: FnMut(&D)->Result<I, E>+'static;
+}
+
+impl<S: Scope, D: Data> TryMap<S, D> for Stream<S, D> {
+    fn try_map<D2, E, L>(&self, policy: FailurePolicy, mut logic: L) -> (Stream<S, D2>, Stream<S, DeadLetter<D>>)
+    where
+        D2: Data,
+        E: Display,
+        L: FnMut(&D)->Result<D2, E>+'static,
+    {
+        self.try_flat_map(policy, move |record| logic(record).map(Some))
+    }
+
+    fn try_flat_map<D2, E, I, L>(&self, policy: FailurePolicy, mut logic: L) -> (Stream<S, D2>, Stream<S, DeadLetter<D>>)
+    where
+        D2: Data,
+        E: Display,
+        I: IntoIterator<Item=D2>,
+        L: FnMut(&D)->Result<I, E>+'static,
+    {
+        let mut builder = OperatorBuilder::new("TryMap".to_owned(), self.scope());
+
+        let mut input = builder.new_input(self, Pipeline);
+        let (mut output1, stream1) = builder.new_output();
+        let (mut output2, stream2) = builder.new_output();
+
+        builder.build(move |_| {
+            let mut vector = Vec::new();
+            let mut records = 0u64;
+            let mut failures = 0u64;
+            move |_frontiers| {
+                let mut output1_handle = output1.activate();
+                let mut output2_handle = output2.activate();
+
+                input.for_each(|time, data| {
+                    data.swap(&mut vector);
+                    let mut out1 = output1_handle.session(&time);
+                    let mut out2 = output2_handle.session(&time);
+                    for datum in vector.drain(..) {
+                        records += 1;
+                        let mut attempts = 0;
+                        loop {
+                            attempts += 1;
+                            match logic(&datum) {
+                                Ok(results) => {
+                                    out1.give_iterator(results.into_iter());
+                                    break;
+                                },
+                                Err(error) if attempts > policy.retries => {
+                                    failures += 1;
+                                    out2.give(DeadLetter { record: datum, error: error.to_string(), attempts });
+                                    break;
+                                },
+                                Err(_) => { },
+                            }
+                        }
+                    }
+                });
+
+                if let Some(rate) = policy.max_failure_rate {
+                    if records >= policy.min_records && failures as f64 > rate * records as f64 {
+                        panic!("TryMap: {} of {} records failed, exceeding the failure rate {}", failures, records, rate);
+                    }
+                }
+            }
+        });
+
+        (stream1, stream2)
+    }
+}
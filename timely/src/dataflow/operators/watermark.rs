@@ -0,0 +1,247 @@
+//! Create new `Streams` of records with event times, whose epochs advance with watermarks.
+//!
+//! Sources whose records carry their own fine-grained timestamps, for example the times at which
+//! sensors took their readings, receive them somewhat out of order, and cannot know when they
+//! have seen all records up to a time. A watermark is an estimate of a time below which no more
+//! records will arrive, generated from the event times observed so far. An input with event
+//! times stamps each record with its event time, and advances its capability to the watermark,
+//! completing the epochs before it. Records that arrive behind the watermark are late, and are
+//! returned to the sender rather than introduced into the dataflow.
+
+use std::rc::Rc;
+use std::cell::RefCell;
+use std::time::Duration;
+
+use crate::Data;
+use crate::logging_core::time::{Instant, since_unix_epoch};
+use crate::scheduling::Activator;
+use crate::dataflow::{Stream, Scope};
+use crate::dataflow::operators::clock::timed_source;
+
+/// Generates watermarks from observed event times.
+///
+/// The watermark trails the greatest event time observed by a fixed bound on the disorder of the
+/// records, and never decreases. A source that observes no records for an idle timeout, if one is
+/// set, is considered idle, and its watermark instead trails the system clock by the bound, so
+/// that it does not hold back the frontier. This suits event times that are durations since the
+/// Unix epoch, as produced by `SystemTime`. On `wasm32-unknown-unknown` the system clock is the
+/// one installed with `timely::logging_core::time::set_unix_clock`.
+///
+/// # Examples
+/// ```
+/// use std::time::Duration;
+/// use timely::dataflow::operators::watermark::Watermarks;
+///
+/// let mut watermarks = Watermarks::bounded(Duration::from_secs(5));
+/// watermarks.observe(Duration::from_secs(10));
+/// watermarks.observe(Duration::from_secs(7));
+/// assert_eq!(watermarks.watermark(), Duration::from_secs(5));
+/// watermarks.observe(Duration::from_secs(20));
+/// assert_eq!(watermarks.watermark(), Duration::from_secs(15));
+/// assert!(watermarks.is_late(Duration::from_secs(12)));
+/// ```
+#[derive(Clone, Debug)]
+pub struct Watermarks {
+    bound: Duration,
+    idle: Option<Duration>,
+    // the greatest event time observed.
+    greatest: Duration,
+    // the instant at which the last event time was observed.
+    observed: Instant,
+    watermark: Duration,
+}
+
+impl Watermarks {
+    /// Watermarks trailing the greatest observed event time by `bound`.
+    ///
+    /// Records whose event times trail the greatest observed event time by no more than `bound`
+    /// are never late.
+    pub fn bounded(bound: Duration) -> Self {
+        Watermarks {
+            bound,
+            idle: None,
+            greatest: Duration::default(),
+            observed: Instant::now(),
+            watermark: Duration::default(),
+        }
+    }
+
+    /// Considers the source idle once it observes no event times for `timeout`.
+    ///
+    /// While idle, the watermark trails the system clock by the bound, rather than the greatest
+    /// observed event time. The source is no longer idle once it observes an event time.
+    ///
+    /// # Examples
+    /// ```
+    /// use std::time::{Duration, SystemTime, UNIX_EPOCH};
+    /// use timely::dataflow::operators::watermark::Watermarks;
+    ///
+    /// let mut watermarks = Watermarks::bounded(Duration::from_secs(1)).idle_after(Duration::from_millis(10));
+    /// watermarks.observe(Duration::from_secs(100));
+    /// assert_eq!(watermarks.watermark(), Duration::from_secs(99));
+    ///
+    /// std::thread::sleep(Duration::from_millis(20));
+    /// let now = SystemTime::now().duration_since(UNIX_EPOCH).unwrap();
+    /// assert!(watermarks.watermark() > now - Duration::from_secs(2));
+    /// ```
+    pub fn idle_after(mut self, timeout: Duration) -> Self {
+        self.idle = Some(timeout);
+        self
+    }
+
+    /// Records the observation of a record with event time `time`.
+    pub fn observe(&mut self, time: Duration) {
+        if self.greatest < time {
+            self.greatest = time;
+        }
+        self.observed = Instant::now();
+    }
+
+    /// True if the source has been idle for the timeout.
+    pub fn is_idle(&self) -> bool {
+        self.idle.map(|timeout| self.observed.elapsed() >= timeout).unwrap_or(false)
+    }
+
+    /// The time remaining until the source is idle, or the timeout itself once it is.
+    ///
+    /// A source should check its watermark again after this time, as the watermark of an idle
+    /// source advances with the system clock. This is `None` if there is no idle timeout.
+    pub fn until_idle(&self) -> Option<Duration> {
+        self.idle.map(|timeout| timeout.checked_sub(self.observed.elapsed()).filter(|remaining| remaining > &Duration::default()).unwrap_or(timeout))
+    }
+
+    /// The current watermark.
+    ///
+    /// No more records are expected with event times less than the watermark.
+    pub fn watermark(&mut self) -> Duration {
+        let estimate = if self.is_idle() {
+            since_unix_epoch()
+        }
+        else {
+            self.greatest
+        };
+        let estimate = estimate.checked_sub(self.bound).unwrap_or_default();
+        if self.watermark < estimate {
+            self.watermark = estimate;
+        }
+        self.watermark
+    }
+
+    /// True if a record with event time `time` would be late, arriving behind the watermark.
+    pub fn is_late(&mut self, time: Duration) -> bool {
+        time < self.watermark()
+    }
+}
+
+/// Create a new `Stream` and `EventTimeHandle` whose epochs advance with watermarks.
+pub trait EventTimeInput : Scope<Timestamp=Duration> {
+    /// Create a new `Stream` and `EventTimeHandle` through which to supply records with event times.
+    ///
+    /// Records sent through the handle are stamped with their event times, and the input advances
+    /// its frontier to the watermark generated by `watermarks` from these event times, without any
+    /// action by the driver. Records behind the watermark are returned by the handle. Each worker
+    /// generates its own watermarks, and the frontier of the stream is the least of them.
+    ///
+    /// # Examples
+    /// ```
+    /// use std::rc::Rc;
+    /// use std::cell::RefCell;
+    /// use std::time::Duration;
+    /// use timely::dataflow::operators::{Inspect, Probe};
+    /// use timely::dataflow::operators::watermark::{EventTimeInput, Watermarks};
+    ///
+    /// timely::execute_directly(|worker| {
+    ///
+    ///     let seen = Rc::new(RefCell::new(Vec::new()));
+    ///     let seen2 = seen.clone();
+    ///     let (mut input, probe) = worker.dataflow(|scope| {
+    ///         let (input, stream) = scope.new_event_time_input(Watermarks::bounded(Duration::from_secs(5)));
+    ///         let probe = stream.inspect_time(move |t, x| seen2.borrow_mut().push((*t, *x)))
+    ///                           .probe();
+    ///         (input, probe)
+    ///     });
+    ///
+    ///     let secs = Duration::from_secs;
+    ///     assert_eq!(input.send(secs(10), "a"), Ok(()));
+    ///     assert_eq!(input.send(secs(7), "b"), Ok(()));
+    ///     assert_eq!(input.send(secs(20), "c"), Ok(()));
+    ///     // the watermark has advanced to fifteen seconds.
+    ///     assert_eq!(input.send(secs(12), "d"), Err("d"));
+    ///
+    ///     // epochs before the watermark complete, and later epochs remain open.
+    ///     let watermark = input.watermark();
+    ///     worker.step_while(|| probe.less_than(&watermark));
+    ///     assert!(probe.less_equal(&watermark));
+    ///     seen.borrow_mut().sort();
+    ///     assert_eq!(*seen.borrow(), vec![(secs(7), "b"), (secs(10), "a"), (secs(20), "c")]);
+    /// });
+    /// ```
+    fn new_event_time_input<D: Data>(&mut self, watermarks: Watermarks) -> (EventTimeHandle<D>, Stream<Self, D>);
+}
+
+impl<G: Scope<Timestamp=Duration>> EventTimeInput for G {
+    fn new_event_time_input<D: Data>(&mut self, watermarks: Watermarks) -> (EventTimeHandle<D>, Stream<G, D>) {
+
+        let shared = Rc::new(RefCell::new(EventTimeState { watermarks, buffer: Vec::new(), closed: false }));
+        // No record was behind the watermark when sent, and so none is behind the capability.
+        let (activator, stream) = timed_source(self, "EventTimeInput", shared.clone(), |state: &mut EventTimeState<D>, buffer| {
+            ::std::mem::swap(buffer, &mut state.buffer);
+            if state.closed { None }
+            else { Some((state.watermarks.watermark(), state.watermarks.until_idle())) }
+        });
+
+        (EventTimeHandle { shared, activator }, stream)
+    }
+}
+
+/// Watermarks and buffered records shared between an `EventTimeHandle` and its operator.
+struct EventTimeState<D> {
+    watermarks: Watermarks,
+    buffer: Vec<(Duration, D)>,
+    closed: bool,
+}
+
+/// A handle to an event-time input `Stream`, used to introduce data to a timely dataflow computation.
+///
+/// Dropping the handle closes the input.
+pub struct EventTimeHandle<D> {
+    shared: Rc<RefCell<EventTimeState<D>>>,
+    activator: Activator,
+}
+
+impl<D> EventTimeHandle<D> {
+
+    /// Sends one record with event time `time` into the corresponding timely dataflow `Stream`.
+    ///
+    /// Returns the record if it is late, with an event time behind the watermark.
+    pub fn send(&mut self, time: Duration, data: D) -> Result<(), D> {
+        let mut shared = self.shared.borrow_mut();
+        if shared.watermarks.is_late(time) {
+            return Err(data);
+        }
+        shared.watermarks.observe(time);
+        if shared.buffer.is_empty() {
+            self.activator.activate();
+        }
+        shared.buffer.push((time, data));
+        Ok(())
+    }
+
+    /// Reports the current watermark.
+    pub fn watermark(&self) -> Duration {
+        self.shared.borrow_mut().watermarks.watermark()
+    }
+
+    /// Closes the input.
+    ///
+    /// This method allows timely dataflow to issue all progress notifications blocked by this input
+    /// and to begin to shut down operators, as this input can no longer produce data.
+    pub fn close(self) { }
+}
+
+impl<D> Drop for EventTimeHandle<D> {
+    fn drop(&mut self) {
+        self.shared.borrow_mut().closed = true;
+        self.activator.activate();
+    }
+}
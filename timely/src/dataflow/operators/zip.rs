@@ -0,0 +1,58 @@
+//! Pairs the records of two streams by their position within each timestamp.
+
+use crate::Data;
+use crate::dataflow::{Stream, Scope};
+use crate::dataflow::channels::pact::Pipeline;
+use crate::dataflow::operators::generic::TimeStash;
+use crate::dataflow::operators::generic::operator::Operator;
+
+/// Pairs the records of two streams by their position within each timestamp.
+pub trait Zip<G: Scope, D: Data> {
+    /// Pairs the i-th record of `self` with the i-th record of `other`, for each timestamp.
+    ///
+    /// Records are paired at each worker, in the order the worker receives them, once the
+    /// timestamp is complete for both inputs. Neither input is exchanged, so the streams should
+    /// be partitioned alike. As with `Iterator::zip`, the records of the longer input at a
+    /// timestamp that have no partner are discarded.
+    ///
+    /// # Examples
+    /// ```
+    /// use timely::dataflow::operators::{ToStream, Zip, Capture};
+    /// use timely::dataflow::operators::capture::Extract;
+    ///
+    /// let captured = timely::example(|scope| {
+    ///     let letters = vec!['a', 'b', 'c'].to_stream(scope);
+    ///     (0 .. 4).to_stream(scope)
+    ///             .zip(&letters)
+    ///             .capture()
+    /// });
+    ///
+    /// assert_eq!(captured.extract(), vec![(0, vec![(0, 'a'), (1, 'b'), (2, 'c')])]);
+    /// ```
+    fn zip<D2: Data>(&self, other: &Stream<G, D2>) -> Stream<G, (D, D2)>;
+}
+
+impl<G: Scope, D: Data> Zip<G, D> for Stream<G, D> {
+    fn zip<D2: Data>(&self, other: &Stream<G, D2>) -> Stream<G, (D, D2)> {
+
+        let mut stash = TimeStash::new();
+
+        self.binary_notify(other, Pipeline, Pipeline, "Zip", vec![], move |input1, input2, output, notificator| {
+
+            // stash the records of each input in arrival order, by timestamp.
+            input1.for_each(|time, data| {
+                let (records1, _): &mut (Vec<D>, Vec<D2>) = stash.entry(time.retain(), notificator, Default::default);
+                records1.extend(data.replace(Vec::new()));
+            });
+            input2.for_each(|time, data| {
+                let (_, records2) = stash.entry(time.retain(), notificator, Default::default);
+                records2.extend(data.replace(Vec::new()));
+            });
+
+            // pair the records of each complete timestamp.
+            stash.complete(notificator, |time, (records1, records2)| {
+                output.session(time).give_iterator(records1.into_iter().zip(records2));
+            });
+        })
+    }
+}
@@ -0,0 +1,255 @@
+//! Descriptions of dataflows, separate from their construction.
+//!
+//! A `Plan` names the operators of a dataflow and the edges between them, without constructing
+//! anything. Plans can be inspected, rewritten, and serialized, for example by a coordinator that
+//! sends the same plan to every worker, and are then instantiated in a scope with the operators
+//! and types of a `dynamic::Registry`.
+
+use std::collections::{BTreeMap, HashMap, HashSet};
+use std::convert::TryFrom;
+
+use crate::dataflow::Scope;
+use crate::dataflow::dynamic::{DynStream, Registry};
+
+/// A node of a plan.
+#[derive(Clone, Debug, Eq, PartialEq, Serialize, Deserialize)]
+pub enum Node {
+    /// A named input, supplied when the plan is instantiated.
+    Input(String),
+    /// A registered operator, applied to the outputs of earlier nodes.
+    Operator {
+        /// The name of the operator in the registry.
+        name: String,
+        /// The indices of the nodes whose outputs are its inputs.
+        inputs: Vec<usize>,
+    },
+}
+
+/// A dataflow of registered operators, with named inputs and outputs.
+///
+/// Nodes only refer to earlier nodes, so the order of the nodes is an order in which they can be
+/// constructed. Deserializing a plan whose nodes or outputs refer to other nodes fails.
+///
+/// # Examples
+/// ```
+/// use timely::dataflow::operators::{ToStream, Map, Exchange, Capture};
+/// use timely::dataflow::operators::capture::Extract;
+/// use timely::dataflow::dynamic::Registry;
+/// use timely::dataflow::plan::Plan;
+///
+/// // a plan, perhaps deserialized from a description sent by a coordinator.
+/// let mut plan = Plan::new();
+/// let input = plan.input("numbers");
+/// let exchanged = plan.operator("exchange", &[input]);
+/// let exchanged = plan.operator("exchange", &[exchanged]);
+/// let squared = plan.operator("square", &[exchanged]);
+/// let _unused = plan.operator("square", &[input]);
+/// plan.output("squares", squared);
+///
+/// plan.collapse_idempotent(&["exchange"]);
+/// plan.eliminate_dead();
+/// assert_eq!(plan.nodes().len(), 3);
+///
+/// let captured = timely::example(move |scope| {
+///     let mut registry = Registry::new();
+///     registry.register_type::<u64>("u64");
+///     registry.register_unary("exchange", |stream| stream.exchange(|x: &u64| *x));
+///     registry.register_unary("square", |stream| stream.map(|x: u64| x * x));
+///
+///     let numbers = registry.erase(&(0 .. 4u64).to_stream(scope)).unwrap();
+///     let inputs = vec![("numbers".to_owned(), numbers)].into_iter().collect();
+///     let outputs = plan.instantiate(&registry, &inputs).unwrap();
+///     registry.recover::<u64>(&outputs["squares"]).unwrap().capture()
+/// });
+///
+/// assert_eq!(captured.extract(), vec![(0, vec![0, 1, 4, 9])]);
+/// ```
+#[derive(Clone, Debug, Default, Eq, PartialEq, Serialize, Deserialize)]
+#[serde(try_from = "Parts")]
+pub struct Plan {
+    nodes: Vec<Node>,
+    outputs: BTreeMap<String, usize>,
+}
+
+// The fields of a deserialized plan, before their indices are checked.
+#[derive(Deserialize)]
+struct Parts {
+    nodes: Vec<Node>,
+    outputs: BTreeMap<String, usize>,
+}
+
+impl TryFrom<Parts> for Plan {
+    type Error = String;
+    fn try_from(parts: Parts) -> Result<Self, String> {
+        for (index, node) in parts.nodes.iter().enumerate() {
+            if let Node::Operator { name, inputs } = node {
+                if let Some(input) = inputs.iter().find(|input| **input >= index) {
+                    return Err(format!("operator {:?} at node {} has input {}, which is not an earlier node", name, index, input));
+                }
+            }
+        }
+        if let Some((name, index)) = parts.outputs.iter().find(|(_, index)| **index >= parts.nodes.len()) {
+            return Err(format!("output {:?} is node {}, which does not exist", name, index));
+        }
+        Ok(Plan { nodes: parts.nodes, outputs: parts.outputs })
+    }
+}
+
+impl Plan {
+    /// An empty plan.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// The nodes of the plan, in order of their index.
+    pub fn nodes(&self) -> &[Node] {
+        &self.nodes
+    }
+
+    /// The named outputs of the plan, and the indices of the nodes producing them.
+    pub fn outputs(&self) -> &BTreeMap<String, usize> {
+        &self.outputs
+    }
+
+    /// Adds the input `name`, returning the index of its node.
+    pub fn input(&mut self, name: &str) -> usize {
+        self.nodes.push(Node::Input(name.to_owned()));
+        self.nodes.len() - 1
+    }
+
+    /// Adds the operator `name` applied to the outputs of `inputs`, returning the index of its node.
+    ///
+    /// # Panics
+    ///
+    /// Panics if any of `inputs` is not the index of a node.
+    pub fn operator(&mut self, name: &str, inputs: &[usize]) -> usize {
+        for input in inputs {
+            assert!(*input < self.nodes.len(), "input {} is not a node", input);
+        }
+        self.nodes.push(Node::Operator { name: name.to_owned(), inputs: inputs.to_vec() });
+        self.nodes.len() - 1
+    }
+
+    /// Names the output of node `index` as the output `name`.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `index` is not the index of a node.
+    pub fn output(&mut self, name: &str, index: usize) {
+        assert!(index < self.nodes.len(), "output {} is not a node", index);
+        self.outputs.insert(name.to_owned(), index);
+    }
+
+    /// Bypasses each unary operator of one of `names` applied to the output of the same operator,
+    /// for example an exchange by a key of records already exchanged by that key.
+    ///
+    /// The bypassed nodes remain, unused, until `eliminate_dead` is called.
+    pub fn collapse_idempotent(&mut self, names: &[&str]) {
+        // the node each node's uses are redirected to; earlier nodes are already redirected.
+        let mut forward = Vec::with_capacity(self.nodes.len());
+        for index in 0 .. self.nodes.len() {
+            if let Node::Operator { inputs, .. } = &mut self.nodes[index] {
+                for input in inputs.iter_mut() {
+                    *input = forward[*input];
+                }
+            }
+            let target = match &self.nodes[index] {
+                Node::Operator { name, inputs } if inputs.len() == 1 && names.contains(&&name[..]) => {
+                    match &self.nodes[inputs[0]] {
+                        Node::Operator { name: inner, inputs: inner_inputs } if inner == name && inner_inputs.len() == 1 => inputs[0],
+                        _ => index,
+                    }
+                },
+                _ => index,
+            };
+            forward.push(target);
+        }
+        for output in self.outputs.values_mut() {
+            *output = forward[*output];
+        }
+    }
+
+    /// Removes the operators whose outputs do not contribute to a named output.
+    ///
+    /// Inputs are retained, so that the plan accepts the same inputs. Indices of retained nodes
+    /// may change.
+    pub fn eliminate_dead(&mut self) {
+        let mut live = HashSet::new();
+        let mut todo = self.outputs.values().cloned().collect::<Vec<_>>();
+        while let Some(index) = todo.pop() {
+            if live.insert(index) {
+                if let Node::Operator { inputs, .. } = &self.nodes[index] {
+                    todo.extend(inputs.iter().cloned());
+                }
+            }
+        }
+        let mut forward = HashMap::new();
+        let mut nodes = Vec::new();
+        for (index, mut node) in self.nodes.drain(..).enumerate() {
+            let retain = match &mut node {
+                Node::Input(_) => true,
+                Node::Operator { inputs, .. } if live.contains(&index) => {
+                    for input in inputs.iter_mut() {
+                        *input = forward[input];
+                    }
+                    true
+                },
+                Node::Operator { .. } => false,
+            };
+            if retain {
+                forward.insert(index, nodes.len());
+                nodes.push(node);
+            }
+        }
+        self.nodes = nodes;
+        for output in self.outputs.values_mut() {
+            *output = forward[output];
+        }
+    }
+
+    /// Constructs the plan in the scope of `inputs`, with the operators of `registry`, returning
+    /// its named outputs.
+    ///
+    /// Returns an error if an input is not supplied, or if an operator cannot be applied.
+    pub fn instantiate<G: Scope>(&self, registry: &Registry<G>, inputs: &HashMap<String, DynStream<G>>) -> Result<HashMap<String, DynStream<G>>, String> {
+        let mut streams: Vec<DynStream<G>> = Vec::with_capacity(self.nodes.len());
+        for node in self.nodes.iter() {
+            let stream = match node {
+                Node::Input(name) => inputs.get(name).cloned().ok_or_else(|| format!("input {:?} not supplied", name))?,
+                Node::Operator { name, inputs } => {
+                    let inputs = inputs.iter().map(|input| streams[*input].clone()).collect::<Vec<_>>();
+                    registry.apply(name, &inputs)?
+                },
+            };
+            streams.push(stream);
+        }
+        Ok(self.outputs.iter().map(|(name, index)| (name.clone(), streams[*index].clone())).collect())
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use std::collections::BTreeMap;
+    use std::convert::TryFrom;
+
+    use super::{Node, Parts, Plan};
+
+    #[test]
+    fn dangling_indices() {
+        let operator = |inputs: Vec<usize>| Node::Operator { name: "square".to_owned(), inputs };
+        let outputs = |index: usize| vec![("squares".to_owned(), index)].into_iter().collect::<BTreeMap<_, _>>();
+
+        let valid = Parts { nodes: vec![Node::Input("numbers".to_owned()), operator(vec![0])], outputs: outputs(1) };
+        assert!(Plan::try_from(valid).is_ok());
+
+        // an operator reading a node that does not exist, or from itself.
+        let dangling = Parts { nodes: vec![Node::Input("numbers".to_owned()), operator(vec![2])], outputs: outputs(1) };
+        assert!(Plan::try_from(dangling).is_err());
+        let cyclic = Parts { nodes: vec![Node::Input("numbers".to_owned()), operator(vec![1])], outputs: outputs(1) };
+        assert!(Plan::try_from(cyclic).is_err());
+
+        // an output that is not a node.
+        let dangling = Parts { nodes: vec![Node::Input("numbers".to_owned()), operator(vec![0])], outputs: outputs(2) };
+        assert_eq!(Plan::try_from(dangling), Err("output \"squares\" is node 2, which does not exist".to_owned()));
+    }
+}
@@ -0,0 +1,78 @@
+//! Typed resources registered with a worker, for use by its operators.
+//!
+//! Resources such as connection pools, configuration, or caches are registered with the worker
+//! once, and operators retrieve them by type from their scope, typically in the constructor
+//! passed to an operator builder, rather than capturing them when the dataflow is assembled.
+//! Resources remain registered across dataflows until removed.
+
+use std::any::{Any, TypeId};
+use std::collections::HashMap;
+use std::rc::Rc;
+
+/// The resources registered with a worker, at most one of each type.
+///
+/// # Examples
+/// ```
+/// use timely::dataflow::operators::{ToStream, Inspect};
+/// use timely::dataflow::operators::generic::operator::Operator;
+/// use timely::dataflow::channels::pact::Pipeline;
+/// use timely::worker::AsWorker;
+///
+/// struct Greeting(String);
+///
+/// timely::execute_directly(|worker| {
+///
+///     worker.resources().insert(Greeting("hello".to_owned()));
+///
+///     worker.dataflow::<u64,_,_>(|scope| {
+///         (0 .. 3)
+///             .to_stream(scope)
+///             .unary(Pipeline, "Greet", |_capability, _info| {
+///                 let greeting = scope.resources().get::<Greeting>().expect("greeting registered");
+///                 move |input, output| {
+///                     input.for_each(|time, data| {
+///                         let mut session = output.session(&time);
+///                         for datum in data.iter() {
+///                             session.give(format!("{} {}", greeting.0, datum));
+///                         }
+///                     });
+///                 }
+///             })
+///             .inspect(|x: &String| assert!(x.starts_with("hello")));
+///     });
+/// });
+/// ```
+#[derive(Default)]
+pub struct Resources {
+    resources: HashMap<TypeId, Rc<dyn Any>>,
+}
+
+impl Resources {
+    /// Registers `resource`, returning any previously registered resource of the same type.
+    pub fn insert<R: 'static>(&mut self, resource: R) -> Option<Rc<R>> {
+        self.resources
+            .insert(TypeId::of::<R>(), Rc::new(resource))
+            .map(|previous| previous.downcast().expect("resource registered with the wrong type"))
+    }
+
+    /// The registered resource of type `R`, if any.
+    pub fn get<R: 'static>(&self) -> Option<Rc<R>> {
+        self.resources
+            .get(&TypeId::of::<R>())
+            .map(|resource| resource.clone().downcast().expect("resource registered with the wrong type"))
+    }
+
+    /// Indicates whether a resource of type `R` is registered.
+    pub fn contains<R: 'static>(&self) -> bool {
+        self.resources.contains_key(&TypeId::of::<R>())
+    }
+
+    /// Removes the registered resource of type `R`, returning it if it existed.
+    ///
+    /// Operators that retrieved the resource retain it.
+    pub fn remove<R: 'static>(&mut self) -> Option<Rc<R>> {
+        self.resources
+            .remove(&TypeId::of::<R>())
+            .map(|resource| resource.downcast().expect("resource registered with the wrong type"))
+    }
+}
@@ -70,6 +70,21 @@ where
     fn log_register(&self) -> ::std::cell::RefMut<crate::logging_core::Registry<crate::logging::WorkerIdentifier>> {
         self.parent.log_register()
     }
+    fn topology(&self) -> ::std::cell::RefMut<crate::dataflow::topology::Topology> {
+        self.parent.topology()
+    }
+    fn metrics(&self) -> ::std::cell::RefMut<crate::dataflow::metrics::Metrics> {
+        self.parent.metrics()
+    }
+    fn state(&self) -> ::std::cell::RefMut<crate::dataflow::state::State> {
+        self.parent.state()
+    }
+    fn memory(&self) -> ::std::cell::RefMut<crate::dataflow::memory::Memory> {
+        self.parent.memory()
+    }
+    fn resources(&self) -> ::std::cell::RefMut<crate::dataflow::resources::Resources> {
+        self.parent.resources()
+    }
 }
 
 impl<'a, G, T> Scheduler for Child<'a, G, T>
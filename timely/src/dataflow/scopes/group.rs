@@ -0,0 +1,140 @@
+//! A named group of operators, built directly in its parent scope.
+
+use std::rc::Rc;
+use std::cell::RefCell;
+
+use crate::communication::{Data, Push, Pull, Message};
+use crate::communication::allocator::thread::{ThreadPusher, ThreadPuller};
+use crate::scheduling::Scheduler;
+use crate::scheduling::activate::Activations;
+use crate::progress::{Timestamp, Operate, SubgraphBuilder};
+use crate::progress::{Source, Target};
+use crate::progress::timestamp::Refines;
+use crate::worker::{AsWorker, Config};
+
+use super::{Child, ScopeParent, Scope};
+
+/// A `Group` adds operators directly to its parent scope, recording that they belong to a named
+/// group of operators.
+///
+/// Unlike a region, a group is not a subgraph: its operators are scheduled and their progress
+/// tracked by the parent scope as if they had been added to it directly, and streams move in and
+/// out of the group without any intervening operators. The group is visible in the dataflow's
+/// `Topology`, which records the group of each of its operators.
+pub struct Group<G: Scope> {
+    /// The scope to which operators are added.
+    pub parent: G,
+    /// The name of the group.
+    name: Rc<String>,
+}
+
+impl<G: Scope> Group<G> {
+    /// Groups operators added to `parent` under `name`.
+    pub fn new(parent: G, name: &str) -> Self {
+        Group { parent, name: Rc::new(name.to_owned()) }
+    }
+    /// This worker's unique identifier.
+    ///
+    /// Ranges from `0` to `self.peers() - 1`.
+    pub fn index(&self) -> usize { self.parent.index() }
+    /// The total number of workers in the computation.
+    pub fn peers(&self) -> usize { self.parent.peers() }
+}
+
+impl<G: Scope> AsWorker for Group<G> {
+    fn config(&self) -> &Config { self.parent.config() }
+    fn index(&self) -> usize { self.parent.index() }
+    fn peers(&self) -> usize { self.parent.peers() }
+    fn allocate<D: Data>(&mut self, identifier: usize, address: &[usize]) -> (Vec<Box<dyn Push<Message<D>>>>, Box<dyn Pull<Message<D>>>) {
+        self.parent.allocate(identifier, address)
+    }
+    fn pipeline<D: 'static>(&mut self, identifier: usize, address: &[usize]) -> (ThreadPusher<Message<D>>, ThreadPuller<Message<D>>) {
+        self.parent.pipeline(identifier, address)
+    }
+    fn new_identifier(&mut self) -> usize {
+        self.parent.new_identifier()
+    }
+    fn log_register(&self) -> ::std::cell::RefMut<'_, crate::logging_core::Registry<crate::logging::WorkerIdentifier>> {
+        self.parent.log_register()
+    }
+    fn topology(&self) -> ::std::cell::RefMut<'_, crate::dataflow::topology::Topology> {
+        self.parent.topology()
+    }
+    fn metrics(&self) -> ::std::cell::RefMut<'_, crate::dataflow::metrics::Metrics> {
+        self.parent.metrics()
+    }
+    fn state(&self) -> ::std::cell::RefMut<'_, crate::dataflow::state::State> {
+        self.parent.state()
+    }
+    fn memory(&self) -> ::std::cell::RefMut<'_, crate::dataflow::memory::Memory> {
+        self.parent.memory()
+    }
+    fn resources(&self) -> ::std::cell::RefMut<'_, crate::dataflow::resources::Resources> {
+        self.parent.resources()
+    }
+}
+
+impl<G: Scope> Scheduler for Group<G> {
+    fn activations(&self) -> Rc<RefCell<Activations>> {
+        self.parent.activations()
+    }
+}
+
+impl<G: Scope> ScopeParent for Group<G> {
+    type Timestamp = G::Timestamp;
+}
+
+impl<G: Scope> Scope for Group<G> {
+    fn name(&self) -> String { (*self.name).clone() }
+    fn addr(&self) -> Vec<usize> { self.parent.addr() }
+    fn add_edge(&self, source: Source, target: Target) {
+        self.parent.add_edge(source, target);
+    }
+
+    fn add_operator_with_indices(&mut self, operator: Box<dyn Operate<Self::Timestamp>>, local: usize, global: usize) {
+        let mut address = self.parent.addr();
+        address.push(local);
+        self.topology().insert_group(address, &self.name);
+        self.parent.add_operator_with_indices(operator, local, global);
+    }
+
+    fn allocate_operator_index(&mut self) -> usize {
+        self.parent.allocate_operator_index()
+    }
+
+    #[inline]
+    fn scoped<T2, R, F>(&mut self, name: &str, func: F) -> R
+    where
+        T2: Timestamp+Refines<G::Timestamp>,
+        F: FnOnce(&mut Child<Self, T2>) -> R,
+    {
+        let index = self.allocate_operator_index();
+        let path = self.addr();
+        let progress_logging = self.log_register().get("timely/progress");
+
+        let subscope = RefCell::new(SubgraphBuilder::new_from(index, path, self.logging(), progress_logging.clone(), name));
+        let result = {
+            let mut builder = Child {
+                subgraph: &subscope,
+                parent: self.clone(),
+                logging: self.logging(),
+                progress_logging,
+            };
+            func(&mut builder)
+        };
+        let subscope = subscope.into_inner().build(self);
+
+        self.add_operator_with_index(Box::new(subscope), index);
+
+        result
+    }
+}
+
+impl<G: Scope> Clone for Group<G> {
+    fn clone(&self) -> Self {
+        Group {
+            parent: self.parent.clone(),
+            name: self.name.clone(),
+        }
+    }
+}
@@ -7,8 +7,10 @@ use crate::communication::Allocate;
 use crate::worker::AsWorker;
 
 pub mod child;
+pub mod group;
 
 pub use self::child::Child;
+pub use self::group::Group;
 
 /// The information a child scope needs from its parent.
 pub trait ScopeParent: AsWorker+Clone {
@@ -132,6 +134,9 @@ pub trait Scope: ScopeParent {
     /// containing scope. It is used mainly to group regions of a dataflow computation, and
     /// provides some computational benefits by abstracting the specifics of the region.
     ///
+    /// A region is a subgraph, with its own progress tracking. To group operators only for
+    /// naming and visualization, consider `group`.
+    ///
     /// # Examples
     /// ```
     /// use timely::dataflow::Scope;
@@ -187,4 +192,44 @@ pub trait Scope: ScopeParent {
         self.scoped::<<Self as ScopeParent>::Timestamp,R,F>(name, func)
     }
 
+    /// Creates a named group of operators, added directly to this scope.
+    ///
+    /// Unlike a region, a group is not a subgraph, and adds no progress tracking of its own. Its
+    /// operators are recorded as members of the group in the dataflow's `Topology`, and drawn
+    /// together by `Topology::write_dot`. Streams move into the group with `enter_group` and out
+    /// with `leave`, neither of which adds operators.
+    ///
+    /// # Examples
+    /// ```
+    /// use timely::dataflow::Scope;
+    /// use timely::dataflow::operators::{ToStream, Map, Inspect, EnterGroup, Leave};
+    ///
+    /// timely::execute_directly(|worker| {
+    ///     worker.dataflow::<u64,_,_>(|scope| {
+    ///         let stream = (0 .. 10u64).to_stream(scope);
+    ///         scope.group("Increment", |inner| {
+    ///             stream.enter_group(inner).map(|x| x + 1).leave()
+    ///         })
+    ///         .inspect(|x| assert!(*x > 0));
+    ///     });
+    ///
+    ///     // the map operator is in the dataflow scope, recorded as a member of the group.
+    ///     let topology = worker.topology();
+    ///     assert_eq!(topology.scopes().count(), 1);
+    ///     assert_eq!(topology.group(&[0, 2]), Some("Increment"));
+    ///     assert_eq!(topology.group(&[0, 1]), None);
+    ///
+    ///     // the group is drawn as a cluster of its operators.
+    ///     let mut dot = Vec::new();
+    ///     topology.write_dot(&mut dot).unwrap();
+    ///     assert!(String::from_utf8(dot).unwrap().contains("label=\"Increment\""));
+    /// });
+    /// ```
+    fn group<R, F>(&mut self, name: &str, func: F) -> R
+    where
+        F: FnOnce(&mut Group<Self>) -> R,
+    {
+        func(&mut Group::new(self.clone(), name))
+    }
+
 }
@@ -0,0 +1,219 @@
+//! Storage for per-key operator state.
+//!
+//! A `StateBackend` stores the values of a `StateHandle`. The default `MemoryBackend` keeps values
+//! in a hash map, and the `FileBackend` keeps only keys in memory and writes values to a file, so
+//! that operators whose values are larger than memory allows can continue to make progress.
+
+use std::cell::RefCell;
+use std::collections::HashMap;
+use std::fs::{self, File, OpenOptions};
+use std::hash::Hash;
+use std::io::{self, Read, Seek, SeekFrom, Write};
+use std::path::{Path, PathBuf};
+
+use abomonation::Abomonation;
+
+/// Storage for the values of per-key state.
+pub trait StateBackend<K, V> {
+    /// The value for `key`, if it exists.
+    fn get(&self, key: &K) -> Option<V>;
+    /// Indicates whether a value exists for `key`.
+    fn contains_key(&self, key: &K) -> bool;
+    /// Sets the value for `key`, returning any previous value.
+    fn insert(&mut self, key: K, value: V) -> Option<V>;
+    /// Removes the value for `key`, returning it if it existed.
+    fn remove(&mut self, key: &K) -> Option<V>;
+    /// Applies `logic` to the value for `key`, first inserting `default()` if no value exists.
+    fn update(&mut self, key: K, default: &mut dyn FnMut()->V, logic: &mut dyn FnMut(&mut V)) {
+        let mut value = self.remove(&key).unwrap_or_else(default);
+        logic(&mut value);
+        self.insert(key, value);
+    }
+    /// Retains only the entries for which `predicate` returns true.
+    fn retain(&mut self, predicate: &mut dyn FnMut(&K, &mut V)->bool);
+    /// Applies `logic` to each entry, in no particular order.
+    fn for_each(&self, logic: &mut dyn FnMut(&K, &V));
+    /// The number of keys with values.
+    fn len(&self) -> usize;
+    /// Indicates whether there are no keys with values.
+    fn is_empty(&self) -> bool { self.len() == 0 }
+    /// An estimate of the bytes held in memory, from the sizes of the key and value types.
+    fn bytes(&self) -> usize { self.len() * (::std::mem::size_of::<K>() + ::std::mem::size_of::<V>()) }
+}
+
+/// Keeps values in memory.
+pub struct MemoryBackend<K, V> {
+    map: HashMap<K, V>,
+}
+
+impl<K, V> Default for MemoryBackend<K, V> {
+    fn default() -> Self {
+        MemoryBackend { map: HashMap::new() }
+    }
+}
+
+impl<K: Hash+Eq, V: Clone> StateBackend<K, V> for MemoryBackend<K, V> {
+    fn get(&self, key: &K) -> Option<V> { self.map.get(key).cloned() }
+    fn contains_key(&self, key: &K) -> bool { self.map.contains_key(key) }
+    fn insert(&mut self, key: K, value: V) -> Option<V> { self.map.insert(key, value) }
+    fn remove(&mut self, key: &K) -> Option<V> { self.map.remove(key) }
+    fn update(&mut self, key: K, default: &mut dyn FnMut()->V, logic: &mut dyn FnMut(&mut V)) {
+        logic(self.map.entry(key).or_insert_with(default))
+    }
+    fn retain(&mut self, predicate: &mut dyn FnMut(&K, &mut V)->bool) {
+        self.map.retain(|key, value| predicate(key, value))
+    }
+    fn for_each(&self, logic: &mut dyn FnMut(&K, &V)) {
+        for (key, value) in self.map.iter() {
+            logic(key, value);
+        }
+    }
+    fn len(&self) -> usize { self.map.len() }
+}
+
+/// Keeps keys in memory, and writes values to a file.
+///
+/// Values are appended to the file when inserted, and the space of replaced and removed values
+/// is reclaimed by `compact`, which rewrites the file with only the current values. The backend
+/// compacts the file itself once replaced and removed values occupy at least half of it.
+///
+/// # Examples
+/// ```
+/// use timely::dataflow::state::{StateBackend, FileBackend};
+///
+/// let path = std::env::temp_dir().join(format!("timely-backend-{}.bin", std::process::id()));
+///
+/// let mut backend = FileBackend::create(&path).unwrap();
+/// for round in 0 .. 10u64 {
+///     for key in 0 .. 100u64 {
+///         backend.insert(key, vec![round; 10]);
+///     }
+/// }
+/// assert_eq!(backend.get(&5), Some(vec![9; 10]));
+/// assert!(2 * backend.garbage() < std::fs::metadata(&path).unwrap().len());
+///
+/// std::fs::remove_file(path).unwrap();
+/// ```
+pub struct FileBackend<K, V> {
+    index: HashMap<K, (u64, usize)>,
+    path: PathBuf,
+    file: RefCell<File>,
+    length: u64,
+    garbage: u64,
+    _phantom: ::std::marker::PhantomData<V>,
+}
+
+impl<K, V> FileBackend<K, V> {
+    /// Creates a backend writing values to the file at `path`, replacing any existing file.
+    pub fn create<P: AsRef<Path>>(path: P) -> io::Result<Self> {
+        let file = OpenOptions::new().read(true).write(true).create(true).truncate(true).open(path.as_ref())?;
+        Ok(FileBackend {
+            index: HashMap::new(),
+            path: path.as_ref().to_path_buf(),
+            file: RefCell::new(file),
+            length: 0,
+            garbage: 0,
+            _phantom: ::std::marker::PhantomData,
+        })
+    }
+
+    /// The number of bytes in the file occupied by replaced or removed values.
+    pub fn garbage(&self) -> u64 { self.garbage }
+}
+
+impl<K: Hash+Eq, V: Abomonation+Clone> FileBackend<K, V> {
+
+    /// Rewrites the file so that it contains only the current values.
+    ///
+    /// The values are copied one at a time to a new file, which then replaces the file.
+    pub fn compact(&mut self) {
+        let mut path = self.path.clone().into_os_string();
+        path.push(".compact");
+        let mut compacted = OpenOptions::new().read(true).write(true).create(true).truncate(true).open(&path).expect("state create failed");
+        let mut bytes = Vec::new();
+        let mut length = 0;
+        for location in self.index.values_mut() {
+            let value: V = read(&self.file, *location);
+            bytes.clear();
+            unsafe { ::abomonation::encode(&value, &mut bytes).expect("state encode failed"); }
+            compacted.write_all(&bytes).expect("state write failed");
+            *location = (length, bytes.len());
+            length += bytes.len() as u64;
+        }
+        fs::rename(&path, &self.path).expect("state rename failed");
+        self.file = RefCell::new(compacted);
+        self.length = length;
+        self.garbage = 0;
+    }
+
+    /// Compacts the file if replaced and removed values occupy at least half of it.
+    fn compact_if_wasteful(&mut self) {
+        if self.garbage > 0 && 2 * self.garbage >= self.length {
+            self.compact();
+        }
+    }
+
+    /// Appends `value` to the file, returning its location.
+    fn append(&mut self, value: &V) -> (u64, usize) {
+        let mut bytes = Vec::new();
+        unsafe { ::abomonation::encode(value, &mut bytes).expect("state encode failed"); }
+        let mut file = self.file.borrow_mut();
+        file.seek(SeekFrom::Start(self.length)).expect("state seek failed");
+        file.write_all(&bytes).expect("state write failed");
+        let location = (self.length, bytes.len());
+        self.length += bytes.len() as u64;
+        location
+    }
+}
+
+/// Reads and decodes the value at `location` in `file`.
+fn read<V: Abomonation+Clone>(file: &RefCell<File>, (offset, length): (u64, usize)) -> V {
+    let mut bytes = vec![0u8; length];
+    let mut file = file.borrow_mut();
+    file.seek(SeekFrom::Start(offset)).expect("state seek failed");
+    file.read_exact(&mut bytes).expect("state read failed");
+    let (value, _) = unsafe { ::abomonation::decode::<V>(&mut bytes) }.expect("state decode failed");
+    value.clone()
+}
+
+impl<K: Hash+Eq, V: Abomonation+Clone> StateBackend<K, V> for FileBackend<K, V> {
+    fn get(&self, key: &K) -> Option<V> {
+        self.index.get(key).map(|location| read(&self.file, *location))
+    }
+    fn contains_key(&self, key: &K) -> bool { self.index.contains_key(key) }
+    fn insert(&mut self, key: K, value: V) -> Option<V> {
+        let location = self.append(&value);
+        let previous = self.index.insert(key, location)?;
+        self.garbage += previous.1 as u64;
+        let previous = read(&self.file, previous);
+        self.compact_if_wasteful();
+        Some(previous)
+    }
+    fn remove(&mut self, key: &K) -> Option<V> {
+        let previous = self.index.remove(key)?;
+        self.garbage += previous.1 as u64;
+        let previous = read(&self.file, previous);
+        self.compact_if_wasteful();
+        Some(previous)
+    }
+    fn retain(&mut self, predicate: &mut dyn FnMut(&K, &mut V)->bool) {
+        // Values may be modified by `predicate`, and so are rewritten if retained.
+        let entries = self.index.drain().collect::<Vec<_>>();
+        for (key, location) in entries {
+            let mut value: V = read(&self.file, location);
+            self.garbage += location.1 as u64;
+            if predicate(&key, &mut value) {
+                let location = self.append(&value);
+                self.index.insert(key, location);
+            }
+        }
+        self.compact_if_wasteful();
+    }
+    fn for_each(&self, logic: &mut dyn FnMut(&K, &V)) {
+        for (key, location) in self.index.iter() {
+            logic(key, &read(&self.file, *location));
+        }
+    }
+    fn len(&self) -> usize { self.index.len() }
+    fn bytes(&self) -> usize { self.index.len() * ::std::mem::size_of::<(K, (u64, usize))>() }
+}
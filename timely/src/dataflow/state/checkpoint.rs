@@ -0,0 +1,98 @@
+//! Checkpoints of operator state, written to and restored from a directory.
+//!
+//! A checkpoint of epoch `epoch` is a directory `epoch` containing, for each worker, a file
+//! `worker-{index}` with the encoded checkpointed state of that worker, and an empty marker
+//! file `worker-{index}.done` written once the state file is complete. A checkpoint is
+//! consistent once every worker has written its marker, and restoration uses the latest
+//! consistent checkpoint.
+//!
+//! Workers write checkpoints when instructed to by the driver, which should do so only once
+//! the epoch is complete, so that the state reflects all input up to and including the epoch.
+//! Input offsets, for example the number of records read from a replayable source, can be
+//! kept in checkpointed state like any other operator state.
+
+use std::rc::Rc;
+use std::cell::Cell;
+use std::fs::{self, File};
+use std::io::{self, Read, Write};
+use std::path::{Path, PathBuf};
+
+/// The encoded checkpointed state of a worker: the address, name, and encoded entries of each state.
+pub type Snapshot = Vec<(Vec<usize>, String, Vec<u8>)>;
+
+/// Writes the checkpoint of `epoch` for worker `index` to `directory`.
+pub fn write(directory: &Path, epoch: u64, index: usize, snapshot: &Snapshot) -> io::Result<()> {
+    let directory = directory.join(epoch.to_string());
+    fs::create_dir_all(&directory)?;
+    let mut bytes = Vec::new();
+    unsafe { ::abomonation::encode(snapshot, &mut bytes)?; }
+    let mut file = File::create(directory.join(format!("worker-{}", index)))?;
+    file.write_all(&bytes)?;
+    file.sync_all()?;
+    File::create(directory.join(format!("worker-{}.done", index)))?.sync_all()
+}
+
+/// Reads the checkpoint of `epoch` for worker `index` from `directory`.
+pub fn read(directory: &Path, epoch: u64, index: usize) -> io::Result<Snapshot> {
+    let mut bytes = Vec::new();
+    File::open(directory.join(epoch.to_string()).join(format!("worker-{}", index)))?.read_to_end(&mut bytes)?;
+    match unsafe { ::abomonation::decode::<Snapshot>(&mut bytes) } {
+        Some((snapshot, _)) => Ok(snapshot.clone()),
+        None => Err(io::Error::new(io::ErrorKind::InvalidData, "checkpoint decode failed")),
+    }
+}
+
+/// The latest epoch in `directory` for which each of `peers` workers has completed its checkpoint.
+///
+/// A directory that does not yet exist contains no checkpoints.
+pub fn latest(directory: &Path, peers: usize) -> io::Result<Option<u64>> {
+    let entries = match fs::read_dir(directory) {
+        Ok(entries) => entries,
+        Err(error) if error.kind() == io::ErrorKind::NotFound => return Ok(None),
+        Err(error) => return Err(error),
+    };
+    let mut latest = None;
+    for entry in entries {
+        let entry = entry?;
+        if let Some(epoch) = entry.file_name().to_str().and_then(|name| name.parse::<u64>().ok()) {
+            let complete = (0 .. peers).all(|index| entry.path().join(format!("worker-{}.done", index)).exists());
+            if complete && latest.map(|latest| latest < epoch).unwrap_or(true) {
+                latest = Some(epoch);
+            }
+        }
+    }
+    Ok(latest)
+}
+
+/// Reports which epochs are contained in a consistent checkpoint.
+///
+/// Without a checkpoint directory, every epoch is reported as durable.
+#[derive(Clone, Debug)]
+pub struct Durability {
+    directory: Option<PathBuf>,
+    peers: usize,
+    latest: Rc<Cell<Option<u64>>>,
+}
+
+impl Durability {
+    /// Creates a handle reporting on the checkpoints of `peers` workers in `directory`.
+    pub fn new(directory: Option<PathBuf>, peers: usize) -> Self {
+        Durability { directory, peers, latest: Rc::new(Cell::new(None)) }
+    }
+
+    /// Indicates whether a consistent checkpoint contains `epoch`.
+    ///
+    /// The checkpoint directory is consulted only if no previously observed checkpoint contains `epoch`.
+    pub fn is_durable(&self, epoch: u64) -> bool {
+        match &self.directory {
+            None => true,
+            Some(_) if self.latest.get().map(|latest| latest >= epoch).unwrap_or(false) => true,
+            Some(directory) => {
+                if let Ok(Some(latest)) = latest(directory, self.peers) {
+                    self.latest.set(Some(latest));
+                }
+                self.latest.get().map(|latest| latest >= epoch).unwrap_or(false)
+            }
+        }
+    }
+}
@@ -0,0 +1,129 @@
+//! Hooks that reclaim operator state once the input frontiers pass given times.
+//!
+//! Operators that keep state for times that are not yet complete, for example the records of
+//! open windows or the inputs of a join, would otherwise each discard that state themselves when
+//! notified. Operators built with `OperatorBuilder` instead register hooks with their
+//! `Compaction`, which the builder invokes after the operator's logic whenever the frontiers of
+//! all of its inputs have passed the hooks' times.
+
+use std::rc::Rc;
+use std::cell::RefCell;
+
+use crate::progress::Timestamp;
+use crate::progress::frontier::MutableAntichain;
+use crate::dataflow::state::StateHandle;
+
+/// Compaction hooks of an operator.
+///
+/// Handles are cheaply cloned, and clones share the same hooks.
+///
+/// # Examples
+/// ```
+/// use timely::dataflow::operators::ToStream;
+/// use timely::dataflow::operators::generic::builder_rc::OperatorBuilder;
+/// use timely::dataflow::channels::pact::Pipeline;
+/// use timely::dataflow::state::State;
+///
+/// timely::example(|scope| {
+///     let stream = (0 .. 10u64).to_stream(scope);
+///
+///     // keys with the time at which they were last seen.
+///     let mut state = State::default();
+///     let seen = state.handle::<u64, u64>(&[0], "seen");
+///
+///     let mut builder = OperatorBuilder::new("Seen".to_owned(), scope.clone());
+///     let mut input = builder.new_input(&stream, Pipeline);
+///     // discard keys once their time is complete.
+///     builder.compaction().retain(&seen, |_key, time| *time);
+///     let checked = seen.clone();
+///     builder.compaction().at(0, move || assert!(checked.is_empty()));
+///     builder.build(move |_capabilities| {
+///         move |_frontiers| {
+///             input.for_each(|time, data| {
+///                 for datum in data.iter() {
+///                     seen.insert(*datum, *time.time());
+///                 }
+///             });
+///         }
+///     });
+/// });
+/// ```
+pub struct Compaction<T: Timestamp> {
+    hooks: Rc<RefCell<Hooks<T>>>,
+}
+
+// A hook invoked with a test of whether the frontiers have passed a time.
+type Retain<T> = Box<dyn FnMut(&dyn Fn(&T)->bool)>;
+
+struct Hooks<T: Timestamp> {
+    // Hooks invoked once, when the frontiers pass their time.
+    once: Vec<(T, Box<dyn FnOnce()>)>,
+    // Hooks invoked each time the frontiers change.
+    always: Vec<Retain<T>>,
+}
+
+impl<T: Timestamp> Clone for Compaction<T> {
+    fn clone(&self) -> Self {
+        Compaction { hooks: self.hooks.clone() }
+    }
+}
+
+impl<T: Timestamp> ::std::fmt::Debug for Compaction<T> {
+    fn fmt(&self, f: &mut ::std::fmt::Formatter) -> ::std::fmt::Result {
+        let hooks = self.hooks.borrow();
+        let times = hooks.once.iter().map(|(time, _)| time).collect::<Vec<_>>();
+        f.debug_struct("Compaction").field("once", &times).field("always", &hooks.always.len()).finish()
+    }
+}
+
+impl<T: Timestamp> Default for Compaction<T> {
+    fn default() -> Self {
+        Compaction { hooks: Rc::new(RefCell::new(Hooks { once: Vec::new(), always: Vec::new() })) }
+    }
+}
+
+impl<T: Timestamp> Compaction<T> {
+
+    /// Invokes `hook` once the frontiers of all inputs have passed `time`.
+    pub fn at<F: FnOnce()+'static>(&self, time: T, hook: F) {
+        self.hooks.borrow_mut().once.push((time, Box::new(hook)));
+    }
+
+    /// Removes the entries of `state` once the frontiers of all inputs have passed the time
+    /// `time_of` reports for them.
+    pub fn retain<K: 'static, V: 'static, F: Fn(&K, &V)->T+'static>(&self, state: &StateHandle<K, V>, time_of: F) {
+        let state = state.clone();
+        self.hooks.borrow_mut().always.push(Box::new(move |passed| {
+            state.retain(|key, value| !passed(&time_of(key, value)));
+        }));
+    }
+
+    /// Indicates whether no hooks are registered.
+    pub fn is_empty(&self) -> bool {
+        let hooks = self.hooks.borrow();
+        hooks.once.is_empty() && hooks.always.is_empty()
+    }
+
+    /// Invokes the hooks whose times `frontiers` have passed.
+    ///
+    /// A time is passed when no frontier is less or equal to it. Hooks registered while hooks are
+    /// invoked are first considered at the next call.
+    pub fn compact(&self, frontiers: &[MutableAntichain<T>]) {
+        let passed = |time: &T| !frontiers.iter().any(|frontier| frontier.less_equal(time));
+        let (due, mut always) = {
+            let mut hooks = self.hooks.borrow_mut();
+            let (due, pending) = ::std::mem::take(&mut hooks.once).into_iter().partition::<Vec<_>, _>(|(time, _)| passed(time));
+            hooks.once = pending;
+            (due, ::std::mem::take(&mut hooks.always))
+        };
+        for hook in always.iter_mut() {
+            hook(&passed);
+        }
+        for (_time, hook) in due {
+            hook();
+        }
+        let mut hooks = self.hooks.borrow_mut();
+        always.append(&mut hooks.always);
+        hooks.always = always;
+    }
+}
@@ -0,0 +1,306 @@
+//! Per-key state for operators, owned by the worker.
+//!
+//! Operators obtain a `StateHandle` for their address from the worker, typically in the
+//! constructor passed to an operator builder, and keep their per-key state in it rather than in
+//! variables captured by their logic. The worker retains the state for as long as the dataflow
+//! is installed and discards it when the dataflow completes or is dropped, so that operator
+//! state cannot outlive its dataflow, and can be enumerated and inspected by the worker.
+
+use std::any::Any;
+use std::rc::Rc;
+use std::cell::RefCell;
+use std::collections::BTreeMap;
+use std::hash::Hash;
+
+use abomonation::Abomonation;
+
+pub mod backend;
+pub mod checkpoint;
+pub mod compaction;
+pub mod spill;
+
+pub use self::backend::{StateBackend, MemoryBackend, FileBackend};
+pub use self::checkpoint::Snapshot;
+pub use self::compaction::Compaction;
+pub use self::spill::SpillBuffer;
+
+/// A handle to per-key state of an operator.
+///
+/// Handles are cheaply cloned, and clones share the same state.
+pub struct StateHandle<K, V> {
+    backend: Rc<RefCell<Box<dyn StateBackend<K, V>>>>,
+}
+
+impl<K, V> Clone for StateHandle<K, V> {
+    fn clone(&self) -> Self {
+        StateHandle { backend: self.backend.clone() }
+    }
+}
+
+impl<K, V> StateHandle<K, V> {
+
+    /// The value for `key`, if it exists.
+    pub fn get(&self, key: &K) -> Option<V> {
+        self.backend.borrow().get(key)
+    }
+
+    /// Indicates whether a value exists for `key`.
+    pub fn contains_key(&self, key: &K) -> bool {
+        self.backend.borrow().contains_key(key)
+    }
+
+    /// Sets the value for `key`, returning any previous value.
+    pub fn insert(&self, key: K, value: V) -> Option<V> {
+        self.backend.borrow_mut().insert(key, value)
+    }
+
+    /// Removes the value for `key`, returning it if it existed.
+    pub fn remove(&self, key: &K) -> Option<V> {
+        self.backend.borrow_mut().remove(key)
+    }
+
+    /// Applies `logic` to the value for `key`, first inserting `default()` if no value exists.
+    pub fn update<F: FnOnce()->V, L: FnOnce(&mut V)->R, R>(&self, key: K, default: F, logic: L) -> R {
+        let mut default = Some(default);
+        let mut logic = Some(logic);
+        let mut result = None;
+        self.backend.borrow_mut().update(
+            key,
+            &mut || (default.take().expect("default called twice"))(),
+            &mut |value| result = Some((logic.take().expect("logic called twice"))(value)),
+        );
+        result.expect("logic not called")
+    }
+
+    /// Retains only the entries for which `predicate` returns true.
+    pub fn retain<F: FnMut(&K, &mut V)->bool>(&self, mut predicate: F) {
+        self.backend.borrow_mut().retain(&mut predicate)
+    }
+
+    /// Applies `logic` to each entry, in no particular order.
+    pub fn for_each<F: FnMut(&K, &V)>(&self, mut logic: F) {
+        self.backend.borrow().for_each(&mut logic)
+    }
+
+    /// The number of keys with values.
+    pub fn len(&self) -> usize {
+        self.backend.borrow().len()
+    }
+
+    /// Indicates whether there are no keys with values.
+    pub fn is_empty(&self) -> bool {
+        self.backend.borrow().is_empty()
+    }
+
+    /// An estimate of the bytes held in memory.
+    pub fn bytes(&self) -> usize {
+        self.backend.borrow().bytes()
+    }
+}
+
+/// The address of an operator and the name of one of its states.
+type Name = (Vec<usize>, String);
+
+/// The state of the operators of the dataflows installed in a worker.
+///
+/// State is identified by the address of the operator and a name, so that an operator may
+/// maintain several independent collections of state.
+///
+/// # Examples
+/// ```
+/// use timely::dataflow::InputHandle;
+/// use timely::dataflow::operators::{Input, Operator, Probe};
+/// use timely::dataflow::channels::pact::Pipeline;
+/// use timely::worker::AsWorker;
+///
+/// timely::execute_directly(|worker| {
+///
+///     let mut input = InputHandle::new();
+///     let probe = worker.dataflow::<usize,_,_>(|scope| {
+///         let stream = scope.input_from(&mut input);
+///         let scope = stream.scope();
+///         stream
+///             .unary(Pipeline, "Count", |_capability, info| {
+///                 let counts = scope.state().handle::<u64, usize>(&info.address, "counts");
+///                 let mut vector = Vec::new();
+///                 move |input, output| {
+///                     input.for_each(|time, data| {
+///                         data.swap(&mut vector);
+///                         let mut session = output.session(&time);
+///                         for word in vector.drain(..) {
+///                             let count = counts.update(word, || 0, |count| { *count += 1; *count });
+///                             session.give((word, count));
+///                         }
+///                     });
+///                 }
+///             })
+///             .probe()
+///     });
+///
+///     for round in 0 .. 10 {
+///         input.send((round % 3) as u64);
+///         input.advance_to(round + 1);
+///         worker.step_while(|| probe.less_than(input.time()));
+///     }
+///
+///     let counts = worker.state().handle::<u64, usize>(&[0, 2], "counts");
+///     assert_eq!(counts.get(&0), Some(4));
+///     assert_eq!(counts.len(), 3);
+/// });
+/// ```
+#[derive(Default)]
+pub struct State {
+    handles: BTreeMap<Name, Box<dyn Any>>,
+    // Estimators of the bytes held in memory by each state.
+    sizes: BTreeMap<Name, Box<dyn Fn()->usize>>,
+    // Encoders of the entries of checkpointed state.
+    encoders: BTreeMap<Name, Box<dyn Fn()->Vec<u8>>>,
+    // Encoded entries restored from a checkpoint, awaiting their handles.
+    restored: BTreeMap<Name, Vec<u8>>,
+}
+
+impl State {
+
+    /// A handle to the state named `name` of the operator at `address`.
+    ///
+    /// The state is created empty in a `MemoryBackend` if it does not yet exist.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the state exists with different key or value types.
+    pub fn handle<K: Hash+Eq+'static, V: Clone+'static>(&mut self, address: &[usize], name: &str) -> StateHandle<K, V> {
+        self.handle_with(address, name, MemoryBackend::default)
+    }
+
+    /// A handle to the state named `name` of the operator at `address`, stored in the backend
+    /// produced by `backend` if the state does not yet exist.
+    ///
+    /// # Examples
+    /// ```
+    /// use timely::dataflow::state::{State, FileBackend};
+    ///
+    /// let path = std::env::temp_dir().join(format!("timely-state-{}.bin", std::process::id()));
+    ///
+    /// let mut state = State::default();
+    /// let handle = state.handle_with::<u64, Vec<u64>, _>(&[0, 1], "lists", || {
+    ///     FileBackend::create(&path).unwrap()
+    /// });
+    ///
+    /// handle.update(3, Vec::new, |list| list.push(7));
+    /// handle.update(3, Vec::new, |list| list.push(8));
+    /// assert_eq!(handle.get(&3), Some(vec![7, 8]));
+    /// assert_eq!(handle.remove(&3), Some(vec![7, 8]));
+    /// assert!(handle.is_empty());
+    ///
+    /// std::fs::remove_file(path).unwrap();
+    /// ```
+    ///
+    /// # Panics
+    ///
+    /// Panics if the state exists with different key or value types.
+    pub fn handle_with<K: 'static, V: 'static, B: StateBackend<K, V>+'static>(&mut self, address: &[usize], name: &str, backend: impl FnOnce()->B) -> StateHandle<K, V> {
+        let key = (address.to_vec(), name.to_owned());
+        let sizes = &mut self.sizes;
+        self.handles
+            .entry(key.clone())
+            .or_insert_with(|| {
+                let backend: Box<dyn StateBackend<K, V>> = Box::new(backend());
+                let handle = StateHandle { backend: Rc::new(RefCell::new(backend)) };
+                let sized = handle.clone();
+                sizes.insert(key, Box::new(move || sized.bytes()));
+                Box::new(handle)
+            })
+            .downcast_ref::<StateHandle<K, V>>()
+            .unwrap_or_else(|| panic!("state {:?} of operator {:?} has different key or value types", name, address))
+            .clone()
+    }
+
+    /// A handle to checkpointed state named `name` of the operator at `address`.
+    ///
+    /// The state is included in the checkpoints written by the worker. If the worker was
+    /// restored from a checkpoint containing the state, the state is created with its entries.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the state exists with different key or value types.
+    pub fn checkpointed_handle<K, V>(&mut self, address: &[usize], name: &str) -> StateHandle<K, V>
+    where
+        K: Hash+Eq+Abomonation+Clone+'static,
+        V: Abomonation+Clone+'static,
+    {
+        self.checkpointed_handle_with(address, name, MemoryBackend::default)
+    }
+
+    /// A handle to checkpointed state named `name` of the operator at `address`, stored in the
+    /// backend produced by `backend` if the state does not yet exist.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the state exists with different key or value types.
+    pub fn checkpointed_handle_with<K, V, B>(&mut self, address: &[usize], name: &str, backend: impl FnOnce()->B) -> StateHandle<K, V>
+    where
+        K: Abomonation+Clone+'static,
+        V: Abomonation+Clone+'static,
+        B: StateBackend<K, V>+'static,
+    {
+        let handle = self.handle_with(address, name, backend);
+        let key = (address.to_vec(), name.to_owned());
+        if let Some(mut bytes) = self.restored.remove(&key) {
+            let (entries, _) = unsafe { ::abomonation::decode::<Vec<(K, V)>>(&mut bytes) }
+                .unwrap_or_else(|| panic!("state {:?} of operator {:?} could not be restored", name, address));
+            for (k, v) in entries.iter() {
+                handle.insert(k.clone(), v.clone());
+            }
+        }
+        let encoded = handle.clone();
+        self.encoders.entry(key).or_insert_with(|| Box::new(move || {
+            let mut entries = Vec::with_capacity(encoded.len());
+            encoded.for_each(|k, v| entries.push((k.clone(), v.clone())));
+            let mut bytes = Vec::new();
+            unsafe { ::abomonation::encode(&entries, &mut bytes).expect("state encode failed"); }
+            bytes
+        }));
+        handle
+    }
+
+    /// Encodes the entries of all checkpointed state.
+    pub fn snapshot(&self) -> Snapshot {
+        self.encoders
+            .iter()
+            .map(|((address, name), encoder)| (address.clone(), name.clone(), encoder()))
+            .collect()
+    }
+
+    /// Supplies entries of checkpointed state, to be installed when their handles are created.
+    pub fn restore(&mut self, snapshot: Snapshot) {
+        for (address, name, bytes) in snapshot {
+            self.restored.insert((address, name), bytes);
+        }
+    }
+
+    /// Iterates over the address and name of each state.
+    pub fn names(&self) -> impl Iterator<Item=(&[usize], &str)> {
+        self.handles.keys().map(|(address, name)| (&address[..], &name[..]))
+    }
+
+    /// The address, name, and estimated bytes in memory of each state.
+    pub fn sizes(&self) -> Vec<(Vec<usize>, String, usize)> {
+        self.sizes
+            .iter()
+            .map(|((address, name), size)| (address.clone(), name.clone(), size()))
+            .collect()
+    }
+
+    /// The estimated bytes in memory of all state.
+    pub fn bytes(&self) -> usize {
+        self.sizes.values().map(|size| size()).sum()
+    }
+
+    /// Discards the state of the operators of the dataflow with the supplied index.
+    pub fn remove_dataflow(&mut self, dataflow_index: usize) {
+        self.handles.retain(|(address, _), _| address.first() != Some(&dataflow_index));
+        self.sizes.retain(|(address, _), _| address.first() != Some(&dataflow_index));
+        self.encoders.retain(|(address, _), _| address.first() != Some(&dataflow_index));
+        self.restored.retain(|(address, _), _| address.first() != Some(&dataflow_index));
+    }
+}
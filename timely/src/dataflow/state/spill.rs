@@ -0,0 +1,188 @@
+//! Buffers of records that overflow to disk.
+//!
+//! Operators that hold back records until a time is complete, for example `StateMachine` with
+//! records for future times, can accumulate more records than fit in memory when the data are
+//! skewed. A `SpillBuffer` holds records in memory up to a threshold, and then writes each full
+//! batch to a temporary file, from which the batches are read back one at a time when drained.
+//!
+//! Batches are written in the same binary encoding used to exchange data between processes.
+
+use std::fs::{self, File, OpenOptions};
+use std::io::{Read, Seek, SeekFrom, Write};
+use std::path::PathBuf;
+use std::sync::atomic::{AtomicUsize, Ordering};
+
+use crate::ExchangeData;
+use crate::communication::Message;
+use crate::worker::Config;
+
+/// Distinguishes the spill files of the buffers of a process.
+static SPILL_FILES: AtomicUsize = AtomicUsize::new(0);
+
+/// A buffer of records, which writes batches of records to disk beyond a threshold.
+///
+/// # Examples
+/// ```
+/// use timely::dataflow::state::spill::SpillBuffer;
+///
+/// let mut buffer = SpillBuffer::spilling_to(std::env::temp_dir(), 100);
+/// for record in 0 .. 1000u64 {
+///     buffer.push(record);
+/// }
+/// assert_eq!(buffer.len(), 1000);
+/// assert_eq!(buffer.spilled(), 1000);
+///
+/// let mut total = 0;
+/// for batch in buffer.drain() {
+///     assert!(batch.len() <= 100);
+///     total += batch.iter().sum::<u64>();
+/// }
+/// assert_eq!(total, 499500);
+/// assert!(buffer.is_empty());
+/// ```
+pub struct SpillBuffer<D> {
+    buffer: Vec<D>,
+    spill: Option<(PathBuf, usize)>,
+    file: Option<(PathBuf, File)>,
+    batches: Vec<(usize, usize)>,
+}
+
+impl<D: ExchangeData> SpillBuffer<D> {
+
+    /// Creates a buffer that holds all records in memory.
+    pub fn new() -> Self {
+        SpillBuffer { buffer: Vec::new(), spill: None, file: None, batches: Vec::new() }
+    }
+
+    /// Creates a buffer that writes batches of `threshold` records to files in `directory`.
+    pub fn spilling_to<P: Into<PathBuf>>(directory: P, threshold: usize) -> Self {
+        assert!(threshold > 0, "spill threshold must be positive");
+        SpillBuffer { buffer: Vec::new(), spill: Some((directory.into(), threshold)), file: None, batches: Vec::new() }
+    }
+
+    /// Creates a buffer that spills as configured by `Config::spill_to`, if at all.
+    pub fn from_config(config: &Config) -> Self {
+        match &config.spill {
+            Some((directory, threshold)) => Self::spilling_to(directory.clone(), *threshold),
+            None => Self::new(),
+        }
+    }
+
+    /// Adds `datum` to the buffer.
+    pub fn push(&mut self, datum: D) {
+        self.buffer.push(datum);
+        self.spill_if_full();
+    }
+
+    /// Moves the contents of `data` into the buffer, leaving `data` empty.
+    pub fn append(&mut self, data: &mut Vec<D>) {
+        match self.spill {
+            Some((_, threshold)) => {
+                while !data.is_empty() {
+                    let count = ::std::cmp::min(threshold - self.buffer.len(), data.len());
+                    self.buffer.extend(data.drain(.. count));
+                    self.spill_if_full();
+                }
+            },
+            None => self.buffer.append(data),
+        }
+    }
+
+    /// The number of records in the buffer, in memory or on disk.
+    pub fn len(&self) -> usize {
+        self.buffer.len() + self.spilled()
+    }
+
+    /// Indicates whether the buffer holds no records.
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// The number of records written to disk.
+    pub fn spilled(&self) -> usize {
+        self.batches.iter().map(|(records, _)| records).sum()
+    }
+
+    /// Removes all records from the buffer, as batches read back one at a time.
+    ///
+    /// Batches written to disk are produced first, followed by the records held in memory.
+    pub fn drain(&mut self) -> Drain<D> {
+        let mut file = self.file.take();
+        if let Some((_, file)) = &mut file {
+            file.seek(SeekFrom::Start(0)).expect("spill seek failed");
+        }
+        let mut batches = ::std::mem::take(&mut self.batches);
+        batches.reverse();
+        Drain { file, batches, buffer: ::std::mem::take(&mut self.buffer) }
+    }
+
+    /// Writes the in-memory records to disk, if there are at least the threshold of them.
+    fn spill_if_full(&mut self) {
+        if let Some((directory, threshold)) = &self.spill {
+            if self.buffer.len() >= *threshold {
+                if self.file.is_none() {
+                    let index = SPILL_FILES.fetch_add(1, Ordering::SeqCst);
+                    let path = directory.join(format!("timely-spill-{}-{}", ::std::process::id(), index));
+                    let file = OpenOptions::new().read(true).write(true).create(true).truncate(true).open(&path).expect("spill create failed");
+                    self.file = Some((path, file));
+                }
+                let records = self.buffer.len();
+                let mut bytes = Vec::new();
+                Message::from_typed(::std::mem::take(&mut self.buffer)).into_bytes(&mut bytes);
+                let (_, file) = self.file.as_mut().expect("spill file absent");
+                file.write_all(&bytes).expect("spill write failed");
+                self.batches.push((records, bytes.len()));
+            }
+        }
+    }
+}
+
+impl<D: ExchangeData> Default for SpillBuffer<D> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<D> Drop for SpillBuffer<D> {
+    fn drop(&mut self) {
+        if let Some((path, _)) = self.file.take() {
+            let _ = fs::remove_file(path);
+        }
+    }
+}
+
+/// The batches of a drained `SpillBuffer`.
+pub struct Drain<D> {
+    file: Option<(PathBuf, File)>,
+    /// The records and bytes of each batch on disk, in reverse order.
+    batches: Vec<(usize, usize)>,
+    buffer: Vec<D>,
+}
+
+impl<D: ExchangeData> Iterator for Drain<D> {
+    type Item = Vec<D>;
+    #[allow(unused_unsafe)]
+    fn next(&mut self) -> Option<Vec<D>> {
+        if let Some((_, length)) = self.batches.pop() {
+            let (_, file) = self.file.as_mut().expect("spill file absent");
+            let mut bytes = vec![0u8; length];
+            file.read_exact(&mut bytes).expect("spill read failed");
+            let bytes = timely_bytes::arc::Bytes::from(bytes);
+            Some(unsafe { Message::<Vec<D>>::from_bytes(bytes) }.into_typed())
+        }
+        else if !self.buffer.is_empty() {
+            Some(::std::mem::take(&mut self.buffer))
+        }
+        else {
+            None
+        }
+    }
+}
+
+impl<D> Drop for Drain<D> {
+    fn drop(&mut self) {
+        if let Some((path, _)) = self.file.take() {
+            let _ = fs::remove_file(path);
+        }
+    }
+}
@@ -50,10 +50,68 @@ impl<S: Scope, D> Stream<S, D> {
     pub fn new(source: Source, output: TeeHelper<S::Timestamp, D>, scope: S) -> Self {
         Stream { name: source, ports: output, scope }
     }
+    /// The number of destinations connected to the stream.
+    ///
+    /// A stream may be used by any number of operators, each of which receives all of its records.
+    /// The last destination connected receives the records the source produced, and the others
+    /// receive copies, so that records are only copied for streams with several destinations.
+    ///
+    /// # Examples
+    /// ```
+    /// use timely::dataflow::operators::{ToStream, Map, Inspect};
+    ///
+    /// timely::example(|scope| {
+    ///     let stream = (0 .. 10).to_stream(scope);
+    ///     assert_eq!(stream.consumers(), 0);
+    ///     stream.inspect(|x| println!("seen: {:?}", x));
+    ///     stream.map(|x| x + 1);
+    ///     assert_eq!(stream.consumers(), 2);
+    /// });
+    /// ```
+    pub fn consumers(&self) -> usize { self.ports.len() }
     /// The name of the stream's source operator.
     pub fn name(&self) -> &Source { &self.name }
     /// The scope immediately containing the stream.
     pub fn scope(&self) -> S { self.scope.clone() }
+    /// Names the operator that produces the stream, in place of the name it was built with.
+    ///
+    /// The name is used by the operator's metrics, by `Topology` and its DOT output, and in panic
+    /// messages. Operators are logged when their scope is built, and so their `OperatesEvent`
+    /// carries the new name.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the stream is an input of its scope, rather than the output of an operator.
+    ///
+    /// # Examples
+    /// ```
+    /// use timely::dataflow::operators::{ToStream, Map, Inspect};
+    ///
+    /// timely::execute_directly(|worker| {
+    ///     worker.dataflow::<u64,_,_>(|scope| {
+    ///         (0 .. 10)
+    ///             .to_stream(scope)
+    ///             .map(|x| x + 1)
+    ///             .named("Increment")
+    ///             .inspect(|x| println!("{:?}", x));
+    ///     });
+    ///
+    ///     let topology = worker.topology();
+    ///     let names = topology.operators().map(|(addr, op)| (addr, op.name.clone())).collect::<Vec<_>>();
+    ///     assert!(names.contains(&(vec![0, 2], "Increment".to_string())));
+    /// });
+    /// ```
+    pub fn named(self, name: &str) -> Self {
+        assert!(self.name.node > 0, "only the output of an operator can be named");
+        let mut address = self.scope.addr();
+        address.push(self.name.node);
+        self.scope.topology().insert_name(address, name);
+        self
+    }
+    /// The same stream, in a scope that shares the progress tracking of its scope.
+    pub(crate) fn rescope<S2: Scope<Timestamp=S::Timestamp>>(&self, scope: S2) -> Stream<S2, D> {
+        Stream { name: self.name, ports: self.ports.clone(), scope }
+    }
 }
 
 impl<S, D> Debug for Stream<S, D>
@@ -0,0 +1,280 @@
+//! Descriptions of the structure of constructed dataflows.
+//!
+//! Each scope reports its operators and the edges between them to the worker when the scope
+//! is built. The worker retains these descriptions for as long as the dataflow is installed.
+//! They can be enumerated to assert on the constructed topology, or rendered in the GraphViz DOT
+//! format to check it visually.
+
+use std::collections::BTreeMap;
+use std::io::{self, Write};
+
+use crate::progress::{Source, Target};
+
+/// An operator hosted in a scope.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct OperatorTopology {
+    /// Index of the operator within its scope.
+    pub index: usize,
+    /// A helpful name.
+    pub name: String,
+    /// The number of inputs.
+    pub inputs: usize,
+    /// The number of outputs.
+    pub outputs: usize,
+}
+
+/// The operators and edges of a single scope.
+///
+/// Edges from `Source` zero are inputs to the scope, and edges to `Target` zero are outputs from the scope.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct ScopeTopology {
+    /// A helpful name.
+    pub name: String,
+    /// Sequence of nested scope identifiers indicating the path from the root to this scope.
+    pub path: Vec<usize>,
+    /// The hosted operators, in order of their index.
+    pub operators: Vec<OperatorTopology>,
+    /// Edges between operator outputs and operator inputs.
+    pub edges: Vec<(Source, Target)>,
+    /// Whether the scope has no cycles and the timestamp of its parent.
+    ///
+    /// Such a scope, for example a region without feedback, is flattened into its parent: the
+    /// parent schedules its operators and tracks their progress directly, and the scope itself
+    /// is never scheduled. The scope is still described here, with its operators at their
+    /// addresses within it.
+    pub flattenable: bool,
+}
+
+impl ScopeTopology {
+
+    /// The operator with the supplied index, if it exists.
+    pub fn operator(&self, index: usize) -> Option<&OperatorTopology> {
+        self.operators.iter().find(|op| op.index == index)
+    }
+
+    /// The address of the operator with the supplied index, from the root of the worker.
+    pub fn address(&self, index: usize) -> Vec<usize> {
+        let mut address = self.path.clone();
+        address.push(index);
+        address
+    }
+
+    /// Edges leaving outputs of the operator with the supplied index.
+    pub fn edges_from(&self, index: usize) -> impl Iterator<Item=&(Source, Target)> {
+        self.edges.iter().filter(move |(source, _)| source.node == index)
+    }
+
+    /// Edges arriving at inputs of the operator with the supplied index.
+    pub fn edges_to(&self, index: usize) -> impl Iterator<Item=&(Source, Target)> {
+        self.edges.iter().filter(move |(_, target)| target.node == index)
+    }
+}
+
+/// The scopes of the dataflows installed in a worker, indexed by path.
+///
+/// # Examples
+/// ```
+/// timely::execute_directly(|worker| {
+///
+///     use timely::dataflow::Scope;
+///     use timely::dataflow::operators::{ToStream, Map, Inspect, Enter, Leave};
+///
+///     worker.dataflow::<usize,_,_>(|scope| {
+///         let stream = (0 .. 10).to_stream(scope);
+///         scope.region(|inner| {
+///             stream.enter(inner).map(|x| x + 1).leave()
+///         })
+///         .inspect(|x| println!("{:?}", x));
+///     });
+///
+///     let topology = worker.topology();
+///
+///     // the dataflow and the region within it.
+///     assert_eq!(topology.scopes().count(), 2);
+///     assert_eq!(topology.dataflow(0).count(), 2);
+///
+///     let region = topology.scope(&[0, 2]).unwrap();
+///     assert_eq!(region.name, "Region");
+///     assert_eq!(region.operator(1).unwrap().name, "Map");
+///     assert_eq!(region.address(1), vec![0, 2, 1]);
+///     assert_eq!(region.edges_from(0).count(), 1);
+///     assert_eq!(region.edges_to(0).count(), 1);
+///     assert!(region.flattenable);
+///
+///     // operators are enumerated with their addresses.
+///     let names = topology.operators().map(|(addr, op)| (addr, op.name.clone())).collect::<Vec<_>>();
+///     assert!(names.contains(&(vec![0, 2, 1], "Map".to_string())));
+/// });
+/// ```
+#[derive(Clone, Debug, Default)]
+pub struct Topology {
+    scopes: BTreeMap<Vec<usize>, ScopeTopology>,
+    groups: BTreeMap<Vec<usize>, String>,
+    names: BTreeMap<Vec<usize>, String>,
+}
+
+impl Topology {
+
+    /// Records the description of a scope, replacing any scope at the same path.
+    pub fn insert(&mut self, scope: ScopeTopology) {
+        self.scopes.insert(scope.path.clone(), scope);
+    }
+
+    /// Removes the descriptions of the dataflow with the supplied index, and all of its scopes.
+    pub fn remove_dataflow(&mut self, dataflow_index: usize) {
+        self.scopes.retain(|path, _| path.first() != Some(&dataflow_index));
+        self.groups.retain(|address, _| address.first() != Some(&dataflow_index));
+        self.names.retain(|address, _| address.first() != Some(&dataflow_index));
+    }
+
+    /// Records that the operator at `address` belongs to the group `name`.
+    pub fn insert_group(&mut self, address: Vec<usize>, name: &str) {
+        self.groups.insert(address, name.to_owned());
+    }
+
+    /// The group of the operator at `address`, if it was added by a `Group` scope.
+    pub fn group(&self, address: &[usize]) -> Option<&str> {
+        self.groups.get(address).map(|name| &name[..])
+    }
+
+    /// Records `name` for the operator at `address`, to replace its own name when its scope is built.
+    pub fn insert_name(&mut self, address: Vec<usize>, name: &str) {
+        self.names.insert(address, name.to_owned());
+    }
+
+    /// Removes the name recorded for the operator at `address`, if any.
+    pub(crate) fn take_name(&mut self, address: &[usize]) -> Option<String> {
+        self.names.remove(address)
+    }
+
+    /// Iterates over all recorded scopes, in order of their paths.
+    pub fn scopes(&self) -> impl Iterator<Item=&ScopeTopology> {
+        self.scopes.values()
+    }
+
+    /// The scope at the supplied path, if it exists.
+    pub fn scope(&self, path: &[usize]) -> Option<&ScopeTopology> {
+        self.scopes.get(path)
+    }
+
+    /// Iterates over the scopes of the dataflow with the supplied index, in order of their paths.
+    pub fn dataflow(&self, dataflow_index: usize) -> impl Iterator<Item=&ScopeTopology> {
+        self.scopes.values().filter(move |scope| scope.path.first() == Some(&dataflow_index))
+    }
+
+    /// Iterates over the operators of all scopes, with their addresses.
+    ///
+    /// Operators that are scopes are included, but the zero-indexed operators standing for
+    /// each scope's own inputs and outputs are not.
+    pub fn operators(&self) -> impl Iterator<Item=(Vec<usize>, &OperatorTopology)> {
+        self.scopes.values().flat_map(|scope| {
+            scope.operators.iter().filter(|op| op.index > 0).map(move |op| (scope.address(op.index), op))
+        })
+    }
+
+    /// Writes the scopes as a GraphViz DOT graph.
+    ///
+    /// Each scope is drawn as a cluster containing its operators and a node for each scope input
+    /// and output. Scopes and operators are labelled with their names and addresses. Operators
+    /// that are themselves scopes are drawn as nested clusters, as are the operators of each group.
+    pub fn write_dot<W: Write>(&self, mut writer: W) -> io::Result<()> {
+        writeln!(writer, "digraph timely {{")?;
+        writeln!(writer, "  compound=true;")?;
+        for (path, scope) in self.scopes.iter() {
+            if path.len() == 1 {
+                self.write_scope(&mut writer, scope, 1)?;
+            }
+        }
+        writeln!(writer, "}}")
+    }
+
+    fn write_scope<W: Write>(&self, writer: &mut W, scope: &ScopeTopology, depth: usize) -> io::Result<()> {
+
+        let indent = "  ".repeat(depth);
+        writeln!(writer, "{}subgraph cluster_{} {{", indent, node_id(&scope.path))?;
+        writeln!(writer, "{}  label={:?};", indent, format!("{} {:?}", scope.name, scope.path))?;
+
+        // The scope's own inputs and outputs, as seen from within the scope.
+        if let Some(external) = scope.operators.iter().find(|op| op.index == 0) {
+            for port in 0 .. external.outputs {
+                writeln!(writer, "{}  {} [label=\"input {}\", shape=invhouse];", indent, input_id(&scope.path, port), port)?;
+            }
+            for port in 0 .. external.inputs {
+                writeln!(writer, "{}  {} [label=\"output {}\", shape=house];", indent, output_id(&scope.path, port), port)?;
+            }
+        }
+
+        // Operators outside of any group, then the operators of each group in its own cluster.
+        let mut groups = BTreeMap::new();
+        for operator in scope.operators.iter().filter(|op| op.index > 0) {
+            let group = self.group(&scope.address(operator.index));
+            groups.entry(group).or_insert_with(Vec::new).push(operator);
+        }
+        for (number, (group, operators)) in groups.into_iter().enumerate() {
+            let depth = if let Some(name) = group {
+                writeln!(writer, "{}  subgraph cluster_{}_group{} {{", indent, node_id(&scope.path), number)?;
+                writeln!(writer, "{}    label={:?};", indent, name)?;
+                depth + 1
+            }
+            else { depth };
+            let inner = "  ".repeat(depth);
+            for operator in operators {
+                let path = scope.address(operator.index);
+                match self.scopes.get(&path) {
+                    Some(child) => self.write_scope(writer, child, depth + 1)?,
+                    None => writeln!(writer, "{}  {} [label={:?}, shape=box];", inner, node_id(&path), format!("{} {:?}", operator.name, path))?,
+                }
+            }
+            if group.is_some() {
+                writeln!(writer, "{}  }}", indent)?;
+            }
+        }
+
+        for (source, target) in scope.edges.iter() {
+            let source = self.source_id(&scope.path, source);
+            let target = self.target_id(&scope.path, target);
+            writeln!(writer, "{}  {} -> {};", indent, source, target)?;
+        }
+
+        writeln!(writer, "{}}}", indent)
+    }
+
+    /// The DOT node identifier for the source `source` of the scope at `path`.
+    fn source_id(&self, path: &[usize], source: &Source) -> String {
+        if source.node == 0 {
+            input_id(path, source.port)
+        }
+        else {
+            let mut path = path.to_vec();
+            path.push(source.node);
+            if self.scopes.contains_key(&path) { output_id(&path, source.port) }
+            else { node_id(&path) }
+        }
+    }
+
+    /// The DOT node identifier for the target `target` of the scope at `path`.
+    fn target_id(&self, path: &[usize], target: &Target) -> String {
+        if target.node == 0 {
+            output_id(path, target.port)
+        }
+        else {
+            let mut path = path.to_vec();
+            path.push(target.node);
+            if self.scopes.contains_key(&path) { input_id(&path, target.port) }
+            else { node_id(&path) }
+        }
+    }
+}
+
+fn node_id(path: &[usize]) -> String {
+    let path = path.iter().map(|x| x.to_string()).collect::<Vec<_>>();
+    format!("op_{}", path.join("_"))
+}
+
+fn input_id(path: &[usize], port: usize) -> String {
+    format!("{}_in{}", node_id(path), port)
+}
+
+fn output_id(path: &[usize], port: usize) -> String {
+    format!("{}_out{}", node_id(path), port)
+}
@@ -1,8 +1,11 @@
 //! Starts a timely dataflow execution from configuration information and per-worker logic.
 
+use std::path::Path;
+
 use crate::communication::{initialize_from, Allocator, allocator::AllocateBuilder, WorkerGuards};
+use crate::communication::allocator::record::{Recorder, RecorderBuilder, Replayer};
 use crate::dataflow::scopes::Child;
-use crate::worker::Worker;
+use crate::worker::{Worker, PanicMonitor, PanicGuard};
 use crate::{CommunicationConfig, WorkerConfig};
 
 /// Configures the execution of a timely dataflow computation.
@@ -135,11 +138,15 @@ where
 ///
 /// The closure may return a result, which will be returned from the computation.
 ///
+/// As the single worker runs only when the closure calls `worker.step()`, tests can interleave
+/// supplying input, stepping the worker, and assertions on probes deterministically. Once the
+/// closure returns, the worker runs until its dataflows complete.
+///
 /// # Examples
 /// ```rust
 /// use timely::dataflow::operators::{ToStream, Inspect};
 ///
-/// // execute a timely dataflow using three worker threads.
+/// // execute a timely dataflow using the current thread.
 /// timely::execute_directly(|worker| {
 ///     worker.dataflow::<(),_,_>(|scope| {
 ///         (0..10).to_stream(scope)
@@ -147,6 +154,33 @@ where
 ///     })
 /// });
 /// ```
+///
+/// This example steps the worker until each round of input has been processed.
+///
+/// ```rust
+/// use timely::dataflow::{InputHandle, ProbeHandle};
+/// use timely::dataflow::operators::{Input, Map, Probe};
+///
+/// timely::execute_directly(|worker| {
+///     let mut input = InputHandle::new();
+///     let mut probe = ProbeHandle::new();
+///     worker.dataflow::<u64,_,_>(|scope| {
+///         scope.input_from(&mut input)
+///              .map(|x: u64| x + 1)
+///              .probe_with(&mut probe);
+///     });
+///
+///     for round in 0 .. 3 {
+///         input.send(round);
+///         input.advance_to(round + 1);
+///         assert!(probe.less_than(input.time()));
+///         while probe.less_than(input.time()) {
+///             worker.step();
+///         }
+///         assert!(probe.less_equal(&(round + 1)));
+///     }
+/// });
+/// ```
 pub fn execute_directly<T, F>(func: F) -> T
 where
     T: Send+'static,
@@ -241,7 +275,7 @@ where
                     let writer = EventWriter::new(stream);
                     let mut logger = BatchLogger::new(writer);
                     result = Some(crate::logging_core::Logger::new(
-                        ::std::time::Instant::now(),
+                        crate::logging_core::time::Instant::now(),
                         ::std::time::Duration::default(),
                         events_setup,
                         move |time, data| logger.publish_batch(time, data)
@@ -258,9 +292,12 @@ where
     let (allocators, other) = config.communication.try_build()?;
 
     let worker_config = config.worker;
+    let panics = std::sync::Arc::new(PanicMonitor::default());
     initialize_from(allocators, other, move |allocator| {
 
         let mut worker = Worker::new(worker_config.clone(), allocator);
+        let _guard = PanicGuard(panics.clone());
+        worker.monitor_panics(panics.clone());
 
         // If an environment variable is set, use it as the default timely logging.
         if let Ok(addr) = ::std::env::var("TIMELY_WORKER_LOG_ADDR") {
@@ -288,6 +325,130 @@ where
     })
 }
 
+/// Executes a timely dataflow as `execute` does, stopping workers whose dataflows remain after `limit`.
+///
+/// Each worker panics once `limit` has elapsed since it started, if it still has dataflows, with a
+/// report of the capabilities held by each of its operators. The panic stops the other workers of
+/// the process, and joining the returned guards reports the failures. The limit is checked as
+/// workers step, which they do in `execute` once the closure returns, and so a closure that
+/// blocks without stepping its worker is not stopped. This suits tests of iterative dataflows,
+/// which might otherwise hang; see also `WorkerConfig::watchdog`, which stops workers that stop
+/// making progress.
+///
+/// # Examples
+/// ```rust
+/// use std::time::Duration;
+/// use timely::dataflow::operators::{ToStream, Operator};
+/// use timely::dataflow::channels::pact::Pipeline;
+///
+/// let guards = timely::execute_with_deadline(timely::Config::process(2), Duration::from_millis(500), |worker| {
+///     worker.dataflow::<u64,_,_>(|scope| {
+///         // an operator that never releases its capability.
+///         (0 .. 10).to_stream(scope)
+///                  .unary::<u64,_,_,_>(Pipeline, "Stuck", |capability, _info| move |input, _output| {
+///                      let _held = &capability;
+///                      input.for_each(|_time, _data| { });
+///                  });
+///     });
+/// }).unwrap();
+///
+/// assert!(guards.join().into_iter().all(|result| result.is_err()));
+/// ```
+pub fn execute_with_deadline<T, F>(
+    mut config: Config,
+    limit: ::std::time::Duration,
+    func: F
+) -> Result<WorkerGuards<T>,String>
+where
+    T:Send+'static,
+    F: Fn(&mut Worker<Allocator>)->T+Send+Sync+'static {
+    config.worker = config.worker.time_limit(limit);
+    execute(config, func)
+}
+
+/// Executes a timely dataflow as `execute` does, recording the communication of each worker to `directory`.
+///
+/// Each worker writes the messages it receives from its channels, and the channel events it acts
+/// on at each step, to the file `worker-<index>.log` in `directory`, which must exist. A worker can
+/// then be re-run alone and deterministically from its log with `replay_worker`, to reproduce a
+/// failure that depends on the order of communication. See the `record` module of the
+/// communication crate for the sources of non-determinism that are not recorded.
+///
+/// # Examples
+/// ```rust
+/// use std::sync::{Arc, Mutex};
+/// use timely::communication::Allocate;
+/// use timely::dataflow::operators::{ToStream, Exchange, Inspect};
+/// use timely::worker::Worker;
+///
+/// // records the batches of data as this worker receives them.
+/// fn build<A: Allocate>(worker: &mut Worker<A>) -> Arc<Mutex<Vec<Vec<u64>>>> {
+///     let seen = Arc::new(Mutex::new(Vec::new()));
+///     let shared = seen.clone();
+///     let index = worker.index() as u64;
+///     worker.dataflow::<u64,_,_>(move |scope| {
+///         (0 .. 100u64).map(move |x| x * 2 + index)
+///                      .to_stream(scope)
+///                      .exchange(|x| *x / 3)
+///                      .inspect_batch(move |_time, data| shared.lock().unwrap().push(data.to_vec()));
+///     });
+///     seen
+/// }
+///
+/// let directory = std::env::temp_dir().join(format!("timely-recorded-{}", std::process::id()));
+/// std::fs::create_dir_all(&directory).unwrap();
+///
+/// let recorded = timely::execute_recorded(timely::Config::process(2), &directory, |worker| {
+///     let seen = build(worker);
+///     while worker.step() { }
+///     let seen = seen.lock().unwrap().clone();
+///     seen
+/// }).unwrap().join().into_iter().map(|result| result.unwrap()).collect::<Vec<_>>();
+///
+/// // the second worker sees the same batches, in the same order, when replayed.
+/// let replayed = timely::replay_worker(directory.join("worker-1.log"), |worker| {
+///     let seen = build(worker);
+///     while worker.step() { }
+///     let seen = seen.lock().unwrap().clone();
+///     seen
+/// }).unwrap();
+/// assert_eq!(replayed, recorded[1]);
+///
+/// std::fs::remove_dir_all(directory).unwrap();
+/// ```
+pub fn execute_recorded<T, F, P>(
+    config: Config,
+    directory: P,
+    func: F
+) -> Result<WorkerGuards<T>,String>
+where
+    T: Send+'static,
+    F: Fn(&mut Worker<Recorder<Allocator>>)->T+Send+Sync+'static,
+    P: AsRef<Path>,
+{
+    let (builders, others) = config.communication.try_build()?;
+    let builders = builders.into_iter().map(|builder| RecorderBuilder::new(builder, &directory)).collect();
+    execute_from(builders, others, config.worker, func)
+}
+
+/// Re-runs a worker recorded by `execute_recorded` from its log at `path`, in the calling thread.
+///
+/// The worker is presented with the messages and channel events of the recorded worker, at the
+/// same pulls and steps, and messages it sends are discarded. The worker should build the same
+/// dataflows and be driven by the same logic as the recorded worker. Once `func` returns, the
+/// worker steps until its dataflows complete or the log is exhausted.
+pub fn replay_worker<T, F, P>(path: P, func: F) -> Result<T, String>
+where
+    F: FnOnce(&mut Worker<Replayer>)->T,
+    P: AsRef<Path>,
+{
+    let (replayer, exhausted) = Replayer::open(path.as_ref()).map_err(|error| format!("failed to open {:?}: {}", path.as_ref(), error))?;
+    let mut worker = Worker::new(WorkerConfig::default(), replayer);
+    let result = func(&mut worker);
+    while !exhausted.get() && worker.step() { }
+    Ok(result)
+}
+
 /// Executes a timely dataflow from supplied arguments and per-communicator logic.
 ///
 /// The `execute` method takes arguments (typically `std::env::args()`) and spins up some number of
@@ -310,6 +471,9 @@ where
 ///
 /// `-p, --process`: identity of this process; from 0 to n-1.
 ///
+/// `--process-threads`: comma-separated numbers of worker threads of each process, for processes
+/// with different numbers of workers, in place of `-w`.
+///
 /// `-h, --hostfile`: a text file whose lines are "hostname:port" in order of process identity.
 /// If not specified, `localhost` will be used, with port numbers increasing from 2101 (chosen
 /// arbitrarily).
@@ -379,8 +543,11 @@ where
     A: AllocateBuilder+'static,
     T: Send+'static,
     F: Fn(&mut Worker<<A as AllocateBuilder>::Allocator>)->T+Send+Sync+'static {
+    let panics = std::sync::Arc::new(PanicMonitor::default());
     initialize_from(builders, others, move |allocator| {
         let mut worker = Worker::new(worker_config.clone(), allocator);
+        let _guard = PanicGuard(panics.clone());
+        worker.monitor_panics(panics.clone());
         let result = func(&mut worker);
         while worker.step_or_park(None) { }
         result
@@ -0,0 +1,11 @@
+//! Bit mixing for deterministic hashes and seeds.
+
+/// Scrambles the bits of `x` (the finalizer of SplitMix64).
+///
+/// Unlike the hashers of the standard library, the function is fixed, so that the identifiers,
+/// seeds, and assignments derived from it do not change between Rust versions.
+pub(crate) fn mix(mut x: u64) -> u64 {
+    x = (x ^ (x >> 30)).wrapping_mul(0xbf58476d1ce4e5b9);
+    x = (x ^ (x >> 27)).wrapping_mul(0x94d049bb133111eb);
+    x ^ (x >> 31)
+}
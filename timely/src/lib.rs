@@ -67,7 +67,7 @@ extern crate timely_communication;
 extern crate timely_bytes;
 extern crate timely_logging;
 
-pub use execute::{execute, execute_directly, example};
+pub use execute::{execute, execute_directly, execute_with_deadline, execute_recorded, replay_worker, example};
 #[cfg(feature = "getopts")]
 pub use execute::execute_from_args;
 pub use order::PartialOrder;
@@ -97,11 +97,19 @@ pub mod dataflow;
 pub mod synchronization;
 pub mod execute;
 pub mod order;
+pub(crate) mod hash;
 
 pub mod logging;
 // pub mod log_events;
 
 pub mod scheduling;
+pub mod allocator;
+
+#[cfg(feature = "prometheus")]
+pub mod prometheus;
+
+#[cfg(feature = "arrow")]
+pub mod arrow;
 
 /// A composite trait for types usable as data in timely dataflow.
 ///
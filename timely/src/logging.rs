@@ -1,4 +1,10 @@
 //! Traits, implementations, and macros related to logging timely events.
+//!
+//! With the `tracing` feature, timely also reports to subscribers of the `tracing` crate. Each
+//! scheduling of a dataflow or an operator runs within a `schedule` span at the trace level,
+//! carrying the name, address, and identifier of the operator, and each batch of records sent on
+//! a channel is a `send` event at the trace level within the span of the sending operator. Any
+//! subscriber can record them, for example to draw flamegraphs or to measure latencies.
 
 /// Type alias for logging timely events.
 pub type WorkerIdentifier = usize;
@@ -9,8 +15,14 @@ pub type TimelyLogger = Logger<TimelyEvent>;
 /// Logger for timely dataflow progress events (the "timely/progress" log stream).
 pub type TimelyProgressLogger = Logger<TimelyProgressEvent>;
 
+use std::rc::Rc;
 use std::time::Duration;
-use crate::dataflow::operators::capture::{Event, EventPusher};
+use crate::Data;
+use crate::communication::Allocate;
+use crate::dataflow::Stream;
+use crate::dataflow::scopes::Child;
+use crate::worker::Worker;
+use crate::dataflow::operators::capture::{Event, EventPusher, EventLink, Replay};
 
 /// Logs events as a timely stream, with progress statements.
 pub struct BatchLogger<T, E, P> where P: EventPusher<Duration, (Duration, E, T)> {
@@ -48,6 +60,61 @@ impl<T, E, P> Drop for BatchLogger<T, E, P> where P: EventPusher<Duration, (Dura
     }
 }
 
+/// Builds a dataflow from the events logged under `name`, captured as a timely stream.
+///
+/// The `logic` closure receives the scope of a new dataflow and the captured stream, whose
+/// records are the time the event was logged, the identifier of the worker, and the event.
+/// The stream is timestamped by the log time, so that its frontier advances as the worker logs.
+/// Once the dataflow is built, a logger is registered under `name`, replacing any existing one.
+///
+/// The dataflow completes only once the logger is removed from the registry and every
+/// operator holding a clone of it has been dropped, so the logger should be removed
+/// before the worker is expected to finish.
+///
+/// # Examples
+/// ```
+/// use timely::dataflow::operators::{ToStream, Inspect, Filter};
+/// use timely::logging::TimelyEvent;
+///
+/// timely::execute_directly(|worker| {
+///
+///     // capture the timely log as a stream, and observe operator schedulings.
+///     timely::logging::capture::<_, TimelyEvent, _, _>(worker, "timely", |_scope, stream| {
+///         stream
+///             .filter(|(_time, _worker, event)| matches!(event, TimelyEvent::Schedule(_)))
+///             .inspect(|x| println!("scheduled: {:?}", x));
+///     });
+///
+///     worker.dataflow::<usize,_,_>(|scope| {
+///         (0 .. 10).to_stream(scope).inspect(|x| println!("seen: {:?}", x));
+///     });
+///
+///     while worker.installed_dataflows().len() > 1 {
+///         worker.step();
+///     }
+///
+///     // allow the capture dataflow to complete.
+///     worker.log_register().remove("timely");
+/// });
+/// ```
+pub fn capture<A, E, R, F>(worker: &mut Worker<A>, name: &str, logic: F) -> R
+where
+    A: Allocate,
+    E: Data,
+    F: FnOnce(&mut Child<Worker<A>, Duration>, Stream<Child<Worker<A>, Duration>, (Duration, WorkerIdentifier, E)>)->R,
+{
+    let link = Rc::new(EventLink::new());
+    let result = worker.dataflow::<Duration,_,_>(|scope| {
+        let stream = Some(link.clone()).replay_into(scope);
+        logic(scope, stream)
+    });
+    let mut logger = BatchLogger::new(link);
+    worker
+        .log_register()
+        .insert::<E,_>(name, move |time, data| logger.publish_batch(time, data));
+    result
+}
+
 #[derive(Serialize, Deserialize, Abomonation, Debug, Clone, Hash, Eq, PartialEq, Ord, PartialOrd)]
 /// The creation of an `Operate` implementor.
 pub struct OperatesEvent {
@@ -139,6 +206,8 @@ pub struct TimelyProgressEvent {
     pub seq_no: usize,
     /// Sequence of nested scope identifiers indicating the path from the root to this instance.
     pub addr: Vec<usize>,
+    /// The name of the scope exchanging progress.
+    pub scope_name: String,
     /// List of message updates, containing Target descriptor, timestamp as string, and delta.
     pub messages: Box<dyn ProgressEventTimestampVec>,
     /// List of capability updates, containing Source descriptor, timestamp as string, and delta.
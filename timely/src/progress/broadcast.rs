@@ -23,6 +23,8 @@ pub struct Progcaster<T:Timestamp> {
     counter: usize,
     /// Sequence of nested scope identifiers indicating the path from the root to this subgraph
     addr: Vec<usize>,
+    /// Name of the subgraph
+    name: String,
     /// Communication channel identifier
     channel_identifier: usize,
 
@@ -31,7 +33,7 @@ pub struct Progcaster<T:Timestamp> {
 
 impl<T:Timestamp+Send> Progcaster<T> {
     /// Creates a new `Progcaster` using a channel from the supplied worker.
-    pub fn new<A: crate::worker::AsWorker>(worker: &mut A, path: &Vec<usize>, mut logging: Option<Logger>, progress_logging: Option<ProgressLogger>) -> Progcaster<T> {
+    pub fn new<A: crate::worker::AsWorker>(worker: &mut A, path: &Vec<usize>, name: &str, mut logging: Option<Logger>, progress_logging: Option<ProgressLogger>) -> Progcaster<T> {
 
         let channel_identifier = worker.new_identifier();
         let (pushers, puller) = worker.allocate(channel_identifier, &path[..]);
@@ -48,6 +50,7 @@ impl<T:Timestamp+Send> Progcaster<T> {
             source: worker_index,
             counter: 0,
             addr,
+            name: name.to_owned(),
             channel_identifier,
             progress_logging,
         }
@@ -84,6 +87,7 @@ impl<T:Timestamp+Send> Progcaster<T> {
                     channel: self.channel_identifier,
                     seq_no: self.counter,
                     addr: self.addr.clone(),
+                    scope_name: self.name.clone(),
                     messages,
                     internal,
                 });
@@ -127,6 +131,7 @@ impl<T:Timestamp+Send> Progcaster<T> {
             let recv_changes = &message.2;
 
             let addr = &mut self.addr;
+            let name = &self.name;
             let channel = self.channel_identifier;
 
             // See comments above about the relatively high cost of this logging, and our
@@ -154,6 +159,7 @@ impl<T:Timestamp+Send> Progcaster<T> {
                     seq_no: counter,
                     channel,
                     addr: addr.clone(),
+                    scope_name: name.clone(),
                     messages,
                     internal,
                 });
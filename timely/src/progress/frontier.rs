@@ -538,6 +538,11 @@ impl<T> MutableAntichain<T> {
         }
     }
 
+    /// Reports the updates accumulated by the antichain, which may repeat times.
+    pub fn updates_iter(&self) -> impl Iterator<Item=&(T, i64)> {
+        self.updates.iter()
+    }
+
     /// Reports the count for a queried time.
     pub fn count_for(&self, query_time: &T) -> i64
     where
@@ -0,0 +1,145 @@
+//! A harness that drives the progress tracking of a single operator, for testing.
+//!
+//! Operators report the messages they consume and produce, and changes to the capabilities they
+//! hold, through their `SharedProgress`, and learn of changes to their input frontiers through it.
+//! The `Harness` plays the part of the operator's scope: it scripts changes to the input frontiers,
+//! schedules the operator, and collects what the operator reports, so that an implementation of
+//! `Operate` can be tested without a worker. Data, if any, is supplied to the operator by the test
+//! through whatever means the operator reads it.
+
+use std::rc::Rc;
+use std::cell::RefCell;
+
+use crate::progress::{Timestamp, Operate, Antichain, ChangeBatch};
+use crate::progress::operate::SharedProgress;
+
+/// Drives the progress tracking of a single operator.
+///
+/// # Examples
+/// ```
+/// use std::rc::Rc;
+/// use std::cell::RefCell;
+/// use timely::scheduling::Schedule;
+/// use timely::progress::{Operate, Antichain};
+/// use timely::progress::operate::SharedProgress;
+/// use timely::progress::harness::Harness;
+///
+/// // A source that holds a capability, advancing it on each schedule until it passes `2`.
+/// struct Countdown { time: Option<u64>, progress: Rc<RefCell<SharedProgress<u64>>> }
+///
+/// impl Schedule for Countdown {
+///     fn name(&self) -> &str { "Countdown" }
+///     fn path(&self) -> &[usize] { &[] }
+///     fn schedule(&mut self) -> bool {
+///         if let Some(time) = self.time.take() {
+///             let mut progress = self.progress.borrow_mut();
+///             progress.internals[0].update(time, -1);
+///             if time < 2 {
+///                 progress.internals[0].update(time + 1, 1);
+///                 self.time = Some(time + 1);
+///             }
+///         }
+///         self.time.is_some()
+///     }
+/// }
+///
+/// impl Operate<u64> for Countdown {
+///     fn inputs(&self) -> usize { 0 }
+///     fn outputs(&self) -> usize { 1 }
+///     fn get_internal_summary(&mut self) -> (Vec<Vec<Antichain<u64>>>, Rc<RefCell<SharedProgress<u64>>>) {
+///         self.progress.borrow_mut().internals[0].update(0, 1);
+///         (Vec::new(), self.progress.clone())
+///     }
+/// }
+///
+/// let operator = Countdown { time: Some(0), progress: Rc::new(RefCell::new(SharedProgress::new(0, 1))) };
+/// let mut harness = Harness::new(Box::new(operator));
+/// assert_eq!(harness.capabilities(0), Antichain::from_elem(0));
+/// assert!(harness.schedule());
+/// assert_eq!(harness.capabilities(0), Antichain::from_elem(1));
+/// assert!(harness.schedule());
+/// assert!(!harness.schedule());
+/// assert_eq!(harness.capabilities(0), Antichain::new());
+/// assert!(harness.produced(0).is_empty());
+/// ```
+pub struct Harness<T: Timestamp> {
+    operator: Box<dyn Operate<T>>,
+    summary: Vec<Vec<Antichain<T::Summary>>>,
+    progress: Rc<RefCell<SharedProgress<T>>>,
+    frontiers: Vec<Antichain<T>>,
+    capabilities: Vec<ChangeBatch<T>>,
+}
+
+impl<T: Timestamp> Harness<T> {
+    /// Prepares `operator` as its scope would, with each input frontier at the minimum timestamp.
+    ///
+    /// The capabilities the operator initially holds are available from `capabilities`.
+    pub fn new(mut operator: Box<dyn Operate<T>>) -> Self {
+        let (summary, progress) = operator.get_internal_summary();
+        let inputs = operator.inputs();
+        let outputs = operator.outputs();
+        for batch in progress.borrow_mut().frontiers.iter_mut() {
+            batch.update(T::minimum(), 1);
+        }
+        operator.set_external_summary();
+        Harness {
+            operator,
+            summary,
+            progress,
+            frontiers: vec![Antichain::from_elem(T::minimum()); inputs],
+            capabilities: vec![ChangeBatch::new(); outputs],
+        }
+    }
+
+    /// The summaries the operator reported from each input to each output.
+    pub fn summary(&self) -> &[Vec<Antichain<T::Summary>>] {
+        &self.summary
+    }
+
+    /// Changes the frontier of `input` to `frontier`, reporting the change to the operator.
+    ///
+    /// An empty frontier indicates that the input is complete. The operator sees the change when
+    /// it is next scheduled.
+    pub fn set_frontier(&mut self, input: usize, frontier: &[T]) {
+        let mut progress = self.progress.borrow_mut();
+        for time in self.frontiers[input].elements().iter() {
+            progress.frontiers[input].update(time.clone(), -1);
+        }
+        for time in frontier.iter() {
+            progress.frontiers[input].update(time.clone(), 1);
+        }
+        self.frontiers[input] = Antichain::from(frontier.to_vec());
+    }
+
+    /// Schedules the operator, returning whether it has outstanding work.
+    pub fn schedule(&mut self) -> bool {
+        self.operator.schedule()
+    }
+
+    /// Drains the counts of messages the operator reports having consumed at `input`.
+    pub fn consumed(&mut self, input: usize) -> Vec<(T, i64)> {
+        self.progress.borrow_mut().consumeds[input].drain().collect()
+    }
+
+    /// Drains the counts of messages the operator reports having produced at `output`.
+    pub fn produced(&mut self, output: usize) -> Vec<(T, i64)> {
+        self.progress.borrow_mut().produceds[output].drain().collect()
+    }
+
+    /// The frontier of the capabilities the operator holds for `output`.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the operator has released more capabilities for a timestamp than it held.
+    pub fn capabilities(&mut self, output: usize) -> Antichain<T> {
+        let mut progress = self.progress.borrow_mut();
+        let capabilities = &mut self.capabilities[output];
+        progress.internals[output].drain_into(capabilities);
+        let mut frontier = Antichain::new();
+        for (time, count) in capabilities.iter() {
+            assert!(*count > 0, "capabilities for {:?} at output {} released more than held", time, output);
+            frontier.insert(time.clone());
+        }
+        frontier
+    }
+}
@@ -1,6 +1,7 @@
 //! Methods which describe an operators topology, and the progress it makes.
 
 use std::rc::Rc;
+use std::any::Any;
 use std::cell::RefCell;
 
 use crate::scheduling::Schedule;
@@ -56,6 +57,19 @@ pub trait Operate<T: Timestamp> : Schedule {
 
     /// Indicates of whether the operator requires `push_external_progress` information or not.
     fn notify_me(&self) -> bool { true }
+
+    /// Indicates whether the operator reads the `outstanding` counts of its shared progress.
+    ///
+    /// The parent scope only computes these counts for operators that read them, and so the
+    /// default is `false`.
+    fn observes_outstanding(&self) -> bool { false }
+
+    /// Hands over the operators of a scope whose progress its parent can track directly.
+    ///
+    /// A subgraph without cycles and with the timestamp of its parent returns its operators,
+    /// which the parent then schedules and tracks as its own, discarding the subgraph. The
+    /// default returns `None`, and the operator is scheduled and tracked as usual.
+    fn flatten(&mut self) -> Option<Box<dyn Any>> { None }
 }
 
 /// Progress information shared between parent and child.
@@ -69,6 +83,12 @@ pub struct SharedProgress<T: Timestamp> {
     pub internals: Vec<ChangeBatch<T>>,
     /// Produced message changes reported by the child operator.
     pub produceds: Vec<ChangeBatch<T>>,
+    /// Outstanding messages at each input, reported by the parent scope.
+    ///
+    /// These are the numbers of messages sent to each input but not yet received, across all
+    /// workers, as known to the parent scope. The parent replaces them with current counts before
+    /// scheduling the operator, and only if the operator `observes_outstanding`.
+    pub outstanding: Vec<ChangeBatch<T>>,
 }
 
 impl<T: Timestamp> SharedProgress<T> {
@@ -79,6 +99,7 @@ impl<T: Timestamp> SharedProgress<T> {
             consumeds: vec![ChangeBatch::new(); inputs],
             internals: vec![ChangeBatch::new(); outputs],
             produceds: vec![ChangeBatch::new(); outputs],
+            outstanding: vec![ChangeBatch::new(); inputs],
         }
     }
 }
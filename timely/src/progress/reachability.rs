@@ -190,18 +190,13 @@ impl<T: Timestamp> Builder<T> {
 
     /// Compiles the current nodes and edges into immutable path summaries.
     ///
-    /// This method has the opportunity to perform some error checking that the path summaries
-    /// are valid, including references to undefined nodes and ports, as well as self-loops with
-    /// default summaries (a serious liveness issue).
+    /// References to undefined nodes and ports are discovered here. The optional logger
+    /// information is baked into the resulting tracker.
     ///
-    /// The optional logger information is baked into the resulting tracker.
+    /// Graphs containing cycles of default path summaries (a serious liveness issue) are not
+    /// checked for here, and are built like any other; callers that should reject them can do
+    /// so with `cyclic_locations`.
     pub fn build(self, logger: Option<logging::TrackerLogger>) -> (Tracker<T>, Vec<Vec<Antichain<T::Summary>>>) {
-
-        if !self.is_acyclic() {
-            println!("Cycle detected without timestamp increment");
-            println!("{:?}", self);
-        }
-
         Tracker::allocate_from(self, logger)
     }
 
@@ -264,6 +259,69 @@ impl<T: Timestamp> Builder<T> {
     /// assert!(builder.is_acyclic());
     /// ```
     pub fn is_acyclic(&self) -> bool {
+        self.cyclic_locations().is_empty()
+    }
+
+    /// Reports locations on, or only reachable through, cycles of default path summaries.
+    ///
+    /// The result is sorted, and is empty exactly when `is_acyclic` returns true.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use timely::progress::frontier::Antichain;
+    /// use timely::progress::{Location, Source, Target};
+    /// use timely::progress::reachability::Builder;
+    ///
+    /// // allocate a new empty topology builder.
+    /// let mut builder = Builder::<usize>::new();
+    ///
+    /// // Two nodes in a loop, with a third node downstream of the loop.
+    /// builder.add_node(0, 1, 1, vec![vec![Antichain::from_elem(0)]]);
+    /// builder.add_node(1, 1, 1, vec![vec![Antichain::from_elem(0)]]);
+    /// builder.add_node(2, 1, 0, vec![vec![]]);
+    ///
+    /// builder.add_edge(Source::new(0, 0), Target::new(1, 0));
+    /// builder.add_edge(Source::new(1, 0), Target::new(0, 0));
+    /// builder.add_edge(Source::new(1, 0), Target::new(2, 0));
+    ///
+    /// assert_eq!(builder.cyclic_locations(), vec![
+    ///     Location::new_target(0, 0),
+    ///     Location::new_source(0, 0),
+    ///     Location::new_target(1, 0),
+    ///     Location::new_source(1, 0),
+    ///     Location::new_target(2, 0),
+    /// ]);
+    /// ```
+    pub fn cyclic_locations(&self) -> Vec<Location> {
+        self.locations_on_cycles(|summary| summary == &Default::default())
+    }
+
+    /// Indicates whether the graph has no cycles at all, including those along which timestamps advance.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use timely::progress::frontier::Antichain;
+    /// use timely::progress::{Source, Target};
+    /// use timely::progress::reachability::Builder;
+    ///
+    /// // allocate a new empty topology builder.
+    /// let mut builder = Builder::<usize>::new();
+    ///
+    /// // A node whose output is connected to its input, advancing timestamps.
+    /// builder.add_node(0, 1, 1, vec![vec![Antichain::from_elem(1)]]);
+    /// builder.add_edge(Source::new(0, 0), Target::new(0, 0));
+    ///
+    /// assert!(builder.is_acyclic());
+    /// assert!(!builder.is_loop_free());
+    /// ```
+    pub fn is_loop_free(&self) -> bool {
+        self.locations_on_cycles(|_summary| true).is_empty()
+    }
+
+    /// Reports locations on, or only reachable through, cycles of summaries satisfying `follow`.
+    fn locations_on_cycles(&self, follow: impl Fn(&T::Summary)->bool) -> Vec<Location> {
 
         let locations = self.shape.iter().map(|(targets, sources)| targets + sources).sum();
         let mut in_degree = HashMap::with_capacity(locations);
@@ -288,7 +346,7 @@ impl<T: Timestamp> Builder<T> {
                 for (output, summaries) in outputs.iter().enumerate() {
                     let source = Location::new_source(index, output);
                     for summary in summaries.elements().iter() {
-                        if summary == &Default::default() {
+                        if follow(summary) {
                             *in_degree.entry(source).or_insert(0) += 1;
                         }
                     }
@@ -325,7 +383,7 @@ impl<T: Timestamp> Builder<T> {
                     for (output, summaries) in self.nodes[node][port].iter().enumerate() {
                         let source = Location::new_source(node, output);
                         for summary in summaries.elements().iter() {
-                            if summary == &Default::default() {
+                            if follow(summary) {
                                 *in_degree.get_mut(&source).unwrap() -= 1;
                                 if in_degree[&source] == 0 {
                                     in_degree.remove(&source);
@@ -339,7 +397,9 @@ impl<T: Timestamp> Builder<T> {
         }
 
         // Acyclic graphs should reduce to empty collections.
-        in_degree.is_empty()
+        let mut cyclic = in_degree.into_iter().map(|(location, _)| location).collect::<Vec<_>>();
+        cyclic.sort();
+        cyclic
     }
 }
 
@@ -6,14 +6,18 @@
 //! of the grouped operators.
 
 use std::rc::Rc;
-use std::cell::RefCell;
-use std::collections::BinaryHeap;
+use std::any::{Any, TypeId};
+use std::cell::{Cell, RefCell};
+use std::fmt::Debug;
+use std::panic;
+use std::collections::{BinaryHeap, HashMap};
 use std::cmp::Reverse;
+use crate::logging_core::time::Instant;
 
 use crate::logging::TimelyLogger as Logger;
 use crate::logging::TimelyProgressLogger as ProgressLogger;
 
-use crate::scheduling::Schedule;
+use crate::scheduling::{Schedule, OutstandingCapability, QueuedMessages};
 use crate::scheduling::activate::Activations;
 
 use crate::progress::frontier::{Antichain, MutableAntichain, MutableAntichainFilter};
@@ -26,6 +30,8 @@ use crate::progress::reachability;
 use crate::progress::timestamp::Refines;
 
 use crate::worker::ProgressMode;
+use crate::dataflow::topology::{ScopeTopology, OperatorTopology};
+use crate::dataflow::metrics::OperatorMetrics;
 
 // IMPORTANT : by convention, a child identifier of zero is used to indicate inputs and outputs of
 // the Subgraph itself. An identifier greater than zero corresponds to an actual child, which can
@@ -56,6 +62,13 @@ where
 
     edge_stash: Vec<(Source, Target)>,
 
+    // operators of flattened child scopes, by the index of the scope, hoisted into this scope when built.
+    flattened: Vec<(usize, Flattened<TInner>)>,
+    // for each scope flattened into this one, its path and the indices here of its operators.
+    hoisted: Vec<(Vec<usize>, HashMap<usize, usize>)>,
+    // counts of records entering flattened scopes, which are instead tracked where they are received.
+    hoisted_inputs: Vec<Rc<RefCell<ChangeBatch<TInner>>>>,
+
     // shared state written to by the datapath, counting records entering this subgraph instance.
     input_messages: Vec<Rc<RefCell<ChangeBatch<TInner>>>>,
 
@@ -116,6 +129,9 @@ where
             children,
             child_count: 1,
             edge_stash: Vec::new(),
+            flattened: Vec::new(),
+            hoisted: Vec::new(),
+            hoisted_inputs: Vec::new(),
             input_messages: Vec::new(),
             output_capabilities: Vec::new(),
             logging,
@@ -130,17 +146,97 @@ where
     }
 
     /// Adds a new child to the subgraph.
-    pub fn add_child(&mut self, child: Box<dyn Operate<TInner>>, index: usize, identifier: usize) {
-        {
-            let mut child_path = self.path.clone();
-            child_path.push(index);
-            self.logging.as_mut().map(|l| l.log(crate::logging::OperatesEvent {
-                id: identifier,
-                addr: child_path,
-                name: child.name().to_owned(),
-            }));
+    ///
+    /// A child scope that hands over its operators with `Operate::flatten` is replaced by a
+    /// placeholder, and its operators are hoisted into this scope when it is built.
+    pub fn add_child(&mut self, mut child: Box<dyn Operate<TInner>>, index: usize, identifier: usize) {
+        if let Some(flattened) = child.flatten() {
+            let flattened = flattened.downcast::<Flattened<TInner>>().expect("flattened scope with a different timestamp");
+            let placeholder = PerOperatorState::flattened(child.name(), index, self.path.clone(), identifier, child.inputs(), child.outputs(), flattened.summary.clone());
+            self.children.push(placeholder);
+            self.flattened.push((index, *flattened));
+        }
+        else {
+            self.children.push(PerOperatorState::new(child, index, self.path.clone(), identifier, self.logging.clone()))
+        }
+    }
+
+    /// Checks that the edges of the subgraph are well formed, before any state depends on them.
+    ///
+    /// Each edge must connect an output port and an input port that exist, and each output of the
+    /// subgraph must be connected to something inside it. Malformed edges would otherwise surface as
+    /// index panics while progress is exchanged, or as outputs whose frontiers never advance.
+    fn validate(&self) {
+        let describe = |node: usize| {
+            match self.children.get(node) {
+                Some(child) => format!("{:?} (index {})", child.name, node),
+                None => format!("unknown operator (index {})", node),
+            }
+        };
+        for &(source, target) in self.edge_stash.iter() {
+            let source_ok = self.children.get(source.node).map(|child| source.port < child.outputs).unwrap_or(false);
+            if !source_ok {
+                panic!("Scope {:?} at {:?} has an edge from output {} of {}, which does not exist", self.name, self.path, source.port, describe(source.node));
+            }
+            let target_ok = self.children.get(target.node).map(|child| target.port < child.inputs).unwrap_or(false);
+            if !target_ok {
+                panic!("Scope {:?} at {:?} has an edge to input {} of {}, which does not exist", self.name, self.path, target.port, describe(target.node));
+            }
+        }
+        for port in 0 .. self.output_capabilities.len() {
+            if !self.edge_stash.iter().any(|&(_, target)| target == Target::new(0, port)) {
+                panic!("Scope {:?} at {:?} has output {} with no incoming edge", self.name, self.path, port);
+            }
         }
-        self.children.push(PerOperatorState::new(child, index, self.path.clone(), identifier, self.logging.clone()))
+    }
+
+    /// Moves the operators of flattened child scopes into this scope, after its own operators.
+    ///
+    /// Returns the edges between the operators, where edges to and from a flattened scope are
+    /// replaced by edges to and from the operators within it connected to the same ports.
+    fn hoist(&mut self) -> Vec<(Source, Target)> {
+
+        // the targets within each flattened scope of its inputs, and the indices here of its operators.
+        let mut scopes = HashMap::new();
+        // edges from hoisted operators, to targets within their scopes.
+        let mut internal = Vec::new();
+
+        for (scope, flattened) in self.flattened.drain(..) {
+            let mut inputs = Vec::new();
+            let mut indices = HashMap::new();
+            for mut child in flattened.children {
+                let edges = ::std::mem::take(&mut child.edges);
+                if child.index == 0 {
+                    inputs = edges;
+                }
+                else if !child.flattened {
+                    let index = self.children.len();
+                    indices.insert(child.index, index);
+                    for (port, targets) in edges.into_iter().enumerate() {
+                        internal.extend(targets.into_iter().map(|target| (scope, Source::new(index, port), target)));
+                    }
+                    child.index = index;
+                    child.edges = vec![Vec::new(); child.outputs];
+                    self.children.push(child);
+                }
+            }
+            for (path, operators) in flattened.hoisted {
+                let operators = operators.into_iter().map(|(inner, outer)| (inner, indices[&outer])).collect();
+                self.hoisted.push((path, operators));
+            }
+            self.hoisted.push((flattened.path, indices.clone()));
+            self.hoisted_inputs.extend(flattened.hoisted_inputs);
+            scopes.insert(scope, (inputs, indices));
+        }
+
+        let mut edges = Vec::new();
+        for &(source, target) in self.edge_stash.iter().filter(|(source, _)| !scopes.contains_key(&source.node)) {
+            resolve(source, target, &scopes, &self.edge_stash, &mut edges);
+        }
+        for (scope, source, target) in internal {
+            resolve_within(scope, source, target, &scopes, &self.edge_stash, &mut edges);
+        }
+        edges
     }
 
     /// Now that initialization is complete, actually build a subgraph.
@@ -160,7 +256,9 @@ where
         // Create empty child zero represenative.
         self.children[0] = PerOperatorState::empty(outputs, inputs);
 
-        let mut builder = reachability::Builder::new();
+        self.validate();
+
+        let mut builder = reachability::Builder::<TInner>::new();
 
         // Child 0 has `inputs` outputs and `outputs` inputs, not yet connected.
         builder.add_node(0, outputs, inputs, vec![vec![Antichain::new(); inputs]; outputs]);
@@ -168,11 +266,74 @@ where
             builder.add_node(index, child.inputs, child.outputs, child.internal_summary.clone());
         }
 
-        for (source, target) in self.edge_stash {
+        // Log each child once, with any name given to it since it was added.
+        for child in self.children.iter_mut().skip(1) {
+            if let Some(name) = worker.topology().take_name(&child.address) {
+                child.name = name;
+            }
+            if let Some(logging) = self.logging.as_mut() {
+                logging.log(crate::logging::OperatesEvent { id: child.id, addr: child.address.clone(), name: child.name.clone() });
+            }
+        }
+
+        // Counters are opt-in, as maintaining them times each schedule call.
+        if worker.config().metrics {
+            for child in self.children.iter_mut().skip(1).filter(|child| !child.flattened) {
+                let metrics = Rc::new(RefCell::new(OperatorMetrics::new(child.name.clone(), child.inputs, child.outputs)));
+                worker.metrics().insert(child.address.clone(), metrics.clone());
+                child.metrics = Some(metrics);
+            }
+        }
+
+        for &(source, target) in self.edge_stash.iter() {
+            builder.add_edge(source, target);
+        }
+
+        // Cycles without timestamp increments would prevent progress tracking from completing.
+        // A scope without any cycles has none of them, and most scopes need only the one check.
+        let loop_free = builder.is_loop_free();
+        if !loop_free {
+            let cyclic = builder.cyclic_locations();
+            if !cyclic.is_empty() {
+                panic!("Scope {:?} at {:?} contains a cycle without timestamp increment, through locations: {:?}", self.name, self.path, cyclic);
+            }
+        }
+
+        // A scope without cycles whose timestamp is that of its parent needs none of its own
+        // progress tracking, and its parent tracks the progress of its operators instead.
+        let flattenable = TypeId::of::<TInner>() == TypeId::of::<TOuter>() && loop_free;
+
+        worker.topology().insert(ScopeTopology {
+            name: self.name.clone(),
+            path: self.path.clone(),
+            operators: self.children.iter().map(|child| OperatorTopology {
+                index: child.index,
+                name: child.name.clone(),
+                inputs: child.inputs,
+                outputs: child.outputs,
+            }).collect(),
+            edges: self.edge_stash.clone(),
+            flattenable,
+        });
+
+        // Progress is tracked through the operators of flattened scopes, in place of the scopes.
+        let edges = self.hoist();
+        let mut builder = reachability::Builder::new();
+        builder.add_node(0, outputs, inputs, vec![vec![Antichain::new(); inputs]; outputs]);
+        for (index, child) in self.children.iter().enumerate().skip(1) {
+            if child.flattened {
+                builder.add_node(index, child.inputs, child.outputs, vec![vec![Antichain::new(); child.outputs]; child.inputs]);
+            }
+            else {
+                builder.add_node(index, child.inputs, child.outputs, child.internal_summary.clone());
+            }
+        }
+        for (source, target) in edges {
             self.children[source.node].edges[source.port].push(target);
             builder.add_edge(source, target);
         }
 
+        // The graph was checked for cycles above, and flattening introduces none.
         // The `None` argument is optional logging infrastructure.
         let path = self.path.clone();
         let reachability_logging =
@@ -181,21 +342,29 @@ where
             .map(|logger| reachability::logging::TrackerLogger::new(path, logger));
         let (tracker, scope_summary) = builder.build(reachability_logging);
 
-        let progcaster = Progcaster::new(worker, &self.path, self.logging.clone(), self.progress_logging.clone());
+        let progcaster = Progcaster::new(worker, &self.path, &self.name, self.logging.clone(), self.progress_logging.clone());
 
-        let mut incomplete = vec![true; self.children.len()];
-        incomplete[0] = false;
-        let incomplete_count = incomplete.len() - 1;
+        let incomplete = self.children.iter().map(|child| child.index > 0 && !child.flattened).collect::<Vec<_>>();
+        let incomplete_count = incomplete.iter().filter(|incomplete| **incomplete).count();
 
         let activations = worker.activations();
 
         activations.borrow_mut().activate(&self.path[..]);
 
+        #[cfg(feature = "fuzz")]
+        let fuzz = {
+            let salt = self.path.iter().cloned().chain(Some(worker.index())).collect::<Vec<_>>();
+            worker.config().fuzz.map(|seed| crate::scheduling::fuzz::Rng::new(seed, &salt))
+        };
+
         Subgraph {
             name: self.name,
             path: self.path,
             inputs,
             outputs,
+            flattenable,
+            hoisted: self.hoisted,
+            hoisted_inputs: self.hoisted_inputs,
             incomplete,
             incomplete_count,
             activations,
@@ -213,10 +382,73 @@ where
             scope_summary,
 
             progress_mode: worker.config().progress_mode,
+
+            #[cfg(feature = "fuzz")]
+            fuzz,
         }
     }
 }
 
+/// The targets within a flattened scope of each of its inputs, and the indices of its operators in its parent.
+type FlattenedPorts = (Vec<Vec<Target>>, HashMap<usize, usize>);
+
+/// Adds to `resolved` edges from `source` to `target`, or if `target` is the input of a flattened
+/// scope, to the targets that input reaches.
+fn resolve(source: Source, target: Target, scopes: &HashMap<usize, FlattenedPorts>, edges: &[(Source, Target)], resolved: &mut Vec<(Source, Target)>) {
+    match scopes.get(&target.node) {
+        Some((inputs, _)) => {
+            for inner in inputs[target.port].iter() {
+                resolve_within(target.node, source, *inner, scopes, edges, resolved);
+            }
+        },
+        None => resolved.push((source, target)),
+    }
+}
+
+/// Adds to `resolved` edges from `source` to `target` within the flattened scope `scope`, or if
+/// `target` is an output of the scope, to the targets that output reaches.
+fn resolve_within(scope: usize, source: Source, target: Target, scopes: &HashMap<usize, FlattenedPorts>, edges: &[(Source, Target)], resolved: &mut Vec<(Source, Target)>) {
+    if target.node == 0 {
+        let output = Source::new(scope, target.port);
+        for &(_, outer) in edges.iter().filter(|(source, _)| *source == output) {
+            resolve(source, outer, scopes, edges, resolved);
+        }
+    }
+    else {
+        let (_, indices) = &scopes[&scope];
+        resolved.push((source, Target::new(indices[&target.node], target.port)));
+    }
+}
+
+thread_local! {
+    /// Set while a panic annotated by `annotate_panic` unwinds through enclosing scopes.
+    static ANNOTATED: Cell<bool> = Cell::new(false);
+}
+
+/// Re-raises the panic `payload` of the operator at `address`, annotated with its name, address,
+/// and input frontiers.
+///
+/// The frontiers bound the timestamps the operator may have been processing, but the timestamp
+/// itself is not known here. A panic is annotated by the innermost operator it unwinds through,
+/// and passes unchanged through the scopes that contain it. Only panics with `&str` or `String`
+/// payloads are annotated, by prefixing their message; other payloads are re-raised unchanged, so
+/// that callers can still downcast them. The panic is re-raised without invoking the panic hook,
+/// which has already printed its message.
+/// The operator is `outermost` if it is scheduled by a dataflow, the last scope to see the panic.
+fn annotate_panic<T: Debug>(name: &str, address: &[usize], outermost: bool, frontiers: &[Vec<T>], payload: Box<dyn Any+Send>) -> ! {
+    if ANNOTATED.with(|annotated| annotated.replace(!outermost)) {
+        panic::resume_unwind(payload);
+    }
+    let message = payload.downcast_ref::<&str>().map(|message| message.to_string())
+        .or_else(|| payload.downcast_ref::<String>().cloned());
+    match message {
+        Some(message) => {
+            let annotation = format!("operator {:?} at {:?} panicked with input frontiers {:?}", name, address, frontiers);
+            panic::resume_unwind(Box::new(format!("{}: {}", annotation, message)))
+        },
+        None => panic::resume_unwind(payload),
+    }
+}
 
 /// A dataflow subgraph.
 ///
@@ -232,10 +464,16 @@ where
     pub path: Vec<usize>,
     inputs: usize,          // number of inputs.
     outputs: usize,         // number of outputs.
+    flattenable: bool,      // whether the parent may track progress through the children instead.
 
     // handles to the children of the scope. index i corresponds to entry i-1, unless things change.
     children: Vec<PerOperatorState<TInner>>,
 
+    // for each scope flattened into this one, its path and the indices here of its operators.
+    hoisted: Vec<(Vec<usize>, HashMap<usize, usize>)>,
+    // counts of records entering flattened scopes, which are instead tracked where they are received.
+    hoisted_inputs: Vec<Rc<RefCell<ChangeBatch<TInner>>>>,
+
     incomplete: Vec<bool>,   // the incompletion status of each child.
     incomplete_count: usize, // the number of incomplete children.
 
@@ -264,6 +502,10 @@ where
     scope_summary: Vec<Vec<Antichain<TInner::Summary>>>,
 
     progress_mode: ProgressMode,
+
+    // randomizes the order in which children are scheduled.
+    #[cfg(feature = "fuzz")]
+    fuzz: Option<crate::scheduling::fuzz::Rng>,
 }
 
 impl<TOuter, TInner> Schedule for Subgraph<TOuter, TInner>
@@ -293,15 +535,33 @@ where
 
         {   // Enqueue active children; scoped to let borrow drop.
             let temp_active = &mut self.temp_active;
-            self.activations
-                .borrow_mut()
-                .for_extensions(&self.path[..], |index| temp_active.push(Reverse(index)));
+            let activations = self.activations.borrow_mut();
+            activations.for_extensions(&self.path[..], |index| temp_active.push(Reverse(index)));
+            // operators of flattened scopes are activated at their own addresses.
+            for (path, indices) in self.hoisted.iter() {
+                activations.for_extensions(&path[..], |index| {
+                    if let Some(child) = indices.get(&index) {
+                        temp_active.push(Reverse(*child));
+                    }
+                });
+            }
         }
 
         // Schedule child operators.
         //
         // We should be able to schedule arbitrary subsets of children, as
         // long as we eventually schedule all children that need to do work.
+        #[cfg(feature = "fuzz")]
+        if let Some(rng) = self.fuzz.as_mut() {
+            // schedule active children in a random order, rather than in order of their indices.
+            let mut active = self.temp_active.drain().map(|Reverse(index)| index).filter(|index| *index > 0).collect::<Vec<_>>();
+            active.sort_unstable();
+            active.dedup();
+            rng.shuffle(&mut active);
+            for index in active {
+                self.activate_child(index);
+            }
+        }
         let mut previous = 0;
         while let Some(Reverse(index)) = self.temp_active.pop() {
             // De-duplicate, and don't revisit.
@@ -326,6 +586,44 @@ where
 
         incomplete || tracking
     }
+
+    fn frontiers_empty(&mut self) -> bool {
+        self.local_pointstamp.is_empty() && self.final_pointstamp.is_empty() && !self.pointstamp_tracker.tracking_anything()
+    }
+
+    fn outstanding_capabilities(&self, reports: &mut Vec<OutstandingCapability>) {
+        for (index, child) in self.children.iter().enumerate().skip(1) {
+            for (port, source) in self.pointstamp_tracker.node_state(index).sources.iter().enumerate() {
+                if !source.pointstamps.is_empty() {
+                    reports.push(OutstandingCapability {
+                        address: child.address.clone(),
+                        name: child.name.clone(),
+                        port,
+                        frontier: format!("{:?}", source.pointstamps.frontier().iter().collect::<Vec<_>>()),
+                    });
+                }
+            }
+            if let Some(operator) = child.operator.as_ref() {
+                operator.outstanding_capabilities(reports);
+            }
+        }
+    }
+
+    fn queued_messages(&self, reports: &mut Vec<QueuedMessages>) {
+        for (index, child) in self.children.iter().enumerate().skip(1) {
+            for (port, target) in self.pointstamp_tracker.node_state(index).targets.iter().enumerate() {
+                reports.push(QueuedMessages {
+                    address: child.address.clone(),
+                    name: child.name.clone(),
+                    port,
+                    count: target.pointstamps.updates_iter().map(|(_, count)| count).sum(),
+                });
+            }
+            if let Some(operator) = child.operator.as_ref() {
+                operator.queued_messages(reports);
+            }
+        }
+    }
 }
 
 
@@ -341,7 +639,23 @@ where
 
         let child = &mut self.children[child_index];
 
-        let incomplete = child.schedule();
+        // Report the messages outstanding at the child's inputs, for operators that adapt to them.
+        if child.outstanding {
+            let child_state = self.pointstamp_tracker.node_state(child_index);
+            let mut shared_progress = child.shared_progress.borrow_mut();
+            for (outstanding, target) in shared_progress.outstanding.iter_mut().zip(child_state.targets.iter()) {
+                outstanding.clear();
+                outstanding.extend(target.pointstamps.updates_iter().cloned());
+            }
+        }
+
+        let incomplete = match panic::catch_unwind(panic::AssertUnwindSafe(|| child.schedule())) {
+            Ok(incomplete) => incomplete,
+            Err(payload) => {
+                let frontiers = self.pointstamp_tracker.node_state(child_index).targets.iter().map(|target| target.implications.frontier().to_vec()).collect::<Vec<_>>();
+                annotate_panic(&child.name, &child.address, self.path.len() <= 1, &frontiers, payload)
+            }
+        };
 
         if incomplete != self.incomplete[child_index] {
             if incomplete { self.incomplete_count += 1; }
@@ -397,6 +711,9 @@ where
     /// at an operator output, rather than message counts. These counts are used only at
     /// mark [XXX] where they are reported upwards to the parent scope.
     fn harvest_inputs(&mut self) {
+        for counts in self.hoisted_inputs.iter() {
+            counts.borrow_mut().clear();
+        }
         for input in 0 .. self.inputs {
             let source = Location::new_source(0, input);
             let mut borrowed = self.input_messages[input].borrow_mut();
@@ -565,24 +882,62 @@ where
         (internal_summary, self.shared_progress.clone())
     }
 
+    fn flatten(&mut self) -> Option<Box<dyn Any>> {
+        if !self.flattenable {
+            return None;
+        }
+        let mut summary = vec![vec![Antichain::new(); self.outputs]; self.inputs];
+        for (input, outputs) in self.scope_summary.iter().enumerate() {
+            for (output, antichain) in outputs.iter().enumerate() {
+                summary[input][output] = antichain.clone();
+            }
+        }
+        let mut hoisted_inputs = ::std::mem::take(&mut self.hoisted_inputs);
+        hoisted_inputs.append(&mut self.input_messages);
+        Some(Box::new(Flattened {
+            path: self.path.clone(),
+            summary,
+            children: ::std::mem::take(&mut self.children),
+            hoisted: ::std::mem::take(&mut self.hoisted),
+            hoisted_inputs,
+        }))
+    }
+
     fn set_external_summary(&mut self) {
         self.accept_frontier();
         self.propagate_pointstamps();  // ensure propagation of input frontiers.
-        self.children
-            .iter_mut()
-            .flat_map(|child| child.operator.as_mut())
-            .for_each(|op| op.set_external_summary());
+        for (index, child) in self.children.iter_mut().enumerate() {
+            if let Some(op) = child.operator.as_mut() {
+                // operators may run their logic for the first time here.
+                if let Err(payload) = panic::catch_unwind(panic::AssertUnwindSafe(|| op.set_external_summary())) {
+                    let frontiers = self.pointstamp_tracker.node_state(index).targets.iter().map(|target| target.implications.frontier().to_vec()).collect::<Vec<_>>();
+                    annotate_panic(&child.name, &child.address, self.path.len() <= 1, &frontiers, payload);
+                }
+            }
+        }
     }
 }
 
+/// The operators of a flattened scope, handed to its parent by `Operate::flatten`.
+struct Flattened<T: Timestamp> {
+    path: Vec<usize>,
+    summary: Vec<Vec<Antichain<T::Summary>>>,
+    children: Vec<PerOperatorState<T>>,
+    hoisted: Vec<(Vec<usize>, HashMap<usize, usize>)>,
+    hoisted_inputs: Vec<Rc<RefCell<ChangeBatch<T>>>>,
+}
+
 struct PerOperatorState<T: Timestamp> {
 
     name: String,       // name of the operator
     index: usize,       // index of the operator within its parent scope
     id: usize,          // worker-unique identifier
+    address: Vec<usize>,    // address of the operator, which may be within a flattened scope
+    flattened: bool,    // indicates a scope whose operators are hoisted into the parent
 
     local: bool,        // indicates whether the operator will exchange data or not
     notify: bool,
+    outstanding: bool,  // indicates whether the operator reads the messages outstanding at its inputs
     inputs: usize,      // number of inputs to the operator
     outputs: usize,     // number of outputs from the operator
 
@@ -595,6 +950,8 @@ struct PerOperatorState<T: Timestamp> {
     internal_summary: Vec<Vec<Antichain<T::Summary>>>,   // cached result from get_internal_summary.
 
     logging: Option<Logger>,
+
+    metrics: Option<Rc<RefCell<OperatorMetrics>>>,  // counters, registered with the worker when built if enabled.
 }
 
 impl<T: Timestamp> PerOperatorState<T> {
@@ -605,24 +962,41 @@ impl<T: Timestamp> PerOperatorState<T> {
             operator:   None,
             index:      0,
             id:         usize::max_value(),
+            address:    Vec::new(),
+            flattened:  false,
             local:      false,
             notify:     true,
+            outstanding: false,
             inputs,
             outputs,
 
             edges: vec![Vec::new(); outputs],
 
             logging: None,
+            metrics: None,
 
             shared_progress: Rc::new(RefCell::new(SharedProgress::new(inputs,outputs))),
             internal_summary: Vec::new(),
         }
     }
 
+    /// A placeholder for a flattened scope, with the scope's summary and no operator.
+    fn flattened(name: &str, index: usize, mut path: Vec<usize>, identifier: usize, inputs: usize, outputs: usize, summary: Vec<Vec<Antichain<T::Summary>>>) -> PerOperatorState<T> {
+        path.push(index);
+        let mut state = PerOperatorState::empty(inputs, outputs);
+        state.name = name.to_owned();
+        state.index = index;
+        state.id = identifier;
+        state.address = path;
+        state.flattened = true;
+        state.internal_summary = summary;
+        state
+    }
+
     pub fn new(
         mut scope: Box<dyn Operate<T>>,
         index: usize,
-        mut _path: Vec<usize>,
+        mut path: Vec<usize>,
         identifier: usize,
         logging: Option<Logger>
     ) -> PerOperatorState<T>
@@ -631,6 +1005,7 @@ impl<T: Timestamp> PerOperatorState<T> {
         let inputs = scope.inputs();
         let outputs = scope.outputs();
         let notify = scope.notify_me();
+        let outstanding = scope.observes_outstanding();
 
         let (internal_summary, shared_progress) = scope.get_internal_summary();
 
@@ -646,18 +1021,24 @@ impl<T: Timestamp> PerOperatorState<T> {
             "operator summary had too few outputs",
         );
 
+        path.push(index);
+
         PerOperatorState {
             name:               scope.name().to_owned(),
             operator:           Some(scope),
             index,
             id:                 identifier,
+            address:            path,
+            flattened:          false,
             local,
             notify,
+            outstanding,
             inputs,
             outputs,
             edges:              vec![vec![]; outputs],
 
             logging,
+            metrics: None,
 
             shared_progress,
             internal_summary,
@@ -681,8 +1062,20 @@ impl<T: Timestamp> PerOperatorState<T> {
                 l.log(crate::logging::ScheduleEvent::start(self.id));
             }
 
+            #[cfg(feature = "tracing")]
+            let _span = tracing_dep::trace_span!("schedule", name = %self.name, address = ?self.address, id = self.id).entered();
+
+            // only measure the schedule call if metrics are enabled for the worker.
+            let measure = self.metrics.as_ref().map(|_| (Instant::now(), crate::allocator::allocated()));
             let incomplete = operator.schedule();
 
+            if let (Some(metrics), Some((start, allocated))) = (self.metrics.as_ref(), measure) {
+                let mut metrics = metrics.borrow_mut();
+                metrics.schedules += 1;
+                metrics.elapsed += start.elapsed();
+                metrics.allocated += crate::allocator::allocated() - allocated;
+            }
+
             // Perhaps log information about the stop of the schedule call.
             if let Some(l) = self.logging.as_mut() {
                 l.log(crate::logging::ScheduleEvent::stop(self.id));
@@ -719,6 +1112,22 @@ impl<T: Timestamp> PerOperatorState<T> {
 
         let shared_progress = &mut *self.shared_progress.borrow_mut();
 
+        if let Some(metrics) = self.metrics.as_ref() {
+            let mut metrics = metrics.borrow_mut();
+            for (input, consumed) in shared_progress.consumeds.iter_mut().enumerate() {
+                for (_time, delta) in consumed.iter() {
+                    metrics.records_in[input] += delta;
+                    metrics.batches_in[input] += 1;
+                }
+            }
+            for (output, produced) in shared_progress.produceds.iter_mut().enumerate() {
+                for (_time, delta) in produced.iter() {
+                    metrics.records_out[output] += delta;
+                    metrics.batches_out[output] += 1;
+                }
+            }
+        }
+
         // Migrate consumeds, internals, produceds into progress statements.
         for (input, consumed) in shared_progress.consumeds.iter_mut().enumerate() {
             let target = Location::new_target(self.index, input);
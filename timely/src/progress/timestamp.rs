@@ -9,6 +9,36 @@ use crate::communication::Data;
 use crate::order::PartialOrder;
 
 /// A composite trait for types that serve as timestamps in timely dataflow.
+///
+/// In addition to the unsigned and signed integer types, `std::time::Duration` may be used
+/// for event-time dataflows, and `()` may be used for dataflows with a single epoch.
+///
+/// # Examples
+/// ```
+/// use std::time::Duration;
+/// use timely::dataflow::operators::{ToStream, Inspect};
+///
+/// timely::execute_directly(|worker| {
+///
+///     // wall-clock event times, advanced by path summaries that are durations.
+///     worker.dataflow::<Duration,_,_>(|scope| {
+///         (0..10u64).to_stream(scope)
+///                   .inspect_time(|t, x| assert_eq!(t, &Duration::default(), "{:?}", x));
+///     });
+///
+///     // 32-bit epochs, for compact progress traffic.
+///     worker.dataflow::<u32,_,_>(|scope| {
+///         (0..10u64).to_stream(scope)
+///                   .inspect_time(|t, _x| assert_eq!(t, &0));
+///     });
+///
+///     // a static dataflow with a single epoch.
+///     worker.dataflow::<(),_,_>(|scope| {
+///         (0..10u64).to_stream(scope)
+///                   .inspect(|x| assert!(x < &10));
+///     });
+/// });
+/// ```
 pub trait Timestamp: Clone+Eq+PartialOrder+Debug+Send+Any+Data+Hash+Ord {
     /// A type summarizing action on a timestamp along a dataflow path.
     type Summary : PathSummary<Self> + 'static;
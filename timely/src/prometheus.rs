@@ -0,0 +1,223 @@
+//! Serves worker metrics over HTTP in the Prometheus text exposition format.
+//!
+//! An `Exporter` binds a listening socket and answers each request from a background thread
+//! with the most recently published metrics of each worker. Workers publish snapshots of their
+//! operator counters and of the messages queued at each operator input with `Exporter::publish`,
+//! and the frontiers of their probes with `Exporter::publish_probe`, for example once per round
+//! of input; the exporter can be cloned and shared between the workers of a process. Operator
+//! counters are only maintained by workers with `WorkerConfig::metrics` enabled.
+//!
+//! Counters are exported as totals, from which Prometheus derives rates (e.g. records per
+//! second) with its `rate` function. Queued messages and probe frontiers are exported as gauges.
+//!
+//! # Examples
+//! ```
+//! use std::io::{Read, Write};
+//! use std::net::TcpStream;
+//!
+//! use timely::dataflow::InputHandle;
+//! use timely::dataflow::operators::{Input, Map, Probe};
+//! use timely::prometheus::Exporter;
+//!
+//! let exporter = Exporter::bind("127.0.0.1:0").unwrap();
+//! let address = exporter.local_addr();
+//!
+//! let mut config = timely::Config::thread();
+//! config.worker = timely::WorkerConfig::default().metrics(true);
+//! timely::execute(config, move |worker| {
+//!
+//!     let mut input = InputHandle::new();
+//!     let probe = worker.dataflow::<usize,_,_>(|scope| {
+//!         scope.input_from(&mut input).map(|x: usize| x + 1).probe()
+//!     });
+//!
+//!     for round in 0 .. 10 {
+//!         input.send(round);
+//!         input.advance_to(round + 1);
+//!         worker.step_while(|| probe.less_than(input.time()));
+//!         exporter.publish(worker);
+//!         exporter.publish_probe(worker, "output", &probe, |time| *time as f64);
+//!     }
+//! }).unwrap();
+//!
+//! let mut stream = TcpStream::connect(address).unwrap();
+//! stream.write_all(b"GET /metrics HTTP/1.1\r\n\r\n").unwrap();
+//! let mut response = String::new();
+//! stream.read_to_string(&mut response).unwrap();
+//! assert!(response.contains("timely_operator_records_in_total{worker=\"0\",address=\"0,2\",name=\"Map\",port=\"0\"} 10"));
+//! assert!(response.contains("timely_operator_queued_messages{worker=\"0\",address=\"0,2\",name=\"Map\",port=\"0\"} 0"));
+//! assert!(response.contains("timely_probe_frontier{worker=\"0\",probe=\"output\"} 10"));
+//! ```
+
+use std::collections::BTreeMap;
+use std::io::{self, Read, Write};
+use std::net::{SocketAddr, TcpListener, TcpStream, ToSocketAddrs};
+use std::sync::{Arc, Mutex};
+
+use crate::communication::Allocate;
+use crate::dataflow::metrics::OperatorMetrics;
+use crate::dataflow::operators::probe::Handle;
+use crate::progress::Timestamp;
+use crate::scheduling::QueuedMessages;
+use crate::worker::Worker;
+
+/// Snapshots of the metrics of each worker, indexed by worker.
+type Snapshots = BTreeMap<usize, Snapshot>;
+
+/// The most recently published metrics of a worker.
+#[derive(Default)]
+struct Snapshot {
+    operators: Vec<(Vec<usize>, OperatorMetrics)>,
+    queues: Vec<QueuedMessages>,
+    probes: BTreeMap<String, f64>,
+}
+
+/// Extracts the per-port values of a counter.
+type PortValues = fn(&OperatorMetrics) -> Vec<String>;
+
+/// Serves the published metrics of workers over HTTP.
+#[derive(Clone)]
+pub struct Exporter {
+    address: SocketAddr,
+    snapshots: Arc<Mutex<Snapshots>>,
+}
+
+impl Exporter {
+
+    /// Binds to `address` and serves published metrics from a background thread.
+    pub fn bind<A: ToSocketAddrs>(address: A) -> io::Result<Self> {
+        let listener = TcpListener::bind(address)?;
+        let address = listener.local_addr()?;
+        let snapshots = Arc::new(Mutex::new(BTreeMap::new()));
+        let shared = snapshots.clone();
+        ::std::thread::Builder::new()
+            .name("timely:prometheus".to_owned())
+            .spawn(move || {
+                // A failed connection should not take down the exporter.
+                for stream in listener.incoming().flatten() {
+                    let _ = respond(stream, &shared);
+                }
+            })?;
+        Ok(Exporter { address, snapshots })
+    }
+
+    /// The address the exporter is listening on.
+    pub fn local_addr(&self) -> SocketAddr { self.address }
+
+    /// Replaces the published metrics of `worker` with a snapshot of its current counters and queues.
+    pub fn publish<A: Allocate>(&self, worker: &Worker<A>) {
+        let operators =
+        worker
+            .metrics()
+            .iter()
+            .map(|(address, metrics)| (address.clone(), metrics))
+            .collect();
+        let queues = worker.queued_messages();
+        let mut snapshots = self.snapshots.lock().expect("prometheus exporter poisoned");
+        let snapshot = snapshots.entry(worker.index()).or_default();
+        snapshot.operators = operators;
+        snapshot.queues = queues;
+    }
+
+    /// Replaces the published frontier of the probe `name` at `worker` with the current frontier of `probe`.
+    ///
+    /// The frontier is exported as the least value `value` assigns to its elements, or `+Inf` if
+    /// the frontier is empty.
+    pub fn publish_probe<A: Allocate, T: Timestamp, F: Fn(&T)->f64>(&self, worker: &Worker<A>, name: &str, probe: &Handle<T>, value: F) {
+        let least = probe.with_frontier(|frontier| frontier.iter().map(&value).fold(f64::INFINITY, f64::min));
+        self.snapshots
+            .lock()
+            .expect("prometheus exporter poisoned")
+            .entry(worker.index())
+            .or_default()
+            .probes
+            .insert(name.to_owned(), least);
+    }
+}
+
+/// Reads a request from `stream` and responds with the current metrics.
+fn respond(mut stream: TcpStream, snapshots: &Mutex<Snapshots>) -> io::Result<()> {
+
+    // The request itself is not interesting; every path is answered with the metrics.
+    let mut request = Vec::new();
+    let mut buffer = [0u8; 1024];
+    while !request.ends_with(b"\r\n\r\n") {
+        let read = stream.read(&mut buffer)?;
+        if read == 0 { break; }
+        request.extend_from_slice(&buffer[.. read]);
+    }
+
+    let mut body = Vec::new();
+    write_exposition(&mut body, &snapshots.lock().expect("prometheus exporter poisoned"))?;
+
+    write!(stream, "HTTP/1.1 200 OK\r\n")?;
+    write!(stream, "Content-Type: text/plain; version=0.0.4\r\n")?;
+    write!(stream, "Content-Length: {}\r\n", body.len())?;
+    write!(stream, "Connection: close\r\n\r\n")?;
+    stream.write_all(&body)?;
+    stream.flush()
+}
+
+/// Writes `snapshots` in the Prometheus text exposition format.
+fn write_exposition<W: Write>(writer: &mut W, snapshots: &Snapshots) -> io::Result<()> {
+
+    let operators = || snapshots.iter().flat_map(|(worker, snapshot)| snapshot.operators.iter().map(move |(address, metrics)| (worker, address, metrics)));
+
+    writeln!(writer, "# HELP timely_operator_schedules_total Number of times the operator has been scheduled.")?;
+    writeln!(writer, "# TYPE timely_operator_schedules_total counter")?;
+    for (worker, address, metrics) in operators() {
+        writeln!(writer, "timely_operator_schedules_total{{{}}} {}", labels(*worker, address, &metrics.name), metrics.schedules)?;
+    }
+
+    writeln!(writer, "# HELP timely_operator_elapsed_seconds_total Time spent scheduling the operator.")?;
+    writeln!(writer, "# TYPE timely_operator_elapsed_seconds_total counter")?;
+    for (worker, address, metrics) in operators() {
+        writeln!(writer, "timely_operator_elapsed_seconds_total{{{}}} {}", labels(*worker, address, &metrics.name), metrics.elapsed.as_secs_f64())?;
+    }
+
+    let ports: [(&str, &str, PortValues); 4] = [
+        ("records_in", "Records consumed at the input port.", |m| m.records_in.iter().map(|x| x.to_string()).collect()),
+        ("batches_in", "Batches consumed at the input port.", |m| m.batches_in.iter().map(|x| x.to_string()).collect()),
+        ("records_out", "Records produced at the output port.", |m| m.records_out.iter().map(|x| x.to_string()).collect()),
+        ("batches_out", "Batches produced at the output port.", |m| m.batches_out.iter().map(|x| x.to_string()).collect()),
+    ];
+    for (family, help, values) in ports.iter() {
+        writeln!(writer, "# HELP timely_operator_{}_total {}", family, help)?;
+        writeln!(writer, "# TYPE timely_operator_{}_total counter", family)?;
+        for (worker, address, metrics) in operators() {
+            for (port, value) in values(metrics).iter().enumerate() {
+                writeln!(writer, "timely_operator_{}_total{{{},port=\"{}\"}} {}", family, labels(*worker, address, &metrics.name), port, value)?;
+            }
+        }
+    }
+
+    writeln!(writer, "# HELP timely_operator_queued_messages Messages sent to the input port but not yet received.")?;
+    writeln!(writer, "# TYPE timely_operator_queued_messages gauge")?;
+    for (worker, snapshot) in snapshots.iter() {
+        for queue in snapshot.queues.iter() {
+            writeln!(writer, "timely_operator_queued_messages{{{},port=\"{}\"}} {}", labels(*worker, &queue.address, &queue.name), queue.port, queue.count)?;
+        }
+    }
+
+    writeln!(writer, "# HELP timely_probe_frontier The least element of the probe's frontier, or +Inf if it is empty.")?;
+    writeln!(writer, "# TYPE timely_probe_frontier gauge")?;
+    for (worker, snapshot) in snapshots.iter() {
+        for (name, value) in snapshot.probes.iter() {
+            let value = if *value == f64::INFINITY { "+Inf".to_owned() } else { value.to_string() };
+            writeln!(writer, "timely_probe_frontier{{worker=\"{}\",probe=\"{}\"}} {}", worker, escape(name), value)?;
+        }
+    }
+
+    Ok(())
+}
+
+/// Labels identifying an operator.
+fn labels(worker: usize, address: &[usize], name: &str) -> String {
+    let address = address.iter().map(|x| x.to_string()).collect::<Vec<_>>().join(",");
+    format!("worker=\"{}\",address=\"{}\",name=\"{}\"", worker, address, escape(name))
+}
+
+/// Escapes a label value.
+fn escape(value: &str) -> String {
+    value.replace('\\', "\\\\").replace('"', "\\\"").replace('\n', "\\n")
+}
@@ -5,7 +5,8 @@ use std::sync::Arc;
 use std::cell::RefCell;
 use std::thread::Thread;
 use std::collections::BinaryHeap;
-use std::time::{Duration, Instant};
+use std::time::Duration;
+use crate::logging_core::time::Instant;
 use std::cmp::Reverse;
 use crossbeam_channel::{Sender, Receiver};
 use futures_util::task::ArcWake;
@@ -167,6 +168,12 @@ impl Activations {
         }
     }
 
+    /// Indicates whether no activations are pending, immediately or after a delay, other than
+    /// those presented by the most recent `advance`.
+    pub fn is_idle(&self) -> bool {
+        self.bounds.len() == self.clean && self.queue.is_empty() && self.rx.is_empty()
+    }
+
     /// Time until next scheduled event.
     ///
     /// This method should be used before putting a worker thread to sleep, as it
@@ -0,0 +1,77 @@
+//! Randomized perturbation of channel deliveries and scheduling, for testing.
+
+use crate::hash::mix;
+use crate::scheduling::Activator;
+
+/// The probability, out of 256, that an input defers the batches it holds.
+const DEFER: u64 = 64;
+
+/// A seeded pseudo-random number generator (SplitMix64).
+pub(crate) struct Rng {
+    state: u64,
+}
+
+impl Rng {
+    /// A generator determined by `seed` and the components of `salt`.
+    pub(crate) fn new(seed: u64, salt: &[usize]) -> Self {
+        let state = salt.iter().fold(mix(seed), |state, x| mix(state ^ *x as u64));
+        Rng { state }
+    }
+
+    pub(crate) fn next(&mut self) -> u64 {
+        self.state = self.state.wrapping_add(0x9e3779b97f4a7c15);
+        mix(self.state)
+    }
+
+    /// A number less than `bound`, which must be positive.
+    pub(crate) fn below(&mut self, bound: usize) -> usize {
+        (self.next() % bound as u64) as usize
+    }
+
+    /// Permutes `items` uniformly at random.
+    pub(crate) fn shuffle<X>(&mut self, items: &mut [X]) {
+        for index in (1 .. items.len()).rev() {
+            let other = self.below(index + 1);
+            items.swap(index, other);
+        }
+    }
+}
+
+/// Batches received by an operator input, delivered after random delays and in random order.
+///
+/// Batches are delayed only while the operator has yet to consume them, which progress tracking
+/// already accounts for, and the operator is activated again for each delay, so that all batches
+/// are eventually delivered.
+pub(crate) struct Deliveries<B> {
+    rng: Rng,
+    held: Vec<B>,
+    current: Option<B>,
+    activator: Activator,
+}
+
+impl<B> Deliveries<B> {
+    pub(crate) fn new(rng: Rng, activator: Activator) -> Self {
+        Deliveries { rng, held: Vec::new(), current: None, activator }
+    }
+
+    /// Holds a received batch for later delivery.
+    pub(crate) fn hold(&mut self, batch: B) {
+        self.held.push(batch);
+    }
+
+    /// A held batch to deliver now, if any.
+    pub(crate) fn next(&mut self) -> Option<&mut B> {
+        if self.held.is_empty() {
+            self.current = None;
+        }
+        else if self.rng.next() % 256 < DEFER {
+            self.activator.activate();
+            self.current = None;
+        }
+        else {
+            let index = self.rng.below(self.held.len());
+            self.current = Some(self.held.swap_remove(index));
+        }
+        self.current.as_mut()
+    }
+}
@@ -1,9 +1,13 @@
 //! Types and traits to activate and schedule fibers.
 
 use std::rc::Rc;
+use std::sync::Arc;
 use std::cell::RefCell;
+use std::task::Waker;
 
 pub mod activate;
+#[cfg(feature = "fuzz")]
+pub(crate) mod fuzz;
 
 pub use self::activate::{Activations, Activator, ActivateOnDrop, SyncActivator};
 
@@ -18,6 +22,45 @@ pub trait Schedule {
     /// The return value indicates whether `self` has outstanding
     /// work and would be upset if the computation terminated.
     fn schedule(&mut self) -> bool;
+    /// Reports the outstanding capabilities of contained operators, for debugging.
+    ///
+    /// Types that do not contain operators report nothing.
+    fn outstanding_capabilities(&self, _reports: &mut Vec<OutstandingCapability>) { }
+    /// Reports the messages in flight to the inputs of contained operators.
+    ///
+    /// Types that do not contain operators report nothing.
+    fn queued_messages(&self, _reports: &mut Vec<QueuedMessages>) { }
+    /// Indicates whether no capabilities are held and no messages are in flight within `self`,
+    /// as far as this worker has learned, so that the frontiers of its contents are empty.
+    ///
+    /// Types that do not contain operators report `true`.
+    fn frontiers_empty(&mut self) -> bool { true }
+}
+
+/// Capabilities held for an output of an operator, as reported for debugging.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct OutstandingCapability {
+    /// The address of the operator.
+    pub address: Vec<usize>,
+    /// A helpful name.
+    pub name: String,
+    /// The output port of the operator.
+    pub port: usize,
+    /// The frontier of the capabilities, formatted for display.
+    pub frontier: String,
+}
+
+/// Messages sent to an input of an operator but not yet received.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct QueuedMessages {
+    /// The address of the operator.
+    pub address: Vec<usize>,
+    /// A helpful name.
+    pub name: String,
+    /// The input port of the operator.
+    pub port: usize,
+    /// The number of messages sent to the input at all workers, as known to this worker.
+    pub count: i64,
 }
 
 /// Methods for types which schedule fibers.
@@ -35,4 +78,12 @@ pub trait Scheduler {
         let sync_activations = self.activations().borrow().sync();
         SyncActivator::new(path, sync_activations)
     }
+
+    /// Constructs a `Waker` that activates the specified operator address when woken.
+    ///
+    /// The waker may be sent to other threads, and woken when external data is ready for the
+    /// operator, for example by a future, a channel, or a readiness notification.
+    fn waker_for(&self, path: &[usize]) -> Waker {
+        futures_util::task::waker(Arc::new(self.sync_activator_for(path)))
+    }
 }
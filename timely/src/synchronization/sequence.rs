@@ -2,7 +2,8 @@
 
 use std::rc::Rc;
 use std::cell::RefCell;
-use std::time::{Instant, Duration};
+use std::time::Duration;
+use crate::logging_core::time::Instant;
 use std::collections::VecDeque;
 
 use crate::{communication::Allocate, ExchangeData, PartialOrder};
@@ -4,18 +4,27 @@ use std::rc::Rc;
 use std::cell::{RefCell, RefMut};
 use std::any::Any;
 use std::str::FromStr;
-use std::time::{Instant, Duration};
+use std::time::Duration;
+use crate::logging_core::time::Instant;
 use std::collections::HashMap;
 use std::collections::hash_map::Entry;
-use std::sync::Arc;
+use std::sync::{Arc, Mutex};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::thread::{self, Thread};
+use std::path::PathBuf;
 
 use crate::communication::{Allocate, Data, Push, Pull};
 use crate::communication::allocator::thread::{ThreadPusher, ThreadPuller};
-use crate::scheduling::{Schedule, Scheduler, Activations};
+use crate::scheduling::{Schedule, Scheduler, Activations, OutstandingCapability, QueuedMessages};
 use crate::progress::timestamp::{Refines};
 use crate::progress::SubgraphBuilder;
 use crate::progress::operate::Operate;
 use crate::dataflow::scopes::Child;
+use crate::dataflow::topology::Topology;
+use crate::dataflow::metrics::Metrics;
+use crate::dataflow::memory::{Memory, MemoryUsage};
+use crate::dataflow::resources::Resources;
+use crate::dataflow::state::{State, checkpoint};
 use crate::logging::TimelyLogger;
 
 /// Different ways in which timely's progress tracking can work.
@@ -87,6 +96,25 @@ impl FromStr for ProgressMode {
 pub struct Config {
     /// The progress mode to use.
     pub(crate) progress_mode: ProgressMode,
+    /// The number of steps without changes to outstanding capabilities before they are reported.
+    pub(crate) stuck_iterations: Option<usize>,
+    /// The time without changes to outstanding capabilities after which the worker panics.
+    pub(crate) watchdog: Option<Duration>,
+    /// The time after the worker's creation after which it panics if its dataflows remain.
+    pub(crate) time_limit: Option<Duration>,
+    /// Whether scopes maintain counters for the operators of installed dataflows.
+    pub(crate) metrics: bool,
+    /// The directory to write checkpoints to, and the number of epochs between checkpoints.
+    pub(crate) checkpoint: Option<(PathBuf, u64)>,
+    /// The directory to restore the latest consistent checkpoint from.
+    pub(crate) restore: Option<PathBuf>,
+    /// Soft and hard limits on the bytes held by the worker's dataflows.
+    pub(crate) memory_budget: Option<(usize, usize)>,
+    /// The directory to write spilled records to, and the number of records held in memory per buffer.
+    pub(crate) spill: Option<(PathBuf, usize)>,
+    /// The seed from which channel deliveries and scheduling are perturbed.
+    #[cfg(feature = "fuzz")]
+    pub(crate) fuzz: Option<u64>,
     /// A map from parameter name to typed parameter values.
     registry: HashMap<String, Arc<dyn Any + Send + Sync>>,
 }
@@ -104,6 +132,8 @@ impl Config {
     #[cfg(feature = "getopts")]
     pub fn install_options(opts: &mut getopts_dep::Options) {
         opts.optopt("", "progress-mode", "progress tracking mode (eager or demand)", "MODE");
+        opts.optopt("", "stuck-iterations", "report outstanding capabilities unchanged for this many steps", "NUM");
+        opts.optopt("", "watchdog", "abort if outstanding capabilities are unchanged for this many seconds", "SECS");
     }
 
     /// Instantiates a configuration based upon the parsed options in `matches`.
@@ -118,7 +148,24 @@ impl Config {
     pub fn from_matches(matches: &getopts_dep::Matches) -> Result<Config, String> {
         let progress_mode = matches
             .opt_get_default("progress-mode", ProgressMode::Eager)?;
-        Ok(Config::default().progress_mode(progress_mode))
+        let mut config = Config::default().progress_mode(progress_mode);
+        let stuck_iterations = matches
+            .opt_get("stuck-iterations")
+            .map_err(|e| format!("invalid stuck iterations: {}", e))?;
+        if let Some(iterations) = stuck_iterations {
+            config = config.stuck_detection(iterations);
+        }
+        let watchdog = matches
+            .opt_get::<f64>("watchdog")
+            .map_err(|e| format!("invalid watchdog interval: {}", e))?;
+        if let Some(seconds) = watchdog {
+            if !(seconds >= 0.0 && seconds < u64::MAX as f64) {
+                return Err(format!("invalid watchdog interval: {}", seconds));
+            }
+            let interval = Duration::from_secs_f64(seconds);
+            config = config.watchdog(interval);
+        }
+        Ok(config)
     }
 
     /// Sets the progress mode to `progress_mode`.
@@ -127,6 +174,197 @@ impl Config {
         self
     }
 
+    /// Reports outstanding capabilities that remain unchanged for `iterations` steps.
+    ///
+    /// When a computation fails to terminate, it is often because some operator holds a
+    /// capability it never releases. With this option set, each worker checks the outstanding
+    /// capabilities of its dataflows after each step, and when they have not changed for
+    /// `iterations` consecutive steps it prints the operators holding them to standard error.
+    ///
+    /// # Examples
+    /// ```rust
+    /// let mut config = timely::Config::thread();
+    /// config.worker = timely::WorkerConfig::default().stuck_detection(1000);
+    /// timely::execute(config, |worker| {
+    ///     use timely::dataflow::operators::{ToStream, Inspect};
+    ///     worker.dataflow::<u64,_,_>(|scope| {
+    ///         (0 .. 10).to_stream(scope).inspect(|x| println!("{:?}", x));
+    ///     });
+    /// }).unwrap();
+    /// ```
+    pub fn stuck_detection(mut self, iterations: usize) -> Self {
+        self.stuck_iterations = Some(iterations);
+        self
+    }
+
+    /// Panics with a report of outstanding capabilities if they remain unchanged for `interval`.
+    ///
+    /// Where `stuck_detection` reports a computation that fails to make progress, the watchdog
+    /// stops it, which suits tests that would otherwise hang. Each worker checks its outstanding
+    /// capabilities after each step, and does not park for longer than the remaining interval.
+    /// The report lists each operator output holding capabilities, with the frontier of those
+    /// capabilities. Input handles hold capabilities too, and so the interval should exceed the
+    /// longest time the driver takes to supply the next round of input.
+    ///
+    /// # Examples
+    /// ```rust
+    /// use std::time::Duration;
+    ///
+    /// let mut config = timely::Config::thread();
+    /// config.worker = timely::WorkerConfig::default().watchdog(Duration::from_secs(60));
+    /// timely::execute(config, |worker| {
+    ///     use timely::dataflow::operators::{ToStream, Inspect};
+    ///     worker.dataflow::<u64,_,_>(|scope| {
+    ///         (0 .. 10).to_stream(scope).inspect(|x| println!("{:?}", x));
+    ///     });
+    /// }).unwrap();
+    /// ```
+    pub fn watchdog(mut self, interval: Duration) -> Self {
+        self.watchdog = Some(interval);
+        self
+    }
+
+    /// Panics with a report of outstanding capabilities if dataflows remain `limit` after the
+    /// worker was created.
+    ///
+    /// The limit is checked as the worker steps, and the worker does not park beyond it. See
+    /// `timely::execute_with_deadline`, which applies a limit to all workers.
+    pub fn time_limit(mut self, limit: Duration) -> Self {
+        self.time_limit = Some(limit);
+        self
+    }
+
+    /// Maintains counters for the operators of installed dataflows, reported by `Worker::metrics`.
+    ///
+    /// Counters are disabled by default, as maintaining them times each schedule call and counts
+    /// the records each operator consumes and produces. Only dataflows built after the option is
+    /// set have counters.
+    ///
+    /// # Examples
+    /// ```rust
+    /// let mut config = timely::Config::thread();
+    /// config.worker = timely::WorkerConfig::default().metrics(true);
+    /// timely::execute(config, |worker| {
+    ///     use timely::dataflow::operators::{ToStream, Inspect};
+    ///     worker.dataflow::<u64,_,_>(|scope| {
+    ///         (0 .. 10).to_stream(scope).inspect(|x| println!("{:?}", x));
+    ///     });
+    ///     assert!(worker.metrics().get(&[0, 2]).is_some());
+    /// }).unwrap();
+    /// ```
+    pub fn metrics(mut self, enabled: bool) -> Self {
+        self.metrics = enabled;
+        self
+    }
+
+    /// Writes checkpoints to `directory`, at epochs that are multiples of `interval`.
+    ///
+    /// Checkpoints are written by `Worker::checkpoint`, which the driver should call once an
+    /// epoch is complete. Only state obtained through `State::checkpointed_handle` is written.
+    pub fn checkpoint_to<P: Into<PathBuf>>(mut self, directory: P, interval: u64) -> Self {
+        assert!(interval > 0, "checkpoint interval must be positive");
+        self.checkpoint = Some((directory.into(), interval));
+        self
+    }
+
+    /// Restores checkpointed state from the latest consistent checkpoint in `directory`, if any.
+    ///
+    /// The epoch of the restored checkpoint is reported by `Worker::restored_epoch`, from which
+    /// the driver should resume its inputs.
+    pub fn restore_from<P: Into<PathBuf>>(mut self, directory: P) -> Self {
+        self.restore = Some(directory.into());
+        self
+    }
+
+    /// Spills records buffered by operators to files in `directory`, beyond `threshold` records per buffer.
+    ///
+    /// Operators that buffer records until a time is complete, such as `StateMachine`, hold at most
+    /// `threshold` records of each buffer in memory, and write further records to disk in batches
+    /// of `threshold` records, reading them back when the time is complete.
+    ///
+    /// # Examples
+    /// ```rust
+    /// use timely::dataflow::operators::{ToStream, Map, Delay, Inspect};
+    /// use timely::dataflow::operators::aggregation::StateMachine;
+    ///
+    /// let mut config = timely::Config::thread();
+    /// config.worker = timely::WorkerConfig::default().spill_to(std::env::temp_dir(), 4);
+    /// timely::execute(config, |worker| {
+    ///     worker.dataflow::<u64,_,_>(|scope| {
+    ///         (0 .. 100u64).to_stream(scope)
+    ///             .delay(|x, _| 10 - x / 10)
+    ///             .map(|x| (x % 2, x))
+    ///             .state_machine(|_key, val, agg: &mut u64| { *agg += val; (false, Some(*agg)) }, |key| *key)
+    ///             .inspect(|x| assert!(*x <= 2500));
+    ///     });
+    /// }).unwrap();
+    /// ```
+    pub fn spill_to<P: Into<PathBuf>>(mut self, directory: P, threshold: usize) -> Self {
+        assert!(threshold > 0, "spill threshold must be positive");
+        self.spill = Some((directory.into(), threshold));
+        self
+    }
+
+    /// Limits the bytes held by the worker's dataflows to a `soft` and a `hard` limit.
+    ///
+    /// After each step, the worker estimates the bytes held in its channels, output buffers, and
+    /// managed state. While usage exceeds `soft`, it signals pressure, and sources such as
+    /// `to_stream` pause until usage drops. Once usage exceeds `hard`, the worker panics with a
+    /// report of its usage and its largest state, rather than continuing to an allocation failure.
+    ///
+    /// # Examples
+    /// ```rust
+    /// let mut config = timely::Config::thread();
+    /// config.worker = timely::WorkerConfig::default().memory_budget(1 << 20, 1 << 30);
+    /// timely::execute(config, |worker| {
+    ///     use timely::dataflow::operators::{ToStream, Inspect};
+    ///     worker.dataflow::<u64,_,_>(|scope| {
+    ///         (0 .. 1_000_000u64).to_stream(scope).inspect(|_| ());
+    ///     });
+    /// }).unwrap();
+    /// ```
+    pub fn memory_budget(mut self, soft: usize, hard: usize) -> Self {
+        assert!(soft <= hard, "soft memory limit exceeds hard memory limit");
+        self.memory_budget = Some((soft, hard));
+        self
+    }
+
+    /// Perturbs channel deliveries and scheduling at random, as determined by `seed`.
+    ///
+    /// Operators built with `OperatorBuilder` receive the batches at each input after random delays
+    /// and in random order, and each scope schedules its active operators in random order. These
+    /// perturbations are within what timely permits, and so do not change the results of correct
+    /// operators, but they expose races in notification logic that otherwise only show up under
+    /// multi-worker timing. Tests can run a computation with many seeds, and reproduce a failure
+    /// from its seed, though perturbations also depend on the timing of messages between workers.
+    ///
+    /// This method is only available if the `fuzz` feature is enabled.
+    ///
+    /// # Examples
+    /// ```rust
+    /// use timely::dataflow::operators::{ToStream, Exchange, Accumulate, Inspect};
+    ///
+    /// for seed in 0 .. 10 {
+    ///     let mut config = timely::Config::process(2);
+    ///     config.worker = timely::WorkerConfig::default().fuzz(seed);
+    ///     timely::execute(config, |worker| {
+    ///         worker.dataflow::<u64,_,_>(|scope| {
+    ///             (0 .. 100u64).to_stream(scope)
+    ///                          .exchange(|x| *x)
+    ///                          .count()
+    ///                          .exchange(|_| 0)
+    ///                          .accumulate(0, |sum, counts| *sum += counts.iter().sum::<usize>())
+    ///                          .inspect(|sum| assert!(*sum == 0 || *sum == 200));
+    ///         });
+    ///     }).unwrap();
+    /// }
+    /// ```
+    #[cfg(feature = "fuzz")]
+    pub fn fuzz(mut self, seed: u64) -> Self {
+        self.fuzz = Some(seed);
+        self
+    }
+
     /// Sets a typed configuration parameter for the given `key`.
     ///
     /// It is recommended to install a single configuration struct using a key
@@ -204,6 +442,16 @@ pub trait AsWorker : Scheduler {
     fn log_register(&self) -> ::std::cell::RefMut<crate::logging_core::Registry<crate::logging::WorkerIdentifier>>;
     /// Provides access to the timely logging stream.
     fn logging(&self) -> Option<crate::logging::TimelyLogger> { self.log_register().get("timely") }
+    /// Provides access to the descriptions of constructed scopes.
+    fn topology(&self) -> ::std::cell::RefMut<Topology>;
+    /// Provides access to the counters of constructed operators.
+    fn metrics(&self) -> ::std::cell::RefMut<Metrics>;
+    /// Provides access to the state of constructed operators.
+    fn state(&self) -> ::std::cell::RefMut<State>;
+    /// Provides access to the memory accounts of constructed dataflows.
+    fn memory(&self) -> ::std::cell::RefMut<Memory>;
+    /// Provides access to the resources registered with the worker.
+    fn resources(&self) -> ::std::cell::RefMut<Resources>;
 }
 
 /// A `Worker` is the entry point to a timely dataflow computation. It wraps a `Allocate`,
@@ -225,6 +473,39 @@ pub struct Worker<A: Allocate> {
     // Temporary storage for channel identifiers during dataflow construction.
     // These are then associated with a dataflow once constructed.
     temp_channel_ids: Rc<RefCell<Vec<usize>>>,
+
+    // Descriptions of the scopes of installed dataflows.
+    topology: Rc<RefCell<Topology>>,
+
+    // Counters for the operators of installed dataflows.
+    metrics: Rc<RefCell<Metrics>>,
+
+    // State of the operators of installed dataflows.
+    state: Rc<RefCell<State>>,
+
+    // Memory accounts of installed dataflows.
+    memory: Rc<RefCell<Memory>>,
+
+    // Resources registered for use by operators.
+    resources: Rc<RefCell<Resources>>,
+
+    // The epoch of the checkpoint the worker was restored from, if any.
+    restored_epoch: Option<u64>,
+
+    // Outstanding capabilities after the most recent step, and the number of steps since they changed.
+    outstanding: Vec<OutstandingCapability>,
+    unchanged_steps: usize,
+    unchanged_since: Instant,
+
+    // Shared with the other workers of the process, to stop when one of them panics.
+    panics: Option<Arc<PanicMonitor>>,
+
+    // Bytes allocated by the most recent step.
+    step_allocated: u64,
+
+    // Whether the worker had no work after the most recent step, and callbacks for when it becomes so.
+    idle: bool,
+    idle_callbacks: Vec<Box<dyn FnMut()>>,
 }
 
 impl<A: Allocate> AsWorker for Worker<A> {
@@ -250,6 +531,11 @@ impl<A: Allocate> AsWorker for Worker<A> {
     fn log_register(&self) -> RefMut<crate::logging_core::Registry<crate::logging::WorkerIdentifier>> {
         self.log_register()
     }
+    fn topology(&self) -> RefMut<Topology> { self.topology() }
+    fn metrics(&self) -> RefMut<Metrics> { self.metrics() }
+    fn state(&self) -> RefMut<State> { self.state() }
+    fn memory(&self) -> RefMut<Memory> { self.memory() }
+    fn resources(&self) -> RefMut<Resources> { self.resources() }
 }
 
 impl<A: Allocate> Scheduler for Worker<A> {
@@ -263,6 +549,14 @@ impl<A: Allocate> Worker<A> {
     pub fn new(config: Config, c: A) -> Worker<A> {
         let now = Instant::now();
         let index = c.index();
+        let peers = c.peers();
+        let state: Rc<RefCell<State>> = Default::default();
+        let restored_epoch = config.restore.as_ref().and_then(|directory| {
+            let epoch = checkpoint::latest(directory, peers).expect("failed to list checkpoints")?;
+            let snapshot = checkpoint::read(directory, epoch, index).expect("failed to read checkpoint");
+            state.borrow_mut().restore(snapshot);
+            Some(epoch)
+        });
         Worker {
             config,
             timer: now,
@@ -275,6 +569,32 @@ impl<A: Allocate> Worker<A> {
             activations: Rc::new(RefCell::new(Activations::new(now))),
             active_dataflows: Default::default(),
             temp_channel_ids:  Default::default(),
+            topology: Default::default(),
+            metrics: Default::default(),
+            state,
+            memory: Default::default(),
+            resources: Default::default(),
+            restored_epoch,
+            outstanding: Vec::new(),
+            unchanged_steps: 0,
+            unchanged_since: now,
+            panics: None,
+            step_allocated: 0,
+            idle: false,
+            idle_callbacks: Vec::new(),
+        }
+    }
+
+    /// Stops the worker, by panicking in `step_or_park`, once another worker sharing `monitor` panics.
+    pub(crate) fn monitor_panics(&mut self, monitor: Arc<PanicMonitor>) {
+        monitor.threads.lock().expect("panic monitor poisoned").push(thread::current());
+        self.panics = Some(monitor);
+    }
+
+    /// Panics if another worker of the process has panicked.
+    fn check_peers(&self) {
+        if self.panics.as_ref().map(|monitor| monitor.panicked.load(Ordering::SeqCst)).unwrap_or(false) {
+            panic!("worker {} stopping, as another worker of the process panicked", self.index());
         }
     }
 
@@ -332,6 +652,9 @@ impl<A: Allocate> Worker<A> {
     /// ```
     pub fn step_or_park(&mut self, duration: Option<Duration>) -> bool {
 
+        self.check_peers();
+        let allocated = crate::allocator::allocated();
+
         {   // Process channel events. Activate responders.
             let mut allocator = self.allocator.borrow_mut();
             allocator.receive();
@@ -365,6 +688,11 @@ impl<A: Allocate> Worker<A> {
             (Some(x), Some(y)) => Some(std::cmp::min(x,y)),
             (x, y) => x.or(y),
         };
+        // Wake in time to enforce the watchdog and time limit.
+        let delay = match (delay, self.next_alarm()) {
+            (Some(x), Some(y)) => Some(std::cmp::min(x,y)),
+            (x, y) => x.or(y),
+        };
 
         if !self.dataflows.borrow().is_empty() && delay != Some(Duration::new(0,0)) {
 
@@ -378,6 +706,8 @@ impl<A: Allocate> Worker<A> {
                 .borrow()
                 .await_events(delay);
 
+            self.check_peers();
+
             // Log return from unpark.
             self.logging().as_mut().map(|l| l.log(crate::logging::ParkEvent::unpark()));
         }
@@ -388,6 +718,10 @@ impl<A: Allocate> Worker<A> {
                 .borrow_mut()
                 .for_extensions(&[], |index| active_dataflows.push(index));
 
+            if !active_dataflows.is_empty() {
+                self.idle = false;
+            }
+
             let mut dataflows = self.dataflows.borrow_mut();
             for index in active_dataflows.drain(..) {
                 // Step dataflow if it exists, remove if not incomplete.
@@ -399,18 +733,172 @@ impl<A: Allocate> Worker<A> {
                         for channel in entry.get_mut().channel_ids.drain(..) {
                             paths.remove(&channel);
                         }
+                        self.topology.borrow_mut().remove_dataflow(index);
+                        self.metrics.borrow_mut().remove_dataflow(index);
+                        self.state.borrow_mut().remove_dataflow(index);
+                        self.memory.borrow_mut().remove_dataflow(index);
                         entry.remove_entry();
                     }
                 }
             }
         }
 
+        if self.config.stuck_iterations.is_some() || self.config.watchdog.is_some() {
+            self.detect_stuck();
+        }
+
+        if let Some(limit) = self.config.time_limit {
+            if self.timer.elapsed() >= limit && !self.dataflows.borrow().is_empty() {
+                self.abort(&format!("dataflows incomplete after time limit of {:?}", limit));
+            }
+        }
+
+        if let Some((soft, hard)) = self.config.memory_budget {
+            self.enforce_memory_budget(soft, hard);
+        }
+
         // Clean up, indicate if dataflows remain.
         self.logging.borrow_mut().flush();
         self.allocator.borrow_mut().release();
+        self.step_allocated = crate::allocator::allocated() - allocated;
+
+        // Report becoming idle, when no activations or channel events remain, and no dataflow has work.
+        let idle =
+            self.activations.borrow().is_idle() &&
+            self.allocator.borrow().events().borrow().is_empty() &&
+            self.dataflows.borrow_mut().values_mut().all(|dataflow| dataflow.frontiers_empty());
+        if idle && !self.idle {
+            for callback in self.idle_callbacks.iter_mut() {
+                callback();
+            }
+        }
+        self.idle = idle;
+
         !self.dataflows.borrow().is_empty()
     }
 
+    /// Indicates whether the worker had no work to perform after its most recent step.
+    ///
+    /// An idle worker has no operators to schedule, and the frontiers of all of its dataflows are
+    /// empty: no capabilities are held and no messages are in flight, at any worker, as far as
+    /// this worker has learned. A dataflow with an open input is not idle, as the input holds a
+    /// capability. Idleness is local: other workers may not yet have learned of the same progress.
+    ///
+    /// # Examples
+    /// ```
+    /// use timely::dataflow::InputHandle;
+    /// use timely::dataflow::operators::{Input, Inspect, Probe};
+    ///
+    /// timely::execute_directly(|worker| {
+    ///
+    ///     let idled = std::rc::Rc::new(std::cell::Cell::new(0));
+    ///     let counter = idled.clone();
+    ///     worker.on_idle(move || counter.set(counter.get() + 1));
+    ///
+    ///     let mut input = InputHandle::new();
+    ///     let probe = worker.dataflow::<usize,_,_>(|scope| {
+    ///         scope.input_from(&mut input)
+    ///              .inspect(|x| println!("seen: {:?}", x))
+    ///              .probe()
+    ///     });
+    ///
+    ///     // while the input is open, the worker is not idle even once all records are processed.
+    ///     for round in 0 .. 3 {
+    ///         input.send(round);
+    ///         input.advance_to(round + 1);
+    ///         worker.step_while(|| probe.less_than(input.time()));
+    ///         worker.step();
+    ///         assert!(!worker.is_idle());
+    ///     }
+    ///
+    ///     input.close();
+    ///     while !worker.is_idle() {
+    ///         worker.step();
+    ///     }
+    ///     assert_eq!(idled.get(), 1);
+    /// });
+    /// ```
+    pub fn is_idle(&self) -> bool {
+        self.idle
+    }
+
+    /// Registers `callback` to be invoked whenever the worker becomes idle at the end of a step.
+    ///
+    /// See `is_idle` for what idleness means.
+    pub fn on_idle<F: FnMut()+'static>(&mut self, callback: F) {
+        self.idle_callbacks.push(Box::new(callback));
+    }
+
+    /// Reports outstanding capabilities once they have been unchanged for the configured number
+    /// of steps, and panics once they have been unchanged for the watchdog interval.
+    fn detect_stuck(&mut self) {
+        let outstanding = self.outstanding_capabilities();
+        if !outstanding.is_empty() && outstanding == self.outstanding {
+            self.unchanged_steps += 1;
+            if Some(self.unchanged_steps) == self.config.stuck_iterations {
+                eprintln!("worker {}: outstanding capabilities unchanged for {} steps:", self.index(), self.unchanged_steps);
+                for capability in self.outstanding.iter() {
+                    eprintln!("  {:?} {}, output {}: {}", capability.address, capability.name, capability.port, capability.frontier);
+                }
+            }
+            if let Some(interval) = self.config.watchdog {
+                if self.unchanged_since.elapsed() >= interval {
+                    self.abort(&format!("outstanding capabilities unchanged for {:?}", interval));
+                }
+            }
+        }
+        else {
+            self.outstanding = outstanding;
+            self.unchanged_steps = 0;
+            self.unchanged_since = Instant::now();
+        }
+    }
+
+    /// The capabilities held by the operators of the worker's dataflows.
+    fn outstanding_capabilities(&self) -> Vec<OutstandingCapability> {
+        let mut outstanding = Vec::new();
+        for dataflow in self.dataflows.borrow().values() {
+            if let Some(operate) = dataflow.operate.as_ref() {
+                operate.outstanding_capabilities(&mut outstanding);
+            }
+        }
+        outstanding
+    }
+
+    /// The time until the watchdog or time limit should next be checked, if either is set.
+    fn next_alarm(&self) -> Option<Duration> {
+        let watchdog = self.config.watchdog.map(|interval| interval.checked_sub(self.unchanged_since.elapsed()).unwrap_or_default());
+        let limit = self.config.time_limit.map(|limit| limit.checked_sub(self.timer.elapsed()).unwrap_or_default());
+        match (watchdog, limit) {
+            (Some(x), Some(y)) => Some(std::cmp::min(x,y)),
+            (x, y) => x.or(y),
+        }
+    }
+
+    /// Panics with `reason` and a report of the capabilities held by each operator.
+    fn abort(&self, reason: &str) -> ! {
+        let mut report = format!("worker {}: {}; outstanding capabilities:", self.index(), reason);
+        for capability in self.outstanding_capabilities() {
+            report.push_str(&format!("\n  {:?} {}, output {}: {}", capability.address, capability.name, capability.port, capability.frontier));
+        }
+        panic!("{}", report);
+    }
+
+    /// Signals pressure above the soft limit, and panics with a report above the hard limit.
+    fn enforce_memory_budget(&mut self, soft: usize, hard: usize) {
+        let usage = self.memory_usage();
+        self.memory.borrow().set_pressure(usage.total() >= soft);
+        if usage.total() >= hard {
+            let mut report = format!("worker {}: memory usage of {} exceeds hard limit of {} bytes", self.index(), usage, hard);
+            let mut states = self.state.borrow().sizes();
+            states.sort_by_key(|x| std::cmp::Reverse(x.2));
+            for (address, name, bytes) in states.into_iter().take(5) {
+                report.push_str(&format!("\n  state {:?} of operator {:?}: {} bytes", name, address, bytes));
+            }
+            panic!("{}", report);
+        }
+    }
+
     /// Calls `self.step()` as long as `func` evaluates to true.
     ///
     /// This method will continually execute even if there is not work
@@ -542,6 +1030,188 @@ impl<A: Allocate> Worker<A> {
         self.logging.borrow_mut()
     }
 
+    /// Provides access to the descriptions of the scopes of installed dataflows.
+    ///
+    /// Each scope is described when it is built, and the description is retained until
+    /// the dataflow completes or is dropped.
+    ///
+    /// # Examples
+    /// ```
+    /// timely::execute_from_args(::std::env::args(), |worker| {
+    ///
+    ///     use timely::dataflow::operators::{ToStream, Inspect};
+    ///
+    ///     worker.dataflow::<usize,_,_>(|scope| {
+    ///         (0 .. 10)
+    ///             .to_stream(scope)
+    ///             .inspect(|x| println!("{:?}", x));
+    ///     });
+    ///
+    ///     // write the dataflow graph in GraphViz DOT format,
+    ///     // for example to `format!("worker-{}.dot", worker.index())`.
+    ///     let mut dot = Vec::new();
+    ///     worker.topology().write_dot(&mut dot).unwrap();
+    ///     assert!(String::from_utf8(dot).unwrap().contains("Inspect"));
+    /// });
+    /// ```
+    pub fn topology(&self) -> RefMut<Topology> {
+        self.topology.borrow_mut()
+    }
+
+    /// Provides access to the counters of the operators of installed dataflows.
+    ///
+    /// When enabled with `WorkerConfig::metrics`, counters are maintained for each operator within
+    /// a dataflow, including nested scopes, and are retained until the dataflow completes or is
+    /// dropped. Otherwise there are no counters.
+    ///
+    /// # Examples
+    /// ```
+    /// let mut config = timely::Config::thread();
+    /// config.worker = timely::WorkerConfig::default().metrics(true);
+    /// timely::execute(config, |worker| {
+    ///
+    ///     use timely::dataflow::operators::{ToStream, Inspect};
+    ///
+    ///     worker.dataflow::<usize,_,_>(|scope| {
+    ///         (0 .. 10)
+    ///             .to_stream(scope)
+    ///             .inspect(|x| println!("{:?}", x));
+    ///     });
+    ///
+    ///     worker.step();
+    ///
+    ///     for (address, metrics) in worker.metrics().iter() {
+    ///         println!("{:?}\t{}\t{:?}", address, metrics.name, metrics.elapsed);
+    ///     }
+    /// }).unwrap();
+    /// ```
+    pub fn metrics(&self) -> RefMut<Metrics> {
+        self.metrics.borrow_mut()
+    }
+
+    /// The messages in flight to each input of the operators of installed dataflows.
+    ///
+    /// Counts include messages sent to the input at all workers, as known to this worker, and
+    /// so describe the queue ahead of the operator across the computation.
+    ///
+    /// # Examples
+    /// ```
+    /// use timely::dataflow::operators::{ToStream, Inspect};
+    ///
+    /// timely::execute_directly(|worker| {
+    ///     worker.dataflow::<usize,_,_>(|scope| {
+    ///         (0 .. 10).to_stream(scope).inspect(|x| println!("{:?}", x));
+    ///     });
+    ///     while worker.step() {
+    ///         for queue in worker.queued_messages() {
+    ///             println!("{:?}\t{}\t{}\t{}", queue.address, queue.name, queue.port, queue.count);
+    ///         }
+    ///     }
+    /// });
+    /// ```
+    pub fn queued_messages(&self) -> Vec<QueuedMessages> {
+        let mut queued = Vec::new();
+        for dataflow in self.dataflows.borrow().values() {
+            if let Some(operate) = dataflow.operate.as_ref() {
+                operate.queued_messages(&mut queued);
+            }
+        }
+        queued
+    }
+
+    /// The bytes allocated by the worker's thread during the most recent step, as counted by
+    /// [`CountingAllocator`](crate::allocator::CountingAllocator).
+    pub fn allocated(&self) -> u64 {
+        self.step_allocated
+    }
+
+    /// Provides access to the state of the operators of installed dataflows.
+    ///
+    /// State is created by operators through handles, and is discarded when the dataflow
+    /// completes or is dropped.
+    pub fn state(&self) -> RefMut<State> {
+        self.state.borrow_mut()
+    }
+
+    /// Writes a checkpoint of `epoch`, if checkpoints are configured and `epoch` is a checkpoint epoch.
+    ///
+    /// The checkpoint contains all checkpointed operator state, and should only be written once
+    /// the epoch is complete, for example once a probe has advanced past it. Returns `true` if a
+    /// checkpoint was written.
+    ///
+    /// # Examples
+    /// ```
+    /// use timely::dataflow::InputHandle;
+    /// use timely::dataflow::operators::{Input, Operator, Probe};
+    /// use timely::dataflow::channels::pact::Pipeline;
+    /// use timely::worker::AsWorker;
+    ///
+    /// let directory = std::env::temp_dir().join(format!("timely-checkpoint-{}", std::process::id()));
+    ///
+    /// // Counts records, and returns the final count.
+    /// let run = |config: timely::WorkerConfig, rounds: u64| {
+    ///     timely::execute(timely::Config { communication: timely::CommunicationConfig::Thread, worker: config }, move |worker| {
+    ///         let mut input = InputHandle::new();
+    ///         let probe = worker.dataflow::<u64,_,_>(|scope| {
+    ///             let stream = scope.input_from(&mut input);
+    ///             let scope = stream.scope();
+    ///             stream
+    ///                 .unary(Pipeline, "Count", |_capability, info| {
+    ///                     let count = scope.state().checkpointed_handle::<(), u64>(&info.address, "count");
+    ///                     move |input, output| {
+    ///                         input.for_each(|time, data| {
+    ///                             let total = count.update((), || 0, |count| { *count += data.len() as u64; *count });
+    ///                             output.session(&time).give(total);
+    ///                         });
+    ///                     }
+    ///                 })
+    ///                 .probe()
+    ///         });
+    ///
+    ///         // resume from the epoch after the restored checkpoint.
+    ///         let start = worker.restored_epoch().map(|epoch| epoch + 1).unwrap_or(0);
+    ///         input.advance_to(start);
+    ///         for round in start .. rounds {
+    ///             input.send(round);
+    ///             input.advance_to(round + 1);
+    ///             worker.step_while(|| probe.less_than(input.time()));
+    ///             worker.checkpoint(round).unwrap();
+    ///         }
+    ///
+    ///         worker.state().checkpointed_handle::<(), u64>(&[0, 2], "count").get(&()).unwrap()
+    ///     }).unwrap().join().pop().unwrap().unwrap()
+    /// };
+    ///
+    /// assert_eq!(run(timely::WorkerConfig::default().checkpoint_to(&directory, 5), 7), 7);
+    /// // restoring from the checkpoint of epoch 5 replays epoch 6 onward.
+    /// assert_eq!(run(timely::WorkerConfig::default().restore_from(&directory), 10), 10);
+    ///
+    /// std::fs::remove_dir_all(directory).unwrap();
+    /// ```
+    pub fn checkpoint(&self, epoch: u64) -> ::std::io::Result<bool> {
+        match &self.config.checkpoint {
+            Some((directory, interval)) if epoch % *interval == 0 => {
+                let snapshot = self.state.borrow().snapshot();
+                checkpoint::write(directory, epoch, self.index(), &snapshot)?;
+                Ok(true)
+            },
+            _ => Ok(false),
+        }
+    }
+
+    /// The epoch of the checkpoint the worker was restored from, if any.
+    pub fn restored_epoch(&self) -> Option<u64> {
+        self.restored_epoch
+    }
+
+    /// A handle reporting which epochs are contained in a consistent checkpoint.
+    ///
+    /// If checkpoints are not configured, every epoch is reported as durable.
+    pub fn durability(&self) -> checkpoint::Durability {
+        let directory = self.config.checkpoint.as_ref().map(|(directory, _)| directory.clone());
+        checkpoint::Durability::new(directory, self.peers())
+    }
+
     /// Construct a new dataflow.
     ///
     /// # Examples
@@ -682,6 +1352,37 @@ impl<A: Allocate> Worker<A> {
             for channel in entry.channel_ids.drain(..) {
                 paths.remove(&channel);
             }
+            self.topology.borrow_mut().remove_dataflow(dataflow_identifier);
+            self.metrics.borrow_mut().remove_dataflow(dataflow_identifier);
+            self.state.borrow_mut().remove_dataflow(dataflow_identifier);
+            self.memory.borrow_mut().remove_dataflow(dataflow_identifier);
+        }
+    }
+
+    /// Provides access to the memory accounts of installed dataflows.
+    ///
+    /// Sources may use `Memory::pressure` to pause while usage exceeds the soft limit of the
+    /// memory budget configured with `Config::memory_budget`.
+    pub fn memory(&self) -> RefMut<Memory> {
+        self.memory.borrow_mut()
+    }
+
+    /// Provides access to the resources registered with the worker.
+    ///
+    /// Resources are registered by type, and retrieved by operators from their scope.
+    pub fn resources(&self) -> RefMut<Resources> {
+        self.resources.borrow_mut()
+    }
+
+    /// The bytes held by the dataflows installed in the worker, by category.
+    ///
+    /// See the `memory` module for how usage is estimated.
+    pub fn memory_usage(&self) -> MemoryUsage {
+        let memory = self.memory.borrow();
+        MemoryUsage {
+            channels: memory.channel_bytes(),
+            buffers: memory.buffer_bytes(),
+            state: self.state.borrow().bytes(),
         }
     }
 
@@ -721,6 +1422,45 @@ impl<A: Allocate> Clone for Worker<A> {
             activations: self.activations.clone(),
             active_dataflows: Vec::new(),
             temp_channel_ids: self.temp_channel_ids.clone(),
+            topology: self.topology.clone(),
+            metrics: self.metrics.clone(),
+            state: self.state.clone(),
+            memory: self.memory.clone(),
+            resources: self.resources.clone(),
+            restored_epoch: self.restored_epoch,
+            outstanding: Vec::new(),
+            unchanged_steps: 0,
+            unchanged_since: Instant::now(),
+            panics: self.panics.clone(),
+            step_allocated: 0,
+            idle: false,
+            idle_callbacks: Vec::new(),
+        }
+    }
+}
+
+/// Shared by the workers of a process, so that a panic in one of them stops the others.
+///
+/// Workers would otherwise wait indefinitely for progress from the panicked worker. Workers in
+/// other processes learn of the panic when its process's connections close without shutdown.
+#[derive(Default)]
+pub(crate) struct PanicMonitor {
+    panicked: AtomicBool,
+    threads: Mutex<Vec<Thread>>,
+}
+
+/// Notifies the workers sharing a `PanicMonitor` if dropped while its thread panics.
+pub(crate) struct PanicGuard(pub(crate) Arc<PanicMonitor>);
+
+impl Drop for PanicGuard {
+    fn drop(&mut self) {
+        if thread::panicking() {
+            self.0.panicked.store(true, Ordering::SeqCst);
+            if let Ok(threads) = self.0.threads.lock() {
+                for thread in threads.iter() {
+                    thread.unpark();
+                }
+            }
         }
     }
 }
@@ -746,7 +1486,14 @@ impl Wrapper {
             l.log(crate::logging::ScheduleEvent::start(self.identifier));
         }
 
+        #[cfg(feature = "tracing")]
+        let span = self.operate.as_ref().map(|op| tracing_dep::trace_span!("schedule", name = op.name(), address = ?op.path(), id = self.identifier).entered());
+
         let incomplete = self.operate.as_mut().map(|op| op.schedule()).unwrap_or(false);
+
+        #[cfg(feature = "tracing")]
+        drop(span);
+
         if !incomplete {
             self.operate = None;
             self.resources = None;
@@ -759,6 +1506,11 @@ impl Wrapper {
 
         incomplete
     }
+
+    /// Indicates whether the frontiers of the dataflow are empty.
+    fn frontiers_empty(&mut self) -> bool {
+        self.operate.as_mut().map(|op| op.frontiers_empty()).unwrap_or(true)
+    }
 }
 
 impl Drop for Wrapper {
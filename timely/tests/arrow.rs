@@ -0,0 +1,71 @@
+//! Reads a stream written by the Arrow Rust implementation (arrow-ipc 54.3.1), with the batches
+//!
+//! - `[(0, "apple", 3, 0.5), (0, "", -1, -2.25), (1, "cherry", 7, 1e10)]`,
+//! - `[]`,
+//! - `[(2, "durian", i64::MIN, f64::INFINITY)]`,
+//!
+//! of columns `time: UInt64`, `key: Utf8`, `count: Int64`, and `mean: Float64`.
+
+#![cfg(feature = "arrow")]
+
+extern crate timely;
+
+use timely::arrow::{DataType, Field, StreamReader};
+use timely::dataflow::operators::{Capture, Map};
+use timely::dataflow::operators::capture::Extract;
+
+const STREAM: &[u8] = include_bytes!("data/arrow-rs.arrows");
+
+fn field(name: &str, data_type: DataType) -> Field {
+    Field { name: name.to_owned(), data_type }
+}
+
+#[test]
+fn read_arrow_rs_stream() {
+    let mut reader = StreamReader::new(STREAM).unwrap();
+    assert_eq!(reader.fields(), &[
+        field("time", DataType::UInt64),
+        field("key", DataType::Utf8),
+        field("count", DataType::Int64),
+        field("mean", DataType::Float64),
+    ]);
+
+    let batch = reader.read_records::<(u64, String, i64, f64)>().unwrap().unwrap();
+    assert_eq!(batch, vec![
+        (0, "apple".to_owned(), 3, 0.5),
+        (0, "".to_owned(), -1, -2.25),
+        (1, "cherry".to_owned(), 7, 1e10),
+    ]);
+    assert_eq!(reader.read_records::<(u64, String, i64, f64)>().unwrap().unwrap(), vec![]);
+    let batch = reader.read_records::<(u64, String, i64, f64)>().unwrap().unwrap();
+    assert_eq!(batch, vec![(2, "durian".to_owned(), i64::MIN, f64::INFINITY)]);
+    assert!(reader.read().unwrap().is_none());
+}
+
+#[test]
+fn replay_arrow_rs_stream() {
+    let captured = timely::example(|scope| {
+        let reader = StreamReader::new(STREAM).unwrap();
+        timely::arrow::source::<_, (String, i64, f64), _>(scope, reader)
+            .map(|(key, count, _mean)| (key, count))
+            .capture()
+    });
+    assert_eq!(captured.extract(), vec![
+        (0, vec![("".to_owned(), -1), ("apple".to_owned(), 3)]),
+        (1, vec![("cherry".to_owned(), 7)]),
+        (2, vec![("durian".to_owned(), i64::MIN)]),
+    ]);
+}
+
+#[test]
+fn reject_negative_row_count() {
+    // the row count is the first occurrence of 3i64 in the metadata of the first batch, which follows the schema.
+    let mut stream = STREAM.to_vec();
+    let schema = 8 + u32::from_le_bytes([stream[4], stream[5], stream[6], stream[7]]) as usize;
+    let rows = 3i64.to_le_bytes();
+    let position = schema + stream[schema ..].windows(8).position(|bytes| bytes == rows).unwrap();
+    stream[position .. position + 8].copy_from_slice(&(-1i64).to_le_bytes());
+    let mut reader = StreamReader::new(&stream[..]).unwrap();
+    let error = reader.read().unwrap_err();
+    assert_eq!(error.to_string(), "invalid row count -1");
+}
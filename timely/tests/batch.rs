@@ -0,0 +1,41 @@
+extern crate timely;
+
+use std::sync::{Arc, Mutex};
+
+use timely::dataflow::channels::pact::Pipeline;
+use timely::dataflow::operators::{Capture, Inspect};
+use timely::dataflow::operators::batch::BatchInput;
+use timely::dataflow::operators::capture::Extract;
+use timely::dataflow::operators::generic::operator::Operator;
+
+// Each batch should be introduced whole at its own epoch, and only once the previous epoch is complete.
+#[test]
+fn batches_at_epoch_boundaries() {
+    let events = Arc::new(Mutex::new(Vec::new()));
+    let arrived = events.clone();
+    let completed = events.clone();
+
+    let captured = timely::execute_directly(move |worker| {
+        worker.dataflow(|scope| {
+            let batches = vec![vec![1u64, 2, 3], vec![4, 5], vec![6]];
+            let (handle, stream) = scope.new_batch_input(batches);
+            let stream = stream.inspect_batch(move |epoch, data| arrived.lock().unwrap().push(("arrived", *epoch, data.len())));
+            let done = stream.unary_notify(Pipeline, "Done", vec![], move |input, output, notificator| {
+                input.for_each(|time, _data| notificator.notify_at(time.retain()));
+                notificator.for_each(|time, _, _| {
+                    completed.lock().unwrap().push(("completed", *time.time(), 0));
+                    output.session(&time).give(());
+                });
+            });
+            handle.completed_by(&done);
+            stream.capture()
+        })
+    });
+
+    assert_eq!(captured.extract(), vec![(0, vec![1, 2, 3]), (1, vec![4, 5]), (2, vec![6])]);
+    assert_eq!(*events.lock().unwrap(), vec![
+        ("arrived", 0, 3), ("completed", 0, 0),
+        ("arrived", 1, 2), ("completed", 1, 0),
+        ("arrived", 2, 1), ("completed", 2, 0),
+    ]);
+}
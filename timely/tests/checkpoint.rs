@@ -0,0 +1,26 @@
+extern crate timely;
+
+use timely::{Config, CommunicationConfig, WorkerConfig};
+
+// Restoring from a directory that does not exist yet starts afresh, as on a first run.
+#[test]
+fn restore_from_fresh_directory() {
+    let directory = std::env::temp_dir().join(format!("timely-restore-fresh-{}", std::process::id()));
+    assert!(!directory.exists());
+
+    let config = Config {
+        communication: CommunicationConfig::Process(2),
+        worker: WorkerConfig::default().restore_from(&directory).checkpoint_to(&directory, 1),
+    };
+    let restored = timely::execute(config, |worker| {
+        let restored = worker.restored_epoch();
+        worker.checkpoint(0).unwrap();
+        restored
+    }).unwrap().join();
+
+    assert_eq!(restored.len(), 2);
+    assert!(restored.into_iter().all(|restored| restored.unwrap().is_none()));
+    assert_eq!(timely::dataflow::state::checkpoint::latest(&directory, 2).unwrap(), Some(0));
+
+    std::fs::remove_dir_all(directory).unwrap();
+}
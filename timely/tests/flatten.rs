@@ -0,0 +1,111 @@
+extern crate timely;
+
+use std::sync::{Arc, Mutex};
+
+use timely::dataflow::{InputHandle, ProbeHandle, Scope};
+use timely::dataflow::operators::{Input, Enter, Leave, Map, Exchange, Filter, Concat, Inspect, Probe};
+use timely::dataflow::operators::generic::operator::Operator;
+use timely::dataflow::channels::pact::Pipeline;
+
+// Regions without cycles are flattened into their parents, which schedule their operators and
+// track their progress directly.
+#[test]
+fn flattened_regions() {
+
+    let seen = Arc::new(Mutex::new(Vec::new()));
+    let shared = seen.clone();
+
+    let mut config = timely::Config::process(3);
+    config.worker = timely::WorkerConfig::default().metrics(true);
+    timely::execute(config, move |worker| {
+
+        let index = worker.index();
+        let seen = shared.clone();
+        let mut input = InputHandle::new();
+        let mut probe = ProbeHandle::new();
+
+        worker.dataflow::<u64,_,_>(|scope| {
+            let stream = scope.input_from(&mut input);
+            let output = scope.region(|outer| {
+                let stream = stream.enter(outer);
+                // a nested region, exchanging records, and one only passing them through.
+                let exchanged = outer.region(|inner| stream.enter(inner).exchange(|x| *x).map(|x| x + 1).leave());
+                let passed = outer.region(|inner| stream.enter(inner).leave());
+                exchanged.concat(&passed.filter(|x| x % 2 == 0)).leave()
+            });
+            output
+                // observes frontiers, which reach it through the flattened regions.
+                .unary_frontier(Pipeline, "Frontier", |_capability, _info| {
+                    move |input, output| {
+                        let frontier = input.frontier().frontier().to_vec();
+                        input.for_each(|time, data| {
+                            assert!(frontier.iter().all(|bound| bound <= time.time()));
+                            output.session(&time).give_vec(&mut data.replace(Vec::new()));
+                        });
+                    }
+                })
+                .inspect_time(move |time, x| seen.lock().unwrap().push((*time, *x)))
+                .probe_with(&mut probe);
+        });
+
+        let regions = worker.topology().scopes().filter(|scope| scope.name == "Region").count();
+        assert_eq!(regions, 3);
+        assert!(worker.topology().scopes().filter(|scope| scope.name == "Region").all(|scope| scope.flattenable));
+
+        for round in 0 .. 5u64 {
+            input.send(round * 3 + index as u64);
+            input.advance_to(round + 1);
+            while probe.less_than(input.time()) {
+                worker.step();
+            }
+        }
+
+        // the regions are not scheduled, while the operators within them are.
+        let topology = worker.topology();
+        let metrics = worker.metrics();
+        for region in topology.scopes().filter(|scope| scope.name == "Region") {
+            assert!(metrics.get(&region.path).is_none());
+            for operator in region.operators.iter().filter(|operator| operator.name == "Map") {
+                assert!(metrics.get(&region.address(operator.index)).unwrap().schedules > 0);
+            }
+        }
+    }).unwrap();
+
+    let mut seen = seen.lock().unwrap().clone();
+    seen.sort();
+    let mut expected = Vec::new();
+    for round in 0 .. 5u64 {
+        for index in 0 .. 3 {
+            let x = round * 3 + index;
+            expected.push((round, x + 1));
+            if x % 2 == 0 { expected.push((round, x)); }
+        }
+    }
+    expected.sort();
+    assert_eq!(seen, expected);
+}
+
+// Regions containing iterative scopes are flattened, and the iterative scopes scheduled by the parent.
+#[test]
+fn flattened_iteration() {
+    use timely::dataflow::operators::{Feedback, ConnectLoop, Branch, ToStream, Capture};
+    use timely::dataflow::operators::capture::Extract;
+    use timely::order::Product;
+
+    let captured = timely::example(|scope| {
+        let stream = (0 .. 4u64).to_stream(scope);
+        scope.region(|region| {
+            let stream = stream.enter(region);
+            region.iterative::<u64,_,_>(|inner| {
+                let (handle, cycle) = inner.feedback(Product::new(Default::default(), 1));
+                let (small, large) = stream.enter(inner).concat(&cycle).map(|x| x + 1).branch(|_time, x| *x >= 10);
+                small.connect_loop(handle);
+                large.leave()
+            })
+            .leave()
+        })
+        .capture()
+    });
+
+    assert_eq!(captured.extract(), vec![(0, vec![10, 10, 10, 10])]);
+}
@@ -0,0 +1,30 @@
+extern crate timely;
+
+use timely::dataflow::InputHandle;
+use timely::dataflow::operators::{Input, Probe, Capture};
+use timely::dataflow::operators::capture::Extract;
+use timely::dataflow::operators::framing::Frame;
+
+// A partial frame should not hold back the output frontier once the input frontier has passed it.
+#[test]
+fn pending_frame_follows_frontier() {
+    let captured = timely::execute_directly(|worker| {
+        let mut input = InputHandle::new();
+        let (probe, captured) = worker.dataflow::<u64,_,_>(|scope| {
+            let frames = scope.input_from(&mut input).split_lines();
+            (frames.probe(), frames.capture())
+        });
+
+        input.send(b"one\ntw".to_vec());
+        input.advance_to(5);
+        worker.step_while(|| probe.less_than(&5));
+        assert!(!probe.less_than(&5));
+
+        input.send(b"o\n".to_vec());
+        input.advance_to(6);
+        worker.step_while(|| probe.less_than(&6));
+        captured
+    });
+
+    assert_eq!(captured.extract(), vec![(0, vec![b"one".to_vec()]), (5, vec![b"two".to_vec()])]);
+}
@@ -0,0 +1,58 @@
+#![cfg(feature = "fuzz")]
+
+extern crate timely;
+
+use std::sync::{Arc, Mutex};
+
+use timely::{Config, WorkerConfig};
+use timely::dataflow::InputHandle;
+use timely::dataflow::operators::{Input, Exchange, Inspect, Probe};
+use timely::dataflow::operators::aggregation::StateMachine;
+
+// Notification-based operators should produce the same results under any perturbation of
+// deliveries and scheduling.
+#[test]
+fn state_machine_under_fuzzing() {
+    for seed in 0 .. 20 {
+        let totals = Arc::new(Mutex::new(Vec::new()));
+        let shared = totals.clone();
+        let mut config = Config::process(3);
+        config.worker = WorkerConfig::default().fuzz(seed);
+        timely::execute(config, move |worker| {
+            let index = worker.index() as u64;
+            let shared = shared.clone();
+            let mut input = InputHandle::new();
+            let probe = worker.dataflow::<u64,_,_>(|scope| {
+                scope.input_from(&mut input)
+                     .exchange(|x: &(u64, u64)| x.1)
+                     .state_machine(|key, val, total: &mut u64| {
+                         *total += val;
+                         (false, Some((*key, *total)))
+                     }, |key| *key)
+                     .inspect_time(move |time, x| shared.lock().unwrap().push((*time, *x)))
+                     .probe()
+            });
+
+            for round in 0 .. 5u64 {
+                for key in 0 .. 4u64 {
+                    input.send((key, index + round));
+                }
+                input.advance_to(round + 1);
+                worker.step_while(|| probe.less_than(input.time()));
+            }
+        }).unwrap();
+
+        // each key receives 0 + 1 + 2 in the first round and 3 more each round, in some order
+        // within each round, so that the greatest total of each round is the total through it.
+        let totals = totals.lock().unwrap().clone();
+        for key in 0 .. 4u64 {
+            let mut expected = 0;
+            for round in 0 .. 5u64 {
+                expected += 3 + 3 * round;
+                let outputs = totals.iter().filter(|(time, (k, _))| *time == round && *k == key).map(|(_, (_, total))| *total).collect::<Vec<_>>();
+                assert_eq!(outputs.len(), 3, "seed {}", seed);
+                assert_eq!(outputs.iter().max(), Some(&expected), "seed {}", seed);
+            }
+        }
+    }
+}
@@ -0,0 +1,60 @@
+extern crate timely;
+
+use std::sync::Arc;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::rc::Rc;
+use std::cell::Cell;
+
+use timely::dataflow::InputHandle;
+use timely::dataflow::operators::{Input, Exchange, Probe};
+
+// A worker whose own input is closed is not idle while another worker's input is open, and
+// becomes idle once all inputs are closed and the dataflow has drained.
+#[test]
+fn idle_with_open_input() {
+
+    let checked = Arc::new(AtomicBool::new(false));
+
+    timely::execute(timely::Config::process(2), move |worker| {
+
+        let index = worker.index();
+        let idled = Rc::new(Cell::new(0));
+        let counter = idled.clone();
+        worker.on_idle(move || counter.set(counter.get() + 1));
+
+        let mut input = InputHandle::new();
+        let probe = worker.dataflow::<u64,_,_>(|scope| {
+            scope.input_from(&mut input)
+                 .exchange(|x: &u64| *x)
+                 .probe()
+        });
+
+        input.send(index as u64);
+        input.advance_to(1);
+        worker.step_while(|| probe.less_than(&1));
+
+        if index == 0 {
+            input.close();
+            for _ in 0 .. 100 {
+                worker.step();
+                assert!(!worker.is_idle());
+            }
+            assert_eq!(idled.get(), 0);
+            checked.store(true, Ordering::SeqCst);
+        }
+        else {
+            while !checked.load(Ordering::SeqCst) {
+                worker.step();
+                assert!(!worker.is_idle());
+            }
+            input.close();
+        }
+
+        while !worker.is_idle() {
+            worker.step();
+        }
+        assert_eq!(idled.get(), 1);
+        assert!(probe.done());
+
+    }).unwrap();
+}
@@ -0,0 +1,57 @@
+extern crate timely;
+
+use std::sync::{Arc, Mutex};
+
+use timely::Config;
+use timely::dataflow::InputHandle;
+use timely::dataflow::operators::{Input, Inspect, Probe};
+use timely::dataflow::operators::command::CommandInput;
+use timely::dataflow::operators::aggregation::MigratingStateMachine;
+
+// Counts per key should be those of a fixed assignment, however often the assignment changes.
+#[test]
+fn states_follow_reassigned_keys() {
+    let seen = Arc::new(Mutex::new(Vec::new()));
+    let shared = seen.clone();
+    timely::execute(Config::process(3), move |worker| {
+        let index = worker.index();
+        let shared = shared.clone();
+        let mut input = InputHandle::new();
+        let mut routing = InputHandle::new();
+        let probe = worker.dataflow::<u64,_,_>(|scope| {
+            let routing = scope.command_stream(&mut routing);
+            scope.input_from(&mut input)
+                 .migrating_state_machine(&routing, 1, |key: &u64, _val: (), count: &mut u64| {
+                     *count += 1;
+                     (false, Some((*key, *count)))
+                 }, |key| *key)
+                 .inspect_time(move |time, (key, count)| shared.lock().unwrap().push((*time, index, *key, *count)))
+                 .probe()
+        });
+
+        let assignments = [(2, vec![0]), (4, vec![1, 2]), (5, vec![2, 0, 1]), (7, vec![1])];
+        for round in 0 .. 10u64 {
+            if index == 0 {
+                for (_, workers) in assignments.iter().filter(|(changed, _)| *changed == round) {
+                    routing.send(workers.clone());
+                }
+            }
+            for key in 0 .. 10 {
+                input.send((key, ()));
+            }
+            input.advance_to(round + 1);
+            routing.advance_to(round + 1);
+            worker.step_while(|| probe.less_than(input.time()));
+        }
+    }).unwrap();
+
+    let seen = seen.lock().unwrap();
+    for key in 0 .. 10u64 {
+        let mut counts = seen.iter().filter(|x| x.2 == key).map(|x| x.3).collect::<Vec<_>>();
+        counts.sort();
+        assert_eq!(counts, (1 ..= 30).collect::<Vec<_>>(), "key {}", key);
+    }
+    // while only the second worker owns keys, it produces all results.
+    assert!(seen.iter().filter(|x| x.0 >= 7).all(|x| x.1 == 1));
+    assert!(seen.iter().filter(|x| x.0 == 2 || x.0 == 3).all(|x| x.1 == 0));
+}
@@ -0,0 +1,70 @@
+extern crate timely;
+
+use timely::Config;
+use timely::dataflow::{InputHandle, Scope};
+use timely::dataflow::operators::{ToStream, Input, Enter, Leave, Map, Exchange, Probe};
+
+// A panic in one worker's operator should stop the other workers, rather than leave them waiting.
+#[test]
+fn panic_stops_peers() {
+    let guards = timely::execute(Config::process(2), |worker| {
+        let index = worker.index();
+        worker.dataflow::<u64,_,_>(|scope| {
+            (0 .. 10u64)
+                .to_stream(scope)
+                .map(move |x| { if index == 0 { panic!("failed on {}", x); } x })
+                .exchange(|x| *x)
+                .probe();
+        });
+    }).unwrap();
+
+    let results = guards.join();
+    assert_eq!(results.len(), 2);
+    assert!(results.iter().all(|result| result.is_err()));
+    // the failing worker reports the operator that panicked, and its message.
+    let error = results[0].as_ref().unwrap_err();
+    assert!(error.contains("operator \"Map\" at [0, 2]"), "unannotated panic: {}", error);
+    assert!(error.contains("failed on 0"), "unannotated panic: {}", error);
+}
+
+// Panics with payloads other than messages are re-raised unchanged, so that they can be downcast.
+#[test]
+fn typed_panic_unchanged() {
+    let result = ::std::panic::catch_unwind(|| {
+        timely::execute_directly(|worker| {
+            worker.dataflow::<u64,_,_>(|scope| {
+                (0 .. 10u64)
+                    .to_stream(scope)
+                    .map(|x| { if x == 5 { ::std::panic::resume_unwind(Box::new(x)); } x })
+                    .probe();
+            });
+        })
+    });
+    assert_eq!(result.unwrap_err().downcast_ref::<u64>(), Some(&5));
+}
+
+// Operators in nested scopes that panic while the dataflow runs should stop their peers as well.
+#[test]
+fn nested_panic_stops_peers() {
+    let guards = timely::execute(Config::process(2), |worker| {
+        let mut input = InputHandle::new();
+        worker.dataflow::<u64,_,_>(|scope| {
+            let stream = scope.input_from(&mut input);
+            scope.region(|inner| {
+                stream.enter(inner)
+                      .map(|x: u64| { if x == 5 { panic!("failed on {}", x); } x })
+                      .leave()
+            })
+            .exchange(|x| *x)
+            .probe();
+        });
+        for round in 0 .. 10 {
+            if worker.index() == 0 { input.send(round); }
+            input.advance_to(round + 1);
+            worker.step();
+        }
+    }).unwrap();
+
+    let results = guards.join();
+    assert!(results.iter().all(|result| result.is_err()));
+}
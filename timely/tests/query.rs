@@ -0,0 +1,73 @@
+extern crate timely;
+
+use timely::Config;
+use timely::dataflow::InputHandle;
+use timely::dataflow::operators::Input;
+use timely::dataflow::operators::arrange::ArrangeByKey;
+use timely::dataflow::operators::query::QueryHandle;
+
+// Each worker's queries should be answered at the worker holding the key, and returned to it.
+#[test]
+fn queries_return_to_issuing_worker() {
+    timely::execute(Config::process(3), |worker| {
+        let index = worker.index() as u64;
+        let mut input = InputHandle::new();
+        let mut queries = QueryHandle::new();
+        worker.dataflow::<u64,_,_>(|scope| {
+            scope.input_from(&mut input)
+                 .arrange_by_key()
+                 .serve(&mut queries);
+        });
+
+        let mut issued = Vec::new();
+        for round in 0 .. 5u64 {
+            input.send((index * 10 + round, round));
+            for key in 0 .. 3 {
+                issued.push((queries.query(key * 10), round));
+            }
+            input.advance_to(round + 1);
+            queries.advance_to(round + 1);
+        }
+
+        while queries.answered() < issued.len() {
+            worker.step();
+        }
+        for (query, round) in issued {
+            assert_eq!(queries.answer(query), Some(vec![0]), "query at round {}", round);
+        }
+    }).unwrap();
+}
+
+// Queries issued behind the arranged input should see the values as of their times, even as the
+// arrangement compacts its index at its input frontier.
+#[test]
+fn queries_behind_compacted_input() {
+    timely::execute_directly(|worker| {
+        let mut input = InputHandle::new();
+        let mut queries = QueryHandle::new();
+        worker.dataflow::<u64,_,_>(|scope| {
+            scope.input_from(&mut input)
+                 .arrange_by_key()
+                 .serve(&mut queries);
+        });
+
+        for round in 0 .. 10u64 {
+            input.send((0u64, 1u64));
+            input.advance_to(round + 1);
+            worker.step();
+        }
+
+        queries.advance_to(3);
+        let early = queries.query(0);
+        queries.advance_to(10);
+        let late = queries.query(0);
+        queries.advance_to(11);
+        input.advance_to(11);
+
+        while queries.answered() < 2 {
+            worker.step();
+        }
+        assert_eq!(queries.answer(early), Some(vec![1; 4]));
+        assert_eq!(queries.answer(late), Some(vec![1; 10]));
+    });
+}
@@ -0,0 +1,49 @@
+extern crate timely;
+
+use std::sync::{Arc, Mutex};
+
+use timely::Config;
+use timely::dataflow::InputHandle;
+use timely::dataflow::operators::{Input, Inspect, Probe};
+use timely::dataflow::operators::side_input::{SideInputStream, WithSideInput};
+
+// Updates from several workers at the same timestamp should be applied in the same order at
+// every worker, and records should see all updates at times up to their own.
+#[test]
+fn workers_see_same_versions() {
+    let seen = Arc::new(Mutex::new(Vec::new()));
+    let shared = seen.clone();
+    timely::execute(Config::process(3), move |worker| {
+        let index = worker.index();
+        let shared = shared.clone();
+        let mut updates = InputHandle::new();
+        let mut input = InputHandle::new();
+        let probe = worker.dataflow::<u64,_,_>(|scope| {
+            let history = scope.input_from(&mut updates).side_input(Vec::new(), |history: &mut Vec<usize>, update| history.push(update));
+            scope.input_from(&mut input)
+                 .with_side_input(&history, |history, time, ()| Some((*time, history.clone())))
+                 .inspect(move |x| shared.lock().unwrap().push(x.clone()))
+                 .probe()
+        });
+
+        for round in 0 .. 4u64 {
+            if round % 2 == 1 {
+                updates.send(index);
+                updates.send(index + 10);
+            }
+            input.send(());
+            updates.advance_to(round + 1);
+            input.advance_to(round + 1);
+            worker.step_while(|| probe.less_than(input.time()));
+        }
+    }).unwrap();
+
+    let seen = seen.lock().unwrap();
+    let once = [0, 10, 1, 11, 2, 12];
+    let twice = once.iter().chain(once.iter()).cloned().collect::<Vec<_>>();
+    assert_eq!(seen.len(), 12);
+    for (time, history) in seen.iter() {
+        let expected = match time { 0 => &[][..], 1 | 2 => &once[..], _ => &twice[..] };
+        assert_eq!(&history[..], expected, "at time {}", time);
+    }
+}
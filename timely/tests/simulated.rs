@@ -0,0 +1,125 @@
+extern crate timely;
+
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+use timely::{CommunicationConfig, Config, WorkerConfig};
+use timely::communication::authentication::Key;
+use timely::communication::allocator::zero_copy::initialize::NetworkConfig;
+use timely::dataflow::InputHandle;
+use timely::dataflow::operators::{Input, Exchange, Inspect, Probe};
+
+fn simulated(process_threads: Vec<usize>, latency: Duration) -> Config {
+    simulated_with(process_threads, latency, NetworkConfig::default())
+}
+
+fn simulated_with(process_threads: Vec<usize>, latency: Duration, network: NetworkConfig) -> Config {
+    Config {
+        communication: CommunicationConfig::simulated(process_threads, latency).network(network),
+        worker: WorkerConfig::default(),
+    }
+}
+
+// Records exchanged between simulated processes should arrive at the workers they are routed to.
+#[test]
+fn exchange_between_simulated_processes() {
+    let received = Arc::new(Mutex::new(Vec::new()));
+    let shared = received.clone();
+    let guards = timely::execute(simulated(vec![2, 2], Duration::from_millis(1)), move |worker| {
+        let index = worker.index();
+        let peers = worker.peers() as u64;
+        let shared = shared.clone();
+        let mut input = InputHandle::new();
+        let probe = worker.dataflow::<u64,_,_>(|scope| {
+            scope.input_from(&mut input)
+                 .exchange(|x: &u64| *x)
+                 .inspect(move |x| shared.lock().unwrap().push((index, *x)))
+                 .probe()
+        });
+
+        for round in 0 .. 3u64 {
+            for offset in 0 .. peers {
+                input.send(round * 100 + index as u64 * 10 + offset);
+            }
+            input.advance_to(round + 1);
+            worker.step_while(|| probe.less_than(input.time()));
+        }
+        peers
+    }).unwrap();
+
+    let peers = guards.join().into_iter().map(|result| result.unwrap()).collect::<Vec<_>>();
+    assert_eq!(peers, vec![4; 4]);
+
+    let mut received = received.lock().unwrap().clone();
+    received.sort();
+    assert_eq!(received.len(), 48);
+    for (index, record) in received {
+        assert_eq!(record % 4, index as u64);
+    }
+}
+
+// Simulated processes may host different numbers of workers.
+#[test]
+fn uneven_simulated_processes() {
+    let guards = timely::execute(simulated(vec![1, 3], Duration::from_millis(0)), |worker| {
+        let index = worker.index();
+        let count = Arc::new(Mutex::new(0));
+        let shared = count.clone();
+        worker.dataflow::<u64,_,_>(|scope| {
+            use timely::dataflow::operators::ToStream;
+            (0 .. 100u64).to_stream(scope)
+                         .exchange(|_| 0)
+                         .inspect(move |_| *shared.lock().unwrap() += 1);
+        });
+        while worker.step_or_park(None) { }
+        let count = *count.lock().unwrap();
+        (index, count)
+    }).unwrap();
+
+    let results = guards.join().into_iter().map(|result| result.unwrap()).collect::<Vec<_>>();
+    assert_eq!(results, vec![(0, 400), (1, 0), (2, 0), (3, 0)]);
+}
+
+// Processes sharing a key should exchange authenticated messages as they would unauthenticated ones.
+#[test]
+fn authenticated_simulated_processes() {
+    let network = NetworkConfig::default().key(Key::new(b"shared secret".to_vec()));
+    let guards = timely::execute(simulated_with(vec![2, 2], Duration::from_millis(0), network), |worker| {
+        let count = Arc::new(Mutex::new(0));
+        let shared = count.clone();
+        worker.dataflow::<u64,_,_>(|scope| {
+            use timely::dataflow::operators::ToStream;
+            (0 .. 100u64).to_stream(scope)
+                         .exchange(|x| *x)
+                         .inspect(move |_| *shared.lock().unwrap() += 1);
+        });
+        while worker.step_or_park(None) { }
+        let count = *count.lock().unwrap();
+        count
+    }).unwrap();
+
+    let counts = guards.join().into_iter().map(|result| result.unwrap()).collect::<Vec<_>>();
+    assert_eq!(counts, vec![100; 4]);
+}
+
+// Shared send and receive threads should serve all connections between simulated processes.
+#[test]
+fn shared_network_threads() {
+    let network = NetworkConfig::default().connections(2).send_threads(1).recv_threads(1);
+    let guards = timely::execute(simulated_with(vec![1, 1, 1], Duration::from_millis(1), network), |worker| {
+        let count = Arc::new(Mutex::new(0));
+        let shared = count.clone();
+        worker.dataflow::<u64,_,_>(|scope| {
+            use timely::dataflow::operators::ToStream;
+            (0 .. 90u64).to_stream(scope)
+                        .exchange(|x| *x)
+                        .inspect(move |_| *shared.lock().unwrap() += 1);
+        });
+        while worker.step_or_park(None) { }
+        let count = *count.lock().unwrap();
+        count
+    }).unwrap();
+
+    let results = guards.join().into_iter().map(|result| result.unwrap()).collect::<Vec<_>>();
+    assert_eq!(results, vec![90; 3]);
+}
@@ -0,0 +1,62 @@
+extern crate timely;
+
+use std::sync::{Arc, Mutex};
+
+use timely::dataflow::{InputHandle, ProbeHandle};
+use timely::dataflow::operators::{Input, Probe};
+use timely::dataflow::operators::generic::builder_rc::OperatorBuilder;
+use timely::dataflow::channels::pact::Partitioned;
+use timely::dataflow::channels::partitioner::{Subset, HashModulo};
+
+// An operator restricted to a subset of the workers is constructed only at those workers, and
+// the frontier of its output advances as only they release their capabilities.
+#[test]
+fn operator_on_subset() {
+
+    let built = Arc::new(Mutex::new(Vec::new()));
+    let seen = Arc::new(Mutex::new(Vec::new()));
+    let (built2, seen2) = (built.clone(), seen.clone());
+
+    timely::execute(timely::Config::process(3), move |worker| {
+
+        let index = worker.index();
+        let (built, seen) = (built2.clone(), seen2.clone());
+        let mut input = InputHandle::new();
+        let mut probe = ProbeHandle::new();
+
+        worker.dataflow::<u64,_,_>(|scope| {
+            let stream = scope.input_from(&mut input);
+            let mut builder = OperatorBuilder::new("Subset".to_owned(), scope.clone());
+            let mut records = builder.new_input(&stream, Partitioned::new(Subset::new(vec![1, 2], HashModulo::new(|x: &u64| *x))));
+            let (mut output, stream) = builder.new_output::<u64>();
+            builder.set_workers(&[1, 2]);
+            builder.build(move |_capabilities| {
+                built.lock().unwrap().push(index);
+                move |_frontiers| {
+                    let mut output = output.activate();
+                    records.for_each(|time, data| {
+                        seen.lock().unwrap().extend(data.iter().map(|x| (index, *x)));
+                        output.session(&time).give_iterator(data.iter().cloned());
+                    });
+                }
+            });
+            stream.probe_with(&mut probe);
+        });
+
+        for round in 0 .. 5u64 {
+            input.send(round * 3 + index as u64);
+            input.advance_to(round + 1);
+            worker.step_while(|| probe.less_than(input.time()));
+        }
+
+    }).unwrap();
+
+    let mut built = built.lock().unwrap().clone();
+    built.sort();
+    assert_eq!(built, vec![1, 2]);
+
+    let mut seen = seen.lock().unwrap().clone();
+    seen.sort_by_key(|(_, x)| *x);
+    assert_eq!(seen.len(), 15);
+    assert!(seen.iter().all(|(index, x)| *index == [1, 2][(*x % 2) as usize]));
+}
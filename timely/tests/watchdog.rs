@@ -0,0 +1,69 @@
+extern crate timely;
+
+use std::time::{Duration, Instant};
+
+use timely::{Config, WorkerConfig};
+use timely::dataflow::InputHandle;
+use timely::dataflow::channels::pact::Pipeline;
+use timely::dataflow::operators::{ToStream, Input, Map, Exchange, Probe};
+use timely::dataflow::operators::generic::operator::Operator;
+
+// A dataflow whose operator never releases its capability should be stopped by the watchdog.
+#[test]
+fn watchdog_stops_stuck_dataflow() {
+    let mut config = Config::process(2);
+    config.worker = WorkerConfig::default().watchdog(Duration::from_millis(200));
+    let start = Instant::now();
+    let guards = timely::execute(config, |worker| {
+        worker.dataflow::<u64,_,_>(|scope| {
+            (0 .. 10u64)
+                .to_stream(scope)
+                .unary::<u64,_,_,_>(Pipeline, "Stuck", |capability, _info| move |input, _output| {
+                    let _held = &capability;
+                    input.for_each(|_time, _data| { });
+                });
+        });
+    }).unwrap();
+
+    let results = guards.join();
+    assert_eq!(results.len(), 2);
+    assert!(results.iter().all(|result| result.is_err()));
+    assert!(start.elapsed() < Duration::from_secs(30));
+}
+
+// A dataflow that makes progress more often than the watchdog interval should be unaffected.
+#[test]
+fn watchdog_spares_progressing_dataflow() {
+    let mut config = Config::process(2);
+    config.worker = WorkerConfig::default().watchdog(Duration::from_secs(10));
+    timely::execute(config, |worker| {
+        let mut input = InputHandle::new();
+        let probe = worker.dataflow::<u64,_,_>(|scope| {
+            scope.input_from(&mut input)
+                 .map(|x: u64| x + 1)
+                 .exchange(|x| *x)
+                 .probe()
+        });
+        for round in 0 .. 10 {
+            input.send(round);
+            input.advance_to(round + 1);
+            worker.step_while(|| probe.less_than(input.time()));
+        }
+    }).unwrap();
+}
+
+// Workers whose dataflows remain after the time limit should stop, even while parked.
+#[test]
+fn deadline_stops_parked_workers() {
+    let start = Instant::now();
+    let guards = timely::execute_with_deadline(Config::process(2), Duration::from_millis(200), |worker| {
+        let mut input = InputHandle::<u64, u64>::new();
+        let probe = worker.dataflow::<u64,_,_>(|scope| scope.input_from(&mut input).probe());
+        input.send(0);
+        // the input is never advanced, so the worker parks indefinitely.
+        worker.step_or_park_while(None, || probe.less_equal(&0));
+    }).unwrap();
+
+    assert!(guards.join().iter().all(|result| result.is_err()));
+    assert!(start.elapsed() < Duration::from_secs(30));
+}